@@ -0,0 +1,165 @@
+use std::cell::Cell;
+
+use bincode::Options;
+use gloo_worker::Codec;
+use js_sys::{Function, Reflect, Uint8Array};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+/// Messages encoded and decoded on one thread so far
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CodecStats {
+    pub messages: u64,
+    pub bytes: u64,
+    /// Seconds spent in bincode and copying from and to the transferred arrays
+    pub seconds: f64,
+}
+
+thread_local! {
+    static CODEC_STATS: Cell<CodecStats> = const {
+        Cell::new(CodecStats {
+            messages: 0,
+            bytes: 0,
+            seconds: 0.,
+        })
+    };
+}
+
+/// Totals of the messages passed by the worker bridges of this thread
+pub fn codec_stats() -> CodecStats {
+    CODEC_STATS.get()
+}
+
+fn record(bytes: usize, begin: f64) {
+    let mut stats = CODEC_STATS.get();
+    stats.messages += 1;
+    stats.bytes += bytes as u64;
+    stats.seconds += timestamp() - begin;
+    CODEC_STATS.set(stats);
+}
+
+/// `performance.now()` of the window or the worker, in seconds
+fn timestamp() -> f64 {
+    let performance = Reflect::get(&js_sys::global(), &"performance".into()).unwrap();
+    let now: Function = Reflect::get(&performance, &"now".into()).unwrap().into();
+    now.call0(&performance).unwrap().as_f64().unwrap() / 1000.
+}
+
+fn options() -> impl Options {
+    bincode::DefaultOptions::new()
+}
+
+pub fn to_bytes<T: Serialize>(value: &T) -> Vec<u8> {
+    options()
+        .serialize(value)
+        .expect("can't serialize a worker message")
+}
+
+pub fn from_bytes<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> T {
+    options()
+        .deserialize(bytes)
+        .expect("can't deserialize a worker message")
+}
+
+/// Bincode with variable length integers, boards are mostly small numbers
+/// which the default fixed width encoding spends 8 bytes each on
+#[derive(Debug)]
+pub struct VarintBincode;
+
+impl Codec for VarintBincode {
+    fn encode<I>(input: I) -> JsValue
+    where
+        I: Serialize,
+    {
+        let begin = timestamp();
+        let bytes = to_bytes(&input);
+        let encoded = Uint8Array::from(bytes.as_slice()).into();
+        record(bytes.len(), begin);
+        encoded
+    }
+
+    fn decode<O>(input: JsValue) -> O
+    where
+        O: for<'de> Deserialize<'de>,
+    {
+        let begin = timestamp();
+        let bytes = Uint8Array::from(input).to_vec();
+        let decoded = from_bytes(&bytes);
+        record(bytes.len(), begin);
+        decoded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{hint::black_box, time::Instant};
+
+    use minesweep_core::{AutomationPolicy, GameOptions, GameView};
+
+    use super::*;
+    use crate::{AutomationRequest, Moves, Solver};
+
+    /// Round trips timed by `faster_than_json`
+    const ROUNDS: usize = 200;
+
+    fn hard_board() -> AutomationRequest {
+        let mut view = GameView::from(
+            GameOptions {
+                safe_pos: Some((15, 8)),
+                seed: Some(1),
+                ..GameOptions::hard()
+            }
+            .build(),
+        );
+        view.left_click(15, 8);
+        view.automation_run();
+        AutomationRequest::Sync {
+            id: 1,
+            view: Box::new(view),
+            run: false,
+            solver: Solver::Session,
+            moves: Moves::All(AutomationPolicy::FlagsFirst),
+            region: None,
+        }
+    }
+
+    #[test]
+    fn smaller_than_default() {
+        let request = hard_board();
+        let varint = to_bytes(&request);
+        let decoded: AutomationRequest = from_bytes(&varint);
+        let fixed = bincode::serialize(&request).unwrap();
+
+        let AutomationRequest::Sync { view: decoded, .. } = decoded else {
+            unreachable!()
+        };
+        let AutomationRequest::Sync { view, .. } = request else {
+            unreachable!()
+        };
+        assert_eq!(decoded, view);
+        assert!(varint.len() * 2 < fixed.len());
+    }
+
+    /// Benchmark of the transfer against JSON, run with `cargo test -- --ignored` in release
+    #[test]
+    #[ignore]
+    fn faster_than_json() {
+        let request = hard_board();
+        let time = |round_trip: &dyn Fn()| {
+            let begin = Instant::now();
+            for _ in 0..ROUNDS {
+                round_trip();
+            }
+            begin.elapsed()
+        };
+        let varint = time(&|| {
+            let bytes = to_bytes(black_box(&request));
+            black_box(from_bytes::<AutomationRequest>(&bytes));
+        });
+        let json = time(&|| {
+            let text = serde_json::to_string(black_box(&request)).unwrap();
+            black_box(serde_json::from_str::<AutomationRequest>(&text).unwrap());
+        });
+        assert!(varint < json, "varint {varint:?}, json {json:?}");
+    }
+}
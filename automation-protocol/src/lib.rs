@@ -0,0 +1,207 @@
+mod codec;
+
+use minesweep_core::{
+    Action, AutomationError, AutomationPolicy, Difficulty, GameView, Rating, Rect, RedrawCells,
+    SolveResult, SolveTier, TraceBundle,
+};
+use serde::{Deserialize, Serialize};
+
+pub use codec::{codec_stats, CodecStats, VarintBincode};
+
+/// Bumped on every incompatible change to the messages, checked by health checks
+pub const PROTOCOL_VERSION: u32 = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AutomationRequest {
+    /// Replace the board kept by the worker and take a step
+    Sync {
+        id: u64,
+        view: Box<GameView>,
+        run: bool,
+        solver: Solver,
+        moves: Moves,
+        /// Solve only the frontier inside, with the plain solver
+        region: Option<Rect>,
+    },
+    /// Apply the actions taken since the board had `base` actions and take a step
+    Step {
+        id: u64,
+        base: usize,
+        actions: Vec<Action>,
+        run: bool,
+        solver: Solver,
+        moves: Moves,
+        /// Solve only the frontier inside, with the plain solver
+        region: Option<Rect>,
+    },
+    /// Solve only the given cells without keeping the board, used by worker pools
+    Solve {
+        id: u64,
+        view: Box<GameView>,
+        cells: Vec<(usize, usize)>,
+    },
+    /// Abort every step request with id up to this, may be sent from any bridge
+    Cancel { id: u64 },
+    /// Drop the solver session, sent on new game or restart
+    Reset,
+    /// Health check, answered between solve slices
+    Ping { version: u32 },
+    /// Hand over the automation steps traced so far, none unless built with the `trace` feature
+    Trace,
+    /// Automate `boards` random boards from `seed` on, until `budget` seconds are spent
+    Benchmark {
+        id: u64,
+        difficulty: Difficulty,
+        boards: u64,
+        seed: u64,
+        solver: Solver,
+        budget: f64,
+    },
+    /// Rate `boards` boards from `seed` on by playing them out headless
+    Explore {
+        id: u64,
+        difficulty: Difficulty,
+        seed: u64,
+        boards: u64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Solver {
+    /// Encode and solve the whole frontier every step
+    Plain,
+    /// Keep a solver session across the steps of a game
+    Session,
+    /// Alternate slices between both and take whichever finishes first
+    Race,
+    /// Try the rules, then small components, then the rest, taking what is found by the
+    /// first tier with moves or when `budget` seconds are spent
+    Tiered { budget: f64 },
+}
+
+/// Which of the moves found by a solve a step makes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Moves {
+    /// Flag the mines and open the safe cells in the way of the policy, as full automation does
+    All(AutomationPolicy),
+    /// Open the safe cells only, for the auto clear assist
+    Safe,
+}
+
+impl Moves {
+    pub fn apply(
+        self,
+        view: &mut GameView,
+        result: SolveResult,
+    ) -> Result<RedrawCells, AutomationError> {
+        match self {
+            Moves::All(policy) => view.automation_apply(result, policy),
+            Moves::Safe => view.auto_clear(result),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RaceRecord {
+    pub wins: u64,
+    /// Seconds spent on the races won
+    pub duration: f64,
+}
+
+impl RaceRecord {
+    pub fn mean(&self) -> f64 {
+        self.duration / self.wins.max(1) as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RaceStats {
+    pub plain: RaceRecord,
+    pub session: RaceRecord,
+}
+
+impl RaceStats {
+    pub fn record(&mut self, winner: Solver, duration: f64) {
+        let record = match winner {
+            Solver::Session => &mut self.session,
+            _ => &mut self.plain,
+        };
+        record.wins += 1;
+        record.duration += duration;
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkStats {
+    pub boards: u64,
+    /// Boards won without guessing, the rest got stuck
+    pub wins: u64,
+    pub steps: u64,
+    /// Seconds spent in automation steps
+    pub duration: f64,
+    pub longest_step: f64,
+}
+
+impl BenchmarkStats {
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.boards.max(1) as f64
+    }
+
+    pub fn mean_board(&self) -> f64 {
+        self.duration / self.boards.max(1) as f64
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AutomationResponse {
+    /// `action` is the automation step taken, to be applied by the app, or why none was taken,
+    /// requests with `run` get one per step until no move is found or the game is over
+    /// `race` holds the outcomes of every race so far when racing
+    /// `tier` is the tier that found the moves of a tiered solve
+    Step {
+        id: u64,
+        /// `GameView::revision` of the board solved, before the step
+        revision: u64,
+        duration: f64,
+        action: Result<Action, AutomationError>,
+        race: Option<RaceStats>,
+        tier: Option<SolveTier>,
+        /// The failing step when the solver contradicted the board, to be downloaded
+        diagnostic: Option<Box<TraceBundle>>,
+    },
+    Solved {
+        id: u64,
+        /// `GameView::revision` of the board solved
+        revision: u64,
+        duration: f64,
+        result: SolveResult,
+    },
+    Cancelled {
+        id: u64,
+    },
+    /// The worker board does not match `base`, a full sync is needed
+    Desync {
+        id: u64,
+    },
+    Benchmarked {
+        id: u64,
+        stats: BenchmarkStats,
+    },
+    /// Ratings in the order of the seeds
+    Explored {
+        id: u64,
+        ratings: Vec<Rating>,
+    },
+    Pong {
+        version: u32,
+        /// Cells examined and cells in total of the sliced solve in flight, if any
+        progress: Option<(usize, usize)>,
+    },
+    Traced {
+        bundle: TraceBundle,
+    },
+    /// The worker panicked and has to be respawned
+    Failed {
+        reason: String,
+    },
+}
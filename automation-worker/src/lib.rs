@@ -1,27 +1,491 @@
+use std::{
+    cell::{Cell, RefCell},
+    mem,
+};
+
+use automation_protocol::{
+    AutomationRequest, AutomationResponse, BenchmarkStats, Moves, RaceStats, Solver,
+    PROTOCOL_VERSION,
+};
 use futures::{SinkExt, StreamExt};
 use gloo_worker::reactor::{reactor, ReactorScope};
-use js_sys::global;
-use minesweep_core::{GameView, RedrawCells};
+use js_sys::{global, Promise};
+use minesweep_core::{
+    Action, AutomationError, AutomationPolicy, BoardLimits, Difficulty, GameOptions, GameResult,
+    GameView, Rater, Rating, Rect, SolvePoll, SolveResult, SolveTask, SolveTier, SolverSession,
+    Symmetry, TieredSolve, TraceBundle, GENERATION_VERSION,
+};
+use tracing::Instrument;
 use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
 use web_sys::WorkerGlobalScope;
 
+/// Seconds of solving between checks for cancellation
+const SLICE: f64 = 0.02;
+
+thread_local! {
+    /// Requests with id up to this are cancelled, shared by all bridges of the worker
+    static CANCELLED: Cell<u64> = const { Cell::new(0) };
+    /// Copy of the board kept in sync with the app through actions
+    static BOARD: RefCell<Option<GameView>> = const { RefCell::new(None) };
+    /// Solver state of the kept board, until the app resets it
+    static SESSION: RefCell<SolverSession> = RefCell::new(SolverSession::new());
+    /// Message of the first panic, the worker is not to be trusted afterwards
+    static PANIC: RefCell<Option<String>> = const { RefCell::new(None) };
+    /// Outcomes of every race run by the worker
+    static RACES: RefCell<RaceStats> = RefCell::new(RaceStats::default());
+    /// Progress of the sliced solve in flight, answered to health checks
+    static PROGRESS: Cell<Option<(usize, usize)>> = const { Cell::new(None) };
+}
+
+/// Log panics and keep their message for health checks to report
+pub fn set_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        console_error_panic_hook::hook(info);
+        PANIC.with(|panic| {
+            if let Ok(mut panic) = panic.try_borrow_mut() {
+                panic.get_or_insert_with(|| info.to_string());
+            }
+        });
+    }));
+}
+
+fn worker_scope() -> WorkerGlobalScope {
+    global().dyn_into::<WorkerGlobalScope>().unwrap()
+}
+
 fn timestamp() -> f64 {
-    global()
-        .dyn_into::<WorkerGlobalScope>()
-        .unwrap()
-        .performance()
-        .unwrap()
-        .now() as f64
-        / 1000.
+    worker_scope().performance().unwrap().now() / 1000.
+}
+
+/// Give the event loop a chance to deliver cancel messages
+async fn yield_now() {
+    let promise = Promise::new(&mut |resolve, _| {
+        worker_scope().set_timeout_with_callback(&resolve).unwrap();
+    });
+    JsFuture::from(promise).await.unwrap();
+}
+
+fn is_cancelled(id: u64) -> bool {
+    CANCELLED.with(|cancelled| id <= cancelled.get())
+}
+
+/// Run the task in slices, returns `None` once cancelled
+async fn solve(id: u64, view: &GameView, mut task: SolveTask) -> Option<SolveTask> {
+    loop {
+        let slice_begin = timestamp();
+        let SolvePoll::Pending { examined, total } =
+            view.poll_solve(&mut task, || timestamp() - slice_begin >= SLICE)
+        else {
+            break;
+        };
+        PROGRESS.set(Some((examined, total)));
+        yield_now().await;
+        if is_cancelled(id) {
+            PROGRESS.set(None);
+            return None;
+        }
+    }
+    PROGRESS.set(None);
+    Some(task)
+}
+
+/// Alternate slices between the tasks of both solvers, returns the first to finish
+/// along with the time it spent
+async fn race(
+    id: u64,
+    view: &GameView,
+    plain: SolveTask,
+    session: SolveTask,
+) -> Option<(Solver, SolveTask, f64)> {
+    let mut tasks = [(Solver::Plain, plain, 0.), (Solver::Session, session, 0.)];
+    loop {
+        for (solver, task, spent) in tasks.iter_mut() {
+            let begin = timestamp();
+            let mut finished = false;
+            while !finished && timestamp() - begin < SLICE / 2. {
+                finished = !task.step(view);
+            }
+            *spent += timestamp() - begin;
+            if finished {
+                return Some((*solver, mem::take(task), *spent));
+            }
+        }
+        yield_now().await;
+        if is_cancelled(id) {
+            return None;
+        }
+    }
+}
+
+/// Run a tiered solve in slices until it finishes or `budget` seconds pass,
+/// returns `None` once cancelled
+async fn tiered(
+    id: u64,
+    view: &GameView,
+    budget: f64,
+) -> Option<Result<(SolveResult, SolveTier), AutomationError>> {
+    let begin = timestamp();
+    let mut solve = TieredSolve::new();
+    let mut slice_begin = begin;
+    while solve.step(view) {
+        if timestamp() - begin > budget {
+            break;
+        }
+        if timestamp() - slice_begin < SLICE {
+            continue;
+        }
+        yield_now().await;
+        if is_cancelled(id) {
+            return None;
+        }
+        slice_begin = timestamp();
+    }
+    Some(solve.finish())
+}
+
+/// Solve with the given solver, returns the solver whose result is taken and its time
+async fn solve_with(
+    id: u64,
+    view: &GameView,
+    solver: Solver,
+    session: &mut SolverSession,
+) -> Option<(Solver, SolveResult, f64)> {
+    let begin = timestamp();
+    let (solver, task, duration) = match solver {
+        Solver::Plain => {
+            let task = solve(id, view, view.solve_task()).await?;
+            (solver, task, timestamp() - begin)
+        }
+        Solver::Session => {
+            let task = solve(id, view, session.solve_task(view)).await?;
+            (solver, task, timestamp() - begin)
+        }
+        Solver::Race => race(id, view, view.solve_task(), session.solve_task(view)).await?,
+        Solver::Tiered { budget } => {
+            let result = tiered(id, view, budget).await?;
+            let result = result.map(|(result, _)| result).unwrap_or_default();
+            return Some((solver, result, timestamp() - begin));
+        }
+    };
+    let result = match solver {
+        Solver::Session => session.finish(task),
+        _ => task.finish(),
+    };
+    Some((solver, result, duration))
+}
+
+async fn step(
+    id: u64,
+    mut view: GameView,
+    solver: Solver,
+    moves: Moves,
+    region: Option<Rect>,
+) -> AutomationResponse {
+    let begin = timestamp();
+    let cached = region.is_none().then(|| view.cached_solve()).flatten();
+    let cache_hit = cached.is_some();
+    // taken out while solving, a concurrent step only loses the cache
+    let mut session = SESSION.take();
+    let mut tier = None;
+    let solved = match (cached, region, solver) {
+        (Some(result), ..) => Some((solver, Ok(result), 0.)),
+        (None, Some(rect), _) => solve(id, &view, view.solve_task_in(rect))
+            .await
+            .map(|task| (Solver::Plain, Ok(task.finish()), timestamp() - begin)),
+        (None, None, Solver::Tiered { budget }) => tiered(id, &view, budget).await.map(|solved| {
+            tier = solved.as_ref().ok().map(|(_, tier)| *tier);
+            (
+                solver,
+                solved.map(|(result, _)| result),
+                timestamp() - begin,
+            )
+        }),
+        (None, None, _) => solve_with(id, &view, solver, &mut session)
+            .await
+            .map(|(winner, result, duration)| (winner, Ok(result), duration)),
+    };
+    SESSION.set(session);
+    let Some((winner, result, duration)) = solved else {
+        BOARD.set(Some(view));
+        return AutomationResponse::Cancelled { id };
+    };
+    let mut diagnostic = None;
+    let result = result.and_then(|result| match view.verify_result(&result) {
+        Ok(()) => Ok(result),
+        Err(step) => {
+            tracing::error!("solver contradicted the board");
+            diagnostic = Some(Box::new(TraceBundle {
+                steps: vec![*step],
+                ..Default::default()
+            }));
+            Err(AutomationError::Solver(
+                "a move contradicts the board".to_string(),
+            ))
+        }
+    });
+    // a tiered solve may leave moves of costlier tiers out, so only full solves are cached
+    if let (None, None, Ok(result)) = (region, tier, &result) {
+        view.remember_solve(result);
+    }
+    let race = (solver == Solver::Race && region.is_none() && !cache_hit).then(|| {
+        RACES.with_borrow_mut(|races| {
+            races.record(winner, duration);
+            *races
+        })
+    });
+    let revision = view.revision();
+    let action = result
+        .and_then(|result| moves.apply(&mut view, result))
+        .map(|_| view.actions().last().cloned().unwrap());
+    BOARD.set(Some(view));
+    AutomationResponse::Step {
+        id,
+        revision,
+        duration: timestamp() - begin,
+        action,
+        race,
+        tier,
+        diagnostic,
+    }
+}
+
+/// Automate boards until stuck or over, returns `None` once cancelled
+async fn benchmark(
+    id: u64,
+    difficulty: Difficulty,
+    boards: u64,
+    seed: u64,
+    solver: Solver,
+    budget: f64,
+) -> Option<BenchmarkStats> {
+    let begin = timestamp();
+    let mut stats = BenchmarkStats::default();
+    let mut session = SolverSession::new();
+    let (x, y) = (difficulty.width() / 2, difficulty.height() / 2);
+    for seed in seed..seed.wrapping_add(boards) {
+        if timestamp() - begin > budget {
+            break;
+        }
+        let mut view = GameView::from(
+            GameOptions {
+                difficulty: difficulty.clone(),
+                safe_pos: Some((x, y)),
+                seed: Some(seed),
+                symmetry: Symmetry::None,
+                learning: false,
+                hidden_count: false,
+                generation: GENERATION_VERSION,
+                limits: BoardLimits::default(),
+            }
+            .build(),
+        );
+        view.left_click(x, y);
+        session.reset();
+        while view.result == GameResult::Playing {
+            let step_begin = timestamp();
+            let (_, result, _) = solve_with(id, &view, solver, &mut session).await?;
+            let moved = view
+                .automation_apply(result, AutomationPolicy::default())
+                .is_ok();
+            let step_duration = timestamp() - step_begin;
+            stats.duration += step_duration;
+            stats.longest_step = stats.longest_step.max(step_duration);
+            if !moved {
+                break;
+            }
+            stats.steps += 1;
+        }
+        stats.boards += 1;
+        if view.result == GameResult::Win {
+            stats.wins += 1;
+        }
+    }
+    Some(stats)
+}
+
+/// Rate boards one round at a time, returns `None` once cancelled
+async fn explore(id: u64, difficulty: Difficulty, seed: u64, boards: u64) -> Option<Vec<Rating>> {
+    let mut ratings = vec![];
+    for seed in seed..seed.wrapping_add(boards) {
+        let mut rater = Rater::new(GameOptions {
+            difficulty: difficulty.clone(),
+            safe_pos: None,
+            seed: Some(seed),
+            symmetry: Symmetry::None,
+            learning: false,
+            hidden_count: false,
+            generation: GENERATION_VERSION,
+            limits: BoardLimits::default(),
+        });
+        while rater.step() {
+            yield_now().await;
+            if is_cancelled(id) {
+                return None;
+            }
+        }
+        ratings.push(rater.finish());
+    }
+    Some(ratings)
+}
+
+/// Bring the kept board up to date, the board is taken out while solving
+fn catch_up(base: usize, actions: Vec<Action>) -> Option<GameView> {
+    let mut view = BOARD.take()?;
+    if view.actions().len() != base {
+        return None;
+    }
+    for action in actions.iter() {
+        view.apply(action);
+    }
+    Some(view)
+}
+
+/// Take one step, or keep stepping when `run`, returns false once the bridge is gone
+async fn respond(
+    scope: &mut ReactorScope<AutomationRequest, AutomationResponse>,
+    id: u64,
+    view: Option<GameView>,
+    run: bool,
+    solver: Solver,
+    moves: Moves,
+    region: Option<Rect>,
+) -> bool {
+    let Some(mut view) = view else {
+        return scope.send(AutomationResponse::Desync { id }).await.is_ok();
+    };
+    loop {
+        if is_cancelled(id) {
+            BOARD.set(Some(view));
+            return scope
+                .send(AutomationResponse::Cancelled { id })
+                .await
+                .is_ok();
+        }
+        let response = step(id, view, solver, moves, region)
+            .instrument(tracing::debug_span!("step", id, ?solver))
+            .await;
+        let stepped = matches!(response, AutomationResponse::Step { action: Ok(_), .. });
+        if scope.send(response).await.is_err() {
+            return false;
+        }
+        if !run || !stepped {
+            return true;
+        }
+        view = match BOARD.take() {
+            Some(view) if view.result == GameResult::Playing => view,
+            board => {
+                BOARD.set(board);
+                return true;
+            }
+        };
+        yield_now().await;
+    }
 }
 
 #[reactor]
-pub async fn Automation(mut scope: ReactorScope<GameView, (f64, GameView, Option<RedrawCells>)>) {
-    while let Some(mut view) = scope.next().await {
-        let begin = timestamp();
-        let redraw = view.automation_step();
-        let result = (timestamp() - begin, view, redraw);
-        if scope.send(result).await.is_err() {
+pub async fn Automation(mut scope: ReactorScope<AutomationRequest, AutomationResponse>) {
+    while let Some(request) = scope.next().await {
+        let connected = match request {
+            AutomationRequest::Sync {
+                id,
+                view,
+                run,
+                solver,
+                moves,
+                region,
+            } => {
+                let view = Some(*view);
+                respond(&mut scope, id, view, run, solver, moves, region).await
+            }
+            AutomationRequest::Step {
+                id,
+                base,
+                actions,
+                run,
+                solver,
+                moves,
+                region,
+            } => {
+                let view = catch_up(base, actions);
+                respond(&mut scope, id, view, run, solver, moves, region).await
+            }
+            AutomationRequest::Solve { id, view, cells } => {
+                let begin = timestamp();
+                let task = view.solve_task_for(cells.into_iter().collect());
+                let solved = solve(id, &view, task)
+                    .instrument(tracing::debug_span!("solve", id))
+                    .await;
+                let response = match solved {
+                    _ if is_cancelled(id) => AutomationResponse::Cancelled { id },
+                    Some(task) => AutomationResponse::Solved {
+                        id,
+                        revision: view.revision(),
+                        duration: timestamp() - begin,
+                        result: task.finish(),
+                    },
+                    None => AutomationResponse::Cancelled { id },
+                };
+                scope.send(response).await.is_ok()
+            }
+            AutomationRequest::Cancel { id } => {
+                CANCELLED.with(|cancelled| cancelled.set(cancelled.get().max(id)));
+                true
+            }
+            AutomationRequest::Reset => {
+                SESSION.with_borrow_mut(SolverSession::reset);
+                true
+            }
+            AutomationRequest::Benchmark {
+                id,
+                difficulty,
+                boards,
+                seed,
+                solver,
+                budget,
+            } => {
+                let response = match benchmark(id, difficulty, boards, seed, solver, budget).await {
+                    Some(stats) => AutomationResponse::Benchmarked { id, stats },
+                    None => AutomationResponse::Cancelled { id },
+                };
+                scope.send(response).await.is_ok()
+            }
+            AutomationRequest::Explore {
+                id,
+                difficulty,
+                seed,
+                boards,
+            } => {
+                let response = match explore(id, difficulty, seed, boards).await {
+                    Some(ratings) => AutomationResponse::Explored { id, ratings },
+                    None => AutomationResponse::Cancelled { id },
+                };
+                scope.send(response).await.is_ok()
+            }
+            AutomationRequest::Ping { version } => {
+                let response = match PANIC.with_borrow(Clone::clone) {
+                    Some(reason) => AutomationResponse::Failed { reason },
+                    None if version != PROTOCOL_VERSION => AutomationResponse::Failed {
+                        reason: format!(
+                            "protocol version {version} is not {PROTOCOL_VERSION} of the worker"
+                        ),
+                    },
+                    None => AutomationResponse::Pong {
+                        version: PROTOCOL_VERSION,
+                        progress: PROGRESS.get(),
+                    },
+                };
+                scope.send(response).await.is_ok()
+            }
+            AutomationRequest::Trace => {
+                let bundle = TraceBundle::take();
+                scope
+                    .send(AutomationResponse::Traced { bundle })
+                    .await
+                    .is_ok()
+            }
+        };
+        if !connected {
             break;
         }
     }
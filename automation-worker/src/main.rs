@@ -1,7 +1,11 @@
-use automation_worker::Automation;
+use automation_protocol::VarintBincode;
+use automation_worker::{set_panic_hook, Automation};
 use gloo_worker::Registrable;
 
 fn main() {
-    console_error_panic_hook::set_once();
-    Automation::registrar().register();
+    set_panic_hook();
+    tracing_wasm::set_as_global_default();
+    Automation::registrar()
+        .encoding::<VarintBincode>()
+        .register();
 }
@@ -0,0 +1,43 @@
+use minesweep_core::{CellView, GameState, GameView};
+
+pub fn parse_view(text: &str) -> Result<GameView, String> {
+    let rows: Vec<Vec<char>> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.chars().collect())
+        .collect();
+    let Some(width) = rows.first().map(Vec::len) else {
+        return Err("board is empty".to_string());
+    };
+    if rows.iter().any(|row| row.len() != width) {
+        return Err("rows of the board have different lengths".to_string());
+    }
+    let mut view = GameView::from(GameState::empty(width, rows.len()));
+    for (y, row) in rows.into_iter().enumerate() {
+        for (x, c) in row.into_iter().enumerate() {
+            let cell = match c {
+                '#' => CellView::Unopened,
+                'F' => CellView::Flagged,
+                '?' => CellView::Questioned,
+                '.' => CellView::Opened(0),
+                '0'..='8' => CellView::Opened(c as u8 - b'0'),
+                c => return Err(format!("invalid character {c:?} in board")),
+            };
+            view.set_cell(x, y, cell);
+        }
+    }
+    Ok(view)
+}
+
+pub fn cell_char(cell: CellView) -> char {
+    use CellView::*;
+    match cell {
+        Unopened | Hovered | Pushed => '#',
+        Flagged => 'F',
+        Questioned => '?',
+        Opened(0) => '.',
+        Opened(n) => (b'0' + n) as char,
+        Mine | WrongMine | Exploded => '*',
+    }
+}
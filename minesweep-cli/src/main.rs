@@ -0,0 +1,488 @@
+mod analysis;
+
+use std::{
+    fs,
+    path::PathBuf,
+    process::ExitCode,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use minesweep_core::{
+    AutomationPolicy, BoardLimits, Difficulty, GameOptions, GameResult, GameState, GameView,
+    GenerationStats, PuzzlePack, QuestionHints, SolveResult, SolverSession, Symmetry, TraceBundle,
+    GENERATION_VERSION, TRACE_BUNDLE_VERSION,
+};
+
+use analysis::{cell_char, parse_view};
+
+#[cfg(feature = "alloc-count")]
+#[global_allocator]
+static ALLOCATOR: minesweep_core::CountingAllocator = minesweep_core::CountingAllocator;
+
+/// Allocations so far, zero unless built with the `alloc-count` feature
+fn allocations() -> usize {
+    #[cfg(feature = "alloc-count")]
+    return minesweep_core::allocations();
+    #[cfg(not(feature = "alloc-count"))]
+    0
+}
+
+#[derive(Debug, Parser)]
+#[command(about = "Generate, solve, benchmark and rate Minesweeper boards")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print a board as text, `*` for mine and `.` for safe cell
+    Generate {
+        #[command(flatten)]
+        board: BoardArgs,
+        /// Retry following seeds until the board clears without guessing from the center
+        #[arg(long)]
+        no_guess: bool,
+        #[arg(long, default_value_t = 1000)]
+        attempts: u64,
+    },
+    /// Print what is known about a board in play, read from a file with one line per row,
+    /// `#` for intact cell, `F` for flag, `?` for question mark and `.` or digits for opened cell
+    Solve {
+        file: PathBuf,
+        /// Rank guesses taking question marks as cells the player suspects to be mines
+        #[arg(long)]
+        suspect: bool,
+    },
+    /// Automate random boards with each solver and compare timings
+    Bench {
+        #[command(flatten)]
+        board: BoardArgs,
+        #[arg(long, default_value_t = 20)]
+        boards: u64,
+    },
+    /// Rate a board from a file or a generated one by how much deduction it takes
+    Rate {
+        #[command(flatten)]
+        board: BoardArgs,
+        /// Board text as printed by `generate`
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Print a puzzle pack as JSON, each puzzle is a position with one forced move left
+    /// that takes more than a single number to find
+    Puzzles {
+        #[command(flatten)]
+        board: BoardArgs,
+        #[arg(long, default_value = "puzzles")]
+        name: String,
+        #[arg(long, default_value_t = 10)]
+        count: usize,
+        #[arg(long, default_value_t = 1000)]
+        attempts: u64,
+    },
+    /// Build boards from consecutive seeds and report how evenly mines are spread over the
+    /// cells and how much the first click opens
+    Fairness {
+        #[command(flatten)]
+        board: BoardArgs,
+        #[arg(long, default_value_t = 1000)]
+        boards: u64,
+    },
+    /// Solve every step of a trace bundle or diagnostic downloaded from the app again and
+    /// report differences and moves contradicting the board
+    Trace {
+        file: PathBuf,
+        /// Directory to write the CNF of each failing step to, as `step-<n>.cnf`
+        #[arg(long)]
+        dimacs: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Level {
+    Easy,
+    Medium,
+    Hard,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Mirror {
+    None,
+    Horizontal,
+    Vertical,
+    Rotational,
+}
+
+#[derive(Debug, Args)]
+struct BoardArgs {
+    #[arg(long, value_enum, default_value_t = Level::Medium)]
+    difficulty: Level,
+    /// Custom size along with mines or density, replacing the difficulty
+    #[arg(long, requires_all = ["height", "amount"])]
+    width: Option<usize>,
+    #[arg(long, requires_all = ["width", "amount"])]
+    height: Option<usize>,
+    #[arg(long, group = "amount", requires_all = ["width", "height"])]
+    mines: Option<usize>,
+    /// Percentage of cells with mine, such as 20
+    #[arg(long, group = "amount", requires_all = ["width", "height"])]
+    density: Option<f64>,
+    /// Random when not given
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Keep the mine layout symmetric
+    #[arg(long, value_enum, default_value_t = Mirror::None)]
+    symmetry: Mirror,
+}
+
+impl BoardArgs {
+    fn difficulty(&self) -> Result<Difficulty, String> {
+        let difficulty = match (self.width, self.height, self.mines, self.density) {
+            (Some(width), Some(height), Some(mines), _) => Difficulty::Custom {
+                width,
+                height,
+                mines,
+            },
+            (Some(width), Some(height), _, Some(percent)) => Difficulty::Density {
+                width,
+                height,
+                density: percent / 100.,
+            },
+            _ => match self.difficulty {
+                Level::Easy => Difficulty::Easy,
+                Level::Medium => Difficulty::Medium,
+                Level::Hard => Difficulty::Hard,
+            },
+        };
+        difficulty.validate().map_err(|err| err.to_string())?;
+        Ok(difficulty)
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        match self.symmetry {
+            Mirror::None => Symmetry::None,
+            Mirror::Horizontal => Symmetry::Horizontal,
+            Mirror::Vertical => Symmetry::Vertical,
+            Mirror::Rotational => Symmetry::Rotational,
+        }
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos() as u64
+        })
+    }
+}
+
+fn center(difficulty: &Difficulty) -> (usize, usize) {
+    (difficulty.width() / 2, difficulty.height() / 2)
+}
+
+fn build(difficulty: &Difficulty, symmetry: Symmetry, seed: u64) -> GameState {
+    GameOptions {
+        difficulty: difficulty.clone(),
+        safe_pos: Some(center(difficulty)),
+        seed: Some(seed),
+        symmetry,
+        learning: false,
+        hidden_count: false,
+        generation: GENERATION_VERSION,
+        limits: BoardLimits::default(),
+    }
+    .build()
+}
+
+/// Whether the moves agree with the board, otherwise the step is written to a diagnostic file
+/// for `trace` to solve again
+fn verify(view: &GameView, result: &SolveResult) -> bool {
+    let Err(step) = view.verify_result(result) else {
+        return true;
+    };
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    let path = format!("diagnostic-{millis}.json");
+    let bundle = TraceBundle {
+        steps: vec![*step],
+        ..Default::default()
+    };
+    match fs::write(&path, serde_json::to_string(&bundle).unwrap()) {
+        Ok(()) => eprintln!("solver contradicted the board, step written to {path}"),
+        Err(err) => eprintln!("solver contradicted the board, {path}: {err}"),
+    }
+    false
+}
+
+/// Open `start` and automate until stuck, returns the view and the rounds of deduction taken
+fn automate(state: GameState, start: (usize, usize)) -> (GameView, usize) {
+    let mut view = GameView::from(state);
+    view.left_click(start.0, start.1);
+    let mut rounds = 0;
+    while view.result == GameResult::Playing {
+        let result = view.solve_cached();
+        if !verify(&view, &result)
+            || view
+                .automation_apply(result, AutomationPolicy::default())
+                .is_err()
+        {
+            break;
+        }
+        rounds += 1;
+    }
+    (view, rounds)
+}
+
+fn generate(board: BoardArgs, no_guess: bool, attempts: u64) -> Result<(), String> {
+    let difficulty = board.difficulty()?;
+    let seed = board.seed();
+    for seed in seed..seed.wrapping_add(if no_guess { attempts } else { 1 }) {
+        let state = build(&difficulty, board.symmetry(), seed);
+        if no_guess && automate(state.clone(), center(&difficulty)).0.result != GameResult::Win {
+            continue;
+        }
+        let (x, y) = center(&difficulty);
+        eprintln!("{difficulty}, seed {seed}, start at {x},{y}");
+        println!("{}", state.to_board_text());
+        return Ok(());
+    }
+    Err(format!("no board without guessing in {attempts} attempts"))
+}
+
+fn solve(file: PathBuf, suspect: bool) -> Result<(), String> {
+    let text = fs::read_to_string(&file).map_err(|err| format!("{}: {err}", file.display()))?;
+    let view = parse_view(&text)?;
+    let SolveResult {
+        must_be_mine,
+        must_not_mine,
+    } = view.solve();
+    for y in 0..view.height() {
+        let row: String = (0..view.width())
+            .map(|x| {
+                if must_be_mine.contains(&(x, y)) {
+                    'X'
+                } else if must_not_mine.contains(&(x, y)) {
+                    'o'
+                } else {
+                    cell_char(view.cell(x, y))
+                }
+            })
+            .collect();
+        println!("{row}");
+    }
+    println!(
+        "{} safe (o), {} mines (X)",
+        must_not_mine.len(),
+        must_be_mine.len()
+    );
+    let hints = match suspect {
+        true => QuestionHints::Suspect,
+        false => QuestionHints::Ignore,
+    };
+    let mut probabilities = view.mine_probabilities(hints);
+    probabilities.retain(|(cell, _)| !must_be_mine.contains(cell) && !must_not_mine.contains(cell));
+    probabilities.sort_by(|a, b| a.1.total_cmp(&b.1));
+    for ((x, y), probability) in probabilities {
+        println!("{x},{y} {:.1}%", probability * 100.);
+    }
+    Ok(())
+}
+
+fn bench(board: BoardArgs, boards: u64) -> Result<(), String> {
+    let difficulty = board.difficulty()?;
+    let seed = board.seed();
+    println!("solver   boards  wins  steps  total(s)  mean(ms)  clauses/step  allocs/board");
+    for (name, use_session) in [("plain", false), ("session", true)] {
+        let (mut wins, mut steps, mut clauses) = (0, 0, 0);
+        let begin = Instant::now();
+        let allocations_begin = allocations();
+        for seed in seed..seed.wrapping_add(boards) {
+            let (x, y) = center(&difficulty);
+            let mut view = GameView::from(build(&difficulty, board.symmetry(), seed));
+            view.left_click(x, y);
+            let mut session = SolverSession::new();
+            while view.result == GameResult::Playing {
+                let mut task = match use_session {
+                    true => session.solve_task(&view),
+                    false => view.solve_task(),
+                };
+                clauses += task.clauses();
+                while task.step(&view) {}
+                let result = match use_session {
+                    true => session.finish(task),
+                    false => task.finish(),
+                };
+                if !verify(&view, &result)
+                    || view
+                        .automation_apply(result, AutomationPolicy::default())
+                        .is_err()
+                {
+                    break;
+                }
+                steps += 1;
+            }
+            wins += (view.result == GameResult::Win) as u64;
+        }
+        let total = begin.elapsed().as_secs_f64();
+        let allocs = (allocations() - allocations_begin) as u64 / boards.max(1);
+        println!(
+            "{name:<8} {boards:>6} {wins:>5} {steps:>6} {total:>9.3} {:>9.1} {:>13} {allocs:>13}",
+            total * 1000. / boards.max(1) as f64,
+            clauses / steps.max(1)
+        );
+    }
+    Ok(())
+}
+
+fn rate(board: BoardArgs, file: Option<PathBuf>) -> Result<(), String> {
+    let (state, start) = match file {
+        Some(file) => {
+            let text =
+                fs::read_to_string(&file).map_err(|err| format!("{}: {err}", file.display()))?;
+            let state = GameState::from_board_text(&text).map_err(|err| err.to_string())?;
+            let start = state.suggested_start().ok_or("board has no safe cell")?;
+            (state, start)
+        }
+        None => {
+            let difficulty = board.difficulty()?;
+            (
+                build(&difficulty, board.symmetry(), board.seed()),
+                center(&difficulty),
+            )
+        }
+    };
+    let (w, h, mines) = (state.width(), state.height(), state.mines());
+    let bv = state.bbbv();
+    let begin = Instant::now();
+    let (view, rounds) = automate(state, start);
+    let time = begin.elapsed().as_secs_f64();
+    println!(
+        "size {w}x{h}, {mines} mines, density {:.1}%",
+        mines as f64 * 100. / (w * h) as f64
+    );
+    println!("3BV {bv}");
+    match view.result {
+        GameResult::Win => println!("clears without guessing in {rounds} rounds of deduction"),
+        _ => println!("needs guessing after {rounds} rounds of deduction"),
+    }
+    println!("solved in {time:.3}s");
+    Ok(())
+}
+
+fn puzzles(board: BoardArgs, name: String, count: usize, attempts: u64) -> Result<(), String> {
+    let pack = PuzzlePack::generate(name, board.difficulty()?, board.seed(), count, attempts);
+    if pack.puzzles.len() < count {
+        eprintln!(
+            "only {} puzzles found in {attempts} attempts",
+            pack.puzzles.len()
+        );
+    }
+    println!(
+        "{}",
+        serde_json::to_string(&pack).map_err(|err| err.to_string())?
+    );
+    Ok(())
+}
+
+fn fairness(board: BoardArgs, boards: u64) -> Result<(), String> {
+    let difficulty = board.difficulty()?;
+    let seed = board.seed();
+    let options = GameOptions {
+        difficulty: difficulty.clone(),
+        safe_pos: Some(center(&difficulty)),
+        seed: None,
+        symmetry: board.symmetry(),
+        learning: false,
+        hidden_count: false,
+        generation: GENERATION_VERSION,
+        limits: BoardLimits::default(),
+    };
+    let stats = GenerationStats::collect(&options, seed..seed.wrapping_add(boards));
+    let (statistic, freedom) = stats.uniformity();
+    let z = stats.uniformity_z();
+    println!("{difficulty}, {boards} boards from seed {seed}");
+    println!("chi-square {statistic:.1} with {freedom} degrees of freedom, z {z:.2}");
+    if z.abs() > 4. {
+        println!("mines are not spread evenly");
+    }
+    let max_opening = stats.opening_sizes.iter().max().copied().unwrap_or(0);
+    println!(
+        "first click opens {:.1} cells on average, {max_opening} at most",
+        stats.mean_opening()
+    );
+    Ok(())
+}
+
+fn trace(file: PathBuf, dimacs: Option<PathBuf>) -> Result<(), String> {
+    let text = fs::read_to_string(&file).map_err(|err| format!("{}: {err}", file.display()))?;
+    let bundle: TraceBundle = serde_json::from_str(&text).map_err(|err| err.to_string())?;
+    if bundle.version != TRACE_BUNDLE_VERSION {
+        return Err(format!(
+            "trace bundle version {} is not {TRACE_BUNDLE_VERSION}",
+            bundle.version
+        ));
+    }
+    let mut failing = 0;
+    for (i, step) in bundle.steps.iter().enumerate() {
+        let contradicts = step.view.verify_result(&step.result).is_err();
+        let reproduced = step.reproduce();
+        if !contradicts && reproduced.is_ok() {
+            continue;
+        }
+        failing += 1;
+        if contradicts {
+            println!("step {i}: traced moves contradict the board");
+            if let Some(completion) = step.completion() {
+                println!("step {i}: the frontier allows {completion}");
+            }
+        }
+        if let Err(result) = reproduced {
+            println!(
+                "step {i}: traced {} safe, {} mines, solved {} safe, {} mines",
+                step.result.must_not_mine.len(),
+                step.result.must_be_mine.len(),
+                result.must_not_mine.len(),
+                result.must_be_mine.len()
+            );
+        }
+        if let Some(dir) = &dimacs {
+            let path = dir.join(format!("step-{i}.cnf"));
+            fs::write(&path, &step.dimacs).map_err(|err| format!("{}: {err}", path.display()))?;
+        }
+    }
+    println!("{failing} of {} steps fail", bundle.steps.len());
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Generate {
+            board,
+            no_guess,
+            attempts,
+        } => generate(board, no_guess, attempts),
+        Command::Solve { file, suspect } => solve(file, suspect),
+        Command::Bench { board, boards } => bench(board, boards),
+        Command::Rate { board, file } => rate(board, file),
+        Command::Puzzles {
+            board,
+            name,
+            count,
+            attempts,
+        } => puzzles(board, name, count, attempts),
+        Command::Fairness { board, boards } => fairness(board, boards),
+        Command::Trace { file, dimacs } => trace(file, dimacs),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
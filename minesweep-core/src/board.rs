@@ -0,0 +1,802 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    hash::{Hash, Hasher},
+};
+
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    thread_rng, RngCore, SeedableRng,
+};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::BoardError;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Custom {
+        width: usize,
+        height: usize,
+        mines: usize,
+    },
+    /// Mines as a fraction of the area, rounded to the nearest count
+    Density {
+        width: usize,
+        height: usize,
+        density: f64,
+    },
+}
+
+impl Hash for Difficulty {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        use Difficulty::*;
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Easy | Medium | Hard => {}
+            Custom {
+                width,
+                height,
+                mines,
+            } => (width, height, mines).hash(state),
+            Density {
+                width,
+                height,
+                density,
+            } => (width, height, density.to_bits()).hash(state),
+        }
+    }
+}
+
+impl Difficulty {
+    pub fn width(&self) -> usize {
+        use Difficulty::*;
+        match self {
+            Easy => 9,
+            Medium => 16,
+            Hard => 30,
+            Custom { width, .. } | Density { width, .. } => *width,
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        use Difficulty::*;
+        match self {
+            Easy => 9,
+            Medium => 16,
+            Hard => 16,
+            Custom { height, .. } | Density { height, .. } => *height,
+        }
+    }
+
+    pub fn mines(&self) -> usize {
+        use Difficulty::*;
+        match self {
+            Easy => 10,
+            Medium => 40,
+            Hard => 99,
+            Custom { mines, .. } => *mines,
+            Density {
+                width,
+                height,
+                density,
+            } => (width.saturating_mul(*height) as f64 * density)
+                .round()
+                .max(0.) as usize,
+        }
+    }
+
+    /// Mines per cell
+    pub fn density(&self) -> f64 {
+        self.mines() as f64 / self.width().saturating_mul(self.height()).max(1) as f64
+    }
+
+    /// Whether a board can be built, see [`GameOptions::build`]
+    pub fn validate(&self) -> Result<(), BoardError> {
+        let (w, h, mines) = (self.width(), self.height(), self.mines());
+        if w == 0 || h == 0 {
+            Err(BoardError::Empty)
+        } else if mines == 0 {
+            Err(BoardError::NoMine)
+        } else if mines >= w.saturating_mul(h) {
+            Err(BoardError::NoSafeCell)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Standard difficulties or a custom one in place of the other
+    pub fn is_standard(&self) -> bool {
+        matches!(
+            self,
+            Difficulty::Easy | Difficulty::Medium | Difficulty::Hard
+        )
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Difficulty::*;
+        let (w, h, mines) = (self.width(), self.height(), self.mines());
+        match self {
+            Easy => write!(f, "Easy"),
+            Medium => write!(f, "Medium"),
+            Hard => write!(f, "Hard"),
+            Custom { .. } => write!(f, "{w}x{h}, {mines} mines"),
+            Density { density, .. } => {
+                write!(f, "{w}x{h}, {:.0}% mines ({mines})", density * 100.)
+            }
+        }
+    }
+}
+
+/// Mirror image the mine layout is kept identical under
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Symmetry {
+    #[default]
+    None,
+    /// Left half mirrors the right half
+    Horizontal,
+    /// Top half mirrors the bottom half
+    Vertical,
+    /// Same after turning the board half a turn
+    Rotational,
+}
+
+impl Symmetry {
+    /// Cell that (x, y) maps to on a `w` by `h` board
+    pub fn mirror(&self, x: usize, y: usize, w: usize, h: usize) -> (usize, usize) {
+        match self {
+            Symmetry::None => (x, y),
+            Symmetry::Horizontal => (w - 1 - x, y),
+            Symmetry::Vertical => (x, h - 1 - y),
+            Symmetry::Rotational => (w - 1 - x, h - 1 - y),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Hash, Serialize, Deserialize)]
+pub struct GameOptions {
+    pub difficulty: Difficulty,
+    pub safe_pos: Option<(usize, usize)>,
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub symmetry: Symmetry,
+    /// Refuse flags on cells without a mine, for beginners
+    #[serde(default)]
+    pub learning: bool,
+    /// Keep the total number of mines from the player, only what is shown changes: the solver
+    /// never uses the count and serialized views still carry the whole board
+    #[serde(default)]
+    pub hidden_count: bool,
+    /// Algorithm placing the mines, options saved before versioning build with the first one
+    #[serde(default)]
+    pub generation: u32,
+    /// Largest board `try_build` accepts, up to whoever builds it so never saved
+    #[serde(skip)]
+    pub limits: BoardLimits,
+}
+
+/// Version of the mine placement of new games, see `GameOptions::build`
+pub const GENERATION_VERSION: u32 = 1;
+
+/// Boards with more cells than this skip the groups of mirrored cells when they have none
+const STREAMED_AREA: usize = 1 << 16;
+
+/// Bounds on the size of boards, so that a mistyped custom size fails instead of running out
+/// of memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BoardLimits {
+    pub max_area: usize,
+    pub max_mines: usize,
+}
+
+impl Default for BoardLimits {
+    fn default() -> Self {
+        Self {
+            max_area: 1_000_000,
+            max_mines: 500_000,
+        }
+    }
+}
+
+impl BoardLimits {
+    pub fn check(&self, difficulty: &Difficulty) -> Result<(), BoardError> {
+        let area = difficulty.width().saturating_mul(difficulty.height());
+        if area > self.max_area {
+            Err(BoardError::TooLarge {
+                area,
+                max: self.max_area,
+            })
+        } else if difficulty.mines() > self.max_mines {
+            Err(BoardError::TooManyMines {
+                mines: difficulty.mines(),
+                max: self.max_mines,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Default for GameOptions {
+    fn default() -> Self {
+        Self::easy()
+    }
+}
+
+impl GameOptions {
+    pub fn easy() -> Self {
+        Self {
+            difficulty: Difficulty::Easy,
+            safe_pos: None,
+            seed: None,
+            symmetry: Symmetry::None,
+            learning: false,
+            hidden_count: false,
+            generation: GENERATION_VERSION,
+            limits: BoardLimits::default(),
+        }
+    }
+
+    pub fn medium() -> Self {
+        Self {
+            difficulty: Difficulty::Medium,
+            safe_pos: None,
+            seed: None,
+            symmetry: Symmetry::None,
+            learning: false,
+            hidden_count: false,
+            generation: GENERATION_VERSION,
+            limits: BoardLimits::default(),
+        }
+    }
+
+    pub fn hard() -> Self {
+        Self {
+            difficulty: Difficulty::Hard,
+            safe_pos: None,
+            seed: None,
+            symmetry: Symmetry::None,
+            learning: false,
+            hidden_count: false,
+            generation: GENERATION_VERSION,
+            limits: BoardLimits::default(),
+        }
+    }
+
+    /// Panics when width, height or mines is zero, when every cell would be filled with mine,
+    /// or when the board is over the limits, see `try_build`
+    ///
+    /// Symmetric boards place mines in mirrored pairs, so one mine less is placed when the
+    /// count cannot be made up of pairs and cells on the mirror axis.
+    ///
+    /// The same options always build the same board, for each `generation`:
+    ///
+    /// - 0: relies on the sampling and shuffling of `rand`, which may change between its releases
+    /// - 1: the rng is `ChaCha12Rng::seed_from_u64(seed)`, both portable by their contract.
+    ///   Cells are grouped with their mirror images, listed in row-major order of the cell
+    ///   of each group with the smallest `(x, y)`, the group holding the safe position is
+    ///   left out. The groups are shuffled
+    ///   by swapping each index `i` from the start with `i + uniform(len - i)`, where
+    ///   `uniform(n)` takes `next_u64` until it is below the largest multiple of `n` and
+    ///   returns it modulo `n`. Groups are then taken in order whenever they fit in the mines
+    ///   left to place.
+    ///
+    /// Later generations build as the latest this release knows.
+    pub fn build(self) -> GameState {
+        match self.try_build() {
+            Ok(state) => state,
+            Err(err) => panic!("invalid game options: {err}"),
+        }
+    }
+
+    /// Whether `try_build` succeeds, short of running out of memory
+    pub fn validate(&self) -> Result<(), BoardError> {
+        self.difficulty.validate()?;
+        self.limits.check(&self.difficulty)
+    }
+
+    /// Build the board as `build` does, or tell why it cannot be built
+    pub fn try_build(mut self) -> Result<GameState, BoardError> {
+        self.validate()?;
+        let w = self.difficulty.width();
+        let h = self.difficulty.height();
+        let mines = self.difficulty.mines();
+        let seed = match self.seed {
+            Some(seed) => seed,
+            None => thread_rng().next_u64(),
+        };
+        self.seed = Some(seed);
+        let mut rng = ChaCha12Rng::seed_from_u64(seed);
+        let mines_pos =
+            if self.generation > 0 && self.symmetry == Symmetry::None && w * h > STREAMED_AREA {
+                self.single_cell_mines(&mut rng, mines)?
+            } else if self.generation > 0 {
+                let mut groups = self.mine_groups();
+                stable_shuffle(&mut groups, &mut rng);
+                fill_groups(groups, mines)
+            } else if self.symmetry == Symmetry::None {
+                let mut mines_pos = (0..h)
+                    .flat_map(|y| (0..w).map(move |x| (x, y)))
+                    .choose_multiple(&mut rng, mines + 1);
+                if let Some(safe_pos) = self.safe_pos {
+                    if let Some(p) = mines_pos.iter().position(|&p| p == safe_pos) {
+                        mines_pos.remove(p);
+                    }
+                }
+                if mines_pos.len() > mines {
+                    mines_pos.shuffle(&mut rng);
+                    mines_pos.pop();
+                }
+                mines_pos
+            } else {
+                self.symmetric_mines(&mut rng, mines)
+            };
+        let mut state = GameState {
+            options: self,
+            mines: try_grid(w, h, false)?,
+            cells: try_grid(w, h, CellState::Unopened)?,
+        };
+        for (x, y) in mines_pos {
+            state.mines[y][x] = true;
+        }
+        Ok(state)
+    }
+
+    /// Same mines as generation 1 places with one group per cell, with a single index per
+    /// cell instead of the groups, only the first `mines` swaps are made since later ones
+    /// never reach the front
+    fn single_cell_mines(
+        &self,
+        rng: &mut ChaCha12Rng,
+        mines: usize,
+    ) -> Result<Vec<(usize, usize)>, BoardError> {
+        let w = self.difficulty.width();
+        let area = w * self.difficulty.height();
+        let mut cells = Vec::new();
+        cells
+            .try_reserve_exact(area)
+            .map_err(|_| BoardError::OutOfMemory)?;
+        cells.extend((0..area).filter(|i| self.safe_pos != Some((i % w, i / w))));
+        let mines = mines.min(cells.len());
+        for i in 0..mines {
+            let j = i + stable_uniform(rng, cells.len() - i);
+            cells.swap(i, j);
+        }
+        Ok(cells[..mines].iter().map(|i| (i % w, i / w)).collect())
+    }
+
+    /// Pick mirrored groups of cells until the mines run out, leaving the group of the safe
+    /// position alone
+    fn symmetric_mines(&self, rng: &mut ChaCha12Rng, mines: usize) -> Vec<(usize, usize)> {
+        let mut groups = self.mine_groups();
+        groups.shuffle(rng);
+        fill_groups(groups, mines)
+    }
+
+    /// Cells grouped with their mirror images in row-major order, without the group of the
+    /// safe position
+    pub(crate) fn mine_groups(&self) -> Vec<Vec<(usize, usize)>> {
+        let (w, h) = (self.difficulty.width(), self.difficulty.height());
+        (0..h)
+            .flat_map(|y| (0..w).map(move |x| (x, y)))
+            .filter_map(|(x, y)| {
+                let mirror = self.symmetry.mirror(x, y, w, h);
+                match (x, y).cmp(&mirror) {
+                    Ordering::Less => Some(vec![(x, y), mirror]),
+                    Ordering::Equal => Some(vec![(x, y)]),
+                    Ordering::Greater => None,
+                }
+            })
+            .filter(|group| {
+                self.safe_pos
+                    .is_none_or(|safe_pos| !group.contains(&safe_pos))
+            })
+            .collect()
+    }
+}
+
+/// Rows of cells allocated one by one, failing instead of aborting when memory runs out
+fn try_grid<T: Clone>(w: usize, h: usize, value: T) -> Result<Vec<Vec<T>>, BoardError> {
+    let mut grid = Vec::new();
+    grid.try_reserve_exact(h)
+        .map_err(|_| BoardError::OutOfMemory)?;
+    for _ in 0..h {
+        let mut row = Vec::new();
+        row.try_reserve_exact(w)
+            .map_err(|_| BoardError::OutOfMemory)?;
+        row.resize(w, value.clone());
+        grid.push(row);
+    }
+    Ok(grid)
+}
+
+/// Take groups in order whenever they fit in the mines left
+fn fill_groups(groups: Vec<Vec<(usize, usize)>>, mines: usize) -> Vec<(usize, usize)> {
+    let mut remaining = mines;
+    let mut mines_pos = vec![];
+    for group in groups {
+        if group.len() <= remaining {
+            remaining -= group.len();
+            mines_pos.extend(group);
+        }
+    }
+    mines_pos
+}
+
+/// Uniform in `0..n` by rejection, unlike `gen_range` its output is fixed by this code
+fn stable_uniform(rng: &mut ChaCha12Rng, n: usize) -> usize {
+    let n = n as u64;
+    let zone = u64::MAX - u64::MAX % n;
+    loop {
+        let value = rng.next_u64();
+        if value < zone {
+            return (value % n) as usize;
+        }
+    }
+}
+
+/// Fisher-Yates shuffle from the front, unlike `shuffle` its output is fixed by this code
+fn stable_shuffle<T>(items: &mut [T], rng: &mut ChaCha12Rng) {
+    for i in 0..items.len() {
+        let j = i + stable_uniform(rng, items.len() - i);
+        items.swap(i, j);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Hash, Serialize, Deserialize)]
+pub enum CellState {
+    Unopened,
+    Flagged,
+    Questioned,
+    Opened,
+}
+
+#[derive(Debug, Clone, PartialEq, Hash, Serialize, Deserialize)]
+pub struct GameState {
+    pub options: GameOptions,
+    pub mines: Vec<Vec<bool>>,
+    pub(crate) cells: Vec<Vec<CellState>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Hash, Serialize, Deserialize)]
+pub enum GameResult {
+    Win,
+    Lose,
+    Playing,
+}
+
+impl GameState {
+    pub fn width(&self) -> usize {
+        self.mines[0].len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.mines.len()
+    }
+
+    pub fn is_mine(&self, x: usize, y: usize) -> bool {
+        self.mines[y][x]
+    }
+
+    pub fn mines(&self) -> usize {
+        let mut mines = 0;
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if self.is_mine(x, y) {
+                    mines += 1;
+                }
+            }
+        }
+        mines
+    }
+
+    pub fn flags(&self) -> usize {
+        let mut flags = 0;
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if self.is_flag(x, y) {
+                    flags += 1;
+                }
+            }
+        }
+        flags
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> CellState {
+        self.cells[y][x]
+    }
+
+    pub fn set_cell(&mut self, x: usize, y: usize, state: CellState) {
+        self.cells[y][x] = state;
+    }
+
+    pub fn nearby_cells(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        self.nearby(x, y).collect()
+    }
+
+    /// Same cells as `nearby_cells` without allocating, for hot loops
+    pub(crate) fn nearby(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (w, h) = (self.width(), self.height());
+        let xs = x.saturating_sub(1)..(x + 2).min(w);
+        (y.saturating_sub(1)..(y + 2).min(h))
+            .flat_map(move |y1| xs.clone().map(move |x1| (x1, y1)))
+            .filter(move |&cell| cell != (x, y))
+    }
+
+    pub fn nearby_mines(&self, x: usize, y: usize) -> u8 {
+        self.nearby(x, y)
+            .filter(|(x, y)| self.is_mine(*x, *y))
+            .count() as u8
+    }
+
+    /// Fewest clicks that clear the board (3BV), one per opening and one per safe cell outside
+    /// every opening
+    pub fn bbbv(&self) -> usize {
+        let (w, h) = (self.width(), self.height());
+        let mut covered = vec![vec![false; w]; h];
+        let mut clicks = 0;
+        for y in 0..h {
+            for x in 0..w {
+                if covered[y][x] || self.is_mine(x, y) || self.nearby_mines(x, y) != 0 {
+                    continue;
+                }
+                clicks += 1;
+                covered[y][x] = true;
+                let mut stack = vec![(x, y)];
+                while let Some((x, y)) = stack.pop() {
+                    for (x1, y1) in self.nearby(x, y) {
+                        if covered[y1][x1] {
+                            continue;
+                        }
+                        covered[y1][x1] = true;
+                        if self.nearby_mines(x1, y1) == 0 {
+                            stack.push((x1, y1));
+                        }
+                    }
+                }
+            }
+        }
+        let outside = (0..h)
+            .flat_map(|y| (0..w).map(move |x| (x, y)))
+            .filter(|&(x, y)| !covered[y][x] && !self.is_mine(x, y))
+            .count();
+        clicks + outside
+    }
+
+    pub fn is_flag(&self, x: usize, y: usize) -> bool {
+        self.cell(x, y) == CellState::Flagged
+    }
+
+    pub fn nearby_flags(&self, x: usize, y: usize) -> u8 {
+        self.nearby(x, y)
+            .filter(|(x, y)| self.is_flag(*x, *y))
+            .count() as u8
+    }
+
+    pub fn is_opened(&self, x: usize, y: usize) -> bool {
+        self.cell(x, y) == CellState::Opened
+    }
+
+    pub fn is_exploded(&self, x: usize, y: usize) -> bool {
+        self.is_opened(x, y) && self.is_mine(x, y)
+    }
+
+    pub fn game_result(&self) -> GameResult {
+        let mut cont = false;
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                match (self.is_opened(x, y), self.is_mine(x, y)) {
+                    (false, false) => cont = true,
+                    (true, false) => (),
+                    (false, true) => (),
+                    (true, true) => return GameResult::Lose,
+                }
+            }
+        }
+        if cont {
+            GameResult::Playing
+        } else {
+            GameResult::Win
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn new_game() {
+        let options = GameOptions {
+            difficulty: Difficulty::Custom {
+                width: 3,
+                height: 3,
+                mines: 3,
+            },
+            seed: Some(1),
+            generation: 0,
+            ..GameOptions::easy()
+        };
+        let state = options.clone().build();
+        assert_eq!(
+            state,
+            GameState {
+                options,
+                mines: vec![
+                    vec![true, false, false],
+                    vec![false, false, false],
+                    vec![true, true, false]
+                ],
+                cells: vec![
+                    vec![
+                        CellState::Unopened,
+                        CellState::Unopened,
+                        CellState::Unopened
+                    ];
+                    3
+                ],
+            }
+        )
+    }
+
+    #[test]
+    fn bbbv() {
+        let state = GameState::from_board_text("*.*..\n.....\n.....").unwrap();
+        assert_eq!(state.bbbv(), 2);
+        let state = GameState::from_board_text("*.*\n...\n*.*").unwrap();
+        assert_eq!(state.bbbv(), 5);
+    }
+
+    #[test]
+    fn symmetry() {
+        for symmetry in [
+            Symmetry::Horizontal,
+            Symmetry::Vertical,
+            Symmetry::Rotational,
+        ] {
+            let state = GameOptions {
+                safe_pos: Some((0, 0)),
+                seed: Some(1),
+                symmetry,
+                generation: 0,
+                ..GameOptions::easy()
+            }
+            .build();
+            assert_eq!(state.mines(), 10);
+            assert!(!state.is_mine(0, 0));
+            for (x, y) in (0..9).flat_map(|y| (0..9).map(move |x| (x, y))) {
+                let (x1, y1) = symmetry.mirror(x, y, 9, 9);
+                assert_eq!(state.is_mine(x, y), state.is_mine(x1, y1));
+            }
+        }
+        // no cell maps to itself on even sides, so the odd count loses a mine
+        let state = GameOptions {
+            symmetry: Symmetry::Rotational,
+            learning: false,
+            generation: 0,
+            ..GameOptions::hard()
+        }
+        .build();
+        assert_eq!(state.mines(), 98);
+    }
+
+    #[test]
+    fn density() {
+        let difficulty = Difficulty::Density {
+            width: 10,
+            height: 10,
+            density: 0.2,
+        };
+        assert_eq!(difficulty.mines(), 20);
+        assert_eq!(difficulty.validate(), Ok(()));
+        assert_eq!(difficulty.to_string(), "10x10, 20% mines (20)");
+        assert_eq!(
+            GameOptions {
+                difficulty,
+                seed: Some(1),
+                generation: 0,
+                ..GameOptions::easy()
+            }
+            .build()
+            .mines(),
+            20
+        );
+        let full = Difficulty::Density {
+            width: 3,
+            height: 3,
+            density: 1.,
+        };
+        assert_eq!(full.validate(), Err(BoardError::NoSafeCell));
+        assert!(!full.is_standard());
+        let huge = Difficulty::Density {
+            width: usize::MAX,
+            height: 2,
+            density: 0.,
+        };
+        assert_eq!(huge.mines(), 0);
+    }
+
+    #[test]
+    fn limits() {
+        let options = GameOptions {
+            difficulty: Difficulty::Custom {
+                width: 2000,
+                height: 2000,
+                mines: 10,
+            },
+            ..GameOptions::easy()
+        };
+        assert_eq!(
+            options.clone().try_build(),
+            Err(BoardError::TooLarge {
+                area: 4_000_000,
+                max: 1_000_000
+            })
+        );
+        let options = GameOptions {
+            limits: BoardLimits {
+                max_area: 4_000_000,
+                max_mines: 5,
+            },
+            ..options
+        };
+        assert!(matches!(
+            options.try_build(),
+            Err(BoardError::TooManyMines { mines: 10, max: 5 })
+        ));
+    }
+
+    #[test]
+    fn streamed() {
+        let options = GameOptions {
+            difficulty: Difficulty::Custom {
+                width: 30,
+                height: 20,
+                mines: 120,
+            },
+            safe_pos: Some((3, 4)),
+            ..GameOptions::easy()
+        };
+        let mut rng = ChaCha12Rng::seed_from_u64(5);
+        let mut groups = options.mine_groups();
+        stable_shuffle(&mut groups, &mut rng);
+        let grouped = fill_groups(groups, 120);
+        let mut rng = ChaCha12Rng::seed_from_u64(5);
+        assert_eq!(options.single_cell_mines(&mut rng, 120).unwrap(), grouped);
+    }
+
+    /// Boards of generation 1 must never change, shared seeds rely on them
+    #[test]
+    fn generation() {
+        let golden = [
+            (Symmetry::None, 42, "***..*..\n...*...*\n*......*\n..**...."),
+            (
+                Symmetry::Rotational,
+                7,
+                "**.....*\n.*....*.\n.*....*.\n*.....**",
+            ),
+        ];
+        for (symmetry, seed, board) in golden {
+            let state = GameOptions {
+                difficulty: Difficulty::Custom {
+                    width: 8,
+                    height: 4,
+                    mines: 10,
+                },
+                safe_pos: Some((2, 1)),
+                seed: Some(seed),
+                symmetry,
+                generation: 1,
+                ..GameOptions::easy()
+            }
+            .build();
+            assert_eq!(state.to_board_text(), board);
+        }
+    }
+}
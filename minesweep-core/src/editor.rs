@@ -0,0 +1,78 @@
+use crate::{
+    BoardError, BoardLimits, CellState, Difficulty, GameOptions, GameResult, GameState, GameView,
+    Symmetry, GENERATION_VERSION,
+};
+
+impl GameState {
+    /// Board without any mine for the editor to fill in
+    pub fn empty(width: usize, height: usize) -> Self {
+        use CellState::Unopened;
+        GameState {
+            options: GameOptions {
+                difficulty: Difficulty::Custom {
+                    width,
+                    height,
+                    mines: 0,
+                },
+                safe_pos: None,
+                seed: None,
+                symmetry: Symmetry::None,
+                learning: false,
+                hidden_count: false,
+                generation: GENERATION_VERSION,
+                limits: BoardLimits::default(),
+            },
+            mines: (0..height).map(|_| vec![false; width]).collect(),
+            cells: (0..height).map(|_| vec![Unopened; width]).collect(),
+        }
+    }
+
+    pub fn toggle_mine(&mut self, x: usize, y: usize) {
+        self.mines[y][x] = !self.mines[y][x];
+        self.sync_difficulty();
+    }
+
+    pub(crate) fn sync_difficulty(&mut self) {
+        self.options.difficulty = Difficulty::Custom {
+            width: self.width(),
+            height: self.height(),
+            mines: self.mines(),
+        };
+    }
+
+    pub fn validate(&self) -> Result<(), BoardError> {
+        if self.height() == 0 || self.width() == 0 {
+            return Err(BoardError::Empty);
+        }
+        let mines = self.mines();
+        if mines == 0 {
+            Err(BoardError::NoMine)
+        } else if mines == self.width() * self.height() {
+            Err(BoardError::NoSafeCell)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// First cell without nearby mines, or any safe cell when there is no such cell
+    pub fn suggested_start(&self) -> Option<(usize, usize)> {
+        let safe_cells = (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| (x, y)))
+            .filter(|(x, y)| !self.is_mine(*x, *y));
+        safe_cells
+            .clone()
+            .find(|(x, y)| self.nearby_mines(*x, *y) == 0)
+            .or_else(|| safe_cells.clone().next())
+    }
+
+    /// Whether automation clears the board from the suggested start without guessing
+    pub fn test_solve(&self) -> bool {
+        let Some((x, y)) = self.suggested_start() else {
+            return false;
+        };
+        let mut view = GameView::from(self.clone());
+        view.left_click(x, y);
+        view.automation_run();
+        view.result == GameResult::Win
+    }
+}
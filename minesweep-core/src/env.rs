@@ -0,0 +1,233 @@
+use crate::{Action, CellView, GameOptions, GameResult, GameState, GameView};
+
+/// Reward shaping of [`Env`], every field is added up for a step
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rewards {
+    pub win: f64,
+    pub lose: f64,
+    /// For each cell opened by the step
+    pub opened: f64,
+    /// For a step that changes nothing, such as clicking an opened cell or outside the board
+    pub wasted: f64,
+}
+
+impl Default for Rewards {
+    fn default() -> Self {
+        Self {
+            win: 1.,
+            lose: -1.,
+            opened: 0.01,
+            wasted: -0.05,
+        }
+    }
+}
+
+/// Board as seen by an agent, each channel holds one value per cell in row-major order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub width: usize,
+    pub height: usize,
+    /// Nearby mines of opened cells divided by 8, 0 elsewhere
+    pub numbers: Vec<f32>,
+    pub opened: Vec<f32>,
+    pub flags: Vec<f32>,
+    /// Intact cells without flag, which are the ones worth clicking
+    pub unopened: Vec<f32>,
+}
+
+impl Observation {
+    fn new(width: usize, height: usize, cell: impl Fn(usize, usize) -> CellView) -> Self {
+        let size = width * height;
+        let mut this = Self {
+            width,
+            height,
+            numbers: vec![0.; size],
+            opened: vec![0.; size],
+            flags: vec![0.; size],
+            unopened: vec![0.; size],
+        };
+        for y in 0..height {
+            for x in 0..width {
+                let i = y * width + x;
+                match cell(x, y) {
+                    CellView::Opened(n) => {
+                        this.numbers[i] = n as f32 / 8.;
+                        this.opened[i] = 1.;
+                    }
+                    CellView::Flagged => this.flags[i] = 1.,
+                    CellView::Unopened
+                    | CellView::Hovered
+                    | CellView::Pushed
+                    | CellView::Questioned => this.unopened[i] = 1.,
+                    // only shown once the game is over
+                    CellView::Mine | CellView::WrongMine | CellView::Exploded => {}
+                }
+            }
+        }
+        this
+    }
+
+    /// Channels stacked as numbers, opened, flags and unopened, shaped `[4, height, width]`
+    pub fn tensor(&self) -> Vec<f32> {
+        [&self.numbers, &self.opened, &self.flags, &self.unopened]
+            .into_iter()
+            .flatten()
+            .copied()
+            .collect()
+    }
+}
+
+/// Training environment, every episode is a new board from consecutive seeds
+#[derive(Debug, Clone)]
+pub struct Env {
+    options: GameOptions,
+    rewards: Rewards,
+    episode: u64,
+    /// `None` until the first step, the board is built around a first left click
+    view: Option<GameView>,
+}
+
+impl Env {
+    /// Episodes use random boards unless `options` has a seed
+    pub fn new(options: GameOptions, rewards: Rewards) -> Self {
+        Self {
+            options,
+            rewards,
+            episode: 0,
+            view: None,
+        }
+    }
+
+    pub fn view(&self) -> Option<&GameView> {
+        self.view.as_ref()
+    }
+
+    pub fn observation(&self) -> Observation {
+        let (w, h) = (
+            self.options.difficulty.width(),
+            self.options.difficulty.height(),
+        );
+        match &self.view {
+            Some(view) => Observation::new(w, h, |x, y| view.cell(x, y)),
+            None => Observation::new(w, h, |_, _| CellView::Unopened),
+        }
+    }
+
+    pub fn reset(&mut self) -> Observation {
+        self.view = None;
+        self.episode += 1;
+        self.observation()
+    }
+
+    fn build(&self, safe_pos: Option<(usize, usize)>) -> GameState {
+        GameOptions {
+            safe_pos,
+            seed: self
+                .options
+                .seed
+                .map(|seed| seed.wrapping_add(self.episode)),
+            ..self.options.clone()
+        }
+        .build()
+    }
+
+    /// Returns the next observation, the reward and whether the episode is over
+    pub fn step(&mut self, action: &Action) -> (Observation, f64, bool) {
+        let (w, h) = (
+            self.options.difficulty.width(),
+            self.options.difficulty.height(),
+        );
+        if self
+            .view
+            .as_ref()
+            .is_some_and(|view| view.result != GameResult::Playing)
+        {
+            return (self.observation(), 0., true);
+        }
+        if action.outside(w, h).is_some() {
+            return (self.observation(), self.rewards.wasted, false);
+        }
+        if self.view.is_none() {
+            let safe_pos = match action {
+                Action::LeftClick(x, y) => Some((*x, *y)),
+                _ => None,
+            };
+            self.view = Some(GameView::from(self.build(safe_pos)));
+        }
+        let view = self.view.as_mut().unwrap();
+        let opened_before = count_opened(view);
+        let actions_before = view.actions().len();
+        view.apply(action);
+        let opened = count_opened(view) - opened_before;
+        let mut reward = opened as f64 * self.rewards.opened;
+        if view.actions().len() == actions_before {
+            reward += self.rewards.wasted;
+        }
+        let done = match view.result {
+            GameResult::Win => {
+                reward += self.rewards.win;
+                true
+            }
+            GameResult::Lose => {
+                reward += self.rewards.lose;
+                true
+            }
+            GameResult::Playing => false,
+        };
+        (self.observation(), reward, done)
+    }
+}
+
+fn count_opened(view: &GameView) -> usize {
+    (0..view.height())
+        .flat_map(|y| (0..view.width()).map(move |x| (x, y)))
+        .filter(|(x, y)| matches!(view.cell(*x, *y), CellView::Opened(_)))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn episode() {
+        let options = GameOptions {
+            seed: Some(5),
+            generation: 0,
+            ..GameOptions::easy()
+        };
+        let mut env = Env::new(options, Rewards::default());
+        let observation = env.reset();
+        assert_eq!(observation.tensor().len(), 4 * 9 * 9);
+        assert!(observation.unopened.iter().all(|cell| *cell == 1.));
+
+        let (observation, reward, done) = env.step(&Action::LeftClick(4, 4));
+        assert!(reward > 0.);
+        assert!(!done);
+        assert_eq!(observation.opened[4 * 9 + 4], 1.);
+        let (_, reward, _) = env.step(&Action::LeftClick(4, 4));
+        assert_eq!(reward, Rewards::default().wasted);
+        let before = env.observation();
+        let (observation, reward, done) = env.step(&Action::LeftClick(9, 0));
+        assert_eq!(observation, before);
+        assert_eq!(reward, Rewards::default().wasted);
+        assert!(!done);
+
+        // open every safe cell
+        let state = env.view().unwrap().state.clone();
+        let mut last = (0., false);
+        for (x, y) in (0..9).flat_map(|y| (0..9).map(move |x| (x, y))) {
+            if !state.is_mine(x, y) && !last.1 {
+                let (_, reward, done) = env.step(&Action::LeftClick(x, y));
+                last = (reward, done);
+            }
+        }
+        assert!(last.1);
+        assert!(last.0 > Rewards::default().win);
+        assert_eq!(env.view().unwrap().result, GameResult::Win);
+        assert!(env.step(&Action::LeftClick(0, 0)).2);
+
+        env.reset();
+        assert!(env.view().is_none());
+    }
+}
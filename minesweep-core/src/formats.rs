@@ -0,0 +1,192 @@
+use std::fmt::Display;
+
+use sha2::{Digest, Sha256};
+
+use crate::{tournament::hex, GameState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardError {
+    Empty,
+    NoMine,
+    NoSafeCell,
+    Ragged,
+    InvalidCharacter(char),
+    HashMismatch,
+    TooLarge {
+        area: usize,
+        max: usize,
+    },
+    TooManyMines {
+        mines: usize,
+        max: usize,
+    },
+    OutOfMemory,
+    /// A binary board ends before all of its mines
+    Truncated,
+    /// A binary board places a mine outside of its size
+    OutOfBoard(usize, usize),
+    /// A replay clicks outside of its board
+    ActionOutOfBoard(usize, usize),
+    /// A replay has an automation step holding more than clicks
+    NestedAction,
+}
+
+impl Display for BoardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use BoardError::*;
+        match self {
+            Empty => write!(f, "board is empty"),
+            NoMine => write!(f, "board has no mine"),
+            NoSafeCell => write!(f, "board has no safe cell"),
+            Ragged => write!(f, "rows of the board have different lengths"),
+            InvalidCharacter(c) => write!(f, "invalid character {c:?} in board"),
+            HashMismatch => write!(f, "board does not match its hash"),
+            TooLarge { area, max } => write!(f, "board of {area} cells is over the {max} allowed"),
+            TooManyMines { mines, max } => write!(f, "{mines} mines are over the {max} allowed"),
+            OutOfMemory => write!(f, "not enough memory for the board"),
+            Truncated => write!(f, "board file ends early"),
+            OutOfBoard(x, y) => write!(f, "mine at {x},{y} is outside the board"),
+            ActionOutOfBoard(x, y) => write!(f, "click at {x},{y} is outside the board"),
+            NestedAction => write!(f, "automation step holds more than clicks"),
+        }
+    }
+}
+
+impl GameState {
+    /// One line per row, `*` for mine and `.` for safe cell
+    pub fn to_board_text(&self) -> String {
+        self.mines
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|mine| if *mine { '*' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// SHA-256 of the size and the mines in row-major order, the same on every platform
+    pub fn board_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update((self.width() as u64).to_le_bytes());
+        hasher.update((self.height() as u64).to_le_bytes());
+        for row in self.mines.iter() {
+            hasher.update(row.iter().map(|mine| *mine as u8).collect::<Vec<_>>());
+        }
+        hex(&hasher.finalize())
+    }
+
+    pub fn from_board_text(text: &str) -> Result<Self, BoardError> {
+        let rows = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                line.chars()
+                    .map(|c| match c {
+                        '*' => Ok(true),
+                        '.' => Ok(false),
+                        c => Err(BoardError::InvalidCharacter(c)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let Some(width) = rows.first().map(Vec::len) else {
+            return Err(BoardError::Empty);
+        };
+        if rows.iter().any(|row| row.len() != width) {
+            return Err(BoardError::Ragged);
+        }
+        let mut state = GameState::empty(width, rows.len());
+        state.mines = rows;
+        state.sync_difficulty();
+        state.validate()?;
+        Ok(state)
+    }
+
+    /// Minesweeper Board Format: width and height in a byte each, the mine count in two bytes
+    /// big endian, then the column and row of every mine, `None` for boards over 255 cells wide
+    /// or high
+    pub fn to_mbf(&self) -> Option<Vec<u8>> {
+        let mines: Vec<_> = (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| (x, y)))
+            .filter(|(x, y)| self.mines[*y][*x])
+            .collect();
+        let mut bytes = vec![
+            u8::try_from(self.width()).ok()?,
+            u8::try_from(self.height()).ok()?,
+        ];
+        bytes.extend(u16::try_from(mines.len()).ok()?.to_be_bytes());
+        for (x, y) in mines {
+            bytes.extend([x as u8, y as u8]);
+        }
+        Some(bytes)
+    }
+
+    pub fn from_mbf(bytes: &[u8]) -> Result<Self, BoardError> {
+        let [width, height, count0, count1, mines @ ..] = bytes else {
+            return Err(BoardError::Truncated);
+        };
+        let (width, height) = (*width as usize, *height as usize);
+        if width == 0 || height == 0 {
+            return Err(BoardError::Empty);
+        }
+        let count = u16::from_be_bytes([*count0, *count1]) as usize;
+        if mines.len() < count * 2 {
+            return Err(BoardError::Truncated);
+        }
+        let mut state = GameState::empty(width, height);
+        for [x, y] in mines.as_chunks::<2>().0.iter().take(count) {
+            let (x, y) = (*x as usize, *y as usize);
+            if x >= width || y >= height {
+                return Err(BoardError::OutOfBoard(x, y));
+            }
+            state.mines[y][x] = true;
+        }
+        state.sync_difficulty();
+        state.validate()?;
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn board_text() {
+        let state = GameState::from_board_text("*..\n...\n..*\n").unwrap();
+        assert_eq!(state.mines(), 2);
+        assert_eq!(state.to_board_text(), "*..\n...\n..*");
+        assert!(state.test_solve());
+        // pinned so that hashes kept in saves stay valid on every platform and release
+        assert_eq!(
+            state.board_hash(),
+            "d2b3386e357fa48dde051c2f044ba74a82352f299f3eca5072ec845b09bdffe8"
+        );
+        assert_eq!(
+            GameState::from_board_text("*.\n..."),
+            Err(BoardError::Ragged)
+        );
+        assert_eq!(
+            GameState::from_board_text("..\n.."),
+            Err(BoardError::NoMine)
+        );
+    }
+
+    #[test]
+    fn mbf() {
+        let state = GameState::from_board_text("*..\n...\n..*\n").unwrap();
+        let bytes = state.to_mbf().unwrap();
+        assert_eq!(bytes, [3, 3, 0, 2, 0, 0, 2, 2]);
+        assert_eq!(GameState::from_mbf(&bytes).unwrap().mines, state.mines);
+        assert_eq!(GameState::from_mbf(&bytes[..7]), Err(BoardError::Truncated));
+        assert_eq!(
+            GameState::from_mbf(&[3, 3, 0, 1, 3, 0]),
+            Err(BoardError::OutOfBoard(3, 0))
+        );
+        assert_eq!(GameState::from_mbf(&[5, 0, 0, 0]), Err(BoardError::Empty));
+        assert_eq!(GameState::from_mbf(&[0, 5, 0, 0]), Err(BoardError::Empty));
+    }
+}
@@ -0,0 +1,136 @@
+use std::{collections::HashSet, ops::Range};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{GameOptions, GameView};
+
+/// Mine placement of the boards built from a range of seeds, for auditing that the
+/// generation favours no cell besides keeping the safe position clear
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationStats {
+    pub width: usize,
+    pub height: usize,
+    pub boards: u64,
+    /// Boards with a mine on each cell, in row-major order
+    pub mine_counts: Vec<u64>,
+    /// Cells never given a mine by design, the safe position and its mirror images
+    pub excluded: Vec<(usize, usize)>,
+    /// Cells opened by clicking the safe position of each board, empty without one
+    pub opening_sizes: Vec<usize>,
+}
+
+impl GenerationStats {
+    /// Build the boards of the options with every seed of the range
+    pub fn collect(options: &GameOptions, seeds: Range<u64>) -> Self {
+        let (width, height) = (options.difficulty.width(), options.difficulty.height());
+        let eligible: HashSet<_> = options.mine_groups().into_iter().flatten().collect();
+        let mut stats = Self {
+            width,
+            height,
+            mine_counts: vec![0; width * height],
+            excluded: (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .filter(|cell| !eligible.contains(cell))
+                .collect(),
+            ..Default::default()
+        };
+        for seed in seeds {
+            let state = GameOptions {
+                seed: Some(seed),
+                ..options.clone()
+            }
+            .build();
+            for y in 0..height {
+                for x in 0..width {
+                    if state.is_mine(x, y) {
+                        stats.mine_counts[y * width + x] += 1;
+                    }
+                }
+            }
+            if let Some((x, y)) = options.safe_pos {
+                let mut view = GameView::from(state);
+                stats.opening_sizes.push(view.left_click(x, y).opened);
+            }
+            stats.boards += 1;
+        }
+        stats
+    }
+
+    /// Chi-square statistic of the mine counts against the same count on every cell that
+    /// may hold a mine, along with its degrees of freedom
+    pub fn uniformity(&self) -> (f64, usize) {
+        let observed: Vec<u64> = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .filter(|cell| !self.excluded.contains(cell))
+            .map(|(x, y)| self.mine_counts[y * self.width + x])
+            .collect();
+        let mean = observed.iter().sum::<u64>() as f64 / observed.len().max(1) as f64;
+        let expected = vec![mean; observed.len()];
+        (
+            chi_square(&observed, &expected),
+            observed.len().saturating_sub(1),
+        )
+    }
+
+    /// Standard score of `uniformity`, boards placed uniformly stay within a few units of zero
+    pub fn uniformity_z(&self) -> f64 {
+        let (statistic, freedom) = self.uniformity();
+        chi_square_z(statistic, freedom)
+    }
+
+    pub fn mean_opening(&self) -> f64 {
+        self.opening_sizes.iter().sum::<usize>() as f64 / self.opening_sizes.len().max(1) as f64
+    }
+}
+
+/// Pearson's chi-square statistic of observed counts against expected ones,
+/// categories expected to be empty are left out
+pub fn chi_square(observed: &[u64], expected: &[f64]) -> f64 {
+    observed
+        .iter()
+        .zip(expected)
+        .filter(|(_, expected)| **expected > 0.)
+        .map(|(observed, expected)| (*observed as f64 - expected).powi(2) / expected)
+        .sum()
+}
+
+/// Standard normal score of a chi-square statistic by the Wilson-Hilferty approximation
+pub fn chi_square_z(statistic: f64, freedom: usize) -> f64 {
+    if freedom == 0 {
+        return 0.;
+    }
+    let k = freedom as f64;
+    let variance = 2. / (9. * k);
+    ((statistic / k).cbrt() - (1. - variance)) / variance.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Symmetry;
+
+    #[test]
+    fn chi_square_z() {
+        assert_eq!(chi_square(&[10, 10], &[10., 10.]), 0.);
+        assert!((chi_square(&[12, 8], &[10., 10.]) - 0.8).abs() < 1e-9);
+        // the median of a chi-square distribution is close to its degrees of freedom
+        assert!(super::chi_square_z(100., 100).abs() < 0.1);
+        assert!(super::chi_square_z(200., 100) > 5.);
+    }
+
+    #[test]
+    fn uniform() {
+        for symmetry in [Symmetry::None, Symmetry::Rotational] {
+            let options = GameOptions {
+                safe_pos: Some((4, 4)),
+                symmetry,
+                ..GameOptions::easy()
+            };
+            let stats = GenerationStats::collect(&options, 0..2000);
+            assert_eq!(stats.excluded, [(4, 4)]);
+            assert_eq!(stats.mine_counts[4 * 9 + 4], 0);
+            assert!(stats.uniformity_z().abs() < 4., "{symmetry:?}");
+            assert!(stats.mean_opening() >= 1.);
+        }
+    }
+}
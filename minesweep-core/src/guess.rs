@@ -0,0 +1,172 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CellView, GameView};
+
+/// Components bigger than this are too slow to enumerate
+const MAX_ENUMERATED_CELLS: usize = 32;
+
+/// How much more likely an arrangement is for each questioned cell it puts a mine under
+const SUSPICION_WEIGHT: f64 = 2.;
+
+/// How questioned cells count when ranking guesses, they never rule an arrangement out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuestionHints {
+    /// Questioned cells are plain unopened cells
+    #[default]
+    Ignore,
+    /// The player suspects a mine under each questioned cell, arrangements agreeing weigh more
+    Suspect,
+}
+
+fn is_unknown(cell: &CellView) -> bool {
+    cell.is_intact() || *cell == CellView::Questioned
+}
+
+impl GameView {
+    /// Chance of each frontier cell being a mine, counting every arrangement of its component
+    /// as equally likely but for question hints, which ignores the total number of mines
+    pub fn mine_probabilities(&self, hints: QuestionHints) -> Vec<((usize, usize), f64)> {
+        let mut probabilities = vec![];
+        for mut cells in self.components_with(is_unknown) {
+            if cells.len() > MAX_ENUMERATED_CELLS {
+                continue;
+            }
+            cells.sort_by_key(|(x, y)| (*y, *x));
+            let index: HashMap<_, _> = cells.iter().enumerate().map(|(i, c)| (*c, i)).collect();
+            let constraint_cells: BTreeSet<_> = cells
+                .iter()
+                .flat_map(|(x, y)| self.nearby_cells(*x, *y))
+                .collect();
+            let constraints: Vec<(usize, Vec<usize>)> = constraint_cells
+                .into_iter()
+                .filter_map(|(x, y)| match self.cell(x, y) {
+                    CellView::Opened(n) => Some((
+                        n.saturating_sub(self.nearby_flags(x, y)) as usize,
+                        self.nearby_cells(x, y)
+                            .iter()
+                            .filter_map(|cell| index.get(cell).copied())
+                            .collect(),
+                    )),
+                    _ => None,
+                })
+                .collect();
+            let weights: Vec<f64> = cells
+                .iter()
+                .map(|(x, y)| match (hints, self.cell(*x, *y)) {
+                    (QuestionHints::Suspect, CellView::Questioned) => SUSPICION_WEIGHT,
+                    _ => 1.,
+                })
+                .collect();
+            let mut enumeration = Enumeration {
+                constraints: &constraints,
+                weights: &weights,
+                assignment: vec![false; cells.len()],
+                counts: vec![0.; cells.len()],
+                total: 0.,
+            };
+            enumeration.run(0, 1.);
+            if enumeration.total == 0. {
+                continue;
+            }
+            for (cell, count) in cells.into_iter().zip(enumeration.counts) {
+                probabilities.push((cell, count / enumeration.total));
+            }
+        }
+        probabilities
+    }
+
+    /// Frontier cell least likely to be a mine, `None` when no component is small enough
+    pub fn best_guess(&self, hints: QuestionHints) -> Option<(usize, usize)> {
+        self.mine_probabilities(hints)
+            .into_iter()
+            .filter(|(_, probability)| *probability < 1.)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(cell, _)| cell)
+    }
+}
+
+/// Weighted count of the arrangements consistent with the constraints
+struct Enumeration<'a> {
+    constraints: &'a [(usize, Vec<usize>)],
+    weights: &'a [f64],
+    assignment: Vec<bool>,
+    counts: Vec<f64>,
+    total: f64,
+}
+
+impl Enumeration<'_> {
+    /// Continue with cells before `i` assigned, whose mines weigh `weight` together
+    fn run(&mut self, i: usize, weight: f64) {
+        for (needed, members) in self.constraints {
+            let mines = members
+                .iter()
+                .filter(|m| **m < i && self.assignment[**m])
+                .count();
+            let unassigned = members.iter().filter(|m| **m >= i).count();
+            if mines > *needed || mines + unassigned < *needed {
+                return;
+            }
+        }
+        if i == self.assignment.len() {
+            self.total += weight;
+            for (count, mine) in self.counts.iter_mut().zip(self.assignment.iter()) {
+                if *mine {
+                    *count += weight;
+                }
+            }
+            return;
+        }
+        self.assignment[i] = false;
+        self.run(i + 1, weight);
+        self.assignment[i] = true;
+        self.run(i + 1, weight * self.weights[i]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameState;
+
+    /// The 1 has three unopened neighbours, one of them the mine
+    fn view(questioned: bool) -> GameView {
+        let mut view = GameView::from(GameState::empty(2, 2));
+        view.set_cell(0, 0, CellView::Opened(1));
+        if questioned {
+            view.set_cell(1, 1, CellView::Questioned);
+        }
+        view
+    }
+
+    #[test]
+    fn probabilities() {
+        let probabilities = view(false).mine_probabilities(QuestionHints::Ignore);
+        assert_eq!(probabilities.len(), 3);
+        for (_, probability) in probabilities {
+            assert!((probability - 1. / 3.).abs() < 1e-9);
+        }
+        let probabilities = view(true).mine_probabilities(QuestionHints::Ignore);
+        assert!(probabilities
+            .iter()
+            .all(|(_, probability)| (probability - 1. / 3.).abs() < 1e-9));
+        let mut view = GameView::from(GameState::empty(1, 2));
+        view.set_cell(0, 0, CellView::Opened(1));
+        assert_eq!(
+            view.mine_probabilities(QuestionHints::Ignore),
+            vec![((0, 1), 1.)]
+        );
+        assert_eq!(view.best_guess(QuestionHints::Ignore), None);
+    }
+
+    #[test]
+    fn suspect() {
+        let view = view(true);
+        for (cell, probability) in view.mine_probabilities(QuestionHints::Suspect) {
+            let expected = if cell == (1, 1) { 0.5 } else { 0.25 };
+            assert!((probability - expected).abs() < 1e-9);
+        }
+        assert_ne!(view.best_guess(QuestionHints::Suspect), Some((1, 1)));
+    }
+}
@@ -0,0 +1,12 @@
+//! Types most consumers need to build boards, play and automate them, `use minesweep_core::prelude::*`
+
+pub use crate::board::{
+    BoardLimits, CellState, Difficulty, GameOptions, GameResult, GameState, Symmetry,
+    GENERATION_VERSION,
+};
+pub use crate::formats::BoardError;
+pub use crate::replay::{Action, Replay};
+pub use crate::solve::{SolvePoll, SolveResult, SolveTask};
+pub use crate::view::{
+    AutomationError, AutomationPolicy, CellView, ClickOutcome, GameView, Gesture, Rect, RedrawCells,
+};
@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Action, AutomationPolicy, BoardError, BoardLimits, CellView, Difficulty, GameOptions,
+    GameResult, GameView, Replay, SolveResult, Symmetry, GENERATION_VERSION,
+};
+
+/// Version of the puzzle pack format, bumped on incompatible changes
+pub const PUZZLE_PACK_VERSION: u32 = 1;
+
+/// Mid-game position with exactly one forced move, which no single number reveals on its own
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Puzzle {
+    /// Game leading to the position
+    pub replay: Replay,
+    /// Left click on the safe cell or right click on the mine
+    pub answer: Action,
+}
+
+impl Puzzle {
+    /// Play the seeded board from `options` until one forced move is left,
+    /// `None` when the game needs a guess or clears first
+    pub fn generate(options: GameOptions) -> Option<Self> {
+        let (x, y) = options.safe_pos.unwrap_or((
+            options.difficulty.width() / 2,
+            options.difficulty.height() / 2,
+        ));
+        let mut view = GameView::from(
+            GameOptions {
+                safe_pos: Some((x, y)),
+                ..options
+            }
+            .build(),
+        );
+        view.left_click(x, y);
+        while view.result == GameResult::Playing {
+            let trivial = view.trivial_moves();
+            if trivial != SolveResult::default() {
+                view.automation_apply(trivial, AutomationPolicy::default())
+                    .ok()?;
+                continue;
+            }
+            let result = view.solve();
+            let answer = match (&result.must_be_mine[..], &result.must_not_mine[..]) {
+                ([], []) => return None,
+                ([(x, y)], []) => Action::RightClick(*x, *y),
+                ([], [(x, y)]) => Action::LeftClick(*x, *y),
+                // take one move at a time so that more positions are passed through
+                (mines, safe) => {
+                    view.automation_apply(
+                        SolveResult {
+                            must_be_mine: mines.iter().take(1).copied().collect(),
+                            must_not_mine: safe
+                                .iter()
+                                .take(mines.is_empty() as usize)
+                                .copied()
+                                .collect(),
+                        },
+                        AutomationPolicy::default(),
+                    )
+                    .ok()?;
+                    continue;
+                }
+            };
+            return Some(Self {
+                replay: view.replay(),
+                answer,
+            });
+        }
+        None
+    }
+
+    pub fn start(&self) -> Result<GameView, BoardError> {
+        self.replay.finish()
+    }
+
+    pub fn is_answer(&self, action: &Action) -> bool {
+        *action == self.answer
+    }
+}
+
+/// Puzzles from consecutive seeds, for frontends to share as JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PuzzlePack {
+    pub version: u32,
+    pub name: String,
+    pub difficulty: Difficulty,
+    pub puzzles: Vec<Puzzle>,
+}
+
+impl PuzzlePack {
+    /// Try up to `attempts` seeds from `seed` on, the pack may hold fewer than `count` puzzles
+    pub fn generate(
+        name: String,
+        difficulty: Difficulty,
+        seed: u64,
+        count: usize,
+        attempts: u64,
+    ) -> Self {
+        let puzzles = (seed..seed.wrapping_add(attempts))
+            .filter_map(|seed| {
+                Puzzle::generate(GameOptions {
+                    difficulty: difficulty.clone(),
+                    safe_pos: None,
+                    seed: Some(seed),
+                    symmetry: Symmetry::None,
+                    learning: false,
+                    hidden_count: false,
+                    generation: GENERATION_VERSION,
+                    limits: BoardLimits::default(),
+                })
+            })
+            .take(count)
+            .collect();
+        Self {
+            version: PUZZLE_PACK_VERSION,
+            name,
+            difficulty,
+            puzzles,
+        }
+    }
+
+    /// Puzzle of the given day, cycling through the pack
+    pub fn daily(&self, day: u64) -> Option<&Puzzle> {
+        if self.puzzles.is_empty() {
+            return None;
+        }
+        self.puzzles.get((day % self.puzzles.len() as u64) as usize)
+    }
+}
+
+impl GameView {
+    /// Moves shown by a single number, where its flags already account for every mine
+    /// or its intact neighbours are all mines
+    pub fn trivial_moves(&self) -> SolveResult {
+        let mut result = SolveResult::default();
+        if self.result != GameResult::Playing {
+            return result;
+        }
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let CellView::Opened(n) = self.cell(x, y) else {
+                    continue;
+                };
+                let intact: Vec<_> = self
+                    .nearby_cells(x, y)
+                    .into_iter()
+                    .filter(|(x, y)| self.cell(*x, *y).is_intact())
+                    .collect();
+                let remaining = n.saturating_sub(self.nearby_flags(x, y)) as usize;
+                let found = if remaining == 0 {
+                    &mut result.must_not_mine
+                } else if remaining == intact.len() {
+                    &mut result.must_be_mine
+                } else {
+                    continue;
+                };
+                for cell in intact {
+                    if !found.contains(&cell) {
+                        found.push(cell);
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn puzzle() {
+        let pack = PuzzlePack::generate("test".to_string(), Difficulty::Medium, 60, 2, 10);
+        assert_eq!(pack.puzzles.len(), 2);
+        for puzzle in pack.puzzles.iter() {
+            let view = puzzle.start().unwrap();
+            assert_eq!(view.result, GameResult::Playing);
+            assert_eq!(view.trivial_moves(), SolveResult::default());
+            let (x, y, mine) = match puzzle.answer {
+                Action::LeftClick(x, y) => (x, y, false),
+                Action::RightClick(x, y) => (x, y, true),
+                _ => unreachable!(),
+            };
+            assert_eq!(view.state.is_mine(x, y), mine);
+            let result = view.solve();
+            assert_eq!(result.must_be_mine.len() + result.must_not_mine.len(), 1);
+            assert!(puzzle.is_answer(&puzzle.answer));
+        }
+        assert_eq!(pack.daily(3), pack.puzzles.get(1));
+    }
+}
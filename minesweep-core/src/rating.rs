@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{AutomationPolicy, GameOptions, GameResult, GameView, QuestionHints, SolveResult};
+
+/// Score of a guess against a round of deduction beyond single numbers
+const GUESS_SCORE: f64 = 10.;
+
+/// How hard a board is to clear, from playing it out headless with deduction and best guesses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rating {
+    /// Options of the board, with the first click as safe position
+    pub options: GameOptions,
+    pub bbbv: usize,
+    /// Rounds of moves, each found by single numbers or by solving the frontier
+    pub rounds: usize,
+    /// Rounds that single numbers could not find
+    pub deep_rounds: usize,
+    /// Cells opened on the best guess when deduction got stuck
+    pub guesses: usize,
+    /// Cleared, otherwise a guess hit a mine
+    pub win: bool,
+}
+
+impl Rating {
+    pub fn rate(options: GameOptions) -> Self {
+        let mut rater = Rater::new(options);
+        while rater.step() {}
+        rater.finish()
+    }
+
+    /// Deep rounds, with guesses counting for several
+    pub fn score(&self) -> f64 {
+        self.deep_rounds as f64 + self.guesses as f64 * GUESS_SCORE
+    }
+}
+
+/// Rating played out one round at a time, so that callers can yield in between
+#[derive(Debug, Clone)]
+pub struct Rater {
+    view: GameView,
+    rating: Rating,
+}
+
+impl Rater {
+    /// Open the safe position, the center when the options leave it out
+    pub fn new(options: GameOptions) -> Self {
+        let (x, y) = options.safe_pos.unwrap_or((
+            options.difficulty.width() / 2,
+            options.difficulty.height() / 2,
+        ));
+        let options = GameOptions {
+            safe_pos: Some((x, y)),
+            ..options
+        };
+        let mut view = GameView::from(options.clone().build());
+        view.left_click(x, y);
+        let rating = Rating {
+            options,
+            bbbv: view.bbbv(),
+            rounds: 0,
+            deep_rounds: 0,
+            guesses: 0,
+            win: false,
+        };
+        Self { view, rating }
+    }
+
+    /// Play one round, or one guess when stuck, false once the game is over
+    pub fn step(&mut self) -> bool {
+        if self.view.result != GameResult::Playing {
+            return false;
+        }
+        let revision = self.view.revision();
+        let trivial = self.view.trivial_moves();
+        let deep = trivial == SolveResult::default();
+        let result = match deep {
+            true => self.view.solve(),
+            false => trivial,
+        };
+        let moved = result != SolveResult::default()
+            && self
+                .view
+                .automation_apply(result, AutomationPolicy::default())
+                .is_ok()
+            && self.view.revision() != revision;
+        if moved {
+            self.rating.rounds += 1;
+            self.rating.deep_rounds += deep as usize;
+            return true;
+        }
+        let guess = self
+            .view
+            .best_guess(QuestionHints::Ignore)
+            .or_else(|| self.first_intact());
+        let Some((x, y)) = guess else {
+            return false;
+        };
+        self.rating.guesses += 1;
+        self.view.left_click(x, y);
+        self.view.revision() != revision
+    }
+
+    fn first_intact(&self) -> Option<(usize, usize)> {
+        (0..self.view.height())
+            .flat_map(|y| (0..self.view.width()).map(move |x| (x, y)))
+            .find(|(x, y)| self.view.cell(*x, *y).is_intact())
+    }
+
+    pub fn finish(mut self) -> Rating {
+        self.rating.win = self.view.result == GameResult::Win;
+        self.rating
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn rating() {
+        let options = |seed| GameOptions {
+            seed: Some(seed),
+            ..GameOptions::easy()
+        };
+        for seed in 0..5 {
+            let rating = Rating::rate(options(seed));
+            assert_eq!(rating, Rating::rate(options(seed)));
+            assert!(rating.bbbv > 0);
+            assert!(rating.win || rating.guesses > 0);
+            assert!(rating.deep_rounds <= rating.rounds);
+            assert_eq!(
+                rating.score(),
+                rating.deep_rounds as f64 + rating.guesses as f64 * 10.
+            );
+            assert_eq!(rating.options.safe_pos, Some((4, 4)));
+        }
+    }
+}
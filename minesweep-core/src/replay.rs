@@ -0,0 +1,424 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BoardError, GameOptions, GameResult, GameState, GameView, RedrawCells};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    LeftClick(usize, usize),
+    RightClick(usize, usize),
+    MiddleClick(usize, usize),
+    Undo,
+    Redo,
+    /// Clicks made by one automation step, undone together
+    Automation(Vec<Action>),
+}
+
+impl Action {
+    /// First click falling outside of a board of the given size
+    pub fn outside(&self, width: usize, height: usize) -> Option<(usize, usize)> {
+        match self {
+            Action::LeftClick(x, y) | Action::RightClick(x, y) | Action::MiddleClick(x, y) => {
+                (*x >= width || *y >= height).then_some((*x, *y))
+            }
+            Action::Undo | Action::Redo => None,
+            Action::Automation(actions) => actions
+                .iter()
+                .find_map(|action| action.outside(width, height)),
+        }
+    }
+
+    /// Whether automation steps hold nothing but clicks, as `GameView::apply` expects
+    pub fn is_well_formed(&self) -> bool {
+        match self {
+            Action::Automation(actions) => actions.iter().all(|action| {
+                matches!(
+                    action,
+                    Action::LeftClick(..) | Action::RightClick(..) | Action::MiddleClick(..)
+                )
+            }),
+            _ => true,
+        }
+    }
+}
+
+/// Timed gaps needed before the timing of a replay says anything
+const TIMING_SAMPLES: usize = 8;
+/// Buckets of the timing histogram per doubling of the gap
+const TIMING_BUCKETS_PER_OCTAVE: f64 = 4.;
+
+/// Help used in a game, so that leaderboards and stats can tell pure runs apart
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Assistance {
+    /// The solver marked safe cells or told what it knew of cells, as reported by the frontend
+    pub hints: bool,
+    /// Chords were checked by the solver, as reported by the frontend
+    pub safe_chord: bool,
+    pub undo: bool,
+    pub automation: bool,
+    pub learning: bool,
+}
+
+impl Assistance {
+    pub fn is_pure(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Time the clock stood still while the player was away, on the clock of `GameView::stamp`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pause {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Pause {
+    /// Milliseconds of the pause between two timestamps
+    fn overlap(&self, from: u64, to: u64) -> u64 {
+        self.end.min(to).saturating_sub(self.start.max(from))
+    }
+}
+
+/// Everything needed to play a game again, the board is kept so that edited boards without seed replay as well
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub options: GameOptions,
+    pub board: String,
+    pub actions: Vec<Action>,
+    /// Time of each action in milliseconds, empty when no action was timed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timestamps: Vec<Option<u64>>,
+    /// `GameState::board_hash` of the board, missing from replays saved before it was added
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub board_hash: Option<String>,
+    /// Help used as recorded by the game, the actions and options prove all but the reported part
+    #[serde(default, skip_serializing_if = "Assistance::is_pure")]
+    pub assistance: Assistance,
+    /// Pauses of the game, left out of the gaps between actions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pauses: Vec<Pause>,
+}
+
+impl Replay {
+    /// View of the board before any action, refused when the board does not match its hash
+    /// or an action cannot be applied to it
+    pub fn start(&self) -> Result<GameView, BoardError> {
+        let mut state = GameState::from_board_text(&self.board)?;
+        Self::check_hash(&state, self.board_hash.as_deref())?;
+        for action in &self.actions {
+            if let Some((x, y)) = action.outside(state.width(), state.height()) {
+                return Err(BoardError::ActionOutOfBoard(x, y));
+            }
+            if !action.is_well_formed() {
+                return Err(BoardError::NestedAction);
+            }
+        }
+        state.options = self.options.clone();
+        let mut view = GameView::from(state);
+        view.report_assists(self.assistance.hints, self.assistance.safe_chord);
+        view.history.pauses = self.pauses.clone();
+        Ok(view)
+    }
+
+    /// Check the board against its hash, replays without one pass
+    pub fn verify_board(&self) -> Result<(), BoardError> {
+        let state = GameState::from_board_text(&self.board)?;
+        Self::check_hash(&state, self.board_hash.as_deref())
+    }
+
+    fn check_hash(state: &GameState, hash: Option<&str>) -> Result<(), BoardError> {
+        match hash {
+            Some(hash) if hash != state.board_hash() => Err(BoardError::HashMismatch),
+            _ => Ok(()),
+        }
+    }
+
+    /// View after every action is applied
+    pub fn finish(&self) -> Result<GameView, BoardError> {
+        self.seek(self.actions.len())
+    }
+
+    /// View after the first `position` actions are applied
+    pub fn seek(&self, position: usize) -> Result<GameView, BoardError> {
+        let mut view = self.start()?;
+        for (i, action) in self.actions.iter().take(position).enumerate() {
+            if let Some(&Some(timestamp)) = self.timestamps.get(i) {
+                view.stamp(timestamp);
+            }
+            view.apply(action);
+        }
+        Ok(view)
+    }
+
+    /// Milliseconds between the action at `index` and the one before, when both were timed,
+    /// without the time paused in between
+    pub fn gap(&self, index: usize) -> Option<u64> {
+        let before = (*self.timestamps.get(index.checked_sub(1)?)?)?;
+        let after = (*self.timestamps.get(index)?)?;
+        let paused: u64 = self
+            .pauses
+            .iter()
+            .map(|pause| pause.overlap(before, after))
+            .sum();
+        Some(after.saturating_sub(before).saturating_sub(paused))
+    }
+
+    /// Milliseconds from the first action to the last without the time paused,
+    /// `None` unless every action was timed
+    pub fn duration(&self) -> Option<u64> {
+        if self.timestamps.len() != self.actions.len() || self.timestamps.contains(&None) {
+            return None;
+        }
+        (1..self.actions.len()).map(|index| self.gap(index)).sum()
+    }
+
+    /// Time taken before each timed action, for thinking time histograms
+    pub fn thinking_times(&self) -> Vec<u64> {
+        (1..self.actions.len())
+            .filter_map(|index| self.gap(index))
+            .collect()
+    }
+
+    /// Shannon entropy in bits of the gaps between timed actions bucketed on a log scale,
+    /// scripted input keeps a steady pace and scores near zero, `None` with too few gaps
+    pub fn timing_entropy(&self) -> Option<f64> {
+        let gaps = self.thinking_times();
+        if gaps.len() < TIMING_SAMPLES {
+            return None;
+        }
+        let mut buckets: HashMap<u32, usize> = HashMap::new();
+        for gap in &gaps {
+            let bucket = ((*gap as f64 + 1.).log2() * TIMING_BUCKETS_PER_OCTAVE) as u32;
+            *buckets.entry(bucket).or_default() += 1;
+        }
+        let total = gaps.len() as f64;
+        Some(
+            buckets
+                .values()
+                .map(|count| {
+                    let p = *count as f64 / total;
+                    -p * p.log2()
+                })
+                .sum(),
+        )
+    }
+}
+
+impl GameView {
+    pub fn actions(&self) -> &[Action] {
+        &self.history.actions
+    }
+
+    /// Record the assists of the frontend in use when the player acted, which the actions
+    /// cannot tell, once reported they stay for the rest of the game
+    pub fn report_assists(&mut self, hints: bool, safe_chord: bool) {
+        self.history.reported.hints |= hints;
+        self.history.reported.safe_chord |= safe_chord;
+    }
+
+    pub fn assistance(&self) -> Assistance {
+        Assistance {
+            undo: self.is_assisted(),
+            automation: self
+                .history
+                .actions
+                .iter()
+                .any(|action| matches!(action, Action::Automation(_))),
+            learning: self.options().learning,
+            ..self.history.reported
+        }
+    }
+
+    /// Time the next action, in milliseconds on the clock of the caller, only the differences
+    /// between timestamps are meaningful, a pause ends with the next timed action
+    pub fn stamp(&mut self, timestamp: u64) -> &mut Self {
+        self.resume(timestamp);
+        self.history.pending_timestamp = Some(timestamp);
+        self
+    }
+
+    /// Time the next action with the system clock
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn stamp_now(&mut self) -> &mut Self {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        self.stamp(now.as_millis() as u64)
+    }
+
+    /// Stop the clock of a game in progress, timestamps on the clock of `stamp`
+    pub fn pause(&mut self, timestamp: u64) {
+        if self.result == GameResult::Playing && self.history.paused_since.is_none() {
+            self.history.paused_since = Some(timestamp);
+        }
+    }
+
+    /// Start the clock again and record the pause
+    pub fn resume(&mut self, timestamp: u64) {
+        if let Some(start) = self.history.paused_since.take() {
+            if timestamp > start {
+                self.history.pauses.push(Pause {
+                    start,
+                    end: timestamp,
+                });
+            }
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.history.paused_since.is_some()
+    }
+
+    /// Fingerprint of the board and the first `len` actions, views with equal digests are equal
+    pub fn digest(&self, len: usize) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.state.to_board_text().hash(&mut hasher);
+        self.history.actions[..len.min(self.history.actions.len())].hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn replay(&self) -> Replay {
+        Replay {
+            options: self.options(),
+            board: self.state.to_board_text(),
+            actions: self.history.actions.clone(),
+            timestamps: match self.history.timestamps.iter().any(Option::is_some) {
+                true => self.history.timestamps.clone(),
+                false => vec![],
+            },
+            board_hash: Some(self.state.board_hash()),
+            assistance: self.assistance(),
+            pauses: self.history.pauses.clone(),
+        }
+    }
+
+    pub fn apply(&mut self, action: &Action) -> RedrawCells {
+        match action {
+            Action::LeftClick(x, y) => self.left_click(*x, *y).redraw,
+            Action::RightClick(x, y) => self.right_click(*x, *y).redraw,
+            Action::MiddleClick(x, y) => self.middle_click(*x, *y).redraw,
+            Action::Undo => self.undo(),
+            Action::Redo => self.redo(),
+            Action::Automation(actions) => {
+                let mut redraw = RedrawCells::default();
+                for action in actions {
+                    redraw.merge(self.click(action.clone()));
+                }
+                self.history.commit(true);
+                redraw
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn replay() {
+        let mut view = GameView::from(
+            GameOptions {
+                difficulty: Difficulty::Custom {
+                    width: 9,
+                    height: 9,
+                    mines: 10,
+                },
+                safe_pos: Some((4, 4)),
+                seed: Some(3),
+                generation: 0,
+                ..GameOptions::easy()
+            }
+            .build(),
+        );
+        view.stamp(1000).left_click(4, 4);
+        view.stamp(2500).right_click(0, 0);
+        view.automation_step(AutomationPolicy::default()).ok();
+        view.undo();
+        view.automation_run();
+        view.report_assists(true, false);
+        let replay = view.replay();
+        assert_eq!(replay.finish().unwrap(), view);
+        let len = view.actions().len();
+        assert_eq!(
+            replay.seek(len - 1).unwrap().digest(len - 1),
+            view.digest(len - 1)
+        );
+        assert_ne!(view.digest(len - 1), view.digest(len));
+        assert_eq!(replay.timestamps[..3], [Some(1000), Some(2500), None]);
+        assert_eq!(replay.thinking_times(), vec![1500]);
+        assert_eq!(replay.duration(), None);
+        assert_eq!(replay.timing_entropy(), None);
+        assert_eq!(
+            replay.assistance,
+            Assistance {
+                hints: true,
+                undo: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(replay.finish().unwrap().assistance(), replay.assistance);
+
+        let mut tampered = replay.clone();
+        tampered.board = tampered.board.replacen('.', "*", 1);
+        assert_eq!(tampered.verify_board(), Err(BoardError::HashMismatch));
+        assert_eq!(tampered.finish(), Err(BoardError::HashMismatch));
+        tampered.board_hash = None;
+        assert!(tampered.finish().is_ok());
+
+        let mut stray = replay.clone();
+        stray
+            .actions
+            .push(Action::Automation(vec![Action::LeftClick(9, 0)]));
+        assert_eq!(stray.finish(), Err(BoardError::ActionOutOfBoard(9, 0)));
+        assert_eq!(stray.seek(len), Err(BoardError::ActionOutOfBoard(9, 0)));
+        let mut nested = replay.clone();
+        nested.actions.push(Action::Automation(vec![
+            Action::LeftClick(0, 0),
+            Action::Undo,
+        ]));
+        assert_eq!(nested.finish(), Err(BoardError::NestedAction));
+    }
+
+    #[test]
+    fn timing_entropy() {
+        let timed = |gaps: &[u64]| {
+            let mut view = GameView::from(GameOptions::default().build());
+            let mut time = 0;
+            view.stamp(time).right_click(0, 0);
+            for gap in gaps {
+                time += gap;
+                view.stamp(time).right_click(0, 0);
+            }
+            view.replay().timing_entropy()
+        };
+        assert_eq!(timed(&[1000; 10]), Some(0.));
+        assert!(timed(&[300, 2000, 700, 5000, 1200, 450, 9000, 800, 1500, 250]).unwrap() > 2.);
+        assert_eq!(timed(&[1000; 3]), None);
+    }
+
+    #[test]
+    fn pauses() {
+        let mut view = GameView::from(GameOptions::default().build());
+        view.stamp(1000).right_click(0, 0);
+        view.pause(2000);
+        view.pause(3000);
+        assert!(view.is_paused());
+        view.resume(62000);
+        view.stamp(63000).right_click(0, 0);
+        let replay = view.replay();
+        assert_eq!(
+            replay.pauses,
+            [Pause {
+                start: 2000,
+                end: 62000
+            }]
+        );
+        assert_eq!(replay.thinking_times(), vec![2000]);
+        assert_eq!(replay.finish().unwrap(), view);
+    }
+}
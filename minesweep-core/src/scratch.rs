@@ -0,0 +1,92 @@
+use std::{cell::RefCell, collections::HashSet};
+
+/// Buffers kept per kind, enough for the nesting of one solve
+const POOL_SIZE: usize = 4;
+
+thread_local! {
+    /// Cleared buffers left by flood fills and solves, reused instead of allocated per click
+    static CELLS: RefCell<Vec<Vec<(usize, usize)>>> = const { RefCell::new(Vec::new()) };
+    static CELL_SETS: RefCell<Vec<HashSet<(usize, usize)>>> = const { RefCell::new(Vec::new()) };
+}
+
+fn with_pooled<T: Default, R>(
+    pool: &'static std::thread::LocalKey<RefCell<Vec<T>>>,
+    clear: fn(&mut T),
+    f: impl FnOnce(&mut T) -> R,
+) -> R {
+    let mut buffer = pool.with_borrow_mut(Vec::pop).unwrap_or_default();
+    let result = f(&mut buffer);
+    clear(&mut buffer);
+    pool.with_borrow_mut(|pool| {
+        if pool.len() < POOL_SIZE {
+            pool.push(buffer);
+        }
+    });
+    result
+}
+
+/// Run with an empty list of cells, its capacity is kept for the next caller
+pub(crate) fn with_cells<R>(f: impl FnOnce(&mut Vec<(usize, usize)>) -> R) -> R {
+    with_pooled(&CELLS, Vec::clear, f)
+}
+
+/// Run with an empty set of cells, its capacity is kept for the next caller
+pub(crate) fn with_cell_set<R>(f: impl FnOnce(&mut HashSet<(usize, usize)>) -> R) -> R {
+    with_pooled(&CELL_SETS, HashSet::clear, f)
+}
+
+#[cfg(feature = "alloc-count")]
+mod count {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+    /// System allocator counting allocations, installed by binaries with `#[global_allocator]`
+    pub struct CountingAllocator;
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    /// Allocations made so far through `CountingAllocator`
+    pub fn allocations() -> usize {
+        ALLOCATIONS.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(feature = "alloc-count")]
+pub use count::{allocations, CountingAllocator};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuse() {
+        let capacity = with_cells(|cells| {
+            cells.extend((0..100).map(|i| (i, i)));
+            cells.capacity()
+        });
+        with_cells(|cells| {
+            assert!(cells.is_empty());
+            assert_eq!(cells.capacity(), capacity);
+            // nested callers get a buffer of their own
+            with_cells(|inner| assert_eq!(inner.capacity(), 0));
+        });
+    }
+}
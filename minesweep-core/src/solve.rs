@@ -1,32 +1,451 @@
-use std::collections::HashSet;
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeSet, HashMap, HashSet},
+    hash::{Hash, Hasher},
+    iter,
+};
 
-use itertools::Itertools;
-use tinysat::{Cnf, Formula, Variable};
+use serde::{Deserialize, Serialize};
+use tinysat::{Cnf, Literal, Model, Polarity, SolverConfig, VarGrid, Variable};
 
-use crate::{CellView, GameResult, GameView};
+use crate::{
+    scratch, Action, AutomationError, CellState, CellView, GameResult, GameView, Rect, RedrawCells,
+};
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct SolveResult {
     pub must_be_mine: Vec<(usize, usize)>,
     pub must_not_mine: Vec<(usize, usize)>,
 }
 
 impl SolveResult {
-    fn merge(&mut self, other: SolveResult) {
+    pub fn merge(&mut self, other: SolveResult) {
         self.must_be_mine.extend(other.must_be_mine);
         self.must_not_mine.extend(other.must_not_mine);
     }
 }
 
+/// Results kept by frontier before the cache starts over
+const SOLVE_CACHE_SIZE: usize = 256;
+
+/// Last solve of a view, kept for display until the board changes, along with earlier
+/// solves of the game by `frontier_key`
+#[derive(Debug, Clone, Default)]
+pub(crate) struct KnownCells {
+    result: SolveResult,
+    fresh: bool,
+    cache: HashMap<u64, SolveResult>,
+}
+
+impl PartialEq for KnownCells {
+    /// Only a cache, views compare equal whether they hold one or not
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+/// Constraints of some cells that solve apart from the rest of the frontier
+#[derive(Debug, Clone)]
+struct SolvePart {
+    constraints: Cnf,
+    cells: Vec<(usize, usize)>,
+    /// Identifies the constraints for a session to skip them later
+    key: Option<u64>,
+    found: bool,
+    /// Model of the last cell examined that could go either way
+    witness: Option<Model>,
+}
+
+/// Solve split into slices of one examined cell, so that callers can stop in between
+#[derive(Debug, Clone, Default)]
+pub struct SolveTask {
+    parts: Vec<SolvePart>,
+    result: SolveResult,
+    /// Keys of finished parts where no move was found
+    settled: Vec<u64>,
+    /// Cells examined so far
+    examined: usize,
+    /// Phases every solve starts from
+    config: SolverConfig,
+    /// Witnesses of the finished parts, for a session to warm-start its next task from
+    witnesses: Vec<Model>,
+}
+
+/// Where a sliced solve stands after `poll_solve`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolvePoll {
+    /// The budget ran out with cells left, `examined` out of `total` so far
+    Pending {
+        examined: usize,
+        total: usize,
+    },
+    Done,
+}
+
+impl SolveTask {
+    /// Examine one more cell, returns false once every cell is examined
+    pub fn step(&mut self, view: &GameView) -> bool {
+        let Some(part) = self.parts.last_mut() else {
+            return false;
+        };
+        let Some((x, y)) = part.cells.pop() else {
+            let part = self.parts.pop().unwrap();
+            if let (Some(key), false) = (part.key, part.found) {
+                self.settled.push(key);
+            }
+            self.witnesses.extend(part.witness);
+            return true;
+        };
+        let (result, witness) = view.check_cell(&part.constraints, x, y, &self.config);
+        part.found |= result != SolveResult::default();
+        if witness.is_some() {
+            part.witness = witness;
+        }
+        self.result.merge(result);
+        self.examined += 1;
+        true
+    }
+
+    /// Clauses left to solve under, summed over the parts
+    pub fn clauses(&self) -> usize {
+        self.parts
+            .iter()
+            .map(|part| part.constraints.num_clauses())
+            .sum()
+    }
+
+    /// Cells examined so far and cells in the whole task
+    pub fn progress(&self) -> (usize, usize) {
+        let remaining: usize = self.parts.iter().map(|part| part.cells.len()).sum();
+        (self.examined, self.examined + remaining)
+    }
+
+    /// Examine every remaining cell at once on the rayon thread pool
+    #[cfg(feature = "rayon")]
+    pub fn run_parallel(&mut self, view: &GameView) {
+        use rayon::prelude::*;
+        for part in std::mem::take(&mut self.parts) {
+            let result = part
+                .cells
+                .par_iter()
+                .map(|(x, y)| view.check_cell(&part.constraints, *x, *y, &self.config).0)
+                .reduce(SolveResult::default, |mut merged, result| {
+                    merged.merge(result);
+                    merged
+                });
+            if let (Some(key), false) = (part.key, part.found || result != SolveResult::default()) {
+                self.settled.push(key);
+            }
+            self.examined += part.cells.len();
+            self.result.merge(result);
+        }
+    }
+
+    pub fn finish(self) -> SolveResult {
+        self.result
+    }
+}
+
+/// Components of at most this many cells are solved by the components tier
+const SMALL_COMPONENT: usize = 24;
+
+/// Ways of solving tried by `TieredSolve`, cheapest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SolveTier {
+    /// Numbers settled by their own flags or intact neighbours, as `trivial_moves` finds
+    Rules,
+    /// SAT on the small components of the frontier, smallest first
+    Components,
+    /// SAT on the whole frontier, as `solve` does
+    Full,
+}
+
+/// Kind of reasoning behind a move, told apart by what `GameView::explain` needs to prove it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Reasoning {
+    /// One number settles the cell
+    Rule,
+    /// Several numbers of the same component together settle the cell
+    Local,
+    /// Nothing revealed proves the cell on its own component, such as a guess or a first click
+    Unproven,
+}
+
+/// Move of an automation step with the revealed cells proving it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deduction {
+    pub action: Action,
+    /// `None` when no revealed cells prove the move, as `GameView::explain` gives
+    pub reasons: Option<Vec<(usize, usize)>>,
+}
+
+impl Deduction {
+    pub fn reasoning(&self) -> Reasoning {
+        match &self.reasons {
+            Some(reasons) if reasons.len() <= 1 => Reasoning::Rule,
+            Some(_) => Reasoning::Local,
+            None => Reasoning::Unproven,
+        }
+    }
+}
+
+/// Solve moving on to a costlier tier only when the cheaper one found no move, sliced like
+/// `SolveTask` so that callers can stop at a latency budget and keep what was found
+#[derive(Debug, Clone)]
+pub struct TieredSolve {
+    tier: SolveTier,
+    /// `None` until the rules are applied
+    task: Option<SolveTask>,
+    finished: bool,
+    /// The full tier is skipped when its constraints have more clauses
+    max_clauses: Option<usize>,
+    oversized: bool,
+}
+
+impl Default for TieredSolve {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TieredSolve {
+    pub fn new() -> Self {
+        Self {
+            tier: SolveTier::Rules,
+            task: None,
+            finished: false,
+            max_clauses: None,
+            oversized: false,
+        }
+    }
+
+    /// Give up with `BudgetExceeded` instead of solving a frontier of more than `max` clauses
+    pub fn with_max_clauses(self, max: usize) -> Self {
+        Self {
+            max_clauses: Some(max),
+            ..self
+        }
+    }
+
+    /// Tier being tried, or the one that found the moves once finished
+    pub fn tier(&self) -> SolveTier {
+        self.tier
+    }
+
+    /// Take one slice, returns false once a tier found moves or every tier was tried
+    pub fn step(&mut self, view: &GameView) -> bool {
+        if self.finished || self.oversized {
+            return false;
+        }
+        let Some(task) = &mut self.task else {
+            self.task = Some(SolveTask {
+                result: view.trivial_moves(),
+                ..Default::default()
+            });
+            return true;
+        };
+        if task.step(view) {
+            return true;
+        }
+        if task.result != SolveResult::default() {
+            self.finished = true;
+            return false;
+        }
+        match self.tier {
+            SolveTier::Rules => {
+                self.tier = SolveTier::Components;
+                *task = view.solve_task_components(SMALL_COMPONENT);
+            }
+            SolveTier::Components => {
+                self.tier = SolveTier::Full;
+                let cells = view
+                    .components()
+                    .into_iter()
+                    .filter(|cells| cells.len() > SMALL_COMPONENT)
+                    .flatten()
+                    .collect();
+                *task = view.solve_task_for(cells);
+                if self.max_clauses.is_some_and(|max| task.clauses() > max) {
+                    *task = SolveTask::default();
+                    self.oversized = true;
+                    return false;
+                }
+            }
+            SolveTier::Full => {
+                self.finished = true;
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Moves found with the tier that found them, possibly cut short by the caller
+    pub fn finish(self) -> Result<(SolveResult, SolveTier), AutomationError> {
+        let result = self.task.map(SolveTask::finish).unwrap_or_default();
+        if !self.finished && result == SolveResult::default() {
+            return Err(AutomationError::BudgetExceeded);
+        }
+        Ok((result, self.tier))
+    }
+}
+
+/// States of a cell and its neighbours, with intact cells left out
+type Neighbourhood = Vec<Option<CellView>>;
+
+/// Solver state kept across steps of the same game, so that constraints are encoded once
+/// and components unchanged since they last gave no move are not solved again
+#[derive(Debug, Clone, Default)]
+pub struct SolverSession {
+    size: (usize, usize),
+    /// Encoded constraint of each cell along with the neighbourhood it was encoded from
+    encoded: HashMap<(usize, usize), (Neighbourhood, Cnf)>,
+    settled: HashSet<u64>,
+    /// Phases from the models of earlier steps
+    hint: SolverConfig,
+}
+
+impl SolverSession {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget everything known, for a new game or a restart
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Start later solves from the values of `model`, such as a model of the previous step
+    pub fn set_hint(&mut self, model: &Model) {
+        self.hint.warm_start(model);
+    }
+
+    fn neighbourhood(view: &GameView, x: usize, y: usize) -> Neighbourhood {
+        iter::once((x, y))
+            .chain(view.nearby_cells(x, y))
+            .map(|(x, y)| Some(view.cell(x, y)).filter(|cell| !cell.is_intact()))
+            .collect()
+    }
+
+    fn encode(&mut self, view: &GameView, x: usize, y: usize) -> Option<Cnf> {
+        let _span = tracing::trace_span!("encode", x, y).entered();
+        let neighbourhood = Self::neighbourhood(view, x, y);
+        if let Some((encoded_from, constraints)) = self.encoded.get(&(x, y)) {
+            if *encoded_from == neighbourhood {
+                return Some(constraints.clone());
+            }
+        }
+        let constraints = view.constraint_cnf(x, y)?;
+        self.encoded
+            .insert((x, y), (neighbourhood, constraints.clone()));
+        Some(constraints)
+    }
+
+    pub fn solve_task(&mut self, view: &GameView) -> SolveTask {
+        if view.result != GameResult::Playing {
+            return SolveTask::default();
+        }
+        if self.size != (view.width(), view.height()) {
+            self.reset();
+            self.size = (view.width(), view.height());
+        }
+        let mut parts = vec![];
+        for cells in view.components() {
+            let constraint_cells: BTreeSet<_> = cells
+                .iter()
+                .flat_map(|(x, y)| view.nearby_cells(*x, *y))
+                .filter(|(x, y)| matches!(view.cell(*x, *y), CellView::Opened(_)))
+                .collect();
+            let mut hasher = DefaultHasher::new();
+            for (x, y) in constraint_cells.iter() {
+                (x, y, Self::neighbourhood(view, *x, *y)).hash(&mut hasher);
+            }
+            let key = hasher.finish();
+            if self.settled.contains(&key) {
+                continue;
+            }
+            let mut constraints = Cnf::default();
+            for (x, y) in constraint_cells {
+                if let Some(encoded) = self.encode(view, x, y) {
+                    constraints.merge(encoded);
+                }
+            }
+            parts.push(SolvePart {
+                constraints,
+                cells,
+                key: Some(key),
+                found: false,
+                witness: None,
+            });
+        }
+        SolveTask {
+            parts,
+            config: self.hint.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Take the result of a task from this session and remember where nothing was found,
+    /// along with the models found to warm-start the next task
+    pub fn finish(&mut self, task: SolveTask) -> SolveResult {
+        self.settled.extend(task.settled);
+        for witness in task.witnesses.iter() {
+            self.set_hint(witness);
+        }
+        task.result
+    }
+}
+
 impl GameView {
+    /// Variables of the board, one layer holding whether each cell is mine
+    fn var_grid(self: &GameView) -> VarGrid {
+        VarGrid::new(self.width(), self.height(), 1)
+    }
+
+    /// `mine(x,y)` for the variable of a cell, `aux(n)` for the extra variables of encodings,
+    /// to print models of the constraints
+    pub fn variable_name(self: &GameView, variable: Variable) -> String {
+        match self.var_grid().cell(variable) {
+            Some((x, y, _)) => format!("mine({x},{y})"),
+            None => format!("aux({})", *variable),
+        }
+    }
+
     /// Returns a variable such that variable is true iff (x, y) is mine
     fn mine_var(self: &GameView, x: usize, y: usize) -> Variable {
-        Variable(y * self.width() + x)
+        self.var_grid()
+            .var(x, y, 0)
+            .expect("cell is out of the board")
     }
 
-    fn constraint_cell(self: &GameView, x: usize, y: usize) -> Option<Formula> {
+    /// Clauses of the constraint of one cell over the mine variables only, the cell is no mine
+    /// and exactly its number of intact neighbours left after flags are
+    fn constraint_cnf(self: &GameView, x: usize, y: usize) -> Option<Cnf> {
+        let mut cnf = Cnf::default();
+        match self.cell(x, y) {
+            CellView::Flagged => cnf.push(vec![Literal::positive(self.mine_var(x, y))]),
+            CellView::Opened(n) => {
+                let intact: Vec<_> = self
+                    .nearby(x, y)
+                    .filter(|(x, y)| self.cell(*x, *y).is_intact())
+                    .map(|(x, y)| self.mine_var(x, y))
+                    .collect();
+                match n.checked_sub(self.nearby_flags(x, y)) {
+                    Some(n) => cnf.merge(Cnf::exactly(&intact, n as usize)),
+                    // over flagged, no assignment fits
+                    None => cnf.push(vec![]),
+                }
+                cnf.push(vec![Literal::negative(self.mine_var(x, y))]);
+            }
+            _ => return None,
+        }
+        Some(cnf)
+    }
+
+    /// Same constraint as `constraint_cnf` built as a formula, kept to check the clauses against
+    #[cfg(test)]
+    fn constraint_cell(self: &GameView, x: usize, y: usize) -> Option<tinysat::Formula> {
+        use itertools::Itertools;
+        use tinysat::Formula::*;
         use CellView::*;
-        use Formula::*;
         match self.cell(x, y) {
             Flagged => Some(Variable(self.mine_var(x, y))),
             Opened(n) => {
@@ -76,51 +495,208 @@ impl GameView {
     }
 
     /// Generate constraints known from current view
-    fn constraints(self: &GameView, intact_cells_to_examine: &HashSet<(usize, usize)>) -> Formula {
-        use Formula::*;
-        let mut cells_to_examine: HashSet<(usize, usize)> = HashSet::new();
-        for (x, y) in intact_cells_to_examine {
-            cells_to_examine.extend(self.nearby_cells(*x, *y));
+    fn constraints(
+        self: &GameView,
+        intact_cells_to_examine: &HashSet<(usize, usize)>,
+    ) -> Option<Cnf> {
+        let _span = tracing::debug_span!("encode", cells = intact_cells_to_examine.len()).entered();
+        scratch::with_cell_set(|cells_to_examine| {
+            for (x, y) in intact_cells_to_examine {
+                cells_to_examine.extend(self.nearby(*x, *y));
+            }
+            cells_to_examine
+                .drain()
+                .filter_map(|(x, y)| self.constraint_cnf(x, y))
+                .reduce(|mut cnf0, cnf1| {
+                    cnf0.merge(cnf1);
+                    cnf0
+                })
+        })
+    }
+
+    /// Moves proven for the cell, or a model when it could go either way
+    fn check_cell(
+        self: &GameView,
+        constraints: &Cnf,
+        x: usize,
+        y: usize,
+        config: &SolverConfig,
+    ) -> (SolveResult, Option<Model>) {
+        let _span = tracing::trace_span!("sat_call", x, y).entered();
+        // the cell is settled unless both of its values extend to a model
+        match constraints.forall_exists_with(&[self.mine_var(x, y)], config) {
+            Ok(mut models) => (SolveResult::default(), models.pop()),
+            Err(impossible) => match impossible[0].polarity() {
+                Polarity::Positive => (
+                    SolveResult {
+                        must_be_mine: vec![],
+                        must_not_mine: vec![(x, y)],
+                    },
+                    None,
+                ),
+                Polarity::Negative => (
+                    SolveResult {
+                        must_be_mine: vec![(x, y)],
+                        must_not_mine: vec![],
+                    },
+                    None,
+                ),
+            },
         }
-        cells_to_examine
-            .into_iter()
-            .filter_map(|(x, y)| self.constraint_cell(x, y))
-            .reduce(|f0, f1| Conjunction(Box::new(f0), Box::new(f1)))
-            .unwrap()
     }
 
-    fn check_cell(self: &GameView, constraints: &Cnf, x: usize, y: usize) -> SolveResult {
-        use Formula::*;
-        let mut assume_is_mine: Cnf = constraints.clone();
-        assume_is_mine.merge(Variable(self.mine_var(x, y)).into());
-        if assume_is_mine.solve().is_unsat() {
-            return SolveResult {
-                must_be_mine: vec![],
-                must_not_mine: vec![(x, y)],
-            };
+    pub fn solve(self: &GameView) -> SolveResult {
+        let _span = tracing::debug_span!("solve").entered();
+        let mut task = self.solve_task();
+        self.poll_solve(&mut task, || false);
+        task.finish()
+    }
+
+    /// Step `task` until it is done or `spent` says the budget of this slice is used up,
+    /// so that single threaded callers can yield in between and resume later
+    pub fn poll_solve(
+        self: &GameView,
+        task: &mut SolveTask,
+        mut spent: impl FnMut() -> bool,
+    ) -> SolvePoll {
+        while task.step(self) {
+            if spent() {
+                let (examined, total) = task.progress();
+                return SolvePoll::Pending { examined, total };
+            }
         }
-        let mut assume_not_mine: Cnf = constraints.clone();
-        assume_not_mine.merge(Negation(Box::new(Variable(self.mine_var(x, y)))).into());
-        if assume_not_mine.solve().is_unsat() {
-            return SolveResult {
-                must_be_mine: vec![(x, y)],
-                must_not_mine: vec![],
-            };
+        SolvePoll::Done
+    }
+
+    /// Fingerprint of everything a solve reads, the intact frontier and the cells around it
+    pub fn frontier_key(self: &GameView) -> u64 {
+        let frontier: BTreeSet<_> = self.frontier().into_iter().collect();
+        let around: BTreeSet<_> = frontier
+            .iter()
+            .flat_map(|(x, y)| self.nearby(*x, *y))
+            .filter(|(x, y)| !self.cell(*x, *y).is_intact())
+            .collect();
+        let mut hasher = DefaultHasher::new();
+        frontier.hash(&mut hasher);
+        for (x, y) in around {
+            (x, y, self.cell(x, y)).hash(&mut hasher);
         }
-        SolveResult::default()
+        hasher.finish()
     }
 
-    pub fn solve(self: &GameView) -> SolveResult {
+    /// Result of an earlier solve of the same frontier in this game
+    pub fn cached_solve(self: &GameView) -> Option<SolveResult> {
         if self.result != GameResult::Playing {
-            return SolveResult::default();
+            return None;
         }
+        self.known.cache.get(&self.frontier_key()).cloned()
+    }
+
+    /// Keep the result of a solve of the board as it is now for `cached_solve`
+    pub fn remember_solve(self: &mut GameView, result: &SolveResult) {
+        if self.result != GameResult::Playing {
+            return;
+        }
+        if self.known.cache.len() >= SOLVE_CACHE_SIZE {
+            self.known.cache.clear();
+        }
+        let key = self.frontier_key();
+        self.known.cache.insert(key, result.clone());
+    }
+
+    /// `solve` unless the same frontier was solved before in this game
+    pub fn solve_cached(self: &mut GameView) -> SolveResult {
+        if let Some(result) = self.cached_solve() {
+            return result;
+        }
+        let result = self.solve();
+        self.remember_solve(&result);
+        result
+    }
+
+    /// Solve unless the last result still holds and keep it,
+    /// returns the cells whose hints appeared or went away
+    pub fn update_known(self: &mut GameView) -> RedrawCells {
+        if self.known.fresh {
+            return RedrawCells::default();
+        }
+        let mut redraw = self.known_cells();
+        self.known.result = self.solve_cached();
+        self.known.fresh = true;
+        redraw.merge(self.known_cells());
+        redraw
+    }
+
+    /// Keep a result solved elsewhere for the board as it is now, like `update_known`
+    /// returns the cells whose hints appeared or went away
+    pub fn set_known(self: &mut GameView, result: SolveResult) -> RedrawCells {
+        let mut redraw = self.known_cells();
+        self.remember_solve(&result);
+        self.known.result = result;
+        self.known.fresh = true;
+        redraw.merge(self.known_cells());
+        redraw
+    }
+
+    /// Cells found safe by the last solve, empty once the board changed
+    pub fn known_safe(self: &GameView) -> &[(usize, usize)] {
+        match self.known.fresh {
+            true => &self.known.result.must_not_mine,
+            false => &[],
+        }
+    }
+
+    /// Cells found to be mines by the last solve, empty once the board changed
+    pub fn known_mines(self: &GameView) -> &[(usize, usize)] {
+        match self.known.fresh {
+            true => &self.known.result.must_be_mine,
+            false => &[],
+        }
+    }
+
+    /// Solve result kept by `update_known`, if the board has not changed since
+    pub fn known(self: &GameView) -> Option<&SolveResult> {
+        self.known.fresh.then_some(&self.known.result)
+    }
+
+    /// Mines left once flags and the mines found by the last solve are taken away,
+    /// only counts what `update_known` kept and is negative when over flagged,
+    /// `None` when the count is hidden
+    pub fn effective_remaining_mines(self: &GameView) -> Option<isize> {
+        let proven = self
+            .known_mines()
+            .iter()
+            .filter(|(x, y)| self.cell(*x, *y) != CellView::Flagged)
+            .count();
+        Some(self.mines()? as isize - self.flags as isize - proven as isize)
+    }
+
+    pub(crate) fn forget_known(self: &mut GameView) {
+        self.known.fresh = false;
+    }
+
+    fn known_cells(self: &GameView) -> RedrawCells {
+        let SolveResult {
+            must_be_mine,
+            must_not_mine,
+        } = &self.known.result;
+        must_be_mine.iter().chain(must_not_mine).copied().collect()
+    }
+
+    /// Intact cells next to an opened or flagged cell
+    fn frontier(self: &GameView) -> HashSet<(usize, usize)> {
+        self.frontier_with(CellView::is_intact)
+    }
+
+    /// Cells counted as `unknown` next to an opened or flagged cell
+    fn frontier_with(self: &GameView, unknown: fn(&CellView) -> bool) -> HashSet<(usize, usize)> {
         let mut cells_to_examine = HashSet::new();
         for y in 0..self.height() {
             for x in 0..self.width() {
                 match self.cell(x, y) {
                     CellView::Flagged | CellView::Opened(_) => {
-                        for (x, y) in self.nearby_cells(x, y) {
-                            if self.cell(x, y).is_intact() {
+                        for (x, y) in self.nearby(x, y) {
+                            if unknown(&self.cell(x, y)) {
                                 cells_to_examine.insert((x, y));
                             }
                         }
@@ -129,21 +705,232 @@ impl GameView {
                 }
             }
         }
-        let constraints = self
-            .constraints(&cells_to_examine)
-            .tseitin_encode(Variable(0x10000));
-        let mut result = SolveResult::default();
-        for (x, y) in cells_to_examine {
-            result.merge(self.check_cell(&constraints, x, y));
+        cells_to_examine
+    }
+
+    /// Split the frontier into groups of cells sharing no constraint, so that each group solves on its own
+    pub fn components(self: &GameView) -> Vec<Vec<(usize, usize)>> {
+        self.components_with(CellView::is_intact)
+    }
+
+    /// Components of the frontier made of the cells counted as `unknown`
+    pub(crate) fn components_with(
+        self: &GameView,
+        unknown: fn(&CellView) -> bool,
+    ) -> Vec<Vec<(usize, usize)>> {
+        let frontier: Vec<_> = self.frontier_with(unknown).into_iter().collect();
+        let index: HashMap<_, _> = frontier
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| (*cell, i))
+            .collect();
+        let mut parent: Vec<usize> = (0..frontier.len()).collect();
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        let mut linked = vec![];
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if !matches!(self.cell(x, y), CellView::Opened(_)) {
+                    continue;
+                }
+                linked.clear();
+                linked.extend(
+                    self.nearby(x, y)
+                        .filter_map(|cell| index.get(&cell).copied()),
+                );
+                for i in linked.iter().skip(1) {
+                    let (root0, root1) = (find(&mut parent, linked[0]), find(&mut parent, *i));
+                    parent[root1] = root0;
+                }
+            }
+        }
+        let mut components: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+        for (i, cell) in frontier.iter().enumerate() {
+            components
+                .entry(find(&mut parent, i))
+                .or_default()
+                .push(*cell);
+        }
+        components.into_values().collect()
+    }
+
+    /// Constraints of the whole frontier as solved by `solve`
+    pub(crate) fn frontier_cnf(self: &GameView) -> Option<Cnf> {
+        self.constraints(&self.frontier())
+    }
+
+    pub fn solve_task(self: &GameView) -> SolveTask {
+        self.solve_task_for(self.frontier())
+    }
+
+    /// Solve only the frontier cells inside `rect`, for progress on part of a huge board
+    pub fn solve_region(self: &GameView, rect: Rect) -> SolveResult {
+        let mut task = self.solve_task_in(rect);
+        while task.step(self) {}
+        task.finish()
+    }
+
+    /// Whether chording the number would open a cell the numbers alone prove to be a mine,
+    /// the flags are left out since a wrong one is what makes a chord lose
+    pub fn refuses_chord(self: &GameView, x: usize, y: usize) -> bool {
+        if self.result != GameResult::Playing {
+            return false;
+        }
+        match self.cell(x, y) {
+            CellView::Opened(n) if n == self.nearby_flags(x, y) => {}
+            _ => return false,
+        }
+        let cells: HashSet<_> = self
+            .nearby(x, y)
+            .filter(|(x, y)| self.state.cell(*x, *y) == CellState::Unopened)
+            .collect();
+        if cells.is_empty() {
+            return false;
+        }
+        let mut state = self.state.clone();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if state.cell(x, y) == CellState::Flagged {
+                    state.set_cell(x, y, CellState::Unopened);
+                }
+            }
+        }
+        let unflagged = GameView::from(state);
+        let mut task = unflagged.solve_task_for(cells);
+        while task.step(&unflagged) {}
+        !task.finish().must_be_mine.is_empty()
+    }
+
+    /// Revealed cells whose numbers and flags alone prove the intact cell, none of them can be
+    /// left out, `None` when the cell is in no component or its component does not settle it
+    pub fn explain(self: &GameView, x: usize, y: usize) -> Option<Vec<(usize, usize)>> {
+        let component = self
+            .components()
+            .into_iter()
+            .find(|component| component.contains(&(x, y)))?;
+        let mut reasons: Vec<_> = component
+            .iter()
+            .flat_map(|(x, y)| self.nearby(*x, *y))
+            .filter(|(x, y)| !self.cell(*x, *y).is_intact())
+            .collect();
+        reasons.sort();
+        reasons.dedup();
+        let proves = |reasons: &[(usize, usize)], assumption: Literal| {
+            let mut cnf = Cnf::default();
+            for (x, y) in reasons {
+                cnf.merge(self.constraint_cnf(*x, *y).unwrap_or_default());
+            }
+            cnf.solve_assuming(&[assumption]).is_unsat()
+        };
+        let mine = self.mine_var(x, y);
+        // the value that cannot hold
+        let refuted = [Literal::positive(mine), Literal::negative(mine)]
+            .into_iter()
+            .find(|l| proves(&reasons, *l))?;
+        let mut i = 0;
+        while i < reasons.len() {
+            let removed = reasons.remove(i);
+            if !proves(&reasons, refuted) {
+                reasons.insert(i, removed);
+                i += 1;
+            }
+        }
+        Some(reasons)
+    }
+
+    /// Opens and flags of the last action when it is an automation step, each explained on the
+    /// game as it was before the step
+    pub fn explain_last_step(self: &GameView) -> Vec<Deduction> {
+        let Some(Action::Automation(actions)) = self.actions().last() else {
+            return vec![];
+        };
+        let Ok(before) = self.replay().seek(self.actions().len() - 1) else {
+            return vec![];
+        };
+        actions
+            .iter()
+            .filter_map(|action| match *action {
+                Action::LeftClick(x, y) | Action::RightClick(x, y) => Some(Deduction {
+                    action: action.clone(),
+                    reasons: before.explain(x, y),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn solve_task_in(self: &GameView, rect: Rect) -> SolveTask {
+        let mut cells = self.frontier();
+        cells.retain(|(x, y)| rect.contains(*x, *y));
+        self.solve_task_for(cells)
+    }
+
+    /// Solve the components of at most `max_cells` cells apart, the smallest first
+    fn solve_task_components(self: &GameView, max_cells: usize) -> SolveTask {
+        if self.result != GameResult::Playing {
+            return SolveTask::default();
+        }
+        let mut components = self.components();
+        components.retain(|cells| cells.len() <= max_cells);
+        // parts are taken from the back
+        components.sort_by_key(|cells| std::cmp::Reverse(cells.len()));
+        let parts = components
+            .into_iter()
+            .filter_map(|cells| {
+                let constraints = self.constraints(&cells.iter().copied().collect())?;
+                Some(SolvePart {
+                    constraints,
+                    cells,
+                    key: None,
+                    found: false,
+                    witness: None,
+                })
+            })
+            .collect();
+        SolveTask {
+            parts,
+            ..Default::default()
+        }
+    }
+
+    /// Solve only the given intact cells, usually one or more components
+    pub fn solve_task_for(self: &GameView, cells: HashSet<(usize, usize)>) -> SolveTask {
+        if self.result != GameResult::Playing {
+            return SolveTask::default();
+        }
+        let Some(constraints) = self.constraints(&cells) else {
+            return SolveTask::default();
+        };
+        SolveTask {
+            parts: vec![SolvePart {
+                constraints,
+                cells: cells.into_iter().collect(),
+                key: None,
+                found: false,
+                witness: None,
+            }],
+            ..Default::default()
         }
-        result
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::{BTreeSet, HashSet};
+
     use crate::*;
 
+    fn sorted(mut result: SolveResult) -> SolveResult {
+        result.must_be_mine.sort();
+        result.must_not_mine.sort();
+        result
+    }
+
     #[test]
     fn simple() {
         let mut view = GameView::from(
@@ -153,8 +940,9 @@ mod tests {
                     height: 5,
                     mines: 2,
                 },
-                safe_pos: None,
                 seed: Some(4),
+                generation: 0,
+                ..GameOptions::easy()
             }
             .build(),
         );
@@ -164,4 +952,354 @@ mod tests {
         let result = view.solve();
         println!("{result:?}");
     }
+
+    #[test]
+    fn components() {
+        let mut view = GameView::from(
+            GameOptions {
+                safe_pos: Some((0, 0)),
+                seed: Some(2),
+                generation: 0,
+                ..GameOptions::hard()
+            }
+            .build(),
+        );
+        view.left_click(0, 0);
+        view.left_click(29, 15);
+        let components = view.components();
+        assert!(components.len() > 1);
+        let mut merged = SolveResult::default();
+        for component in components {
+            let mut task = view.solve_task_for(component.into_iter().collect());
+            while task.step(&view) {}
+            merged.merge(task.finish());
+        }
+        assert_eq!(sorted(merged), sorted(view.solve()));
+    }
+
+    #[test]
+    fn region() {
+        let mut view = GameView::from(
+            GameOptions {
+                safe_pos: Some((0, 0)),
+                seed: Some(2),
+                generation: 0,
+                ..GameOptions::hard()
+            }
+            .build(),
+        );
+        view.left_click(0, 0);
+        view.left_click(29, 15);
+        let rect = Rect::from_corners((12, 10), (0, 0));
+        let result = view.solve_region(rect);
+        let full = view.solve();
+        assert!(result.must_be_mine.len() + result.must_not_mine.len() > 0);
+        for (x, y) in result.must_be_mine.iter() {
+            assert!(rect.contains(*x, *y) && full.must_be_mine.contains(&(*x, *y)));
+        }
+        for (x, y) in result.must_not_mine.iter() {
+            assert!(rect.contains(*x, *y) && full.must_not_mine.contains(&(*x, *y)));
+        }
+    }
+
+    #[test]
+    fn session() {
+        let mut view = GameView::from(
+            GameOptions {
+                safe_pos: Some((0, 0)),
+                seed: Some(2),
+                generation: 0,
+                ..GameOptions::hard()
+            }
+            .build(),
+        );
+        view.left_click(0, 0);
+        view.left_click(29, 15);
+        let mut session = SolverSession::new();
+        loop {
+            let mut task = session.solve_task(&view);
+            while task.step(&view) {}
+            let result = session.finish(task);
+            assert_eq!(sorted(result.clone()), sorted(view.solve()));
+            if view
+                .automation_apply(result, AutomationPolicy::default())
+                .is_err()
+            {
+                break;
+            }
+        }
+        // every component is settled once stuck, with the models found kept as phases
+        assert!(!session.hint.phases.is_empty());
+        assert!(!session.solve_task(&view).step(&view));
+    }
+
+    #[test]
+    fn poll() {
+        let mut view = GameView::from(
+            GameOptions {
+                safe_pos: Some((0, 0)),
+                seed: Some(2),
+                generation: 0,
+                ..GameOptions::hard()
+            }
+            .build(),
+        );
+        view.left_click(0, 0);
+        let mut task = view.solve_task();
+        let (_, total) = task.progress();
+        let mut last = 0;
+        while let SolvePoll::Pending {
+            examined,
+            total: left,
+        } = view.poll_solve(&mut task, || true)
+        {
+            assert_eq!(left, total);
+            assert!(last <= examined && examined <= total);
+            last = examined;
+        }
+        assert!(total > 0);
+        assert_eq!(task.progress(), (total, total));
+        assert_eq!(sorted(task.finish()), sorted(view.solve()));
+    }
+
+    #[test]
+    fn known() {
+        let mut view = GameView::from(
+            GameOptions {
+                safe_pos: Some((0, 0)),
+                seed: Some(2),
+                generation: 0,
+                ..GameOptions::hard()
+            }
+            .build(),
+        );
+        view.left_click(0, 0);
+        assert!(view.known_safe().is_empty());
+        let redraw = view.update_known();
+        let result = view.known().unwrap().clone();
+        assert_eq!(sorted(result.clone()), sorted(view.solve()));
+        assert_eq!(
+            redraw.cells.len(),
+            result.must_be_mine.len() + result.must_not_mine.len()
+        );
+        assert!(view.update_known().is_empty());
+        let proven = view.known_mines().len() as isize;
+        assert_eq!(
+            view.effective_remaining_mines(),
+            Some(view.mines as isize - view.flags as isize - proven)
+        );
+        view.automation_step(AutomationPolicy::default()).unwrap();
+        assert!(view.known().is_none());
+        assert!(view.known_safe().is_empty() && view.known_mines().is_empty());
+        let solved = view.solve();
+        view.set_known(solved.clone());
+        assert_eq!(view.known(), Some(&solved));
+        assert_eq!(view.cached_solve(), Some(solved));
+        assert!(view.update_known().is_empty());
+        assert_eq!(sorted(view.known().unwrap().clone()), sorted(view.solve()));
+    }
+
+    #[test]
+    fn tiered() {
+        let mut view = GameView::from(
+            GameOptions {
+                safe_pos: Some((8, 8)),
+                seed: Some(1),
+                generation: 0,
+                ..GameOptions::medium()
+            }
+            .build(),
+        );
+        view.left_click(8, 8);
+        assert_eq!(
+            TieredSolve::new().finish(),
+            Err(AutomationError::BudgetExceeded)
+        );
+        let mut tiers = BTreeSet::new();
+        loop {
+            let mut solve = TieredSolve::new();
+            while solve.step(&view) {}
+            let (result, tier) = solve.finish().unwrap();
+            if result == SolveResult::default() {
+                break;
+            }
+            let full = view.solve();
+            assert!(result
+                .must_be_mine
+                .iter()
+                .all(|c| full.must_be_mine.contains(c)));
+            assert!(result
+                .must_not_mine
+                .iter()
+                .all(|c| full.must_not_mine.contains(c)));
+            tiers.insert(tier);
+            let mut capped = TieredSolve::new().with_max_clauses(0);
+            while capped.step(&view) {}
+            match capped.finish() {
+                Ok((_, tier)) => assert_ne!(tier, SolveTier::Full),
+                Err(err) => assert_eq!(err, AutomationError::BudgetExceeded),
+            }
+            view.automation_apply(result, AutomationPolicy::default())
+                .unwrap();
+        }
+        assert_eq!(view.result, GameResult::Win);
+        assert!(tiers.contains(&SolveTier::Rules) && tiers.contains(&SolveTier::Components));
+    }
+
+    #[test]
+    fn refuses_chord() {
+        let board = GameState::from_board_text("...\n...\n.*.").unwrap();
+        let mut view = GameView::from(board.clone());
+        view.left_click(0, 0);
+        view.right_click(0, 2);
+        assert!(view.refuses_chord(0, 1) && view.refuses_chord(1, 1));
+        assert!(!view.refuses_chord(2, 1));
+        let mut view = GameView::from(board);
+        view.left_click(0, 0);
+        view.right_click(1, 2);
+        assert!(!view.refuses_chord(0, 1));
+        view.middle_click(0, 1);
+        assert_eq!(view.cell(0, 2), CellView::Opened(1));
+    }
+
+    #[test]
+    fn explain() {
+        let board = GameState::from_board_text("...\n...\n.*.").unwrap();
+        let mut view = GameView::from(board);
+        view.left_click(0, 0);
+        assert_eq!(view.explain(2, 2), Some(vec![(0, 1), (1, 1)]));
+        assert_eq!(view.explain(1, 2), Some(vec![(0, 1), (1, 1), (2, 1)]));
+        assert_eq!(view.explain(0, 0), None);
+
+        let board = GameState::from_board_text("*.*..\n.....\n.....").unwrap();
+        let mut view = GameView::from(board);
+        view.left_click(4, 2);
+        assert!(view.explain_last_step().is_empty());
+        view.automation_step(AutomationPolicy::default()).unwrap();
+        let deductions = view.explain_last_step();
+        assert_eq!(
+            deductions
+                .iter()
+                .map(|deduction| (deduction.action.clone(), deduction.reasoning()))
+                .collect::<HashSet<_>>(),
+            HashSet::from([
+                (Action::RightClick(2, 0), Reasoning::Rule),
+                (Action::RightClick(0, 0), Reasoning::Local),
+                (Action::LeftClick(1, 0), Reasoning::Local),
+            ])
+        );
+    }
+
+    #[test]
+    fn direct_cnf() {
+        let mut view = GameView::from(
+            GameOptions {
+                safe_pos: Some((0, 0)),
+                seed: Some(2),
+                generation: 0,
+                ..GameOptions::hard()
+            }
+            .build(),
+        );
+        view.left_click(0, 0);
+        view.automation_step(AutomationPolicy::default()).unwrap();
+        assert_eq!(sorted(view.solve()), sorted(solve_by_formula(&view)));
+    }
+
+    /// Solve the frontier with the Tseitin encoding of `constraint_cell`
+    fn solve_by_formula(view: &GameView) -> SolveResult {
+        let frontier = view.frontier();
+        let mut cells: Vec<_> = frontier
+            .iter()
+            .flat_map(|(x, y)| view.nearby_cells(*x, *y))
+            .filter(|(x, y)| !view.cell(*x, *y).is_intact())
+            .collect();
+        cells.sort();
+        cells.dedup();
+        let max_mine_var = view.mine_var(view.width() - 1, view.height() - 1);
+        let mut from_formula = tinysat::Cnf::default();
+        for (x, y) in cells {
+            let extra_vars = from_formula.fresh_var_after(max_mine_var);
+            from_formula.merge(
+                view.constraint_cell(x, y)
+                    .unwrap()
+                    .tseitin_encode(extra_vars),
+            );
+        }
+        let mut expected = SolveResult::default();
+        for (x, y) in frontier {
+            expected.merge(
+                view.check_cell(&from_formula, x, y, &tinysat::SolverConfig::default())
+                    .0,
+            );
+        }
+        expected
+    }
+
+    #[test]
+    fn large_board_formula() {
+        // board variables reach past 0x10000, where extra variables used to start
+        let mut view = GameView::from(
+            GameOptions {
+                difficulty: Difficulty::Custom {
+                    width: 300,
+                    height: 300,
+                    mines: 9000,
+                },
+                safe_pos: Some((299, 299)),
+                seed: Some(1),
+                generation: 0,
+                ..GameOptions::easy()
+            }
+            .build(),
+        );
+        view.left_click(299, 299);
+        assert!(view
+            .frontier()
+            .iter()
+            .any(|(x, y)| *view.mine_var(*x, *y) > 0x10000));
+        assert_eq!(sorted(view.solve()), sorted(solve_by_formula(&view)));
+    }
+
+    #[test]
+    fn cache() {
+        let mut view = GameView::from(
+            GameOptions {
+                safe_pos: Some((0, 0)),
+                seed: Some(2),
+                generation: 0,
+                ..GameOptions::hard()
+            }
+            .build(),
+        );
+        view.left_click(0, 0);
+        assert!(view.cached_solve().is_none());
+        let result = view.solve_cached();
+        let (x, y) = *view.frontier().iter().next().unwrap();
+        view.right_click(x, y);
+        assert!(view.cached_solve().is_none());
+        // flagged, questioned and covered again
+        view.right_click(x, y);
+        view.right_click(x, y);
+        assert_eq!(view.cached_solve(), Some(result));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel() {
+        let mut view = GameView::from(
+            GameOptions {
+                safe_pos: Some((0, 0)),
+                seed: Some(2),
+                generation: 0,
+                ..GameOptions::hard()
+            }
+            .build(),
+        );
+        view.left_click(0, 0);
+        view.left_click(29, 15);
+        let mut task = view.solve_task();
+        task.run_parallel(&view);
+        assert_eq!(sorted(task.finish()), sorted(view.solve()));
+    }
 }
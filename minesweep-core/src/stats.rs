@@ -0,0 +1,236 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Action, BoardError, CellView, Difficulty, GameResult, GameView, Replay};
+
+/// Sums over finished games, kept as sums so that tallies merge without losing precision
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Tally {
+    pub games: usize,
+    pub wins: usize,
+    /// Cells opened without being proven safe, the first click of each game aside
+    pub guesses: usize,
+    /// Seconds spent solving, as reported by the frontend
+    pub solver_time: f64,
+}
+
+impl Tally {
+    pub fn win_rate(&self) -> Option<f64> {
+        self.average(self.wins as f64)
+    }
+
+    pub fn average_guesses(&self) -> Option<f64> {
+        self.average(self.guesses as f64)
+    }
+
+    pub fn average_solver_time(&self) -> Option<f64> {
+        self.average(self.solver_time)
+    }
+
+    pub fn merge(&mut self, other: &Tally) {
+        self.games += other.games;
+        self.wins += other.wins;
+        self.guesses += other.guesses;
+        self.solver_time += other.solver_time;
+    }
+
+    fn average(&self, sum: f64) -> Option<f64> {
+        (self.games > 0).then(|| sum / self.games as f64)
+    }
+}
+
+/// Games of one difficulty, split by whether automation took any step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyStats {
+    pub difficulty: Difficulty,
+    pub manual: Tally,
+    pub automated: Tally,
+}
+
+impl DifficultyStats {
+    pub fn total(&self) -> Tally {
+        let mut total = self.manual.clone();
+        total.merge(&self.automated);
+        total
+    }
+}
+
+/// Per difficulty aggregates of finished games, in the order difficulties were first seen
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Aggregator {
+    difficulties: Vec<DifficultyStats>,
+}
+
+impl Aggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stats(&self) -> &[DifficultyStats] {
+        &self.difficulties
+    }
+
+    pub fn get(&self, difficulty: &Difficulty) -> Option<&DifficultyStats> {
+        self.difficulties
+            .iter()
+            .find(|stats| stats.difficulty == *difficulty)
+    }
+
+    /// Count a game by replaying it, games still being played are skipped
+    pub fn add_game(&mut self, view: &GameView, solver_time: f64) -> Result<(), BoardError> {
+        self.add_replay(&view.replay(), solver_time)
+    }
+
+    /// Count a replayed game, games still being played are skipped
+    pub fn add_replay(&mut self, replay: &Replay, solver_time: f64) -> Result<(), BoardError> {
+        let (view, guesses, automated) = play_counting_guesses(replay)?;
+        if view.result == GameResult::Playing {
+            return Ok(());
+        }
+        let tally = self.entry(&replay.options.difficulty);
+        let tally = match automated {
+            true => &mut tally.automated,
+            false => &mut tally.manual,
+        };
+        tally.merge(&Tally {
+            games: 1,
+            wins: (view.result == GameResult::Win) as usize,
+            guesses,
+            solver_time,
+        });
+        Ok(())
+    }
+
+    pub fn merge(&mut self, other: &Aggregator) {
+        for stats in other.stats() {
+            let entry = self.entry(&stats.difficulty);
+            entry.manual.merge(&stats.manual);
+            entry.automated.merge(&stats.automated);
+        }
+    }
+
+    fn entry(&mut self, difficulty: &Difficulty) -> &mut DifficultyStats {
+        let index = match self
+            .difficulties
+            .iter()
+            .position(|stats| stats.difficulty == *difficulty)
+        {
+            Some(index) => index,
+            None => {
+                self.difficulties.push(DifficultyStats {
+                    difficulty: difficulty.clone(),
+                    manual: Tally::default(),
+                    automated: Tally::default(),
+                });
+                self.difficulties.len() - 1
+            }
+        };
+        &mut self.difficulties[index]
+    }
+}
+
+impl Replay {
+    /// Cells opened by the player or automation that the board did not prove safe
+    pub fn guesses(&self) -> Result<usize, BoardError> {
+        Ok(play_counting_guesses(self)?.1)
+    }
+}
+
+/// Play a replay until the game ends, counting guesses and whether automation was used
+fn play_counting_guesses(replay: &Replay) -> Result<(GameView, usize, bool), BoardError> {
+    let mut view = replay.start()?;
+    let mut guesses = 0;
+    let mut automated = false;
+    for action in &replay.actions {
+        if view.result != GameResult::Playing {
+            break;
+        }
+        let clicks = match action {
+            Action::LeftClick(..) => std::slice::from_ref(action),
+            Action::Automation(actions) => {
+                automated = true;
+                actions.as_slice()
+            }
+            _ => &[],
+        };
+        guesses += count_guesses(&view, clicks);
+        view.apply(action);
+    }
+    Ok((view, guesses, automated))
+}
+
+/// Intact cells opened by `clicks` that the board before them does not prove safe
+fn count_guesses(view: &GameView, clicks: &[Action]) -> usize {
+    let opened = (0..view.height())
+        .any(|y| (0..view.width()).any(|x| matches!(view.cell(x, y), CellView::Opened(_))));
+    let opening: Vec<_> = clicks
+        .iter()
+        .filter_map(|click| match click {
+            Action::LeftClick(x, y) if view.cell(*x, *y).is_intact() => Some((*x, *y)),
+            _ => None,
+        })
+        .collect();
+    if !opened || opening.is_empty() {
+        return 0;
+    }
+    let safe: HashSet<_> = view.solve().must_not_mine.into_iter().collect();
+    opening.iter().filter(|cell| !safe.contains(cell)).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    use super::*;
+
+    #[test]
+    fn aggregate() {
+        let options = GameOptions {
+            safe_pos: Some((4, 4)),
+            seed: Some(4),
+            generation: 0,
+            ..GameOptions::easy()
+        };
+        let lose = |view: &mut GameView| {
+            let (x, y) = (0..9)
+                .flat_map(|y| (0..9).map(move |x| (x, y)))
+                .find(|&(x, y)| view.cell(x, y).is_intact() && view.state.mines[y][x])
+                .unwrap();
+            view.left_click(x, y);
+        };
+        let mut aggregator = Aggregator::new();
+        let mut automated = GameView::from(options.clone().build());
+        automated.left_click(4, 4);
+        aggregator.add_game(&automated, 0.).unwrap();
+        assert!(aggregator.stats().is_empty());
+        while automated
+            .automation_step(AutomationPolicy::default())
+            .is_ok()
+        {}
+        if automated.result == GameResult::Playing {
+            lose(&mut automated);
+        }
+        aggregator.add_game(&automated, 1.5).unwrap();
+
+        let mut lost = GameView::from(options.clone().build());
+        lost.left_click(4, 4);
+        lose(&mut lost);
+        assert_eq!(lost.result, GameResult::Lose);
+        assert_eq!(lost.replay().guesses(), Ok(1));
+        aggregator.add_replay(&lost.replay(), 0.).unwrap();
+
+        let stats = aggregator.get(&Difficulty::Easy).unwrap();
+        assert_eq!(stats.manual.games, 1);
+        assert_eq!(stats.manual.win_rate(), Some(0.));
+        assert_eq!(stats.manual.guesses, 1);
+        assert_eq!(stats.automated.games, 1);
+        assert_eq!(stats.automated.average_solver_time(), Some(1.5));
+        assert_eq!(stats.total().games, 2);
+
+        let mut merged = aggregator.clone();
+        merged.merge(&aggregator);
+        assert_eq!(merged.stats().len(), 1);
+        assert_eq!(merged.get(&Difficulty::Easy).unwrap().total().games, 4);
+    }
+}
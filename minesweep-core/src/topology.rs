@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+/// Shape of the grid, geometry is in cell units with the board starting at the origin
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Topology {
+    #[default]
+    Square,
+}
+
+impl Topology {
+    /// Corners of the cell in drawing order
+    pub fn cell_polygon(&self, x: usize, y: usize) -> Vec<(f64, f64)> {
+        match self {
+            Topology::Square => {
+                let (x, y) = (x as f64, y as f64);
+                vec![(x, y), (x + 1., y), (x + 1., y + 1.), (x, y + 1.)]
+            }
+        }
+    }
+
+    /// Size of a board of `w` by `h` cells
+    pub fn bounds(&self, w: usize, h: usize) -> (f64, f64) {
+        match self {
+            Topology::Square => (w as f64, h as f64),
+        }
+    }
+
+    /// Cell under the point on a board of `w` by `h` cells, points on the border belong to
+    /// the closest cell
+    pub fn hit_test(&self, (px, py): (f64, f64), w: usize, h: usize) -> Option<(usize, usize)> {
+        let (width, height) = self.bounds(w, h);
+        if !(0. ..=width).contains(&px) || !(0. ..=height).contains(&py) {
+            return None;
+        }
+        match self {
+            Topology::Square => Some((
+                (px.floor() as usize).min(w - 1),
+                (py.floor() as usize).min(h - 1),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_test() {
+        let topology = Topology::Square;
+        for (x, y) in [(0, 0), (3, 1), (4, 2)] {
+            let polygon = topology.cell_polygon(x, y);
+            let n = polygon.len() as f64;
+            let center = polygon
+                .iter()
+                .fold((0., 0.), |(cx, cy), (px, py)| (cx + px / n, cy + py / n));
+            assert_eq!(topology.hit_test(center, 5, 3), Some((x, y)));
+        }
+        assert_eq!(topology.hit_test((5., 3.), 5, 3), Some((4, 2)));
+        assert_eq!(topology.hit_test((-0.1, 1.), 5, 3), None);
+        assert_eq!(topology.hit_test((2., 3.1), 5, 3), None);
+    }
+}
@@ -0,0 +1,199 @@
+use std::fmt::Display;
+
+use rand::{thread_rng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    Action, BoardLimits, Difficulty, GameOptions, GameResult, Replay, Symmetry, GENERATION_VERSION,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TournamentError {
+    NoSuchRound(usize),
+    CommitmentMismatch,
+    OtherBoard,
+    BoardMismatch,
+    Automated,
+    InvalidReplay(String),
+}
+
+impl Display for TournamentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use TournamentError::*;
+        match self {
+            NoSuchRound(round) => write!(f, "pack has no round {round}"),
+            CommitmentMismatch => write!(f, "revealed seed does not match its commitment"),
+            OtherBoard => write!(f, "replay is of another board"),
+            BoardMismatch => write!(f, "replay board does not match its seed"),
+            Automated => write!(f, "replay uses automation"),
+            InvalidReplay(err) => write!(f, "invalid replay: {err}"),
+        }
+    }
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Seed of one round along with the salt keeping it from being guessed off its commitment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SealedSeed {
+    pub seed: u64,
+    pub salt: String,
+}
+
+impl SealedSeed {
+    pub fn commitment(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        hasher.update([0]);
+        hasher.update(self.seed.to_le_bytes());
+        hex(&hasher.finalize())
+    }
+}
+
+/// Rounds of a tournament as published before it starts, seeds are revealed round by round
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeedPack {
+    pub name: String,
+    pub difficulty: Difficulty,
+    /// SHA-256 of each round's salt and seed
+    pub commitments: Vec<String>,
+    /// Mine placement the boards are built with, packs published before versioning use the first
+    #[serde(default)]
+    pub generation: u32,
+}
+
+/// Pack kept by the organizer, holding every seed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SealedSeedPack {
+    pub pack: SeedPack,
+    pub seeds: Vec<SealedSeed>,
+}
+
+impl SealedSeedPack {
+    pub fn generate(name: String, difficulty: Difficulty, rounds: usize) -> Self {
+        let mut rng = thread_rng();
+        let seeds: Vec<_> = (0..rounds)
+            .map(|_| {
+                let mut salt = [0; 16];
+                rng.fill_bytes(&mut salt);
+                SealedSeed {
+                    seed: rng.next_u64(),
+                    salt: hex(&salt),
+                }
+            })
+            .collect();
+        Self {
+            pack: SeedPack {
+                name,
+                difficulty,
+                commitments: seeds.iter().map(SealedSeed::commitment).collect(),
+                generation: GENERATION_VERSION,
+            },
+            seeds,
+        }
+    }
+
+    pub fn reveal(&self, round: usize) -> Option<&SealedSeed> {
+        self.seeds.get(round)
+    }
+}
+
+impl SeedPack {
+    /// Check a revealed seed against the commitment of its round
+    pub fn open(&self, round: usize, seed: &SealedSeed) -> Result<(), TournamentError> {
+        let commitment = self
+            .commitments
+            .get(round)
+            .ok_or(TournamentError::NoSuchRound(round))?;
+        if *commitment != seed.commitment() {
+            return Err(TournamentError::CommitmentMismatch);
+        }
+        Ok(())
+    }
+
+    /// Options every player of a round builds the board from, starting at the center
+    pub fn options(&self, seed: &SealedSeed) -> GameOptions {
+        GameOptions {
+            difficulty: self.difficulty.clone(),
+            safe_pos: Some((self.difficulty.width() / 2, self.difficulty.height() / 2)),
+            seed: Some(seed.seed),
+            symmetry: Symmetry::None,
+            learning: false,
+            hidden_count: false,
+            generation: self.generation,
+            limits: BoardLimits::default(),
+        }
+    }
+
+    /// Replay a submitted game of a round once its seed is revealed, returns how it ended
+    pub fn verify(
+        &self,
+        round: usize,
+        seed: &SealedSeed,
+        replay: &Replay,
+    ) -> Result<GameResult, TournamentError> {
+        self.open(round, seed)?;
+        let options = self.options(seed);
+        if replay.options != options {
+            return Err(TournamentError::OtherBoard);
+        }
+        if options.build().to_board_text() != replay.board {
+            return Err(TournamentError::BoardMismatch);
+        }
+        if replay
+            .actions
+            .iter()
+            .any(|action| matches!(action, Action::Automation(_)))
+        {
+            return Err(TournamentError::Automated);
+        }
+        let view = replay
+            .finish()
+            .map_err(|err| TournamentError::InvalidReplay(err.to_string()))?;
+        Ok(view.result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn seed_pack() {
+        let sealed = SealedSeedPack::generate("cup".to_string(), Difficulty::Easy, 3);
+        let pack = &sealed.pack;
+        let seed = sealed.reveal(1).unwrap();
+        assert_eq!(pack.open(1, seed), Ok(()));
+        assert_eq!(pack.open(0, seed), Err(TournamentError::CommitmentMismatch));
+        assert_eq!(pack.open(3, seed), Err(TournamentError::NoSuchRound(3)));
+
+        let mut view = GameView::from(pack.options(seed).build());
+        view.left_click(4, 4);
+        assert_eq!(
+            pack.verify(1, seed, &view.replay()),
+            Ok(GameResult::Playing)
+        );
+        let other = SealedSeed {
+            seed: seed.seed.wrapping_add(1),
+            salt: seed.salt.clone(),
+        };
+        assert!(pack.verify(1, &other, &view.replay()).is_err());
+        let mut replay = view.replay();
+        replay.actions.push(Action::Automation(vec![]));
+        assert_eq!(
+            pack.verify(1, seed, &replay),
+            Err(TournamentError::Automated)
+        );
+        let mut replay = view.replay();
+        replay.actions.push(Action::LeftClick(4, 9));
+        assert_eq!(
+            pack.verify(1, seed, &replay),
+            Err(TournamentError::InvalidReplay(
+                BoardError::ActionOutOfBoard(4, 9).to_string()
+            ))
+        );
+    }
+}
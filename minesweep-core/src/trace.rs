@@ -0,0 +1,187 @@
+#[cfg(feature = "trace")]
+use std::{cell::RefCell, collections::VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{GameView, SolveResult};
+
+/// Version of the trace bundle format, bumped on incompatible changes
+pub const TRACE_BUNDLE_VERSION: u32 = 1;
+
+/// Steps kept by the trace, older ones are dropped
+#[cfg(feature = "trace")]
+const TRACE_CAPACITY: usize = 256;
+
+#[cfg(feature = "trace")]
+thread_local! {
+    static TRACE: RefCell<VecDeque<TraceStep>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// One automation step as the solver saw it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceStep {
+    pub view: GameView,
+    /// Constraints of the whole frontier, empty when there is no frontier
+    pub dimacs: String,
+    pub result: SolveResult,
+    /// Board in the text format, missing from steps traced before it was added
+    #[serde(default)]
+    pub board: String,
+}
+
+impl TraceStep {
+    pub fn new(view: &GameView, result: &SolveResult) -> Self {
+        Self {
+            view: view.clone(),
+            dimacs: view
+                .frontier_cnf()
+                .map(|cnf| cnf.to_dimacs())
+                .unwrap_or_default(),
+            result: result.clone(),
+            board: view.state.to_board_text(),
+        }
+    }
+
+    /// Solve the view again, returns the moves found when they differ from the recorded ones
+    pub fn reproduce(&self) -> Result<(), SolveResult> {
+        let sorted = |mut result: SolveResult| {
+            result.must_be_mine.sort();
+            result.must_not_mine.sort();
+            result
+        };
+        let result = sorted(self.view.solve());
+        if result == sorted(self.result.clone()) {
+            Ok(())
+        } else {
+            Err(result)
+        }
+    }
+
+    /// Some placement of mines the frontier constraints allow, with variables named by cell
+    pub fn completion(&self) -> Option<String> {
+        let cnf = self.view.frontier_cnf()?;
+        Some(
+            cnf.solve()
+                .named(|v| self.view.variable_name(v))
+                .to_string(),
+        )
+    }
+}
+
+/// Recorded steps dumped to be solved again elsewhere
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceBundle {
+    pub version: u32,
+    pub steps: Vec<TraceStep>,
+}
+
+impl Default for TraceBundle {
+    fn default() -> Self {
+        Self {
+            version: TRACE_BUNDLE_VERSION,
+            steps: vec![],
+        }
+    }
+}
+
+impl TraceBundle {
+    /// Take the steps recorded on this thread so far, always empty without the `trace` feature
+    pub fn take() -> Self {
+        #[cfg(feature = "trace")]
+        let steps = TRACE.with_borrow_mut(|trace| trace.drain(..).collect());
+        #[cfg(not(feature = "trace"))]
+        let steps = vec![];
+        Self {
+            version: TRACE_BUNDLE_VERSION,
+            steps,
+        }
+    }
+
+    pub fn merge(&mut self, other: TraceBundle) {
+        self.steps.extend(other.steps);
+    }
+}
+
+impl GameView {
+    /// Check the moves of a solve against the mines of the board, a contradiction is a solver
+    /// bug returned as a step to be dumped and solved again elsewhere
+    pub fn verify_result(&self, result: &SolveResult) -> Result<(), Box<TraceStep>> {
+        let wrong_mine = result
+            .must_be_mine
+            .iter()
+            .any(|(x, y)| !self.state.mines[*y][*x]);
+        let wrong_safe = result
+            .must_not_mine
+            .iter()
+            .any(|(x, y)| self.state.mines[*y][*x]);
+        match wrong_mine || wrong_safe {
+            true => Err(Box::new(TraceStep::new(self, result))),
+            false => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "trace")]
+pub(crate) fn record(view: &GameView, result: &SolveResult) {
+    let step = TraceStep::new(view, result);
+    TRACE.with_borrow_mut(|trace| {
+        if trace.len() == TRACE_CAPACITY {
+            trace.pop_front();
+        }
+        trace.push_back(step);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn verify_result() {
+        let mut view = GameView::from(
+            GameOptions {
+                safe_pos: Some((8, 8)),
+                seed: Some(1),
+                ..GameOptions::medium()
+            }
+            .build(),
+        );
+        view.left_click(8, 8);
+        let mut result = view.solve();
+        assert!(view.verify_result(&result).is_ok());
+        let mine = (0..16)
+            .flat_map(|y| (0..16).map(move |x| (x, y)))
+            .find(|&(x, y)| view.state.mines[y][x])
+            .unwrap();
+        result.must_not_mine.push(mine);
+        let step = view.verify_result(&result).unwrap_err();
+        assert!(step.dimacs.starts_with("p cnf "));
+        assert_eq!(step.board, view.state.to_board_text());
+        assert_eq!(step.result, result);
+        let completion = step.completion().unwrap();
+        assert!(completion.starts_with("sat: mine("));
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn trace() {
+        TraceBundle::take();
+        let mut view = GameView::from(
+            GameOptions {
+                safe_pos: Some((8, 8)),
+                seed: Some(1),
+                ..GameOptions::medium()
+            }
+            .build(),
+        );
+        view.left_click(8, 8);
+        view.automation_run();
+        let bundle = TraceBundle::take();
+        assert!(!bundle.steps.is_empty());
+        assert!(bundle.steps[0].dimacs.starts_with("p cnf "));
+        for step in bundle.steps.iter() {
+            assert_eq!(step.reproduce(), Ok(()));
+        }
+        assert!(TraceBundle::take().steps.is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Action, AutomationPolicy, GameResult, GameState, GameView, RedrawCells};
+
+const BOARD: &str = ".....\n.....\n...*.\n.....";
+
+#[derive(Debug, PartialEq)]
+enum Task {
+    Click(Action),
+    Automation,
+    Done,
+}
+
+const STEPS: [(&str, Task); 5] = [
+    (
+        "Left click the highlighted cell to open it. \
+        A cell without nearby mines opens its neighbors as well.",
+        Task::Click(Action::LeftClick(0, 0)),
+    ),
+    (
+        "Numbers tell how many mines are nearby. \
+        The highlighted cell is the only covered neighbor of the 1 above it, so it must be a mine. \
+        Right click it to place a flag.",
+        Task::Click(Action::RightClick(3, 2)),
+    ),
+    (
+        "This 1 already has its mine flagged, so its other neighbors are safe. \
+        Middle click it to open them all at once.",
+        Task::Click(Action::MiddleClick(3, 1)),
+    ),
+    (
+        "Automation finds such deductions by itself with a SAT solver. \
+        Press Step to let it finish the board.",
+        Task::Automation,
+    ),
+    ("Well done! Start a new game to play for real.", Task::Done),
+];
+
+/// Scripted walkthrough over a small fixed board, only the expected input is accepted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tutorial {
+    pub view: GameView,
+    step: usize,
+}
+
+impl Default for Tutorial {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Self {
+            view: GameView::from(GameState::from_board_text(BOARD).unwrap()),
+            step: 0,
+        }
+    }
+
+    fn task(&self) -> &Task {
+        &STEPS[self.step].1
+    }
+
+    pub fn message(&self) -> &'static str {
+        STEPS[self.step].0
+    }
+
+    pub fn expects_automation(&self) -> bool {
+        *self.task() == Task::Automation
+    }
+
+    pub fn is_finished(&self) -> bool {
+        *self.task() == Task::Done
+    }
+
+    /// Cell the player should act on in the current step
+    pub fn expected_cell(&self) -> Option<(usize, usize)> {
+        match self.task() {
+            Task::Click(
+                Action::LeftClick(x, y) | Action::RightClick(x, y) | Action::MiddleClick(x, y),
+            ) => Some((*x, *y)),
+            _ => None,
+        }
+    }
+
+    fn advance(&mut self, mut redraw: RedrawCells) -> RedrawCells {
+        redraw.extend(self.expected_cell());
+        self.step += 1;
+        redraw.extend(self.expected_cell());
+        redraw
+    }
+
+    /// Returns `None` when the click is not what current step asks for
+    pub fn click(&mut self, action: Action) -> Option<RedrawCells> {
+        if *self.task() != Task::Click(action.clone()) {
+            return None;
+        }
+        let redraw = self.view.apply(&action);
+        Some(self.advance(redraw))
+    }
+
+    pub fn automation_step(&mut self) -> Option<RedrawCells> {
+        if !self.expects_automation() {
+            return None;
+        }
+        let redraw = self
+            .view
+            .automation_step(AutomationPolicy::default())
+            .ok()?;
+        if self.view.result == GameResult::Playing {
+            return Some(redraw);
+        }
+        Some(self.advance(redraw))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn tutorial() {
+        let mut tutorial = Tutorial::new();
+        assert!(tutorial.click(Action::LeftClick(1, 1)).is_none());
+        assert!(tutorial.click(Action::LeftClick(0, 0)).is_some());
+        assert!(tutorial.click(Action::RightClick(3, 2)).is_some());
+        assert!(tutorial.click(Action::MiddleClick(3, 1)).is_some());
+        while !tutorial.is_finished() {
+            tutorial.automation_step().unwrap();
+        }
+        assert_eq!(tutorial.view.result, GameResult::Win);
+    }
+}
@@ -0,0 +1,1207 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    replay::{Assistance, Pause},
+    scratch,
+    solve::KnownCells,
+    Action, CellState, GameOptions, GameResult, GameState, SolveResult, Topology,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CellView {
+    Unopened,
+    Hovered,
+    Pushed,
+    Flagged,
+    Questioned,
+    Opened(u8),
+    Mine,
+    WrongMine,
+    Exploded,
+}
+
+impl CellView {
+    pub fn is_intact(&self) -> bool {
+        use CellView::*;
+        matches!(self, Unopened | Hovered | Pushed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Gesture {
+    Hover(usize, usize),
+    LeftOrRightPush(usize, usize),
+    MidPush(usize, usize),
+    /// Keyboard cursor, looks like hover
+    Focus(usize, usize),
+    /// Finger down on a cell
+    TouchPush(usize, usize),
+    /// Finger held on a number to open its neighbours, looks like a middle push
+    TouchHold(usize, usize),
+    None,
+}
+
+impl Gesture {
+    pub fn position(&self) -> Option<(usize, usize)> {
+        use Gesture::*;
+        match *self {
+            Hover(x, y)
+            | LeftOrRightPush(x, y)
+            | MidPush(x, y)
+            | Focus(x, y)
+            | TouchPush(x, y)
+            | TouchHold(x, y) => Some((x, y)),
+            None => Option::None,
+        }
+    }
+
+    /// Whether the neighbours of the cell are pushed as well
+    pub fn is_area(&self) -> bool {
+        matches!(self, Gesture::MidPush(..) | Gesture::TouchHold(..))
+    }
+
+    /// Look of an unopened cell under this gesture
+    pub fn intact_cell(&self, x: usize, y: usize) -> CellView {
+        let Some((x0, y0)) = self.position() else {
+            return CellView::Unopened;
+        };
+        if (x, y) == (x0, y0) {
+            match self {
+                Gesture::LeftOrRightPush(..) | Gesture::TouchPush(..) => CellView::Pushed,
+                _ => CellView::Hovered,
+            }
+        } else if self.is_area() && x.abs_diff(x0) <= 1 && y.abs_diff(y0) <= 1 {
+            CellView::Pushed
+        } else {
+            CellView::Unopened
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameView {
+    pub(crate) state: GameState,
+    pub(crate) cells: Vec<Vec<CellView>>,
+    pub result: GameResult,
+    pub gesture: Gesture,
+    pub(crate) mines: usize,
+    pub flags: usize,
+    pub(crate) history: History,
+    #[serde(skip)]
+    pub(crate) known: KnownCells,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CellChange {
+    pub(crate) x: usize,
+    pub(crate) y: usize,
+    pub(crate) from: CellState,
+    pub(crate) to: CellState,
+}
+
+/// Undo and redo stacks, each entry is every cell change made by one player action,
+/// along with the actions taken so far for replay
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct History {
+    pub(crate) undo: Vec<Vec<CellChange>>,
+    pub(crate) redo: Vec<Vec<CellChange>>,
+    pub(crate) pending: Vec<CellChange>,
+    pub(crate) undos: usize,
+    pub(crate) actions: Vec<Action>,
+    pub(crate) pending_actions: Vec<Action>,
+    /// Time of each action, see `GameView::stamp`
+    pub(crate) timestamps: Vec<Option<u64>>,
+    pub(crate) pending_timestamp: Option<u64>,
+    /// Assists reported by the frontend, see `GameView::report_assists`
+    pub(crate) reported: Assistance,
+    /// Finished pauses, see `GameView::pause`
+    #[serde(default)]
+    pub(crate) pauses: Vec<Pause>,
+    #[serde(default)]
+    pub(crate) paused_since: Option<u64>,
+    /// See `GameView::revision`, kept across restarts
+    #[serde(default)]
+    pub(crate) revision: u64,
+}
+
+impl History {
+    pub(crate) fn push_action(&mut self, action: Action) {
+        self.revision += 1;
+        self.actions.push(action);
+        self.timestamps.push(self.pending_timestamp.take());
+    }
+
+    pub(crate) fn record(&mut self, x: usize, y: usize, from: CellState, to: CellState) {
+        self.pending.push(CellChange { x, y, from, to });
+    }
+
+    pub(crate) fn commit(&mut self, automation: bool) {
+        let actions = std::mem::take(&mut self.pending_actions);
+        if self.pending.is_empty() {
+            self.pending_timestamp = None;
+            return;
+        }
+        if automation {
+            self.push_action(Action::Automation(actions));
+        } else {
+            let timestamp = self.pending_timestamp;
+            for action in actions {
+                self.pending_timestamp = timestamp;
+                self.push_action(action);
+            }
+            self.pending_timestamp = None;
+        }
+        self.undo.push(std::mem::take(&mut self.pending));
+        self.redo.clear();
+    }
+}
+
+impl From<GameState> for GameView {
+    fn from(state: GameState) -> Self {
+        let result = GameResult::Playing;
+        let cells = (0..state.height())
+            .map(|_| (0..state.width()).map(|_| CellView::Unopened).collect())
+            .collect();
+        let mines = state.mines();
+        let mut this = Self {
+            state,
+            cells,
+            result,
+            gesture: Gesture::None,
+            mines,
+            flags: 0,
+            history: History::default(),
+            known: KnownCells::default(),
+        };
+        this.refresh_game_result();
+        this.refresh_all_cell();
+        this
+    }
+}
+
+/// Rectangle of cells, such as a region selected for automation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    /// Smallest rectangle holding both cells
+    pub fn from_corners((x0, y0): (usize, usize), (x1, y1): (usize, usize)) -> Self {
+        Self {
+            x: x0.min(x1),
+            y: y0.min(y1),
+            width: x0.abs_diff(x1) + 1,
+            height: y0.abs_diff(y1) + 1,
+        }
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+}
+
+impl From<Rect> for RedrawRegion {
+    fn from(rect: Rect) -> Self {
+        RedrawRegion::Rect {
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: rect.height,
+        }
+    }
+}
+
+/// Cells to draw again in one pass, instead of listing them one by one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RedrawRegion {
+    Rect {
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    },
+    All,
+}
+
+impl RedrawRegion {
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        match *self {
+            RedrawRegion::Rect {
+                x: x0,
+                y: y0,
+                width,
+                height,
+            } => (x0..x0 + width).contains(&x) && (y0..y0 + height).contains(&y),
+            RedrawRegion::All => true,
+        }
+    }
+
+    /// Cells of the region on a board of the given size
+    pub fn cells(&self, w: usize, h: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (x0, y0, x1, y1) = match *self {
+            RedrawRegion::Rect {
+                x,
+                y,
+                width,
+                height,
+            } => (x, y, (x + width).min(w), (y + height).min(h)),
+            RedrawRegion::All => (0, 0, w, h),
+        };
+        (y0..y1).flat_map(move |y| (x0..x1).map(move |x| (x, y)))
+    }
+}
+
+/// Cells to draw again, each cell appears once however many changes touched it,
+/// cells inside one of the regions are not listed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RedrawCells {
+    pub cells: BTreeSet<(usize, usize)>,
+    pub regions: Vec<RedrawRegion>,
+}
+
+impl RedrawCells {
+    pub fn redraw_all() -> Self {
+        Self::region(RedrawRegion::All)
+    }
+
+    pub fn region(region: RedrawRegion) -> Self {
+        Self {
+            cells: BTreeSet::new(),
+            regions: vec![region],
+        }
+    }
+
+    pub fn single(x: usize, y: usize) -> Self {
+        Self {
+            cells: BTreeSet::from([(x, y)]),
+            regions: vec![],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty() && self.regions.is_empty()
+    }
+
+    pub fn is_all(&self) -> bool {
+        self.regions.contains(&RedrawRegion::All)
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        self.cells.contains(&(x, y)) || self.regions.iter().any(|region| region.contains(x, y))
+    }
+
+    pub fn insert(&mut self, (x, y): (usize, usize)) {
+        if !self.regions.iter().any(|region| region.contains(x, y)) {
+            self.cells.insert((x, y));
+        }
+    }
+
+    pub fn merge(&mut self, other: RedrawCells) {
+        if self.is_all() || other.is_all() {
+            *self = Self::redraw_all();
+            return;
+        }
+        for region in other.regions {
+            self.cells.retain(|(x, y)| !region.contains(*x, *y));
+            if !self.regions.contains(&region) {
+                self.regions.push(region);
+            }
+        }
+        self.extend(other.cells);
+    }
+
+    /// Every cell to draw on a board of the given size, regions expanded
+    pub fn iter(&self, w: usize, h: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let mut cells = self.cells.clone();
+        cells.extend(self.regions.iter().flat_map(|region| region.cells(w, h)));
+        cells.into_iter()
+    }
+}
+
+impl FromIterator<(usize, usize)> for RedrawCells {
+    fn from_iter<T: IntoIterator<Item = (usize, usize)>>(iter: T) -> Self {
+        Self {
+            cells: iter.into_iter().collect(),
+            regions: vec![],
+        }
+    }
+}
+
+impl Extend<(usize, usize)> for RedrawCells {
+    fn extend<T: IntoIterator<Item = (usize, usize)>>(&mut self, iter: T) {
+        for cell in iter {
+            self.insert(cell);
+        }
+    }
+}
+
+/// Everything a click changed, so that callers need not compare the view before and after
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClickOutcome {
+    pub redraw: RedrawCells,
+    /// Result after the click
+    pub result: GameResult,
+    /// Cells opened by the click, including the ones opened by cascading
+    pub opened: usize,
+    pub flags_delta: isize,
+    /// Mine opened by the click, which lost the game
+    pub exploded_at: Option<(usize, usize)>,
+}
+
+/// Why an automation step made no move
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AutomationError {
+    GameOver,
+    /// Every cell left needs a guess
+    NoMoves,
+    /// Solver ran out of its time before finding any move
+    BudgetExceeded,
+    Solver(String),
+}
+
+impl Display for AutomationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use AutomationError::*;
+        match self {
+            GameOver => write!(f, "game is over"),
+            NoMoves => write!(f, "no possible move found"),
+            BudgetExceeded => write!(f, "solver ran out of time"),
+            Solver(reason) => write!(f, "solver failed: {reason}"),
+        }
+    }
+}
+
+/// How an automation step makes the moves found by a solve
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AutomationPolicy {
+    /// Flag the mines, open the safe cells, then chord every number
+    #[default]
+    FlagsFirst,
+    /// Open the safe cells, flag the mines, then chord every number
+    OpensFirst,
+    /// Open the safe cells only, as players of no flag style do, nothing is chorded
+    NoFlag,
+}
+
+impl AutomationPolicy {
+    pub fn flags(self) -> bool {
+        self != AutomationPolicy::NoFlag
+    }
+
+    pub fn chords(self) -> bool {
+        self != AutomationPolicy::NoFlag
+    }
+}
+
+impl GameView {
+    pub fn options(&self) -> GameOptions {
+        self.state.options.clone()
+    }
+
+    pub fn width(&self) -> usize {
+        self.state.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.state.height()
+    }
+
+    /// 3BV of the board, see `GameState::bbbv`
+    pub fn bbbv(&self) -> usize {
+        self.state.bbbv()
+    }
+
+    /// Every board is square for now
+    pub fn topology(&self) -> Topology {
+        Topology::Square
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> CellView {
+        self.cells[y][x]
+    }
+
+    pub fn set_cell(&mut self, x: usize, y: usize, cell: CellView) {
+        self.cells[y][x] = cell;
+    }
+
+    pub fn nearby_cells(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        self.state.nearby_cells(x, y)
+    }
+
+    pub(crate) fn nearby(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.state.nearby(x, y)
+    }
+
+    pub fn nearby_mines(&self, x: usize, y: usize) -> u8 {
+        self.state.nearby_mines(x, y)
+    }
+
+    pub fn nearby_flags(&self, x: usize, y: usize) -> u8 {
+        self.state.nearby_flags(x, y)
+    }
+
+    /// Whether an opened number has more nearby flags than its value
+    pub fn is_flag_warning(&self, x: usize, y: usize) -> bool {
+        self.state.is_opened(x, y)
+            && !self.state.is_mine(x, y)
+            && self.nearby_flags(x, y) > self.nearby_mines(x, y)
+    }
+
+    /// Whether flagging the cell is refused because it has no mine, only in learning mode
+    pub fn refuses_flag(&self, x: usize, y: usize) -> bool {
+        self.state.options.learning
+            && self.state.cell(x, y) == CellState::Unopened
+            && !self.state.is_mine(x, y)
+    }
+
+    /// Mines on the board, `None` in games hiding the count
+    pub fn mines(&self) -> Option<usize> {
+        (!self.state.options.hidden_count).then_some(self.mines)
+    }
+
+    pub fn is_over_flagged(&self) -> bool {
+        self.mines().is_some_and(|mines| self.flags > mines)
+    }
+
+    fn set_state(&mut self, x: usize, y: usize, state: CellState) {
+        self.history.record(x, y, self.state.cell(x, y), state);
+        self.state.set_cell(x, y, state);
+        self.forget_known();
+    }
+
+    fn refresh_game_result(&mut self) {
+        self.result = self.state.game_result();
+        if self.result == GameResult::Win {
+            self.flags = self.mines;
+        }
+    }
+
+    fn refresh_all_cell(&mut self) -> RedrawCells {
+        let mut redraw = RedrawCells::default();
+        for y in 0..self.state.height() {
+            for x in 0..self.state.width() {
+                redraw.merge(self.refresh_cell(x, y));
+            }
+        }
+        // the whole board is revealed once the game is over
+        if self.result != GameResult::Playing && !redraw.is_empty() {
+            return RedrawCells::redraw_all();
+        }
+        redraw
+    }
+
+    fn refresh_3x3_cell(&mut self, x: usize, y: usize) -> RedrawCells {
+        let mut redraw = RedrawCells::default();
+        redraw.merge(self.refresh_cell(x, y));
+        for (x, y) in self.nearby_cells(x, y) {
+            redraw.merge(self.refresh_cell(x, y));
+        }
+        redraw
+    }
+
+    fn refresh_gesture(&mut self, gesture: Gesture) -> RedrawCells {
+        match gesture.position() {
+            Some((x, y)) if gesture.is_area() => self.refresh_3x3_cell(x, y),
+            Some((x, y)) => self.refresh_cell(x, y),
+            None => Default::default(),
+        }
+    }
+
+    fn refresh_cell(&mut self, x: usize, y: usize) -> RedrawCells {
+        use CellView::*;
+        use GameResult::*;
+        let previous_cell_view = self.cell(x, y);
+        let cell_view = match (self.result, self.state.is_mine(x, y), self.state.cell(x, y)) {
+            (Win, true, CellState::Unopened) => Flagged,
+            (Win, true, CellState::Flagged) => Flagged,
+            (Win, true, CellState::Questioned) => Flagged,
+            (Win, true, CellState::Opened) => unreachable!(),
+            (Win, false, CellState::Opened) => Opened(self.nearby_mines(x, y)),
+            (Win, false, _) => unreachable!(),
+            (Lose, true, CellState::Unopened) => Mine,
+            (Lose, true, CellState::Flagged) => Flagged,
+            (Lose, true, CellState::Questioned) => Questioned,
+            (Lose, true, CellState::Opened) => Exploded,
+            (Lose, false, CellState::Unopened) => Unopened,
+            (Lose, false, CellState::Flagged) => WrongMine,
+            (Lose, false, CellState::Questioned) => Questioned,
+            (Lose, false, CellState::Opened) => Opened(self.nearby_mines(x, y)),
+            (Playing, true, CellState::Unopened) => Unopened,
+            (Playing, true, CellState::Flagged) => Flagged,
+            (Playing, true, CellState::Questioned) => Questioned,
+            (Playing, true, CellState::Opened) => unreachable!(),
+            (Playing, false, CellState::Unopened) => Unopened,
+            (Playing, false, CellState::Flagged) => Flagged,
+            (Playing, false, CellState::Questioned) => Questioned,
+            (Playing, false, CellState::Opened) => Opened(self.nearby_mines(x, y)),
+        };
+        let cell_view = if self.result == Playing && cell_view == Unopened {
+            self.gesture.intact_cell(x, y)
+        } else {
+            cell_view
+        };
+        self.set_cell(x, y, cell_view);
+        if previous_cell_view != cell_view {
+            RedrawCells::single(x, y)
+        } else {
+            Default::default()
+        }
+    }
+
+    fn open(&mut self, x: usize, y: usize) -> RedrawCells {
+        let mut redraw = RedrawCells::default();
+        if self.result != GameResult::Playing {
+            return Default::default();
+        }
+        use CellState::*;
+        if self.state.cell(x, y) != Unopened {
+            return Default::default();
+        }
+        if self.state.is_mine(x, y) {
+            self.set_state(x, y, Opened);
+        } else {
+            scratch::with_cells(|cells_to_left_click| {
+                cells_to_left_click.push((x, y));
+                while let Some((x, y)) = cells_to_left_click.pop() {
+                    if self.state.cell(x, y) == Unopened {
+                        self.set_state(x, y, Opened);
+                        redraw.merge(self.refresh_cell(x, y));
+                        if self.nearby_mines(x, y) == 0 {
+                            cells_to_left_click.extend(self.state.nearby(x, y));
+                        }
+                    }
+                }
+            });
+        }
+        self.refresh_game_result();
+        if self.result != GameResult::Playing {
+            redraw.merge(self.refresh_all_cell())
+        }
+        redraw
+    }
+
+    fn mark(&mut self, x: usize, y: usize) -> RedrawCells {
+        if self.result != GameResult::Playing {
+            return Default::default();
+        }
+        use CellState::*;
+        let cell_state = self.state.cell(x, y);
+        let new_cell_state = match cell_state {
+            Unopened if self.refuses_flag(x, y) => return Default::default(),
+            Unopened => {
+                self.flags += 1;
+                Flagged
+            }
+            Flagged => {
+                self.flags -= 1;
+                Questioned
+            }
+            Questioned => Unopened,
+            Opened => return Default::default(),
+        };
+        self.set_state(x, y, new_cell_state);
+        let mut redraw = self.refresh_cell(x, y);
+        // flag warnings of nearby numbers may change
+        for (x, y) in self.nearby_cells(x, y) {
+            if self.state.is_opened(x, y) {
+                redraw.insert((x, y));
+            }
+        }
+        redraw
+    }
+
+    fn chord(&mut self, x: usize, y: usize) -> RedrawCells {
+        if self.result != GameResult::Playing {
+            return Default::default();
+        }
+        use CellState::*;
+        if self.state.cell(x, y) != Opened || self.nearby_mines(x, y) != self.nearby_flags(x, y) {
+            return Default::default();
+        }
+        let mut redraw = RedrawCells::default();
+        for (x, y) in self.nearby_cells(x, y) {
+            if self.state.cell(x, y) == Unopened {
+                if (!self.state.is_mine(x, y)) && self.nearby_mines(x, y) == 0 {
+                    redraw.merge(self.open(x, y));
+                } else {
+                    self.set_state(x, y, Opened);
+                }
+            }
+        }
+        self.refresh_game_result();
+        if self.result != GameResult::Playing {
+            redraw.merge(self.refresh_all_cell())
+        } else {
+            redraw.merge(self.refresh_3x3_cell(x, y))
+        }
+        redraw
+    }
+
+    /// Apply a click without committing it to history
+    pub(crate) fn click(&mut self, action: Action) -> RedrawCells {
+        let _span = tracing::debug_span!("click", ?action).entered();
+        let changes = self.history.pending.len();
+        let redraw = match action {
+            Action::LeftClick(x, y) => self.open(x, y),
+            Action::RightClick(x, y) => self.mark(x, y),
+            Action::MiddleClick(x, y) => self.chord(x, y),
+            _ => unreachable!(),
+        };
+        if self.history.pending.len() > changes {
+            self.history.pending_actions.push(action);
+        }
+        redraw
+    }
+
+    /// Apply a click as one player action and sum up what it changed
+    fn click_outcome(&mut self, action: Action) -> ClickOutcome {
+        let flags = self.flags as isize;
+        let redraw = self.click(action);
+        let opened: Vec<_> = self
+            .history
+            .pending
+            .iter()
+            .filter(|change| change.to == CellState::Opened)
+            .map(|change| (change.x, change.y))
+            .collect();
+        self.history.commit(false);
+        ClickOutcome {
+            redraw,
+            result: self.result,
+            opened: opened.len(),
+            flags_delta: self.flags as isize - flags,
+            exploded_at: opened.into_iter().find(|(x, y)| self.state.is_mine(*x, *y)),
+        }
+    }
+
+    pub fn left_click(&mut self, x: usize, y: usize) -> ClickOutcome {
+        self.click_outcome(Action::LeftClick(x, y))
+    }
+
+    pub fn right_click(&mut self, x: usize, y: usize) -> ClickOutcome {
+        self.click_outcome(Action::RightClick(x, y))
+    }
+
+    pub fn middle_click(&mut self, x: usize, y: usize) -> ClickOutcome {
+        self.click_outcome(Action::MiddleClick(x, y))
+    }
+
+    /// Cover every cell again while keeping the same mines, history is dropped
+    pub fn restart(&mut self) -> RedrawCells {
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                self.state.set_cell(x, y, CellState::Unopened);
+            }
+        }
+        self.flags = 0;
+        self.history = History {
+            revision: self.history.revision + 1,
+            ..Default::default()
+        };
+        self.forget_known();
+        self.refresh_game_result();
+        self.refresh_all_cell();
+        RedrawCells::redraw_all()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.undo.is_empty()
+    }
+
+    /// Bumped on every action and restart, results computed on an older revision are stale
+    pub fn revision(&self) -> u64 {
+        self.history.revision
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty()
+    }
+
+    /// Whether undo has been used in this game, such game should not be recorded as a pure run
+    pub fn is_assisted(&self) -> bool {
+        self.history.undos > 0
+    }
+
+    pub fn undo(&mut self) -> RedrawCells {
+        let Some(changes) = self.history.undo.pop() else {
+            self.history.pending_timestamp = None;
+            return Default::default();
+        };
+        for change in changes.iter().rev() {
+            self.state.set_cell(change.x, change.y, change.from);
+        }
+        self.history.redo.push(changes);
+        self.history.undos += 1;
+        self.history.push_action(Action::Undo);
+        self.refresh_after_history()
+    }
+
+    pub fn redo(&mut self) -> RedrawCells {
+        let Some(changes) = self.history.redo.pop() else {
+            self.history.pending_timestamp = None;
+            return Default::default();
+        };
+        for change in changes.iter() {
+            self.state.set_cell(change.x, change.y, change.to);
+        }
+        self.history.undo.push(changes);
+        self.history.push_action(Action::Redo);
+        self.refresh_after_history()
+    }
+
+    fn refresh_after_history(&mut self) -> RedrawCells {
+        self.forget_known();
+        self.flags = self.state.flags();
+        self.refresh_game_result();
+        self.refresh_all_cell()
+    }
+
+    pub fn gesture(&mut self, gesture: Gesture) -> RedrawCells {
+        let previous_gesture = self.gesture;
+        self.gesture = gesture;
+        let mut redraw = self.refresh_gesture(previous_gesture);
+        redraw.merge(self.refresh_gesture(gesture));
+        redraw
+    }
+
+    pub fn is_draggable(&self, x: usize, y: usize) -> bool {
+        match self.result {
+            GameResult::Win | GameResult::Lose => true,
+            GameResult::Playing => matches!(
+                self.cell(x, y),
+                CellView::Opened(_) | CellView::Flagged | CellView::Questioned
+            ),
+        }
+    }
+
+    pub fn automation_step(
+        &mut self,
+        policy: AutomationPolicy,
+    ) -> Result<RedrawCells, AutomationError> {
+        if self.result != GameResult::Playing {
+            return Err(AutomationError::GameOver);
+        }
+        let result = match self.known() {
+            Some(known) => known.clone(),
+            None => self.solve_cached(),
+        };
+        self.automation_apply(result, policy)
+    }
+
+    /// Make the moves found by a solve as one automation step, in the order of the policy
+    pub fn automation_apply(
+        &mut self,
+        result: SolveResult,
+        policy: AutomationPolicy,
+    ) -> Result<RedrawCells, AutomationError> {
+        if self.result != GameResult::Playing {
+            return Err(AutomationError::GameOver);
+        }
+        #[cfg(feature = "trace")]
+        crate::trace::record(self, &result);
+        let SolveResult {
+            mut must_be_mine,
+            must_not_mine,
+        } = result;
+        if !policy.flags() {
+            must_be_mine.clear();
+        }
+        if must_be_mine.is_empty() && must_not_mine.is_empty() {
+            return Err(AutomationError::NoMoves);
+        }
+        let flags = must_be_mine
+            .into_iter()
+            .map(|(x, y)| Action::RightClick(x, y));
+        let opens = must_not_mine
+            .into_iter()
+            .map(|(x, y)| Action::LeftClick(x, y));
+        let actions: Vec<_> = match policy {
+            AutomationPolicy::OpensFirst => opens.chain(flags).collect(),
+            _ => flags.chain(opens).collect(),
+        };
+        let mut redraw = RedrawCells::default();
+        for action in actions {
+            // TODO: detect human interference
+            redraw.merge(self.click(action));
+        }
+        if policy.chords() {
+            for y in 0..self.height() {
+                for x in 0..self.width() {
+                    redraw.merge(self.click(Action::MiddleClick(x, y)));
+                }
+            }
+        }
+        self.history.commit(true);
+        Ok(redraw)
+    }
+
+    /// Flag the intact neighbours of every number with as many mines left as intact neighbours,
+    /// a cheap assist without solving, recorded as one automation step
+    pub fn autoflag(&mut self) -> RedrawCells {
+        let mut redraw = RedrawCells::default();
+        for (x, y) in self.trivial_moves().must_be_mine {
+            redraw.merge(self.click(Action::RightClick(x, y)));
+        }
+        self.history.commit(true);
+        redraw
+    }
+
+    /// Open only the cells a solve found safe as one automation step, flags are left to the player
+    /// and nothing is chorded, so that wrong flags of the player open no mine
+    pub fn auto_clear(&mut self, result: SolveResult) -> Result<RedrawCells, AutomationError> {
+        self.automation_apply(result, AutomationPolicy::NoFlag)
+    }
+
+    /// Repeat automation steps with the default policy until no move is found or the game is over
+    pub fn automation_run(&mut self) -> RedrawCells {
+        let mut redraw = RedrawCells::default();
+        while let Ok(step) = self.automation_step(AutomationPolicy::default()) {
+            redraw.merge(step);
+        }
+        redraw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn game_view() {
+        let mut view = GameView::from(
+            GameOptions {
+                difficulty: Difficulty::Custom {
+                    width: 3,
+                    height: 3,
+                    mines: 3,
+                },
+                seed: Some(1),
+                generation: 0,
+                ..GameOptions::easy()
+            }
+            .build(),
+        );
+        view.left_click(1, 1);
+        assert_eq!(
+            view.state.cells,
+            vec![
+                vec![
+                    CellState::Unopened,
+                    CellState::Unopened,
+                    CellState::Unopened,
+                ],
+                vec![CellState::Unopened, CellState::Opened, CellState::Unopened,],
+                vec![
+                    CellState::Unopened,
+                    CellState::Unopened,
+                    CellState::Unopened,
+                ]
+            ],
+        );
+        assert_eq!(
+            view.cells,
+            vec![
+                vec![CellView::Unopened, CellView::Unopened, CellView::Unopened],
+                vec![CellView::Unopened, CellView::Opened(3), CellView::Unopened],
+                vec![CellView::Unopened, CellView::Unopened, CellView::Unopened]
+            ]
+        );
+        assert_eq!(view.result, GameResult::Playing);
+        view.right_click(2, 1);
+        assert_eq!(
+            view.state.cells,
+            vec![
+                vec![
+                    CellState::Unopened,
+                    CellState::Unopened,
+                    CellState::Unopened,
+                ],
+                vec![CellState::Unopened, CellState::Opened, CellState::Flagged,],
+                vec![
+                    CellState::Unopened,
+                    CellState::Unopened,
+                    CellState::Unopened,
+                ]
+            ],
+        );
+        assert_eq!(
+            view.cells,
+            vec![
+                vec![CellView::Unopened, CellView::Unopened, CellView::Unopened],
+                vec![CellView::Unopened, CellView::Opened(3), CellView::Flagged],
+                vec![CellView::Unopened, CellView::Unopened, CellView::Unopened]
+            ]
+        );
+        assert_eq!(view.result, GameResult::Playing);
+        view.left_click(0, 0);
+        assert_eq!(
+            view.state.cells,
+            vec![
+                vec![CellState::Opened, CellState::Unopened, CellState::Unopened,],
+                vec![CellState::Unopened, CellState::Opened, CellState::Flagged,],
+                vec![
+                    CellState::Unopened,
+                    CellState::Unopened,
+                    CellState::Unopened,
+                ]
+            ],
+        );
+        assert_eq!(
+            view.cells,
+            vec![
+                vec![CellView::Exploded, CellView::Unopened, CellView::Unopened],
+                vec![CellView::Unopened, CellView::Opened(3), CellView::WrongMine],
+                vec![CellView::Mine, CellView::Mine, CellView::Unopened]
+            ]
+        );
+        assert_eq!(view.result, GameResult::Lose);
+    }
+
+    #[test]
+    fn click_outcome() {
+        let mut view = GameView::from(
+            GameOptions {
+                difficulty: Difficulty::Custom {
+                    width: 3,
+                    height: 3,
+                    mines: 3,
+                },
+                seed: Some(1),
+                generation: 0,
+                ..GameOptions::easy()
+            }
+            .build(),
+        );
+        let outcome = view.left_click(1, 1);
+        assert_eq!(outcome.opened, 1);
+        assert_eq!(outcome.result, GameResult::Playing);
+        assert_eq!(view.right_click(2, 1).flags_delta, 1);
+        assert_eq!(view.left_click(1, 1).opened, 0);
+        let outcome = view.left_click(0, 0);
+        assert_eq!(outcome.exploded_at, Some((0, 0)));
+        assert_eq!(outcome.result, GameResult::Lose);
+    }
+
+    #[test]
+    fn gestures() {
+        let mut view = GameView::from(GameOptions::easy().build());
+        view.gesture(Gesture::Focus(0, 0));
+        assert_eq!(view.cell(0, 0), CellView::Hovered);
+        let redraw = view.gesture(Gesture::TouchHold(4, 4));
+        assert_eq!(view.cell(0, 0), CellView::Unopened);
+        assert_eq!(view.cell(4, 4), CellView::Hovered);
+        assert_eq!(view.cell(3, 5), CellView::Pushed);
+        assert_eq!(redraw.cells.len(), 10);
+        view.gesture(Gesture::TouchPush(4, 4));
+        assert_eq!(view.cell(4, 4), CellView::Pushed);
+        assert_eq!(view.cell(3, 5), CellView::Unopened);
+    }
+
+    #[test]
+    fn automation_error() {
+        let mut view = GameView::from(GameOptions::easy().build());
+        assert_eq!(
+            view.automation_step(AutomationPolicy::default()),
+            Err(AutomationError::NoMoves)
+        );
+        let (x, y) = (0..9)
+            .flat_map(|y| (0..9).map(move |x| (x, y)))
+            .find(|(x, y)| view.state.is_mine(*x, *y))
+            .unwrap();
+        view.left_click(x, y);
+        assert_eq!(
+            view.automation_step(AutomationPolicy::default()),
+            Err(AutomationError::GameOver)
+        );
+    }
+
+    #[test]
+    fn autoflag() {
+        let mut view = GameView::from(GameState::from_board_text("*...*..").unwrap());
+        view.left_click(2, 0);
+        let redraw = view.autoflag();
+        assert_eq!(view.flags, 2);
+        assert_eq!(view.cell(0, 0), CellView::Flagged);
+        assert!(redraw.contains(4, 0));
+        assert!(view.autoflag().is_empty());
+        assert!(matches!(view.actions().last(), Some(Action::Automation(_))));
+    }
+
+    #[test]
+    fn auto_clear() {
+        let mut view = GameView::from(
+            GameOptions {
+                safe_pos: Some((4, 4)),
+                seed: Some(4),
+                generation: 0,
+                ..GameOptions::easy()
+            }
+            .build(),
+        );
+        view.left_click(4, 4);
+        let result = view.solve();
+        assert!(!result.must_be_mine.is_empty());
+        assert!(view.auto_clear(result).is_ok());
+        assert_eq!(view.flags, 0);
+        let Some(Action::Automation(actions)) = view.actions().last() else {
+            unreachable!()
+        };
+        assert!(actions
+            .iter()
+            .all(|action| matches!(action, Action::LeftClick(..))));
+    }
+
+    #[test]
+    fn automation_policy() {
+        let options = GameOptions {
+            safe_pos: Some((4, 4)),
+            seed: Some(4),
+            generation: 0,
+            ..GameOptions::easy()
+        };
+        let first_action = |policy| {
+            let mut view = GameView::from(options.clone().build());
+            view.left_click(4, 4);
+            view.automation_step(policy).unwrap();
+            let Some(Action::Automation(actions)) = view.actions().last() else {
+                unreachable!()
+            };
+            (actions[0].clone(), view.flags)
+        };
+        assert!(matches!(
+            first_action(AutomationPolicy::FlagsFirst),
+            (Action::RightClick(..), 1..)
+        ));
+        assert!(matches!(
+            first_action(AutomationPolicy::OpensFirst),
+            (Action::LeftClick(..), 1..)
+        ));
+        assert!(matches!(
+            first_action(AutomationPolicy::NoFlag),
+            (Action::LeftClick(..), 0)
+        ));
+    }
+
+    #[test]
+    fn redraw_cells() {
+        let mut redraw = RedrawCells::single(1, 0);
+        redraw.merge([(0, 0), (1, 0)].into_iter().collect());
+        redraw.extend([(0, 0)]);
+        assert_eq!(redraw.iter(3, 2).collect::<Vec<_>>(), [(0, 0), (1, 0)]);
+        redraw.merge(RedrawCells::region(RedrawRegion::Rect {
+            x: 0,
+            y: 0,
+            width: 2,
+            height: 2,
+        }));
+        assert!(redraw.cells.is_empty());
+        redraw.insert((2, 1));
+        assert!(redraw.contains(1, 1) && redraw.contains(2, 1) && !redraw.contains(2, 0));
+        assert_eq!(redraw.iter(3, 2).count(), 5);
+        redraw.merge(RedrawCells::redraw_all());
+        assert_eq!(redraw, RedrawCells::redraw_all());
+        assert_eq!(redraw.iter(3, 2).count(), 6);
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut view = GameView::from(
+            GameOptions {
+                difficulty: Difficulty::Custom {
+                    width: 3,
+                    height: 3,
+                    mines: 3,
+                },
+                seed: Some(1),
+                generation: 0,
+                ..GameOptions::easy()
+            }
+            .build(),
+        );
+        view.left_click(1, 1);
+        view.right_click(2, 1);
+        view.left_click(0, 0);
+        assert_eq!(view.result, GameResult::Lose);
+        assert!(!view.is_assisted());
+        view.undo();
+        assert_eq!(view.result, GameResult::Playing);
+        assert_eq!(view.cell(0, 0), CellView::Unopened);
+        assert_eq!(view.cell(2, 1), CellView::Flagged);
+        assert!(view.is_assisted());
+        view.undo();
+        assert_eq!(view.flags, 0);
+        assert_eq!(view.cell(2, 1), CellView::Unopened);
+        view.redo();
+        assert_eq!(view.flags, 1);
+        assert_eq!(view.cell(2, 1), CellView::Flagged);
+        assert!(view.can_redo());
+        view.right_click(0, 2);
+        assert!(!view.can_redo());
+        assert_eq!(view.revision(), 7);
+        view.right_click(1, 1);
+        assert_eq!(view.revision(), 7);
+        view.restart();
+        assert_eq!(view.revision(), 8);
+    }
+
+    #[test]
+    fn hidden_count() {
+        let options = GameOptions {
+            safe_pos: Some((4, 4)),
+            seed: Some(2),
+            ..GameOptions::easy()
+        };
+        let mut shown = GameView::from(options.clone().build());
+        let mut hidden = GameView::from(
+            GameOptions {
+                hidden_count: true,
+                ..options
+            }
+            .build(),
+        );
+        assert_eq!(shown.mines(), Some(10));
+        assert_eq!(hidden.mines(), None);
+        for view in [&mut shown, &mut hidden] {
+            view.left_click(4, 4);
+        }
+        // the solver never relies on the count
+        assert_eq!(shown.solve(), hidden.solve());
+        assert_eq!(hidden.effective_remaining_mines(), None);
+        for x in 0..9 {
+            for y in 0..2 {
+                hidden.right_click(x, y);
+            }
+        }
+        assert!(hidden.flags > 10 && !hidden.is_over_flagged());
+    }
+
+    #[test]
+    fn learning() {
+        let state = GameOptions {
+            difficulty: Difficulty::Custom {
+                width: 3,
+                height: 3,
+                mines: 3,
+            },
+            seed: Some(1),
+            learning: true,
+            generation: 0,
+            ..GameOptions::easy()
+        }
+        .build();
+        let mut view = GameView::from(state.clone());
+        for (x, y) in (0..3).flat_map(|y| (0..3).map(move |x| (x, y))) {
+            view.right_click(x, y);
+            let flagged = view.cell(x, y) == CellView::Flagged;
+            assert_eq!(flagged, state.is_mine(x, y));
+        }
+        assert_eq!(view.flags, 3);
+        assert!(view.options().learning);
+    }
+}
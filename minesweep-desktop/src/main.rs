@@ -0,0 +1,408 @@
+use std::{
+    fs,
+    sync::mpsc::{channel, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use eframe::egui::{self, Align2, Color32, FontId, PointerButton, Pos2, Rect, Sense, Stroke, Vec2};
+use minesweep_core::{
+    AutomationPolicy, CellView, GameOptions, GameResult, GameView, Replay, SolveResult,
+};
+
+const CELL_SIZE: f32 = 24.;
+const PLAYBACK_INTERVAL: Duration = Duration::from_millis(400);
+const DIGIT_COLORS: [Color32; 8] = [
+    Color32::from_rgb(0x00, 0x00, 0xff),
+    Color32::from_rgb(0x00, 0x80, 0x00),
+    Color32::from_rgb(0xff, 0x00, 0x00),
+    Color32::from_rgb(0x00, 0x00, 0x80),
+    Color32::from_rgb(0x80, 0x00, 0x00),
+    Color32::from_rgb(0x00, 0x80, 0x80),
+    Color32::from_rgb(0x00, 0x00, 0x00),
+    Color32::from_rgb(0x80, 0x80, 0x80),
+];
+
+/// Result of a solve on a background thread, tagged with the game and the actions it saw
+struct Solved {
+    game: u64,
+    actions: usize,
+    result: SolveResult,
+}
+
+struct DesktopApp {
+    options: GameOptions,
+    /// `None` until the first click, which is always safe
+    view: Option<GameView>,
+    /// Bumped whenever the board is replaced so that solves of an older board are dropped
+    game: u64,
+    started: Option<Instant>,
+    finished: Option<Duration>,
+    sender: Sender<Solved>,
+    receiver: Receiver<Solved>,
+    solving: bool,
+    running: bool,
+    /// Replay being played back, index of the next action and when it was last applied
+    playback: Option<(Replay, usize, Instant)>,
+    replay_path: String,
+    message: String,
+}
+
+impl DesktopApp {
+    fn new() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            options: GameOptions::medium(),
+            view: None,
+            game: 0,
+            started: None,
+            finished: None,
+            sender,
+            receiver,
+            solving: false,
+            running: false,
+            playback: None,
+            replay_path: "replay.json".to_string(),
+            message: String::new(),
+        }
+    }
+
+    fn is_playing(&self) -> bool {
+        self.view
+            .as_ref()
+            .is_some_and(|view| view.result == GameResult::Playing)
+    }
+
+    fn replace_view(&mut self, view: Option<GameView>) {
+        self.view = view;
+        self.game += 1;
+        self.started = None;
+        self.finished = None;
+        self.running = false;
+        self.playback = None;
+        self.message.clear();
+    }
+
+    fn click(&mut self, button: PointerButton, x: usize, y: usize) {
+        if self.playback.is_some() {
+            return;
+        }
+        if self.view.is_none() && button == PointerButton::Primary {
+            let options = GameOptions {
+                safe_pos: Some((x, y)),
+                ..self.options.clone()
+            };
+            self.view = Some(GameView::from(options.build()));
+        }
+        let Some(view) = &mut self.view else {
+            return;
+        };
+        self.started.get_or_insert_with(Instant::now);
+        match button {
+            PointerButton::Primary => view.left_click(x, y),
+            PointerButton::Secondary => view.right_click(x, y),
+            PointerButton::Middle => view.middle_click(x, y),
+            _ => return,
+        };
+        self.check_finished();
+    }
+
+    fn check_finished(&mut self) {
+        if self.view.is_some() && !self.is_playing() && self.finished.is_none() {
+            self.finished = self.started.map(|started| started.elapsed());
+            self.running = false;
+        }
+    }
+
+    /// Solve a copy of the board on a background thread instead of a web worker
+    fn automate(&mut self, ctx: &egui::Context) {
+        if self.solving || !self.is_playing() || self.playback.is_some() {
+            return;
+        }
+        let view = self.view.clone().unwrap();
+        let (game, sender, ctx) = (self.game, self.sender.clone(), ctx.clone());
+        self.solving = true;
+        thread::spawn(move || {
+            let actions = view.actions().len();
+            let result = view.solve();
+            let _ = sender.send(Solved {
+                game,
+                actions,
+                result,
+            });
+            ctx.request_repaint();
+        });
+    }
+
+    fn poll_automation(&mut self, ctx: &egui::Context) {
+        while let Ok(Solved {
+            game,
+            actions,
+            result,
+        }) = self.receiver.try_recv()
+        {
+            self.solving = false;
+            let Some(view) = self.view.as_mut().filter(|_| game == self.game) else {
+                continue;
+            };
+            // the player moved meanwhile, solve again for a run or drop the step
+            if view.actions().len() != actions {
+                if self.running {
+                    self.automate(ctx);
+                }
+                continue;
+            }
+            if let Err(err) = view.automation_apply(result, AutomationPolicy::default()) {
+                self.message = format!("Automation stopped: {err}");
+                self.running = false;
+                continue;
+            }
+            self.check_finished();
+            if self.running {
+                self.automate(ctx);
+            }
+        }
+    }
+
+    fn save_replay(&mut self) {
+        let Some(view) = &self.view else {
+            return;
+        };
+        let json = serde_json::to_string(&view.replay()).unwrap();
+        self.message = match fs::write(&self.replay_path, json) {
+            Ok(()) => format!("Saved to {}", self.replay_path),
+            Err(err) => format!("Save failed: {err}"),
+        };
+    }
+
+    fn load_replay(&mut self) {
+        let replay = fs::read_to_string(&self.replay_path)
+            .map_err(|err| err.to_string())
+            .and_then(|json| serde_json::from_str::<Replay>(&json).map_err(|err| err.to_string()))
+            .and_then(|replay| {
+                let start = replay.start().map_err(|err| err.to_string())?;
+                Ok((replay, start))
+            });
+        match replay {
+            Ok((replay, start)) => {
+                self.options = replay.options.clone();
+                self.replace_view(Some(start));
+                self.playback = Some((replay, 0, Instant::now()));
+            }
+            Err(err) => self.message = format!("Load failed: {err}"),
+        }
+    }
+
+    fn poll_playback(&mut self, ctx: &egui::Context) {
+        let Some((replay, index, last)) = &mut self.playback else {
+            return;
+        };
+        if last.elapsed() < PLAYBACK_INTERVAL {
+            ctx.request_repaint_after(PLAYBACK_INTERVAL - last.elapsed());
+            return;
+        }
+        match (replay.actions.get(*index), &mut self.view) {
+            (Some(action), Some(view)) => {
+                view.apply(action);
+                *index += 1;
+                *last = Instant::now();
+                ctx.request_repaint_after(PLAYBACK_INTERVAL);
+            }
+            _ => self.playback = None,
+        }
+    }
+
+    fn status(&self) -> String {
+        let (mines, flags, result) = match &self.view {
+            Some(view) => (view.mines(), view.flags, view.result),
+            None => (
+                (!self.options.hidden_count).then(|| self.options.difficulty.mines()),
+                0,
+                GameResult::Playing,
+            ),
+        };
+        let mines = mines.map_or("?".to_string(), |mines| mines.to_string());
+        let time = self
+            .finished
+            .or_else(|| self.started.map(|started| started.elapsed()))
+            .unwrap_or_default()
+            .as_secs();
+        let result = match result {
+            GameResult::Win => "You win 😎",
+            GameResult::Lose => "You lose 😵",
+            GameResult::Playing if self.playback.is_some() => "Replaying 📼",
+            GameResult::Playing if self.running => "Automation running 🤖",
+            GameResult::Playing => "Playing 🙂",
+        };
+        format!(
+            "{result}    Mines: {flags}/{mines}    Time: {:02}:{:02}",
+            time / 60,
+            time % 60
+        )
+    }
+
+    fn board(&mut self, ui: &mut egui::Ui) {
+        let (w, h) = (
+            self.options.difficulty.width(),
+            self.options.difficulty.height(),
+        );
+        let size = Vec2::new(w as f32, h as f32) * CELL_SIZE;
+        let (response, painter) = ui.allocate_painter(size, Sense::click());
+        let origin = response.rect.min;
+        for y in 0..h {
+            for x in 0..w {
+                let cell = self
+                    .view
+                    .as_ref()
+                    .map_or(CellView::Unopened, |view| view.cell(x, y));
+                let rect = Rect::from_min_size(
+                    origin + Vec2::new(x as f32, y as f32) * CELL_SIZE,
+                    Vec2::splat(CELL_SIZE),
+                );
+                draw_cell(&painter, rect, cell);
+            }
+        }
+        let Some(pos) = response.interact_pointer_pos() else {
+            return;
+        };
+        let Some((x, y)) = cell_at(origin, pos, w, h) else {
+            return;
+        };
+        for button in [
+            PointerButton::Primary,
+            PointerButton::Secondary,
+            PointerButton::Middle,
+        ] {
+            if response.clicked_by(button) {
+                self.click(button, x, y);
+            }
+        }
+    }
+}
+
+fn draw_cell(painter: &egui::Painter, rect: Rect, cell: CellView) {
+    use CellView::*;
+    let fill = match cell {
+        Opened(_) | Mine | WrongMine => Color32::from_gray(0xdd),
+        Exploded => Color32::RED,
+        _ => Color32::from_gray(0xaa),
+    };
+    painter.rect(
+        rect.shrink(1.),
+        2.,
+        fill,
+        Stroke::new(1_f32, Color32::from_gray(0x88)),
+    );
+    let (text, color) = match cell {
+        Flagged => ("🚩".to_string(), Color32::RED),
+        Questioned => ("?".to_string(), Color32::BLACK),
+        Opened(n) if n > 0 => (n.to_string(), DIGIT_COLORS[(n as usize - 1).min(7)]),
+        Mine | Exploded => ("💣".to_string(), Color32::BLACK),
+        WrongMine => ("❌".to_string(), Color32::RED),
+        _ => return,
+    };
+    painter.text(
+        rect.center(),
+        Align2::CENTER_CENTER,
+        text,
+        FontId::proportional(CELL_SIZE * 0.6),
+        color,
+    );
+}
+
+/// Cell under a point of the board drawn from `origin`
+fn cell_at(origin: Pos2, pos: Pos2, w: usize, h: usize) -> Option<(usize, usize)> {
+    let offset = (pos - origin) / CELL_SIZE;
+    if offset.x < 0. || offset.y < 0. {
+        return None;
+    }
+    let (x, y) = (offset.x as usize, offset.y as usize);
+    (x < w && y < h).then_some((x, y))
+}
+
+impl eframe::App for DesktopApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_automation(ctx);
+        self.poll_playback(ctx);
+        if self.is_playing() && self.finished.is_none() {
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+        egui::TopBottomPanel::top("controls").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                for (name, options) in [
+                    ("Easy", GameOptions::easy()),
+                    ("Medium", GameOptions::medium()),
+                    ("Hard", GameOptions::hard()),
+                ] {
+                    if ui.button(name).clicked() {
+                        self.options = options;
+                        self.replace_view(None);
+                    }
+                }
+                ui.separator();
+                if ui.button("Restart").clicked() {
+                    let view = self.view.take().map(|mut view| {
+                        view.restart();
+                        view
+                    });
+                    self.replace_view(view);
+                }
+                ui.add_enabled_ui(self.is_playing() && self.playback.is_none(), |ui| {
+                    if ui.button("Step").clicked() {
+                        self.automate(ctx);
+                    }
+                    if ui.toggle_value(&mut self.running, "Automation").changed() && self.running {
+                        self.automate(ctx);
+                    }
+                });
+            });
+            ui.horizontal(|ui| {
+                ui.label("Replay file");
+                ui.text_edit_singleline(&mut self.replay_path);
+                if ui.button("Save").clicked() {
+                    self.save_replay();
+                }
+                if ui.button("Load").clicked() {
+                    self.load_replay();
+                }
+            });
+        });
+        egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(self.status());
+                ui.colored_label(Color32::DARK_RED, &self.message);
+            });
+        });
+        egui::CentralPanel::default().show(ctx, |ui| {
+            egui::ScrollArea::both().show(ui, |ui| self.board(ui));
+        });
+    }
+}
+
+fn main() -> eframe::Result {
+    eframe::run_native(
+        "Minesweep Automated",
+        eframe::NativeOptions::default(),
+        Box::new(|_| Ok(Box::new(DesktopApp::new()))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_to_cell() {
+        let origin = Pos2::new(10., 20.);
+        assert_eq!(cell_at(origin, Pos2::new(11., 21.), 9, 9), Some((0, 0)));
+        assert_eq!(
+            cell_at(origin, Pos2::new(10. + CELL_SIZE * 2.5, 21.), 9, 9),
+            Some((2, 0))
+        );
+        assert_eq!(cell_at(origin, Pos2::new(9., 21.), 9, 9), None);
+        assert_eq!(
+            cell_at(origin, Pos2::new(11., 20. + CELL_SIZE * 9.), 9, 9),
+            None
+        );
+    }
+}
@@ -0,0 +1,170 @@
+#ifndef MINESWEEP_H
+#define MINESWEEP_H
+
+/* Generated by cbindgen from minesweep-ffi, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define MINESWEEP_CELL_UNOPENED -1
+
+#define MINESWEEP_CELL_FLAGGED -2
+
+#define MINESWEEP_CELL_QUESTIONED -3
+
+#define MINESWEEP_CELL_MINE -4
+
+#define MINESWEEP_CELL_WRONG_MINE -5
+
+#define MINESWEEP_CELL_EXPLODED -6
+
+/**
+ * Returned for null games and cells out of the board
+ */
+#define MINESWEEP_CELL_INVALID -128
+
+typedef enum MinesweepResult {
+  MINESWEEP_RESULT_PLAYING,
+  MINESWEEP_RESULT_WIN,
+  MINESWEEP_RESULT_LOSE,
+} MinesweepResult;
+
+/**
+ * Opaque game handle
+ */
+typedef struct MinesweepGame MinesweepGame;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Create a game, returns null when width, height or mines is zero, mines fill the board or
+ * the board is over the default `BoardLimits`
+ *
+ * A `seed` of 0 picks a random board.
+ */
+struct MinesweepGame *minesweep_game_new(uintptr_t width,
+                                         uintptr_t height,
+                                         uintptr_t mines,
+                                         uint64_t seed);
+
+/**
+ * Release a game created by `minesweep_game_new`
+ *
+ * # Safety
+ *
+ * `game` must be null or a live pointer returned by `minesweep_game_new`, it is dangling
+ * afterwards.
+ */
+void minesweep_game_free(struct MinesweepGame *game);
+
+/**
+ * Open a cell, the first one builds the board around it, returns false out of the board
+ *
+ * # Safety
+ *
+ * `game` must be null or a live pointer returned by `minesweep_game_new`.
+ */
+bool minesweep_left_click(struct MinesweepGame *game, uintptr_t x, uintptr_t y);
+
+/**
+ * Cycle the mark of a cell, returns false out of the board or before the first left click
+ *
+ * # Safety
+ *
+ * `game` must be null or a live pointer returned by `minesweep_game_new`.
+ */
+bool minesweep_right_click(struct MinesweepGame *game, uintptr_t x, uintptr_t y);
+
+/**
+ * Open the neighbours of a number with enough flags, same returns as `minesweep_right_click`
+ *
+ * # Safety
+ *
+ * `game` must be null or a live pointer returned by `minesweep_game_new`.
+ */
+bool minesweep_middle_click(struct MinesweepGame *game, uintptr_t x, uintptr_t y);
+
+/**
+ * Take one automation step, returns false when no move could be deduced
+ *
+ * # Safety
+ *
+ * `game` must be null or a live pointer returned by `minesweep_game_new`.
+ */
+bool minesweep_automation_step(struct MinesweepGame *game);
+
+/**
+ * # Safety
+ *
+ * `game` must be null or a live pointer returned by `minesweep_game_new`.
+ */
+void minesweep_undo(struct MinesweepGame *game);
+
+/**
+ * # Safety
+ *
+ * `game` must be null or a live pointer returned by `minesweep_game_new`.
+ */
+void minesweep_redo(struct MinesweepGame *game);
+
+/**
+ * Nearby mines 0 to 8 for opened cells, otherwise one of the `MINESWEEP_CELL_*` constants
+ *
+ * # Safety
+ *
+ * `game` must be null or a live pointer returned by `minesweep_game_new`.
+ */
+int8_t minesweep_cell(struct MinesweepGame *game, uintptr_t x, uintptr_t y);
+
+/**
+ * # Safety
+ *
+ * `game` must be null or a live pointer returned by `minesweep_game_new`.
+ */
+enum MinesweepResult minesweep_result(struct MinesweepGame *game);
+
+/**
+ * 0 for null games
+ *
+ * # Safety
+ *
+ * `game` must be null or a live pointer returned by `minesweep_game_new`.
+ */
+uintptr_t minesweep_width(struct MinesweepGame *game);
+
+/**
+ * 0 for null games
+ *
+ * # Safety
+ *
+ * `game` must be null or a live pointer returned by `minesweep_game_new`.
+ */
+uintptr_t minesweep_height(struct MinesweepGame *game);
+
+/**
+ * 0 for null games
+ *
+ * # Safety
+ *
+ * `game` must be null or a live pointer returned by `minesweep_game_new`.
+ */
+uintptr_t minesweep_mines(struct MinesweepGame *game);
+
+/**
+ * Flags placed so far, 0 for null games
+ *
+ * # Safety
+ *
+ * `game` must be null or a live pointer returned by `minesweep_game_new`.
+ */
+uintptr_t minesweep_flags(struct MinesweepGame *game);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MINESWEEP_H */
@@ -0,0 +1,314 @@
+//! C interface of the engine, see `include/minesweep.h`
+//!
+//! A game is created with `minesweep_game_new` and owned by the caller until it is passed to
+//! `minesweep_game_free`, after which the pointer must not be used again. Every other function
+//! borrows the game for the duration of the call only, and accepts null by doing nothing.
+//! Games are not thread-safe, use each one from a single thread at a time.
+
+use minesweep_core::prelude::*;
+
+pub const MINESWEEP_CELL_UNOPENED: i8 = -1;
+pub const MINESWEEP_CELL_FLAGGED: i8 = -2;
+pub const MINESWEEP_CELL_QUESTIONED: i8 = -3;
+pub const MINESWEEP_CELL_MINE: i8 = -4;
+pub const MINESWEEP_CELL_WRONG_MINE: i8 = -5;
+pub const MINESWEEP_CELL_EXPLODED: i8 = -6;
+/// Returned for null games and cells out of the board
+pub const MINESWEEP_CELL_INVALID: i8 = -128;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MinesweepResult {
+    Playing,
+    Win,
+    Lose,
+}
+
+/// Opaque game handle
+pub struct MinesweepGame {
+    options: GameOptions,
+    /// `None` until the first left click, which is always safe
+    view: Option<GameView>,
+}
+
+impl MinesweepGame {
+    fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.options.difficulty.width() && y < self.options.difficulty.height()
+    }
+}
+
+/// Borrow the game behind a pointer from C, `None` for null
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `minesweep_game_new`.
+unsafe fn borrow<'a>(game: *mut MinesweepGame) -> Option<&'a mut MinesweepGame> {
+    game.as_mut()
+}
+
+/// Create a game, returns null when width, height or mines is zero, mines fill the board or
+/// the board is over the default `BoardLimits`
+///
+/// A `seed` of 0 picks a random board.
+#[no_mangle]
+pub extern "C" fn minesweep_game_new(
+    width: usize,
+    height: usize,
+    mines: usize,
+    seed: u64,
+) -> *mut MinesweepGame {
+    let difficulty = Difficulty::Custom {
+        width,
+        height,
+        mines,
+    };
+    let options = GameOptions {
+        difficulty,
+        safe_pos: None,
+        seed: (seed != 0).then_some(seed),
+        symmetry: Symmetry::None,
+        learning: false,
+        hidden_count: false,
+        generation: GENERATION_VERSION,
+        limits: BoardLimits::default(),
+    };
+    if width.checked_mul(height).is_none() || options.validate().is_err() {
+        return std::ptr::null_mut();
+    }
+    let game = MinesweepGame {
+        options,
+        view: None,
+    };
+    Box::into_raw(Box::new(game))
+}
+
+/// Release a game created by `minesweep_game_new`
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `minesweep_game_new`, it is dangling
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn minesweep_game_free(game: *mut MinesweepGame) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+/// Open a cell, the first one builds the board around it, returns false out of the board
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `minesweep_game_new`.
+#[no_mangle]
+pub unsafe extern "C" fn minesweep_left_click(
+    game: *mut MinesweepGame,
+    x: usize,
+    y: usize,
+) -> bool {
+    let Some(game) = borrow(game).filter(|game| game.contains(x, y)) else {
+        return false;
+    };
+    let view = game.view.get_or_insert_with(|| {
+        let options = GameOptions {
+            safe_pos: Some((x, y)),
+            ..game.options.clone()
+        };
+        GameView::from(options.build())
+    });
+    view.left_click(x, y);
+    true
+}
+
+/// Cycle the mark of a cell, returns false out of the board or before the first left click
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `minesweep_game_new`.
+#[no_mangle]
+pub unsafe extern "C" fn minesweep_right_click(
+    game: *mut MinesweepGame,
+    x: usize,
+    y: usize,
+) -> bool {
+    match borrow(game).filter(|game| game.contains(x, y)) {
+        Some(MinesweepGame {
+            view: Some(view), ..
+        }) => {
+            view.right_click(x, y);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Open the neighbours of a number with enough flags, same returns as `minesweep_right_click`
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `minesweep_game_new`.
+#[no_mangle]
+pub unsafe extern "C" fn minesweep_middle_click(
+    game: *mut MinesweepGame,
+    x: usize,
+    y: usize,
+) -> bool {
+    match borrow(game).filter(|game| game.contains(x, y)) {
+        Some(MinesweepGame {
+            view: Some(view), ..
+        }) => {
+            view.middle_click(x, y);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Take one automation step, returns false when no move could be deduced
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `minesweep_game_new`.
+#[no_mangle]
+pub unsafe extern "C" fn minesweep_automation_step(game: *mut MinesweepGame) -> bool {
+    match borrow(game) {
+        Some(MinesweepGame {
+            view: Some(view), ..
+        }) if view.result == GameResult::Playing => {
+            view.automation_step(AutomationPolicy::default()).is_ok()
+        }
+        _ => false,
+    }
+}
+
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `minesweep_game_new`.
+#[no_mangle]
+pub unsafe extern "C" fn minesweep_undo(game: *mut MinesweepGame) {
+    if let Some(MinesweepGame {
+        view: Some(view), ..
+    }) = borrow(game)
+    {
+        view.undo();
+    }
+}
+
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `minesweep_game_new`.
+#[no_mangle]
+pub unsafe extern "C" fn minesweep_redo(game: *mut MinesweepGame) {
+    if let Some(MinesweepGame {
+        view: Some(view), ..
+    }) = borrow(game)
+    {
+        view.redo();
+    }
+}
+
+/// Nearby mines 0 to 8 for opened cells, otherwise one of the `MINESWEEP_CELL_*` constants
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `minesweep_game_new`.
+#[no_mangle]
+pub unsafe extern "C" fn minesweep_cell(game: *mut MinesweepGame, x: usize, y: usize) -> i8 {
+    let Some(game) = borrow(game).filter(|game| game.contains(x, y)) else {
+        return MINESWEEP_CELL_INVALID;
+    };
+    let Some(view) = &game.view else {
+        return MINESWEEP_CELL_UNOPENED;
+    };
+    match view.cell(x, y) {
+        CellView::Unopened | CellView::Hovered | CellView::Pushed => MINESWEEP_CELL_UNOPENED,
+        CellView::Flagged => MINESWEEP_CELL_FLAGGED,
+        CellView::Questioned => MINESWEEP_CELL_QUESTIONED,
+        CellView::Opened(n) => n as i8,
+        CellView::Mine => MINESWEEP_CELL_MINE,
+        CellView::WrongMine => MINESWEEP_CELL_WRONG_MINE,
+        CellView::Exploded => MINESWEEP_CELL_EXPLODED,
+    }
+}
+
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `minesweep_game_new`.
+#[no_mangle]
+pub unsafe extern "C" fn minesweep_result(game: *mut MinesweepGame) -> MinesweepResult {
+    match borrow(game).and_then(|game| game.view.as_ref()) {
+        Some(view) => match view.result {
+            GameResult::Playing => MinesweepResult::Playing,
+            GameResult::Win => MinesweepResult::Win,
+            GameResult::Lose => MinesweepResult::Lose,
+        },
+        None => MinesweepResult::Playing,
+    }
+}
+
+/// 0 for null games
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `minesweep_game_new`.
+#[no_mangle]
+pub unsafe extern "C" fn minesweep_width(game: *mut MinesweepGame) -> usize {
+    borrow(game).map_or(0, |game| game.options.difficulty.width())
+}
+
+/// 0 for null games
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `minesweep_game_new`.
+#[no_mangle]
+pub unsafe extern "C" fn minesweep_height(game: *mut MinesweepGame) -> usize {
+    borrow(game).map_or(0, |game| game.options.difficulty.height())
+}
+
+/// 0 for null games
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `minesweep_game_new`.
+#[no_mangle]
+pub unsafe extern "C" fn minesweep_mines(game: *mut MinesweepGame) -> usize {
+    borrow(game).map_or(0, |game| game.options.difficulty.mines())
+}
+
+/// Flags placed so far, 0 for null games
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `minesweep_game_new`.
+#[no_mangle]
+pub unsafe extern "C" fn minesweep_flags(game: *mut MinesweepGame) -> usize {
+    borrow(game)
+        .and_then(|game| game.view.as_ref())
+        .map_or(0, |view| view.flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game() {
+        assert!(minesweep_game_new(3, 3, 9, 1).is_null());
+        let game = minesweep_game_new(9, 9, 10, 1);
+        unsafe {
+            assert_eq!(minesweep_cell(game, 4, 4), MINESWEEP_CELL_UNOPENED);
+            assert_eq!(minesweep_cell(game, 9, 0), MINESWEEP_CELL_INVALID);
+            assert!(!minesweep_right_click(game, 0, 0));
+            assert!(minesweep_left_click(game, 4, 4));
+            assert!(minesweep_cell(game, 4, 4) >= 0);
+            while minesweep_result(game) == MinesweepResult::Playing
+                && minesweep_automation_step(game)
+            {}
+            assert_ne!(minesweep_result(game), MinesweepResult::Lose);
+            minesweep_game_free(game);
+            assert_eq!(minesweep_width(std::ptr::null_mut()), 0);
+            minesweep_game_free(std::ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use minesweep_core::{Difficulty, Replay};
+use multiplayer_protocol::{Entry, Submission, LEADERBOARD_SIZE};
+
+/// Accepted results kept in memory along with their replays, fastest first
+#[derive(Default)]
+pub struct Leaderboard {
+    entries: Vec<(Entry, Replay)>,
+    next_id: u64,
+}
+
+impl Leaderboard {
+    pub fn submit(&mut self, submission: Submission, key: &str) -> Result<Entry, String> {
+        submission.verify(key)?;
+        if self
+            .entries
+            .iter()
+            .any(|(_, replay)| *replay == submission.replay)
+        {
+            return Err("result already submitted".to_string());
+        }
+        let entry = Entry {
+            id: self.next_id,
+            name: submission.name,
+            seed: submission.seed,
+            difficulty: submission.difficulty,
+            time: submission.time,
+        };
+        self.next_id += 1;
+        let index = self
+            .entries
+            .partition_point(|(other, _)| other.time <= entry.time);
+        self.entries
+            .insert(index, (entry.clone(), submission.replay));
+        Ok(entry)
+    }
+
+    /// Fastest results of every difficulty when `difficulty` is `None`
+    pub fn ranking(&self, difficulty: Option<&Difficulty>) -> Vec<Entry> {
+        self.entries
+            .iter()
+            .map(|(entry, _)| entry)
+            .filter(|entry| difficulty.is_none_or(|difficulty| entry.difficulty == *difficulty))
+            .take(LEADERBOARD_SIZE)
+            .cloned()
+            .collect()
+    }
+
+    pub fn replay(&self, id: u64) -> Option<Replay> {
+        self.entries
+            .iter()
+            .find(|(entry, _)| entry.id == id)
+            .map(|(_, replay)| replay.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minesweep_core::{GameOptions, GameResult, GameView};
+
+    use super::*;
+
+    fn win(difficulty: Difficulty, seed: u64, time: u64) -> Submission {
+        let state = GameOptions {
+            difficulty,
+            seed: Some(seed),
+            ..GameOptions::easy()
+        }
+        .build();
+        let mut view = GameView::from(state.clone());
+        for y in 0..state.height() {
+            for x in 0..state.width() {
+                if !state.is_mine(x, y) {
+                    view.stamp(0).left_click(x, y);
+                }
+            }
+        }
+        assert_eq!(view.result, GameResult::Win);
+        Submission::new(format!("player {seed}"), &view, time, "").unwrap()
+    }
+
+    #[test]
+    fn ranking() {
+        let mut leaderboard = Leaderboard::default();
+        leaderboard
+            .submit(win(Difficulty::Easy, 1, 30), "")
+            .unwrap();
+        leaderboard
+            .submit(win(Difficulty::Easy, 2, 10), "")
+            .unwrap();
+        let medium = leaderboard
+            .submit(win(Difficulty::Medium, 3, 20), "")
+            .unwrap();
+        assert!(leaderboard.submit(win(Difficulty::Easy, 1, 5), "").is_err());
+
+        let times =
+            |entries: Vec<Entry>| entries.iter().map(|entry| entry.time).collect::<Vec<_>>();
+        assert_eq!(times(leaderboard.ranking(None)), [10, 20, 30]);
+        assert_eq!(
+            times(leaderboard.ranking(Some(&Difficulty::Easy))),
+            [10, 30]
+        );
+        assert!(leaderboard.replay(medium.id).is_some());
+    }
+}
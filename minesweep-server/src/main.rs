@@ -0,0 +1,223 @@
+mod leaderboard;
+mod room;
+
+use std::{
+    collections::{hash_map, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        Path, Query, State, WebSocketUpgrade,
+    },
+    http::StatusCode,
+    response::Response,
+    routing::get,
+    Json, Router,
+};
+use minesweep_core::{Difficulty, Replay};
+use multiplayer_protocol::{ClientMessage, Entry, ServerMessage, Submission, PROTOCOL_VERSION};
+use serde::Deserialize;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tower_http::cors::CorsLayer;
+
+use leaderboard::Leaderboard;
+use room::Room;
+
+type Rooms = Arc<Mutex<HashMap<String, Room>>>;
+
+#[derive(Clone)]
+struct Server {
+    rooms: Rooms,
+    leaderboard: Arc<Mutex<Leaderboard>>,
+    /// Secret shared with the app for signing results, from `LEADERBOARD_KEY`
+    key: Arc<str>,
+}
+
+async fn send(socket: &mut WebSocket, message: ServerMessage) -> Result<(), axum::Error> {
+    socket.send(Message::text(message.to_json())).await
+}
+
+async fn receive(socket: &mut WebSocket) -> Option<Result<ClientMessage, String>> {
+    loop {
+        match socket.recv().await? {
+            Ok(Message::Text(text)) => return Some(ClientMessage::from_json(&text)),
+            Ok(Message::Close(_)) | Err(_) => return None,
+            Ok(_) => continue,
+        }
+    }
+}
+
+/// Wait for the player to join a room, creating it if needed
+async fn join(
+    socket: &mut WebSocket,
+    rooms: &Rooms,
+) -> Option<(String, u64, Receiver<ServerMessage>)> {
+    let reason = match receive(socket).await? {
+        Ok(ClientMessage::Join {
+            version,
+            room: name,
+            name: player_name,
+            options,
+        }) if version == PROTOCOL_VERSION => {
+            let joined = {
+                let mut rooms = rooms.lock().unwrap();
+                let room = match rooms.entry(name.clone()) {
+                    hash_map::Entry::Occupied(entry) => Ok(entry.into_mut()),
+                    hash_map::Entry::Vacant(entry) => {
+                        Room::new(options).map(|room| entry.insert(room))
+                    }
+                };
+                room.map(|room| {
+                    let (player, receiver) = room.join(player_name);
+                    (player, receiver, room.sync())
+                })
+            };
+            let (player, receiver, sync) = match joined {
+                Ok(joined) => joined,
+                Err(reason) => {
+                    let _ = send(socket, ServerMessage::Failed { reason }).await;
+                    return None;
+                }
+            };
+            let welcome = send(socket, ServerMessage::Welcome { player }).await;
+            if welcome.is_err() || send(socket, sync).await.is_err() {
+                leave(rooms, &name, player);
+                return None;
+            }
+            return Some((name, player, receiver));
+        }
+        Ok(ClientMessage::Join { version, .. }) => {
+            format!("protocol version {version} is not supported, expected {PROTOCOL_VERSION}")
+        }
+        Ok(_) => "join a room first".to_string(),
+        Err(reason) => reason,
+    };
+    let _ = send(socket, ServerMessage::Failed { reason }).await;
+    None
+}
+
+fn leave(rooms: &Rooms, name: &str, player: u64) {
+    let mut rooms = rooms.lock().unwrap();
+    if let Some(room) = rooms.get_mut(name) {
+        room.leave(player);
+        if room.is_empty() {
+            rooms.remove(name);
+        }
+    }
+}
+
+/// Forward the player's messages to the room and everything happening in the room back
+async fn play(mut socket: WebSocket, rooms: Rooms) {
+    let Some((name, player, mut receiver)) = join(&mut socket, &rooms).await else {
+        return;
+    };
+    loop {
+        let reply = tokio::select! {
+            message = receive(&mut socket) => {
+                let Some(message) = message else {
+                    break;
+                };
+                let result = message.and_then(|message| {
+                    let mut rooms = rooms.lock().unwrap();
+                    rooms.get_mut(&name).unwrap().handle(player, message)
+                });
+                match result {
+                    Ok(()) => continue,
+                    Err(reason) => ServerMessage::Failed { reason },
+                }
+            }
+            message = receiver.recv() => match message {
+                Ok(message) => message,
+                // fell too far behind to catch up action by action
+                Err(RecvError::Lagged(_)) => rooms.lock().unwrap()[&name].sync(),
+                Err(RecvError::Closed) => break,
+            },
+        };
+        if send(&mut socket, reply).await.is_err() {
+            break;
+        }
+    }
+    leave(&rooms, &name, player);
+}
+
+async fn upgrade(upgrade: WebSocketUpgrade, State(server): State<Server>) -> Response {
+    upgrade.on_upgrade(move |socket| play(socket, server.rooms))
+}
+
+async fn submit(
+    State(server): State<Server>,
+    Json(submission): Json<Submission>,
+) -> Result<Json<Entry>, (StatusCode, String)> {
+    let mut leaderboard = server.leaderboard.lock().unwrap();
+    match leaderboard.submit(submission, &server.key) {
+        Ok(entry) => Ok(Json(entry)),
+        Err(reason) => Err((StatusCode::BAD_REQUEST, reason)),
+    }
+}
+
+#[derive(Deserialize)]
+struct RankingQuery {
+    difficulty: Option<String>,
+}
+
+async fn ranking(
+    State(server): State<Server>,
+    Query(query): Query<RankingQuery>,
+) -> Result<Json<Vec<Entry>>, (StatusCode, String)> {
+    let difficulty = match query.difficulty.as_deref() {
+        None => None,
+        Some("easy") => Some(Difficulty::Easy),
+        Some("medium") => Some(Difficulty::Medium),
+        Some("hard") => Some(Difficulty::Hard),
+        Some(other) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("unknown difficulty {other}"),
+            ))
+        }
+    };
+    let leaderboard = server.leaderboard.lock().unwrap();
+    Ok(Json(leaderboard.ranking(difficulty.as_ref())))
+}
+
+async fn replay(
+    State(server): State<Server>,
+    Path(id): Path<u64>,
+) -> Result<Json<Replay>, StatusCode> {
+    let leaderboard = server.leaderboard.lock().unwrap();
+    leaderboard
+        .replay(id)
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:3000".to_string());
+    let key = match std::env::var("LEADERBOARD_KEY") {
+        Ok(key) if !key.is_empty() => key,
+        _ => {
+            return Err(std::io::Error::other(
+                "LEADERBOARD_KEY must be set to the secret the app signs results with",
+            ))
+        }
+    };
+    let server = Server {
+        rooms: Rooms::default(),
+        leaderboard: Default::default(),
+        key: key.into(),
+    };
+    let app = Router::new()
+        .route("/ws", get(upgrade))
+        .route("/leaderboard", get(ranking).post(submit))
+        .route("/leaderboard/replay/{id}", get(replay))
+        .layer(CorsLayer::permissive())
+        .with_state(server);
+    let listener = tokio::net::TcpListener::bind(&address).await?;
+    println!("listening on {address}, rooms at /ws and rankings at /leaderboard");
+    axum::serve(listener, app).await
+}
@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+
+use minesweep_core::{Action, BoardLimits, GameOptions, GameView};
+use multiplayer_protocol::{ClientMessage, Player, ServerMessage};
+use tokio::sync::broadcast::{self, Receiver, Sender};
+
+/// Messages a player may fall behind by before being sent a `Sync` instead
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Shared game, every action goes through here so that all players see the same order
+pub struct Room {
+    options: GameOptions,
+    /// `None` until the first click, which is always safe
+    view: Option<GameView>,
+    players: BTreeMap<u64, Player>,
+    next_player: u64,
+    sender: Sender<ServerMessage>,
+}
+
+/// Options under the server's own limits, refused if the board could not be built
+fn checked(options: GameOptions) -> Result<GameOptions, String> {
+    let options = GameOptions {
+        limits: BoardLimits::default(),
+        ..options
+    };
+    options.validate().map_err(|err| err.to_string())?;
+    Ok(options)
+}
+
+impl Room {
+    pub fn new(options: GameOptions) -> Result<Self, String> {
+        Ok(Self {
+            options: checked(options)?,
+            view: None,
+            players: BTreeMap::new(),
+            next_player: 0,
+            sender: broadcast::channel(CHANNEL_CAPACITY).0,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    pub fn join(&mut self, name: String) -> (u64, Receiver<ServerMessage>) {
+        let id = self.next_player;
+        self.next_player += 1;
+        let player = Player {
+            id,
+            name,
+            cursor: None,
+        };
+        self.players.insert(id, player.clone());
+        let receiver = self.sender.subscribe();
+        self.broadcast(ServerMessage::Joined(player));
+        (id, receiver)
+    }
+
+    pub fn leave(&mut self, player: u64) {
+        if self.players.remove(&player).is_some() {
+            self.broadcast(ServerMessage::Left { player });
+        }
+    }
+
+    pub fn sync(&self) -> ServerMessage {
+        ServerMessage::Sync {
+            options: self.options.clone(),
+            replay: self.view.as_ref().map(|view| Box::new(view.replay())),
+            players: self.players.values().cloned().collect(),
+        }
+    }
+
+    pub fn handle(&mut self, player: u64, message: ClientMessage) -> Result<(), String> {
+        match message {
+            ClientMessage::Join { .. } => Err("already in a room".to_string()),
+            ClientMessage::Act(action) => self.act(player, action),
+            ClientMessage::Cursor(cursor) => {
+                if cursor.is_some_and(|(x, y)| !self.contains(x, y)) {
+                    return Err("cursor out of the board".to_string());
+                }
+                if let Some(player) = self.players.get_mut(&player) {
+                    player.cursor = cursor;
+                }
+                self.broadcast(ServerMessage::Cursor { player, cursor });
+                Ok(())
+            }
+            ClientMessage::NewGame(options) => {
+                self.options = checked(options)?;
+                self.view = None;
+                self.broadcast(self.sync());
+                Ok(())
+            }
+        }
+    }
+
+    fn act(&mut self, player: u64, action: Action) -> Result<(), String> {
+        if !self.is_valid(&action) {
+            return Err("action out of the board".to_string());
+        }
+        let Some(view) = &mut self.view else {
+            // build the board around the first click, everyone gets it as a whole
+            if let Action::LeftClick(x, y) = action {
+                let options = GameOptions {
+                    safe_pos: Some((x, y)),
+                    ..self.options.clone()
+                };
+                let state = options.try_build().map_err(|err| err.to_string())?;
+                let mut view = GameView::from(state);
+                view.left_click(x, y);
+                self.view = Some(view);
+                self.broadcast(self.sync());
+            }
+            return Ok(());
+        };
+        view.apply(&action);
+        self.broadcast(ServerMessage::Applied { player, action });
+        Ok(())
+    }
+
+    fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.options.difficulty.width() && y < self.options.difficulty.height()
+    }
+
+    fn is_valid(&self, action: &Action) -> bool {
+        match action {
+            Action::LeftClick(x, y) | Action::RightClick(x, y) | Action::MiddleClick(x, y) => {
+                self.contains(*x, *y)
+            }
+            Action::Undo | Action::Redo => true,
+            Action::Automation(actions) => {
+                action.is_well_formed() && actions.iter().all(|action| self.is_valid(action))
+            }
+        }
+    }
+
+    fn broadcast(&self, message: ServerMessage) {
+        // nobody listening is fine, the room is about to be dropped
+        let _ = self.sender.send(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minesweep_core::{CellView, Difficulty};
+
+    use super::*;
+
+    #[test]
+    fn shared_board() {
+        let mut room = Room::new(GameOptions {
+            seed: Some(1),
+            ..GameOptions::easy()
+        })
+        .unwrap();
+        let (alice, mut alice_messages) = room.join("alice".to_string());
+        let (bob, mut bob_messages) = room.join("bob".to_string());
+        assert!(matches!(
+            alice_messages.try_recv(),
+            Ok(ServerMessage::Joined(Player { id, .. })) if id == alice
+        ));
+        assert!(matches!(
+            alice_messages.try_recv(),
+            Ok(ServerMessage::Joined(Player { id, .. })) if id == bob
+        ));
+        bob_messages.try_recv().unwrap();
+
+        room.handle(alice, ClientMessage::Act(Action::LeftClick(4, 4)))
+            .unwrap();
+        let Ok(ServerMessage::Sync {
+            replay: Some(replay),
+            ..
+        }) = bob_messages.try_recv()
+        else {
+            panic!("expected a sync with the board");
+        };
+        let mut view = replay.finish().unwrap();
+        assert!(matches!(view.cell(4, 4), CellView::Opened(_)));
+        alice_messages.try_recv().unwrap();
+
+        let intact = (0..9)
+            .flat_map(|y| (0..9).map(move |x| (x, y)))
+            .find(|(x, y)| view.cell(*x, *y) == CellView::Unopened)
+            .unwrap();
+        let flag = Action::RightClick(intact.0, intact.1);
+        room.handle(bob, ClientMessage::Act(flag.clone())).unwrap();
+        let Ok(ServerMessage::Applied { player, action }) = alice_messages.try_recv() else {
+            panic!("expected the flag");
+        };
+        assert_eq!((player, &action), (bob, &flag));
+        view.apply(&action);
+        assert_eq!(Some(&view), room.view.as_ref());
+
+        assert!(room
+            .handle(bob, ClientMessage::Act(Action::LeftClick(9, 0)))
+            .is_err());
+        let crowded = GameOptions {
+            difficulty: Difficulty::Custom {
+                width: 9,
+                height: 9,
+                mines: 81,
+            },
+            ..room.options.clone()
+        };
+        assert!(room.handle(bob, ClientMessage::NewGame(crowded)).is_err());
+        let huge = GameOptions {
+            difficulty: Difficulty::Custom {
+                width: 1 << 20,
+                height: 1 << 20,
+                mines: 10,
+            },
+            limits: BoardLimits {
+                max_area: usize::MAX,
+                max_mines: usize::MAX,
+            },
+            ..room.options.clone()
+        };
+        assert!(room.handle(bob, ClientMessage::NewGame(huge)).is_err());
+        room.leave(bob);
+        assert!(!room.is_empty());
+    }
+}
@@ -0,0 +1,398 @@
+use std::{
+    io::{self, stdout},
+    sync::mpsc::{channel, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use minesweep_core::prelude::*;
+use ratatui::{
+    crossterm::{
+        event::{
+            self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind,
+            MouseButton, MouseEventKind,
+        },
+        execute,
+    },
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+    DefaultTerminal, Frame,
+};
+
+const HELP: &str = "arrows/hjkl move  space open  f flag  c chord  s step  a automation  u/U undo/redo  r restart  n new  q quit";
+
+/// Result of a solve on a background thread, tagged with the game and the actions it saw
+struct Solved {
+    game: u64,
+    actions: usize,
+    result: SolveResult,
+}
+
+struct App {
+    options: GameOptions,
+    /// `None` until the first click, which is always safe
+    view: Option<GameView>,
+    cursor: (usize, usize),
+    /// Bumped on new game and restart so that solves of an older board are dropped
+    game: u64,
+    started: Option<Instant>,
+    finished: Option<Duration>,
+    board_area: Rect,
+    sender: Sender<Solved>,
+    receiver: Receiver<Solved>,
+    solving: bool,
+    running: bool,
+    message: String,
+    quit: bool,
+}
+
+impl App {
+    fn new(options: GameOptions) -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            options,
+            view: None,
+            cursor: (0, 0),
+            game: 0,
+            started: None,
+            finished: None,
+            board_area: Rect::default(),
+            sender,
+            receiver,
+            solving: false,
+            running: false,
+            message: String::new(),
+            quit: false,
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.options.difficulty.width()
+    }
+
+    fn height(&self) -> usize {
+        self.options.difficulty.height()
+    }
+
+    fn is_playing(&self) -> bool {
+        self.view
+            .as_ref()
+            .is_some_and(|view| view.result == GameResult::Playing)
+    }
+
+    fn new_game(&mut self) {
+        self.view = None;
+        self.game += 1;
+        self.started = None;
+        self.finished = None;
+        self.running = false;
+        self.message.clear();
+    }
+
+    fn restart(&mut self) {
+        if let Some(view) = &mut self.view {
+            view.restart();
+            self.game += 1;
+            self.started = Some(Instant::now());
+            self.finished = None;
+            self.running = false;
+            self.message.clear();
+        }
+    }
+
+    fn left_click(&mut self, x: usize, y: usize) {
+        let view = self.view.get_or_insert_with(|| {
+            let options = GameOptions {
+                safe_pos: Some((x, y)),
+                ..self.options.clone()
+            };
+            GameView::from(options.build())
+        });
+        self.started.get_or_insert_with(Instant::now);
+        view.left_click(x, y);
+        self.check_finished();
+    }
+
+    fn right_click(&mut self, x: usize, y: usize) {
+        if let Some(view) = &mut self.view {
+            view.right_click(x, y);
+            self.check_finished();
+        }
+    }
+
+    fn middle_click(&mut self, x: usize, y: usize) {
+        if let Some(view) = &mut self.view {
+            view.middle_click(x, y);
+            self.check_finished();
+        }
+    }
+
+    fn check_finished(&mut self) {
+        if self.view.is_some() && !self.is_playing() && self.finished.is_none() {
+            self.finished = self.started.map(|started| started.elapsed());
+            self.running = false;
+        }
+    }
+
+    /// Solve a copy of the board on a native thread, the result comes back through the channel
+    fn automate(&mut self) {
+        if self.solving || !self.is_playing() {
+            return;
+        }
+        let view = self.view.clone().unwrap();
+        let (game, sender) = (self.game, self.sender.clone());
+        self.solving = true;
+        thread::spawn(move || {
+            let actions = view.actions().len();
+            let result = view.solve();
+            let _ = sender.send(Solved {
+                game,
+                actions,
+                result,
+            });
+        });
+    }
+
+    fn poll_automation(&mut self) {
+        while let Ok(Solved {
+            game,
+            actions,
+            result,
+        }) = self.receiver.try_recv()
+        {
+            self.solving = false;
+            let Some(view) = self.view.as_mut().filter(|_| game == self.game) else {
+                continue;
+            };
+            // the player moved meanwhile, solve again for a run or drop the step
+            if view.actions().len() != actions {
+                if self.running {
+                    self.automate();
+                }
+                continue;
+            }
+            if let Err(err) = view.automation_apply(result, AutomationPolicy::default()) {
+                self.message = format!("Automation stopped: {err}");
+                self.running = false;
+                continue;
+            }
+            self.check_finished();
+            if self.running {
+                self.automate();
+            }
+        }
+    }
+
+    fn on_key(&mut self, code: KeyCode) {
+        let (x, y) = self.cursor;
+        self.message.clear();
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Left | KeyCode::Char('h') => self.cursor.0 = x.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') => self.cursor.0 = (x + 1).min(self.width() - 1),
+            KeyCode::Up | KeyCode::Char('k') => self.cursor.1 = y.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.cursor.1 = (y + 1).min(self.height() - 1),
+            KeyCode::Char(' ') | KeyCode::Enter => self.left_click(x, y),
+            KeyCode::Char('f') => self.right_click(x, y),
+            KeyCode::Char('c') => self.middle_click(x, y),
+            KeyCode::Char('s') => self.automate(),
+            KeyCode::Char('a') => {
+                self.running = !self.running && self.is_playing();
+                if self.running {
+                    self.automate();
+                }
+            }
+            KeyCode::Char('u') => {
+                if let Some(view) = &mut self.view {
+                    view.undo();
+                }
+            }
+            KeyCode::Char('U') => {
+                if let Some(view) = &mut self.view {
+                    view.redo();
+                }
+            }
+            KeyCode::Char('r') => self.restart(),
+            KeyCode::Char('n') => self.new_game(),
+            _ => {}
+        }
+    }
+
+    fn on_mouse(&mut self, button: MouseButton, column: u16, row: u16) {
+        let Some((x, y)) = cell_at(self.board_area, column, row, self.width(), self.height())
+        else {
+            return;
+        };
+        self.cursor = (x, y);
+        self.message.clear();
+        match button {
+            MouseButton::Left => self.left_click(x, y),
+            MouseButton::Right => self.right_click(x, y),
+            MouseButton::Middle => self.middle_click(x, y),
+        }
+    }
+
+    fn status(&self) -> Line<'static> {
+        let (mines, flags, result) = match &self.view {
+            Some(view) => (view.mines(), view.flags, view.result),
+            None => (
+                (!self.options.hidden_count).then(|| self.options.difficulty.mines()),
+                0,
+                GameResult::Playing,
+            ),
+        };
+        let mines = mines.map_or("?".to_string(), |mines| mines.to_string());
+        let time = self
+            .finished
+            .or_else(|| self.started.map(|started| started.elapsed()))
+            .unwrap_or_default()
+            .as_secs();
+        let result = match result {
+            GameResult::Win => "You win 😎".green().bold(),
+            GameResult::Lose => "You lose 😵".red().bold(),
+            GameResult::Playing if self.running => "Automation running 🤖".cyan(),
+            GameResult::Playing => "Playing 🙂".into(),
+        };
+        Line::from(vec![
+            result,
+            format!("  Mines: {flags}/{mines}").into(),
+            format!("  Time: {:02}:{:02}", time / 60, time % 60).into(),
+            format!("  {}", self.message).yellow(),
+        ])
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let board_width = self.width() as u16 * 2 + 2;
+        let board_height = self.height() as u16 + 2;
+        let [status, board, help] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(board_height),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        self.board_area = Rect {
+            width: board_width.min(board.width),
+            ..board
+        };
+        frame.render_widget(Paragraph::new(self.status()), status);
+        let lines: Vec<Line> = (0..self.height())
+            .map(|y| {
+                Line::from(
+                    (0..self.width())
+                        .map(|x| {
+                            let cell = self
+                                .view
+                                .as_ref()
+                                .map_or(CellView::Unopened, |view| view.cell(x, y));
+                            let (text, style) = cell_span(cell);
+                            if (x, y) == self.cursor {
+                                Span::styled(text, style.add_modifier(Modifier::REVERSED))
+                            } else {
+                                Span::styled(text, style)
+                            }
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" Minesweep Automated ")),
+            self.board_area,
+        );
+        frame.render_widget(Paragraph::new(HELP.dark_gray()), help);
+    }
+}
+
+/// Each cell takes two columns so that the board looks square
+fn cell_span(cell: CellView) -> (&'static str, Style) {
+    use CellView::*;
+    let style = Style::default();
+    match cell {
+        Unopened | Hovered | Pushed => ("· ", style.fg(Color::DarkGray)),
+        Flagged => ("F ", style.fg(Color::Red).bold()),
+        Questioned => ("? ", style.fg(Color::Yellow)),
+        Opened(0) => ("  ", style),
+        Opened(n) => {
+            const DIGITS: [&str; 8] = ["1 ", "2 ", "3 ", "4 ", "5 ", "6 ", "7 ", "8 "];
+            const COLORS: [Color; 8] = [
+                Color::Blue,
+                Color::Green,
+                Color::Red,
+                Color::Magenta,
+                Color::LightRed,
+                Color::Cyan,
+                Color::White,
+                Color::Gray,
+            ];
+            let i = (n as usize - 1).min(7);
+            (DIGITS[i], style.fg(COLORS[i]).bold())
+        }
+        Mine => ("* ", style),
+        WrongMine => ("x ", style.fg(Color::Red)),
+        Exploded => ("* ", style.bg(Color::Red)),
+    }
+}
+
+/// Cell under a terminal position, inside the border of the board
+fn cell_at(board: Rect, column: u16, row: u16, w: usize, h: usize) -> Option<(usize, usize)> {
+    let x = column.checked_sub(board.x + 1)? as usize / 2;
+    let y = row.checked_sub(board.y + 1)? as usize;
+    (x < w && y < h).then_some((x, y))
+}
+
+fn parse_options() -> GameOptions {
+    let mut args = std::env::args().skip(1);
+    let mut options = match args.next().as_deref() {
+        Some("easy") => GameOptions::easy(),
+        Some("hard") => GameOptions::hard(),
+        _ => GameOptions::medium(),
+    };
+    options.seed = args.next().and_then(|seed| seed.parse().ok());
+    options
+}
+
+fn run(terminal: &mut DefaultTerminal, mut app: App) -> io::Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| app.draw(frame))?;
+        if event::poll(Duration::from_millis(50))? {
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => app.on_key(key.code),
+                Event::Mouse(mouse) => {
+                    if let MouseEventKind::Down(button) = mouse.kind {
+                        app.on_mouse(button, mouse.column, mouse.row);
+                    }
+                }
+                _ => {}
+            }
+        }
+        app.poll_automation();
+    }
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    let app = App::new(parse_options());
+    let mut terminal = ratatui::init();
+    execute!(stdout(), EnableMouseCapture)?;
+    let result = run(&mut terminal, app);
+    execute!(stdout(), DisableMouseCapture)?;
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mouse_to_cell() {
+        let board = Rect::new(2, 1, 20, 7);
+        assert_eq!(cell_at(board, 3, 2, 9, 5), Some((0, 0)));
+        assert_eq!(cell_at(board, 4, 2, 9, 5), Some((0, 0)));
+        assert_eq!(cell_at(board, 5, 3, 9, 5), Some((1, 1)));
+        assert_eq!(cell_at(board, 2, 2, 9, 5), None);
+        assert_eq!(cell_at(board, 3, 7, 9, 5), None);
+    }
+}
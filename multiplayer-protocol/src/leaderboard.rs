@@ -0,0 +1,197 @@
+use minesweep_core::{
+    Action, BoardLimits, Difficulty, GameOptions, GameResult, GameView, Replay, Symmetry,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Entries returned for one ranking
+pub const LEADERBOARD_SIZE: usize = 50;
+
+/// Won game sent for ranking, the server replays it before accepting
+///
+/// The signature is keyed with a secret shared by the app and the server, which only keeps out
+/// results not made by the app, the replay is what proves the win.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Submission {
+    pub name: String,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    /// Seconds taken as measured by the player, no less than the replay's timestamps span
+    pub time: u64,
+    pub replay_hash: String,
+    pub replay: Replay,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Uploaded replay of the result, fetched from `/leaderboard/replay/{id}`
+    pub id: u64,
+    pub name: String,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub time: u64,
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub fn replay_hash(replay: &Replay) -> String {
+    hex(&Sha256::digest(serde_json::to_vec(replay).unwrap()))
+}
+
+impl Submission {
+    /// `None` unless the game was won without any assistance or hidden count on a seeded
+    /// standard board
+    pub fn new(name: String, view: &GameView, time: u64, key: &str) -> Option<Self> {
+        let replay = view.replay();
+        let seed = replay.options.seed?;
+        let difficulty = replay.options.difficulty.clone();
+        if view.result != GameResult::Win
+            || !replay.assistance.is_pure()
+            || !difficulty.is_standard()
+            || replay.options.symmetry != Symmetry::None
+            || replay.options.hidden_count
+        {
+            return None;
+        }
+        let mut submission = Self {
+            name,
+            seed,
+            difficulty,
+            time,
+            replay_hash: replay_hash(&replay),
+            replay,
+            signature: String::new(),
+        };
+        submission.signature = submission.sign(key);
+        Some(submission)
+    }
+
+    fn sign(&self, key: &str) -> String {
+        let mut hasher = Sha256::new();
+        for field in [
+            key,
+            &self.name,
+            &self.seed.to_string(),
+            &serde_json::to_string(&self.difficulty).unwrap(),
+            &self.time.to_string(),
+            &self.replay_hash,
+        ] {
+            hasher.update(field.as_bytes());
+            hasher.update([0]);
+        }
+        hex(&hasher.finalize())
+    }
+
+    /// Check the signature and replay the game on the board its seed builds, under the
+    /// server's own limits
+    pub fn verify(&self, key: &str) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name is empty".to_string());
+        }
+        if self.signature != self.sign(key) {
+            return Err("signature does not match".to_string());
+        }
+        if self.replay_hash != replay_hash(&self.replay) {
+            return Err("replay hash does not match".to_string());
+        }
+        let options = &self.replay.options;
+        if options.seed != Some(self.seed) || options.difficulty != self.difficulty {
+            return Err("replay is of another board".to_string());
+        }
+        if !self.difficulty.is_standard() || options.symmetry != Symmetry::None {
+            return Err("only standard boards are ranked".to_string());
+        }
+        if options.learning {
+            return Err("learning mode games are not ranked".to_string());
+        }
+        if options.hidden_count {
+            return Err("hidden count games are not ranked".to_string());
+        }
+        let state = GameOptions {
+            limits: BoardLimits::default(),
+            ..options.clone()
+        }
+        .try_build()
+        .map_err(|err| err.to_string())?;
+        if state.to_board_text() != self.replay.board {
+            return Err("replay board does not match its seed".to_string());
+        }
+        if self.replay.board_hash.is_none() {
+            return Err("replay has no board hash".to_string());
+        }
+        self.replay.verify_board().map_err(|err| err.to_string())?;
+        if self
+            .replay
+            .actions
+            .iter()
+            .any(|action| matches!(action, Action::Automation(_)))
+        {
+            return Err("automated games are not ranked".to_string());
+        }
+        let view = self.replay.finish().map_err(|err| err.to_string())?;
+        if !view.assistance().is_pure() {
+            return Err("assisted games are not ranked".to_string());
+        }
+        if view.result != GameResult::Win {
+            return Err("replay is not a win".to_string());
+        }
+        let Some(duration) = self.replay.duration() else {
+            return Err("replay is not timed".to_string());
+        };
+        if self.time < duration / 1000 {
+            return Err("time is shorter than the replay".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify() {
+        let options = GameOptions {
+            safe_pos: Some((4, 4)),
+            seed: Some(7),
+            ..GameOptions::easy()
+        };
+        let state = options.build();
+        let mut view = GameView::from(state.clone());
+        // open every safe cell by hand
+        for y in 0..9 {
+            for x in 0..9 {
+                if !state.is_mine(x, y) {
+                    view.stamp((y * 9 + x) as u64 * 100).left_click(x, y);
+                }
+            }
+        }
+        assert_eq!(view.result, GameResult::Win);
+        let submission = Submission::new("alice".to_string(), &view, 42, "key").unwrap();
+        assert_eq!(submission.verify("key"), Ok(()));
+        assert!(submission.verify("other").is_err());
+
+        let mut forged = submission.clone();
+        forged.time = 1;
+        assert!(forged.verify("key").is_err());
+        forged.signature = forged.sign("key");
+        assert_eq!(
+            forged.verify("key"),
+            Err("time is shorter than the replay".to_string())
+        );
+        let mut forged = submission.clone();
+        forged.replay.actions.push(Action::LeftClick(9, 0));
+        forged.replay.timestamps.push(Some(9000));
+        forged.replay_hash = replay_hash(&forged.replay);
+        forged.signature = forged.sign("key");
+        assert!(forged.verify("key").is_err());
+        let mut forged = submission;
+        forged.replay.actions.truncate(1);
+        forged.replay_hash = replay_hash(&forged.replay);
+        forged.signature = forged.sign("key");
+        assert!(forged.verify("key").is_err());
+    }
+}
@@ -0,0 +1,84 @@
+mod leaderboard;
+
+use minesweep_core::{Action, GameOptions, Replay};
+use serde::{Deserialize, Serialize};
+
+pub use leaderboard::{replay_hash, Entry, Submission, LEADERBOARD_SIZE};
+
+/// Bumped on every incompatible change to the messages, checked on join
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Sent by players, the first message on a connection must be `Join`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// Enter a room, creating it with `options` when nobody is there yet
+    Join {
+        version: u32,
+        room: String,
+        name: String,
+        options: GameOptions,
+    },
+    /// Act on the shared board, applied once the server echoes it back
+    Act(Action),
+    Cursor(Option<(usize, usize)>),
+    /// Replace the shared board for everyone in the room
+    NewGame(GameOptions),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ServerMessage {
+    /// Id given to the joining player, followed by a `Sync`
+    Welcome {
+        player: u64,
+    },
+    /// Whole room state, sent on join, on new board and when a player fell behind
+    Sync {
+        options: GameOptions,
+        /// `None` until the first click, which builds the board around it
+        replay: Option<Box<Replay>>,
+        players: Vec<Player>,
+    },
+    /// Action taken by a player, in the order the server applied them
+    Applied {
+        player: u64,
+        action: Action,
+    },
+    Cursor {
+        player: u64,
+        cursor: Option<(usize, usize)>,
+    },
+    Joined(Player),
+    Left {
+        player: u64,
+    },
+    Failed {
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Player {
+    pub id: u64,
+    pub name: String,
+    pub cursor: Option<(usize, usize)>,
+}
+
+impl ClientMessage {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|err| err.to_string())
+    }
+}
+
+impl ServerMessage {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|err| err.to_string())
+    }
+}
@@ -1,44 +1,193 @@
-use automation_worker::Automation;
-use ev::{mousemove, mouseup};
-use futures::{SinkExt, StreamExt};
-use gloo_worker::Spawnable;
+mod benchmark;
+mod checkpoint;
+mod explain;
+mod explorer;
+mod health;
+mod history;
+mod idle;
+mod import;
+mod inspect;
+mod leaderboard;
+mod motion;
+mod mouse;
+mod pool;
+mod profile;
+mod result;
+mod room;
+mod spectator;
+mod sprites;
+mod storage;
+mod telemetry;
+mod tooltip;
+
+use automation_protocol::{AutomationRequest, AutomationResponse, Moves, RaceStats, Solver};
+use ev::{dragover, keydown, mousemove, mouseup, paste};
+use futures::SinkExt;
 use html::Canvas;
-use js_sys::{Object, Reflect};
-use leptos::logging::log;
+use js_sys::{Date, Object, Reflect};
 use leptos::*;
 use leptos_dom::helpers::set_property;
 use leptos_meta::*;
 use leptos_use::{
-    use_event_listener, use_interval, use_mouse, use_mouse_in_element, use_window_size,
-    UseIntervalReturn, UseMouseInElementReturn, UseMouseReturn, UseWindowSizeReturn,
+    use_clipboard, use_event_listener, use_interval, use_mouse, use_mouse_in_element,
+    use_window_size, UseClipboardReturn, UseIntervalReturn, UseMouseInElementReturn,
+    UseMouseReturn, UseWindowSizeReturn,
 };
 use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet, VecDeque},
+    rc::Rc,
+    time::Duration,
+};
 use stylers::style_str;
 use wasm_bindgen::{prelude::*, JsValue};
-use web_sys::{CanvasRenderingContext2d, HtmlDivElement, HtmlImageElement};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, HtmlDivElement, HtmlImageElement};
 
+use benchmark::BenchmarkDrawer;
+use explain::ExplainStep;
+use explorer::SeedExplorerDrawer;
+use history::HistoryDrawer;
+use idle::{
+    pause_when_away, persisted_pause_settings, PauseControls, PauseSettings, ResumeOverlay,
+};
+use import::{import, is_board_text};
+use inspect::{next_inspection, queue_step, Inspection};
+use leaderboard::LeaderboardDrawer;
 use minesweep_core::{
-    CellView, Difficulty, GameOptions, GameResult, GameView, Gesture, RedrawCells,
+    Action, AutomationError, AutomationPolicy, BoardError, BoardLimits, CellView, Difficulty,
+    GameOptions, GameResult, GameState, GameView, Gesture, Rect, RedrawCells, RedrawRegion,
+    SolveTier, Symmetry, Topology, TraceBundle, Tutorial, GENERATION_VERSION,
 };
+use motion::{persisted_motion_settings, MotionControls, MotionSettings};
+use mouse::{persisted_mouse_settings, ButtonAction, MouseControls, MouseSettings};
+use multiplayer_protocol::ClientMessage;
+use pool::{spawn_worker, WorkerPool, POOL_MIN_CELLS};
+use profile::{Profile, ProfileChooser, ProfileDrawer};
+use result::{shared_game, ResultCard};
+use room::{Room, RoomDrawer};
+use spectator::{Spectator, SpectatorControls};
+use sprites::{persisted_sprite_pack, SpriteControls, SpritePack, SPRITE_NAMES};
+use telemetry::{Telemetry, TelemetryOverlay};
+use tooltip::CellTooltip;
 
 const INITIAL_SCALE: f64 = 1.;
 const SCALE_FACTOR: f64 = 1.1;
 const PADDING: f64 = 20.;
-const CELL_SIZE: f64 = 50.;
-const CELL_GAP: f64 = 2.;
+const MIN_CELL_SIZE: f64 = 20.;
+const MAX_CELL_SIZE: f64 = 80.;
+/// Seconds a step of the live assist solver may take before giving up on costlier tiers
+const LIVE_ASSIST_BUDGET: f64 = 0.05;
+/// Quiet time after the last click before the worker is asked for safe hints
+const HINT_DEBOUNCE: Duration = Duration::from_millis(150);
+/// Seconds after the last press or key before automation may touch the board
+const INTERACTION_QUIET: f64 = 0.3;
+/// Seconds a redraw may take on average before drawing is reduced, until redraws take half
+const FRAME_BUDGET: f64 = 1. / 60.;
+
+/// Flags over mines, or flags alone in games hiding the count
+fn mine_counter(flags: usize, mines: Option<usize>) -> String {
+    match mines {
+        Some(mines) => format!("Mines: {flags}/{mines}"),
+        None => format!("Flags: {flags}"),
+    }
+}
 
 fn timestamp() -> f64 {
     window().performance().unwrap().now() as f64 / 1000.
 }
 
+/// How the board is turned on screen, mirrored left to right before being rotated
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Orientation {
+    /// Clockwise, `0..4`
+    quarter_turns: u8,
+    mirrored: bool,
+}
+
+impl Orientation {
+    fn rotate(self) -> Self {
+        Self {
+            quarter_turns: (self.quarter_turns + 1) % 4,
+            ..self
+        }
+    }
+
+    /// Affine map `[a, b, c, d, e, f]` from canvas pixels to the turned canvas with its top
+    /// left corner kept at zero, `(x, y)` goes to `(a x + c y + e, b x + d y + f)` as in CSS
+    fn matrix(&self, w: f64, h: f64) -> [f64; 6] {
+        let mirror = match self.mirrored {
+            true => [-1., 0., 0., 1., w, 0.],
+            false => [1., 0., 0., 1., 0., 0.],
+        };
+        let rotate = match self.quarter_turns {
+            1 => [0., 1., -1., 0., h, 0.],
+            2 => [-1., 0., 0., -1., w, h],
+            3 => [0., -1., 1., 0., 0., w],
+            _ => [1., 0., 0., 1., 0., 0.],
+        };
+        let [a0, b0, c0, d0, e0, f0] = mirror;
+        let [a1, b1, c1, d1, e1, f1] = rotate;
+        [
+            a1 * a0 + c1 * b0,
+            b1 * a0 + d1 * b0,
+            a1 * c0 + c1 * d0,
+            b1 * c0 + d1 * d0,
+            a1 * e0 + c1 * f0 + e1,
+            b1 * e0 + d1 * f0 + f1,
+        ]
+    }
+
+    /// Size of the turned canvas
+    fn bounds(&self, w: f64, h: f64) -> (f64, f64) {
+        match self.quarter_turns % 2 {
+            0 => (w, h),
+            _ => (h, w),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct Transform {
     origin_x: f64,
     origin_y: f64,
     scale: f64,
+    orientation: Orientation,
+    /// Canvas size in pixels, before turning
+    size: (f64, f64),
 }
 
 impl Transform {
+    /// Canvas pixel under a point of the page
+    fn to_canvas(&self, x: f64, y: f64) -> (f64, f64) {
+        let (w, h) = self.size;
+        let [a, b, c, d, e, f] = self.orientation.matrix(w, h);
+        let x = (x - self.origin_x) / self.scale - e;
+        let y = (y - self.origin_y) / self.scale - f;
+        let det = a * d - b * c;
+        ((d * x - c * y) / det, (a * y - b * x) / det)
+    }
+
+    fn css(&self) -> String {
+        let (w, h) = self.size;
+        let [a, b, c, d, e, f] = self.orientation.matrix(w, h).map(|v| v * self.scale);
+        format!(
+            "matrix({a}, {b}, {c}, {d}, {}, {})",
+            e + self.origin_x,
+            f + self.origin_y
+        )
+    }
+
+    /// Turn the board in place, keeping its center where it is on the page
+    fn orient(&mut self, orientation: Orientation) {
+        let (w, h) = self.size;
+        let (w0, h0) = self.orientation.bounds(w, h);
+        let (w1, h1) = orientation.bounds(w, h);
+        self.origin_x += (w0 - w1) / 2. * self.scale;
+        self.origin_y += (h0 - h1) / 2. * self.scale;
+        self.orientation = orientation;
+    }
+
     fn wheel(&mut self, scale_origin_x: f64, scale_origin_y: f64, wheel: f64) {
         self.scale(
             scale_origin_x,
@@ -61,6 +210,57 @@ impl Transform {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Layout {
+    cell_size: f64,
+    cell_gap: f64,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::medium()
+    }
+}
+
+impl Layout {
+    fn small() -> Self {
+        Self::with_cell_size(30.)
+    }
+
+    fn medium() -> Self {
+        Self::with_cell_size(50.)
+    }
+
+    fn large() -> Self {
+        Self::with_cell_size(70.)
+    }
+
+    /// Gap grows with cell size so that the grid density looks the same
+    fn with_cell_size(cell_size: f64) -> Self {
+        let cell_size = cell_size.clamp(MIN_CELL_SIZE, MAX_CELL_SIZE);
+        Self {
+            cell_size,
+            cell_gap: (cell_size / 25.).round().max(1.),
+        }
+    }
+
+    fn preset(&self) -> &'static str {
+        if *self == Self::small() {
+            "small"
+        } else if *self == Self::medium() {
+            "medium"
+        } else if *self == Self::large() {
+            "large"
+        } else {
+            "custom"
+        }
+    }
+
+    fn pitch(&self) -> f64 {
+        self.cell_size + self.cell_gap
+    }
+}
+
 fn clear(ctx: &CanvasRenderingContext2d, canvas: &HtmlElement<Canvas>) {
     ctx.save();
     ctx.set_fill_style(&"white".into());
@@ -68,22 +268,84 @@ fn clear(ctx: &CanvasRenderingContext2d, canvas: &HtmlElement<Canvas>) {
     ctx.restore();
 }
 
-fn map_pixel_size(view: &MaybeUninitGameView) -> (f64, f64) {
+fn map_pixel_size(layout: &Layout, view: &MaybeUninitGameView) -> (f64, f64) {
+    let (w, h) = view.topology().bounds(view.width(), view.height());
     (
-        (view.width() as f64 * (CELL_SIZE + CELL_GAP)) - CELL_GAP,
-        (view.height() as f64 * (CELL_SIZE + CELL_GAP)) - CELL_GAP,
+        w * layout.pitch() - layout.cell_gap,
+        h * layout.pitch() - layout.cell_gap,
     )
 }
 
-fn map_pixel_size_with_padding(view: &MaybeUninitGameView) -> (f64, f64) {
+fn map_pixel_size_with_padding(layout: &Layout, view: &MaybeUninitGameView) -> (f64, f64) {
+    let (w, h) = map_pixel_size(layout, view);
+    (w + PADDING * 2., h + PADDING * 2.)
+}
+
+/// Pixel bounds of the cell polygon given by the topology, without the gap
+fn cell_bounds(layout: &Layout, topology: Topology, x: usize, y: usize) -> (f64, f64, f64, f64) {
+    let polygon = topology.cell_polygon(x, y);
+    let (x0, y0, x1, y1) = polygon.iter().fold(
+        (
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ),
+        |(x0, y0, x1, y1), &(x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+    );
     (
-        (view.width() as f64 * (CELL_SIZE + CELL_GAP)) - CELL_GAP + PADDING * 2.,
-        (view.height() as f64 * (CELL_SIZE + CELL_GAP)) - CELL_GAP + PADDING * 2.,
+        x0 * layout.pitch() + PADDING,
+        y0 * layout.pitch() + PADDING,
+        (x1 - x0) * layout.pitch() - layout.cell_gap,
+        (y1 - y0) * layout.pitch() - layout.cell_gap,
     )
 }
 
-fn init_view(ctx: &CanvasRenderingContext2d, images: &Images, view: &MaybeUninitGameView) {
-    let (w_pixels, h_pixels) = map_pixel_size(view);
+/// Reduced drawing skips hover effects and draws square cells, for devices that cannot
+/// keep up with redraws
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Quality {
+    Full,
+    Reduced,
+}
+
+/// What is drawn over the cells, and how finely
+#[derive(Debug, Clone, Copy)]
+struct DrawOptions<'a> {
+    region: Option<Rect>,
+    hints: bool,
+    quality: Quality,
+    inspection: Option<&'a Inspection>,
+    /// Index of the solver component of each frontier cell, outlined when shown
+    components: Option<&'a HashMap<(usize, usize), usize>>,
+    motion: MotionSettings,
+}
+
+/// Outline colors of the solver components, repeated for more components
+const COMPONENT_COLORS: [&str; 8] = [
+    "#3b82f6", "#ec4899", "#14b8a6", "#a855f7", "#f97316", "#84cc16", "#06b6d4", "#eab308",
+];
+
+/// Component index of every frontier cell, for the components overlay
+fn component_map(view: &MaybeUninitGameView) -> HashMap<(usize, usize), usize> {
+    let MaybeUninitGameView::GameView(view) = view else {
+        return HashMap::new();
+    };
+    view.components()
+        .into_iter()
+        .enumerate()
+        .flat_map(|(i, cells)| cells.into_iter().map(move |cell| (cell, i)))
+        .collect()
+}
+
+fn init_view(
+    ctx: &CanvasRenderingContext2d,
+    images: &Images,
+    layout: &Layout,
+    view: &MaybeUninitGameView,
+    options: DrawOptions<'_>,
+) {
+    let (w_pixels, h_pixels) = map_pixel_size(layout, view);
     ctx.set_stroke_style(&"#777".into());
     ctx.set_line_width(2.);
     ctx.stroke_rect(
@@ -92,22 +354,101 @@ fn init_view(ctx: &CanvasRenderingContext2d, images: &Images, view: &MaybeUninit
         w_pixels + PADDING,
         h_pixels + PADDING,
     );
-    for (x, y) in RedrawCells::redraw_all(view.width(), view.height()).iter() {
-        redraw_cell(ctx, images, view.cell(*x, *y), *x, *y);
-    }
+    redraw_view(
+        ctx,
+        images,
+        layout,
+        view,
+        options,
+        &RedrawCells::redraw_all(),
+    );
 }
 
 fn redraw_view(
     ctx: &CanvasRenderingContext2d,
     images: &Images,
+    layout: &Layout,
     view: &MaybeUninitGameView,
+    options: DrawOptions<'_>,
     redraw: &RedrawCells,
 ) {
-    for (x, y) in redraw.iter() {
-        redraw_cell(ctx, images, view.cell(*x, *y), *x, *y);
+    let hinted: HashSet<_> = match options.hints {
+        true => view.known_safe().iter().copied().collect(),
+        false => HashSet::new(),
+    };
+    let reasons: HashSet<_> = options
+        .inspection
+        .and_then(|inspection| inspection.reasons.as_ref())
+        .into_iter()
+        .flatten()
+        .copied()
+        .collect();
+    for (x, y) in redraw.iter(view.width(), view.height()) {
+        redraw_cell(ctx, images, layout, view, x, y, options);
+        if hinted.contains(&(x, y)) && view.cell(x, y) == CellView::Unopened {
+            draw_hint(ctx, layout, view, x, y);
+        }
+        if let Some(inspection) = options.inspection.filter(|i| i.cell() == (x, y)) {
+            let color = match inspection.is_mine() {
+                true => "#ef4444",
+                false => "#22c55e",
+            };
+            draw_outline(ctx, layout, view, x, y, color);
+        } else if reasons.contains(&(x, y)) {
+            draw_outline(ctx, layout, view, x, y, "#f59e0b");
+        } else if let Some(i) = options.components.and_then(|c| c.get(&(x, y))) {
+            draw_outline(
+                ctx,
+                layout,
+                view,
+                x,
+                y,
+                COMPONENT_COLORS[i % COMPONENT_COLORS.len()],
+            );
+        }
     }
 }
 
+/// Thick border around a cell of the inspected move or of a solver component
+fn draw_outline(
+    ctx: &CanvasRenderingContext2d,
+    layout: &Layout,
+    view: &MaybeUninitGameView,
+    x: usize,
+    y: usize,
+    color: &str,
+) {
+    let (x, y, w, h) = cell_bounds(layout, view.topology(), x, y);
+    ctx.set_stroke_style(&color.into());
+    ctx.set_line_width(4.);
+    ctx.begin_path();
+    ctx.round_rect_with_f64(x + 2., y + 2., w - 4., h - 4., 3.)
+        .unwrap();
+    ctx.stroke();
+}
+
+/// Small dot on a cell proven safe, left for the player to open
+fn draw_hint(
+    ctx: &CanvasRenderingContext2d,
+    layout: &Layout,
+    view: &MaybeUninitGameView,
+    x: usize,
+    y: usize,
+) {
+    let (x, y, w, h) = cell_bounds(layout, view.topology(), x, y);
+    ctx.set_fill_style(&"#22c55e".into());
+    ctx.begin_path();
+    ctx.arc(
+        x + w / 2.,
+        y + h / 2.,
+        w.min(h) / 10.,
+        0.,
+        std::f64::consts::TAU,
+    )
+    .unwrap();
+    ctx.fill();
+}
+
 #[derive(Debug, Clone)]
 struct Images {
     numbers: Vec<HtmlImageElement>,
@@ -118,24 +459,72 @@ struct Images {
     explosion: HtmlImageElement,
 }
 
+impl Images {
+    fn new() -> Self {
+        let image = || HtmlImageElement::new().unwrap();
+        Images {
+            numbers: (0..9).map(|_| image()).collect(),
+            flag: image(),
+            question: image(),
+            mine: image(),
+            wrong_mine: image(),
+            explosion: image(),
+        }
+    }
+
+    fn by_name(&self) -> impl Iterator<Item = (&'static str, &HtmlImageElement)> {
+        SPRITE_NAMES.into_iter().zip(self.numbers.iter().chain([
+            &self.flag,
+            &self.question,
+            &self.mine,
+            &self.wrong_mine,
+            &self.explosion,
+        ]))
+    }
+
+    /// Point the images at the sprites of a pack, or at the built-in ones it leaves out,
+    /// resolves once every image is decoded
+    async fn load(&self, pack: Option<&SpritePack>) {
+        let mut decoding = vec![];
+        for (name, image) in self.by_name() {
+            match pack.and_then(|pack| pack.url(name)) {
+                Some(url) => image.set_src(url),
+                // an opened cell with no mine around is blank unless a pack draws it
+                None if name == "0" => {
+                    image.remove_attribute("src").unwrap();
+                    continue;
+                }
+                None => image.set_src(&format!("/public/{name}.svg")),
+            }
+            decoding.push(wasm_bindgen_futures::JsFuture::from(image.decode()));
+        }
+        futures::future::join_all(decoding).await;
+    }
+}
+
 fn redraw_cell(
     ctx: &CanvasRenderingContext2d,
     images: &Images,
-    cell: CellView,
+    layout: &Layout,
+    view: &MaybeUninitGameView,
     x: usize,
     y: usize,
+    options: DrawOptions<'_>,
 ) {
-    let x = x as f64 * (CELL_SIZE + CELL_GAP) + PADDING;
-    let y = y as f64 * (CELL_SIZE + CELL_GAP) + PADDING;
-    let w = CELL_SIZE;
-    let h = CELL_SIZE;
+    let selected = options.region.is_some_and(|rect| rect.contains(x, y));
+    let square = options.quality == Quality::Reduced;
+    let cell = match view.cell(x, y) {
+        CellView::Hovered if !options.motion.hover => CellView::Unopened,
+        CellView::Pushed if !options.motion.press => CellView::Unopened,
+        cell => cell,
+    };
+    let warning = view.is_flag_warning(x, y);
+    let highlight = view.is_highlighted(x, y);
+    let cursor = view.cursor_color(x, y);
+    let (x, y, w, h) = cell_bounds(layout, view.topology(), x, y);
+    let gap = layout.cell_gap;
     ctx.set_fill_style(&"white".into());
-    ctx.fill_rect(
-        x - CELL_GAP / 2.,
-        y - CELL_GAP / 2.,
-        w + CELL_GAP,
-        h + CELL_GAP,
-    );
+    ctx.fill_rect(x - gap / 2., y - gap / 2., w + gap, h + gap);
     match cell {
         CellView::Unopened | CellView::Hovered | CellView::Pushed => {
             match cell {
@@ -144,22 +533,21 @@ fn redraw_cell(
                 CellView::Pushed => ctx.set_fill_style(&"#e0e0e0".into()),
                 _ => unreachable!(),
             }
-            ctx.begin_path();
-            ctx.round_rect_with_f64(x, y, w, h, 3.).unwrap();
+            cell_path(ctx, x, y, w, h, square);
             ctx.fill();
         }
         _ => {
             match cell {
                 CellView::Flagged => ctx.set_fill_style(&"#f0f0f0".into()),
                 CellView::Questioned => ctx.set_fill_style(&"#f0f0f0".into()),
+                CellView::Opened(_) if warning => ctx.set_fill_style(&"#ffe4e4".into()),
                 CellView::Opened(_) => ctx.set_fill_style(&"white".into()),
                 CellView::Mine => ctx.set_fill_style(&"white".into()),
                 CellView::WrongMine => ctx.set_fill_style(&"white".into()),
                 CellView::Exploded => ctx.set_fill_style(&"white".into()),
                 _ => unreachable!(),
             }
-            ctx.begin_path();
-            ctx.round_rect_with_f64(x, y, w, h, 3.).unwrap();
+            cell_path(ctx, x, y, w, h, square);
             ctx.fill();
             let image = match cell {
                 CellView::Flagged => &images.flag,
@@ -174,86 +562,136 @@ fn redraw_cell(
                 .unwrap();
         }
     }
+    if highlight {
+        ctx.set_stroke_style(&"#0ea5e9".into());
+        ctx.set_line_width(2.);
+        ctx.begin_path();
+        ctx.round_rect_with_f64(x + 1., y + 1., w - 2., h - 2., 3.)
+            .unwrap();
+        ctx.stroke();
+    }
+    if selected {
+        ctx.set_stroke_style(&"#a855f7".into());
+        ctx.set_line_width(1.);
+        ctx.begin_path();
+        ctx.round_rect_with_f64(x + 0.5, y + 0.5, w - 1., h - 1., 3.)
+            .unwrap();
+        ctx.stroke();
+    }
+    if let Some(color) = cursor {
+        ctx.set_stroke_style(&color.into());
+        ctx.set_line_width(3.);
+        ctx.begin_path();
+        ctx.round_rect_with_f64(x + 1.5, y + 1.5, w - 3., h - 3., 3.)
+            .unwrap();
+        ctx.stroke();
+    }
+}
+
+fn cell_path(ctx: &CanvasRenderingContext2d, x: f64, y: f64, w: f64, h: f64, square: bool) {
+    ctx.begin_path();
+    match square {
+        true => ctx.rect(x, y, w, h),
+        false => ctx.round_rect_with_f64(x, y, w, h, 3.).unwrap(),
+    }
 }
 
 fn ray_cast(
     t: &Transform,
+    layout: &Layout,
     view: &MaybeUninitGameView,
     mouse_x: f64,
     mouse_y: f64,
 ) -> Option<(usize, usize)> {
-    let w = view.width();
-    let h = view.height();
-    let (w_pixels, h_pixels) = map_pixel_size(view);
-    let x = (mouse_x - t.origin_x) / t.scale - PADDING;
-    let y = (mouse_y - t.origin_y) / t.scale - PADDING;
-    let x = x + CELL_GAP / 2.;
-    let y = y + CELL_GAP / 2.;
-    // inside map && inside cell
-    if 0. <= x && x <= (w_pixels + CELL_GAP) && 0. <= y && y <= (h_pixels + CELL_GAP) {
-        Some((
-            (x / (CELL_SIZE + CELL_GAP))
-                .floor()
-                .clamp(0., (w - 1) as f64) as usize,
-            (y / (CELL_SIZE + CELL_GAP))
-                .floor()
-                .clamp(0., (h - 1) as f64) as usize,
-        ))
-    } else {
-        None
+    let gap = layout.cell_gap;
+    let (x, y) = t.to_canvas(mouse_x, mouse_y);
+    // cell units with the gap split evenly around each cell
+    let x = (x - PADDING + gap / 2.) / layout.pitch();
+    let y = (y - PADDING + gap / 2.) / layout.pitch();
+    view.topology()
+        .hit_test((x, y), view.width(), view.height())
+}
+
+/// Lighter help than automation, each toggled on its own
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Assists {
+    /// Flag the neighbours of numbers with only mines left around after every click
+    autoflag: bool,
+    /// Open the cells the solver proves safe after every click, in the background
+    auto_clear: bool,
+    /// Refuse chords that the solver proves would open a mine
+    safe_chord: bool,
+    /// Mark the cells the solver proves safe after every click, without opening them
+    hints: bool,
+    /// Tell what is known of the intact cell under the pointer
+    analysis: bool,
+}
+
+impl Assists {
+    /// Whether the worker solves the board after every click
+    fn solves_in_background(&self) -> bool {
+        self.hints || self.analysis
     }
 }
 
 #[component]
-fn Map(view: RwSignal<MaybeUninitGameView>, redraw: RwSignal<RedrawCells>) -> impl IntoView {
-    let images: Images = {
-        let mut numbers = Vec::new();
-        numbers.push(HtmlImageElement::new().unwrap());
-        for n in 1..9 {
-            let number = HtmlImageElement::new().unwrap();
-            number.set_src(&format!("/public/{n}.svg"));
-            numbers.push(number)
-        }
-        let flag = HtmlImageElement::new().unwrap();
-        flag.set_src("/public/flag.svg");
-        let question = HtmlImageElement::new().unwrap();
-        question.set_src("/public/question.svg");
-        let mine = HtmlImageElement::new().unwrap();
-        mine.set_src("/public/mine.svg");
-        let wrong_mine = HtmlImageElement::new().unwrap();
-        wrong_mine.set_src("/public/wrong_mine.svg");
-        let explosion = HtmlImageElement::new().unwrap();
-        explosion.set_src("/public/explosion.svg");
-        Images {
-            numbers,
-            flag,
-            question,
-            mine,
-            wrong_mine,
-            explosion,
+fn Map(
+    view: RwSignal<MaybeUninitGameView>,
+    redraw: RwSignal<RedrawCells>,
+    layout: RwSignal<Layout>,
+    orientation: RwSignal<Orientation>,
+    mouse: RwSignal<MouseSettings>,
+    motion: RwSignal<MotionSettings>,
+    sprites: RwSignal<Option<SpritePack>>,
+    assists: RwSignal<Assists>,
+    player_moved: Trigger,
+    region: RwSignal<Option<Rect>>,
+    inspection: RwSignal<Option<Inspection>>,
+    telemetry: RwSignal<Telemetry>,
+    show_components: RwSignal<bool>,
+) -> impl IntoView {
+    let images = Images::new();
+    create_effect({
+        let images = images.clone();
+        move |_| {
+            let pack = sprites();
+            let images = images.clone();
+            spawn_local(async move {
+                images.load(pack.as_ref()).await;
+                redraw.set(RedrawCells::redraw_all());
+            });
         }
-    };
+    });
 
     let canvas: NodeRef<Canvas> = create_node_ref();
+    let refused_flag_alert_ref: NodeRef<html::Custom> = create_node_ref();
+    let refused_chord_alert_ref: NodeRef<html::Custom> = create_node_ref();
+    let quality = store_value(Quality::Full);
+    // seconds a redraw takes, smoothed over the last few
+    let redraw_time = store_value(0.);
     let transform = create_rw_signal(Transform {
         origin_x: 0.,
         origin_y: 0.,
         scale: 1.,
+        orientation: Orientation::default(),
+        size: (0., 0.),
     });
 
     let UseWindowSizeReturn { width, height } = use_window_size();
     // initialize canvas and transform
     create_effect({
         let images = images.clone();
-        move |previous_map_size| {
+        move |previous_map_size_and_layout| {
             redraw.track();
+            let layout = layout();
             let map_size = view.with_untracked(|view| (view.width(), view.height()));
-            if previous_map_size == Some(map_size) {
-                return map_size;
+            if previous_map_size_and_layout == Some((map_size, layout)) {
+                return (map_size, layout);
             }
-            let begin = timestamp();
+            let _span = tracing::debug_span!("redraw").entered();
             let canvas = canvas().unwrap();
-            let (w_pixels, h_pixels) = view.with_untracked(map_pixel_size_with_padding);
+            let (w_pixels, h_pixels) =
+                view.with_untracked(|view| map_pixel_size_with_padding(&layout, view));
             canvas.set_width(w_pixels as u32);
             canvas.set_height(h_pixels as u32);
             let options = Object::new();
@@ -266,13 +704,26 @@ fn Map(view: RwSignal<MaybeUninitGameView>, redraw: RwSignal<RedrawCells>) -> im
                 .unwrap();
             clear(&ctx, &canvas);
             update!(|transform| {
-                transform.origin_x = (width.get_untracked() / 2. - w_pixels / 2.) * INITIAL_SCALE;
-                transform.origin_y = (height.get_untracked() / 2. - h_pixels / 2.) * INITIAL_SCALE;
+                transform.size = (w_pixels, h_pixels);
+                let (w_turned, h_turned) = transform.orientation.bounds(w_pixels, h_pixels);
+                transform.origin_x = (width.get_untracked() / 2. - w_turned / 2.) * INITIAL_SCALE;
+                transform.origin_y = (height.get_untracked() / 2. - h_turned / 2.) * INITIAL_SCALE;
                 transform.scale = INITIAL_SCALE;
             });
-            view.with_untracked(|view| init_view(&ctx, &images, view));
-            log!("init {:.3}s", timestamp() - begin);
-            map_size
+            let inspected = inspection.get_untracked();
+            let components = show_components
+                .get_untracked()
+                .then(|| view.with_untracked(component_map));
+            let options = DrawOptions {
+                region: region.get_untracked(),
+                hints: assists.get_untracked().hints,
+                quality: quality.get_value(),
+                inspection: inspected.as_ref(),
+                components: components.as_ref(),
+                motion: motion.get_untracked(),
+            };
+            view.with_untracked(|view| init_view(&ctx, &images, &layout, view, options));
+            (map_size, layout)
         }
     });
 
@@ -281,14 +732,16 @@ fn Map(view: RwSignal<MaybeUninitGameView>, redraw: RwSignal<RedrawCells>) -> im
         y: mouse_y,
         ..
     } = use_mouse();
-    let (mouse_down, set_mouse_down) = create_signal(None);
+    let (mouse_down, set_mouse_down) = create_signal(None::<ButtonAction>);
     let (hover, set_hover) = create_signal(None::<(usize, usize)>);
+    // first corner of the region being selected by dragging with shift held
+    let (select_from, set_select_from) = create_signal(None::<(usize, usize)>);
     let (offset_x, set_offset_x) = create_signal(None::<f64>);
     let (offset_y, set_offset_y) = create_signal(None::<f64>);
 
     // update transform according to mouse state
     create_effect(move |_| {
-        if mouse_down() != Some(0) || offset_x().is_none() || offset_y().is_none() {
+        if mouse_down().is_none() || offset_x().is_none() || offset_y().is_none() {
             return;
         }
         update!(|transform| {
@@ -299,34 +752,74 @@ fn Map(view: RwSignal<MaybeUninitGameView>, redraw: RwSignal<RedrawCells>) -> im
 
     // mouse event listener
     let _ = use_event_listener(document(), mouseup, move |_| {
+        if select_from().is_some() {
+            set_select_from(None);
+            return;
+        }
         match (mouse_down(), hover()) {
-            (Some(0), Some((x, y))) => {
+            (Some(ButtonAction::Open), Some((x, y))) => {
                 let mut next_redraw = Default::default();
                 update!(|view| next_redraw = view.left_click(x, y));
+                if assists.get_untracked().autoflag {
+                    update!(|view| next_redraw.merge(view.autoflag()));
+                }
                 redraw.set(next_redraw);
             }
-            (Some(1), Some((x, y))) => {
+            (Some(ButtonAction::Chord), Some((x, y)))
+                if assists.get_untracked().safe_chord && with!(|view| view.refuses_chord(x, y)) =>
+            {
+                alert_toast(refused_chord_alert_ref);
+            }
+            (Some(ButtonAction::Chord), Some((x, y))) => {
                 let mut next_redraw = Default::default();
                 update!(|view| next_redraw = view.middle_click(x, y));
+                if assists.get_untracked().autoflag {
+                    update!(|view| next_redraw.merge(view.autoflag()));
+                }
                 redraw.set(next_redraw);
             }
-            (Some(2), Some((x, y))) => {
+            (Some(ButtonAction::Flag), Some((x, y))) => {
+                if with!(|view| view.refuses_flag(x, y)) {
+                    alert_toast(refused_flag_alert_ref);
+                }
                 let mut next_redraw = Default::default();
                 update!(|view| next_redraw = view.right_click(x, y));
                 redraw.set(next_redraw);
             }
             _ => {}
         }
+        if mouse_down().is_some_and(|action| action != ButtonAction::Pan) && hover().is_some() {
+            let Assists {
+                hints,
+                safe_chord,
+                analysis,
+                ..
+            } = assists.get_untracked();
+            view.update_untracked(|view| {
+                if let MaybeUninitGameView::GameView(view) = view {
+                    view.report_assists(hints || analysis, safe_chord);
+                }
+            });
+            player_moved.notify();
+        }
         set_offset_x(None);
         set_offset_y(None);
         set_mouse_down(None);
     });
     let _ = use_event_listener(document(), mousemove, move |_| {
-        let ray_cast_result =
-            with!(|transform, view| ray_cast(transform, view, mouse_x(), mouse_y()));
+        let ray_cast_result = with!(|transform, layout, view| ray_cast(
+            transform,
+            layout,
+            view,
+            mouse_x(),
+            mouse_y()
+        ));
         if let Some((x, y)) = ray_cast_result {
             if hover() != Some((x, y)) {
                 set_hover(Some((x, y)));
+                if let Some(from) = select_from() {
+                    region.set(Some(Rect::from_corners(from, (x, y))));
+                }
             }
         } else if hover().is_some() {
             set_hover(None);
@@ -341,16 +834,20 @@ fn Map(view: RwSignal<MaybeUninitGameView>, redraw: RwSignal<RedrawCells>) -> im
             redraw.set(next_redraw);
         }
         (None, Some((x, y))) => {
+            let gesture = match quality.get_value() {
+                Quality::Full => Gesture::Hover(x, y),
+                Quality::Reduced => Gesture::None,
+            };
             let mut next_redraw = Default::default();
-            update!(|view| next_redraw = view.gesture(Gesture::Hover(x, y)));
+            update!(|view| next_redraw = view.gesture(gesture));
             redraw.set(next_redraw);
         }
-        (Some(0 | 2), Some((x, y))) => {
+        (Some(ButtonAction::Open | ButtonAction::Flag), Some((x, y))) => {
             let mut redraw_1 = Default::default();
             update!(|view| redraw_1 = view.gesture(Gesture::LeftOrRightPush(x, y)));
             redraw.set(redraw_1);
         }
-        (Some(1), Some((x, y))) => {
+        (Some(ButtonAction::Chord), Some((x, y))) => {
             let mut redraw_1 = Default::default();
             update!(|view| redraw_1 = view.gesture(Gesture::MidPush(x, y)));
             redraw.set(redraw_1);
@@ -359,26 +856,61 @@ fn Map(view: RwSignal<MaybeUninitGameView>, redraw: RwSignal<RedrawCells>) -> im
         _ => {}
     });
 
+    // outline the selected region, clearing the previous one
+    create_effect(move |previous: Option<Option<Rect>>| {
+        let current = region();
+        let mut next_redraw = RedrawCells::default();
+        for rect in [previous.flatten(), current].into_iter().flatten() {
+            next_redraw.merge(RedrawCells::region(rect.into()));
+        }
+        if !next_redraw.is_empty() {
+            redraw.set(next_redraw);
+        }
+        current
+    });
+
+    // draw everything again with the overlay or motion settings changed
+    create_effect(move |first| {
+        show_components.track();
+        motion.track();
+        if first.is_some() {
+            redraw.set(RedrawCells::redraw_all());
+        }
+    });
+
+    // outline the inspected move and its reasons, clearing the previous ones
+    create_effect(move |previous: Option<Option<Inspection>>| {
+        let current = inspection();
+        let mut next_redraw = RedrawCells::default();
+        for inspection in [previous.flatten(), current.clone()].iter().flatten() {
+            for cell in inspection.cells() {
+                next_redraw.insert(cell);
+            }
+        }
+        if !next_redraw.is_empty() {
+            redraw.set(next_redraw);
+        }
+        current
+    });
+
+    create_effect(move |_| {
+        let orientation = orientation();
+        if transform.with_untracked(|transform| transform.orientation != orientation) {
+            update!(|transform| transform.orient(orientation));
+        }
+    });
+
     // transform
     create_effect(move |_| {
         let canvas = canvas().unwrap();
-        let t = transform();
-        (*canvas)
-            .style()
-            .set_property(
-                "transform",
-                &format!(
-                    "translate({}px, {}px) scale({})",
-                    t.origin_x, t.origin_y, t.scale
-                ),
-            )
-            .unwrap();
+        let css = with!(|transform| transform.css());
+        (*canvas).style().set_property("transform", &css).unwrap();
     });
 
     // redraw
     create_effect(move |_| {
         with!(|redraw| if !redraw.is_empty() {
-            let begin = timestamp();
+            let _span = tracing::debug_span!("init").entered();
             let canvas = canvas().unwrap();
             let options = Object::new();
             Reflect::set(&options, &"alpha".into(), &JsValue::FALSE).unwrap();
@@ -388,8 +920,55 @@ fn Map(view: RwSignal<MaybeUninitGameView>, redraw: RwSignal<RedrawCells>) -> im
                 .unwrap()
                 .dyn_into::<CanvasRenderingContext2d>()
                 .unwrap();
-            view.with_untracked(|view| redraw_view(&ctx, &images, view, redraw));
-            log!("redraw {:.3}s", timestamp() - begin);
+            let layout = layout.get_untracked();
+            let inspected = inspection.get_untracked();
+            let components = show_components
+                .get_untracked()
+                .then(|| view.with_untracked(component_map));
+            // components merge and split with every move, their cells are all drawn again
+            let redraw = match &components {
+                Some(components) => {
+                    let mut redraw = redraw.clone();
+                    for cell in components.keys() {
+                        redraw.insert(*cell);
+                    }
+                    Cow::Owned(redraw)
+                }
+                None => Cow::Borrowed(redraw),
+            };
+            let mut options = DrawOptions {
+                region: region.get_untracked(),
+                hints: assists.get_untracked().hints,
+                quality: quality.get_value(),
+                inspection: inspected.as_ref(),
+                components: components.as_ref(),
+                motion: motion.get_untracked(),
+            };
+            let begin = timestamp();
+            view.with_untracked(|view| redraw_view(&ctx, &images, &layout, view, options, &redraw));
+            let elapsed = timestamp() - begin;
+            telemetry.update(|telemetry| telemetry.redraw.record(elapsed));
+            let average = redraw_time.get_value() * 0.8 + elapsed * 0.2;
+            redraw_time.set_value(average);
+            options.quality = match options.quality {
+                Quality::Full if average > FRAME_BUDGET => Quality::Reduced,
+                Quality::Reduced if average < FRAME_BUDGET / 2. => Quality::Full,
+                quality => quality,
+            };
+            if options.quality != quality.get_value() {
+                tracing::debug!(?options.quality, average, "drawing quality changed");
+                quality.set_value(options.quality);
+                view.with_untracked(|view| {
+                    redraw_view(
+                        &ctx,
+                        &images,
+                        &layout,
+                        view,
+                        options,
+                        &RedrawCells::redraw_all(),
+                    )
+                });
+            }
         });
     });
 
@@ -417,20 +996,47 @@ fn Map(view: RwSignal<MaybeUninitGameView>, redraw: RwSignal<RedrawCells>) -> im
             ev.prevent_default();
         } on:mousedown=move |ev| {
             let hover = hover();
-            if ev.button() == 0
-                && (hover.is_none()
-                    || with!(|view| view.is_draggable(hover.unwrap().0, hover.unwrap().1)))
-            {
+            let Some(action) = mouse.get_untracked().action(ev.button()) else {
+                return;
+            };
+            if action == ButtonAction::Open && ev.shift_key() {
+                if let Some(cell) = hover {
+                    set_select_from(Some(cell));
+                    region.set(Some(Rect::from_corners(cell, cell)));
+                }
+                return;
+            }
+            let pan = match action {
+                ButtonAction::Pan => true,
+                ButtonAction::Open => {
+                    hover.is_none()
+                        || with!(|view| view.is_draggable(hover.unwrap().0, hover.unwrap().1))
+                }
+                _ => false,
+            };
+            if pan {
+                // keep the browser from autoscrolling or selecting text while panning
+                ev.prevent_default();
                 with!(|transform| set_offset_x(Some(mouse_x() - transform.origin_x)));
                 with!(|transform| set_offset_y(Some(mouse_y() - transform.origin_y)));
             }
-            set_mouse_down(Some(ev.button()));
+            set_mouse_down(Some(action));
         } on:wheel=move |ev| {
             update!(|transform| transform.wheel(mouse_x(), mouse_y(), ev.delta_y()));
         }>
             <canvas on:contextmenu=move |ev| {
                 ev.prevent_default();
             } ref=canvas> "Canvas required." </canvas>
+            <CellTooltip view hover enabled=Signal::derive(move || with!(|assists| assists.analysis)) x=mouse_x y=mouse_y />
+            <ResumeOverlay view />
+            <sl-alert variant="warning" duration="2000" countdown="ltr" closable ref=refused_flag_alert_ref>
+                <sl-icon slot="icon" name="exclamation-triangle"></sl-icon>
+                "No mine there, the flag was refused by learning mode."
+            </sl-alert>
+            <sl-alert variant="warning" duration="2000" countdown="ltr" closable ref=refused_chord_alert_ref>
+                <sl-icon slot="icon" name="exclamation-triangle"></sl-icon>
+                "A flag around is wrong, the chord was refused by safe chord."
+            </sl-alert>
         </div>
     }
 }
@@ -458,14 +1064,104 @@ extern "C" {
     fn alert_toast_ffi(alert: &JsValue);
 }
 
+/// Milliseconds since epoch, timestamps the clicks kept in replays
+fn now() -> u64 {
+    Date::now() as u64
+}
+
 fn alert_toast(alert: NodeRef<html::Custom>) {
     alert_toast_ffi(&(alert.get_untracked().unwrap().into_any()));
 }
 
+#[wasm_bindgen(inline_js = "export function download_ffi(name, type, content) { \
+    const a = document.createElement('a'); \
+    a.href = URL.createObjectURL(new Blob([content], { type })); \
+    a.download = name; \
+    a.click(); \
+    setTimeout(() => URL.revokeObjectURL(a.href), 0); \
+}")]
+extern "C" {
+    fn download_ffi(name: &str, mime: &str, content: &str);
+}
+
+fn download(name: &str, mime: &str, content: &str) {
+    download_ffi(name, mime, content);
+}
+
+#[wasm_bindgen(inline_js = "export function pick_text_file_ffi() { \
+    return new Promise((resolve) => { \
+        const input = document.createElement('input'); \
+        input.type = 'file'; \
+        input.onchange = () => input.files[0] ? input.files[0].text().then(resolve) : resolve(null); \
+        input.click(); \
+    }); \
+}")]
+extern "C" {
+    fn pick_text_file_ffi() -> js_sys::Promise;
+}
+
+#[wasm_bindgen(inline_js = "export function dropped_file_ffi(ev) { \
+    const file = ev.dataTransfer && ev.dataTransfer.files[0]; \
+    return file ? file.arrayBuffer().then((buffer) => new Uint8Array(buffer)) : Promise.resolve(null); \
+}")]
+extern "C" {
+    fn dropped_file_ffi(ev: &JsValue) -> js_sys::Promise;
+}
+
+/// Bytes of the first file of a drop event
+async fn dropped_file(ev: web_sys::DragEvent) -> Option<Vec<u8>> {
+    let bytes = wasm_bindgen_futures::JsFuture::from(dropped_file_ffi(&ev))
+        .await
+        .ok()?;
+    (!bytes.is_null()).then(|| js_sys::Uint8Array::new(&bytes).to_vec())
+}
+
+#[wasm_bindgen(inline_js = "export function pasted_text_ffi(ev) { \
+    return ev.clipboardData ? ev.clipboardData.getData('text/plain') : ''; \
+}")]
+extern "C" {
+    fn pasted_text_ffi(ev: &JsValue) -> String;
+}
+
+/// Let the user pick a file and read it as text
+async fn pick_text_file() -> Option<String> {
+    wasm_bindgen_futures::JsFuture::from(pick_text_file_ffi())
+        .await
+        .ok()?
+        .as_string()
+}
+
 fn into_html_element_untracked(ref_: NodeRef<html::Custom>) -> web_sys::HtmlElement {
     (*ref_.get_untracked().unwrap().into_any()).clone()
 }
 
+fn drawer_is_open(drawer: NodeRef<html::Custom>) -> bool {
+    Reflect::get(&into_html_element_untracked(drawer), &"open".into())
+        .ok()
+        .and_then(|open| open.as_bool())
+        .unwrap_or(false)
+}
+
+/// Whether a key press or paste goes to a text field, which page wide handlers must leave alone
+fn is_typing(ev: &web_sys::Event) -> bool {
+    let Some(target) = ev.target() else {
+        return false;
+    };
+    let editable = Reflect::get(&target, &"isContentEditable".into())
+        .ok()
+        .and_then(|editable| editable.as_bool())
+        .unwrap_or(false);
+    let tag = Reflect::get(&target, &"tagName".into())
+        .ok()
+        .and_then(|tag| tag.as_string())
+        .unwrap_or_default();
+    editable
+        || matches!(
+            tag.as_str(),
+            "INPUT" | "TEXTAREA" | "SELECT" | "SL-INPUT" | "SL-TEXTAREA" | "SL-SELECT"
+        )
+}
+
 fn read_input_untracked(ref_: NodeRef<html::Custom>) -> Option<i64> {
     Reflect::get(&into_html_element_untracked(ref_), &"value".into())
         .ok()?
@@ -474,12 +1170,41 @@ fn read_input_untracked(ref_: NodeRef<html::Custom>) -> Option<i64> {
         .ok()
 }
 
+fn read_text_untracked(ref_: NodeRef<html::Custom>) -> String {
+    Reflect::get(&into_html_element_untracked(ref_), &"value".into())
+        .ok()
+        .and_then(|value| value.as_string())
+        .unwrap_or_default()
+}
+
+fn select_value(ev: &JsValue) -> String {
+    let target = Reflect::get(ev, &"target".into()).unwrap();
+    Reflect::get(&target, &"value".into())
+        .unwrap()
+        .as_string()
+        .unwrap_or_default()
+}
+
 #[component]
 fn Controls(
+    profile: Profile,
     view: RwSignal<MaybeUninitGameView>,
     redraw: RwSignal<RedrawCells>,
+    layout: RwSignal<Layout>,
+    orientation: RwSignal<Orientation>,
+    mouse: RwSignal<MouseSettings>,
+    motion: RwSignal<MotionSettings>,
+    sprites: RwSignal<Option<SpritePack>>,
+    pause_settings: RwSignal<PauseSettings>,
     new_game: WriteSignal<GameOptions>,
     restart: Trigger,
+    assists: RwSignal<Assists>,
+    player_moved: Trigger,
+    region: RwSignal<Option<Rect>>,
+    inspection: RwSignal<Option<Inspection>>,
+    telemetry: RwSignal<Telemetry>,
+    /// Debug overlay of the solver components
+    show_components: RwSignal<bool>,
 ) -> impl IntoView {
     let div_ref = create_node_ref();
     let UseMouseInElementReturn {
@@ -512,12 +1237,152 @@ fn Controls(
     });
     let seed_ref: NodeRef<html::Custom> = create_node_ref();
     let (difficulty, set_difficulty) = create_signal(Difficulty::Easy);
+    let (symmetry, set_symmetry) = create_signal(Symmetry::None);
+    let (learning, set_learning) = create_signal(false);
+    let (hidden_count, set_hidden_count) = create_signal(false);
     let width_ref: NodeRef<html::Custom> = create_node_ref();
     let height_ref: NodeRef<html::Custom> = create_node_ref();
     let mines_ref: NodeRef<html::Custom> = create_node_ref();
+    let density_ref: NodeRef<html::Custom> = create_node_ref();
     let new_game_drawer_ref: NodeRef<html::Custom> = create_node_ref();
     let invalid_config_alert_ref: NodeRef<html::Custom> = create_node_ref();
     let restart_dialog_ref: NodeRef<html::Custom> = create_node_ref();
+    let abandon_dialog_ref: NodeRef<html::Custom> = create_node_ref();
+    let history_drawer_ref: NodeRef<html::Custom> = create_node_ref();
+    let benchmark_drawer_ref: NodeRef<html::Custom> = create_node_ref();
+    let explorer_drawer_ref: NodeRef<html::Custom> = create_node_ref();
+    let room_drawer_ref: NodeRef<html::Custom> = create_node_ref();
+    let leaderboard_drawer_ref: NodeRef<html::Custom> = create_node_ref();
+    let profile_drawer_ref: NodeRef<html::Custom> = create_node_ref();
+    let read_difficulty = move || match difficulty() {
+        Difficulty::Custom { .. } => {
+            let width = read_input_untracked(width_ref)?;
+            let height = read_input_untracked(height_ref)?;
+            let mines = read_input_untracked(mines_ref)?;
+            if width <= 0 || height <= 0 || mines <= 0 || width * height <= mines {
+                return None;
+            }
+            Some(Difficulty::Custom {
+                width: width as usize,
+                height: height as usize,
+                mines: mines as usize,
+            })
+        }
+        Difficulty::Density { .. } => {
+            let width = read_input_untracked(width_ref)?;
+            let height = read_input_untracked(height_ref)?;
+            let percent: f64 = read_text_untracked(density_ref).trim().parse().ok()?;
+            if width <= 0 || height <= 0 {
+                return None;
+            }
+            let difficulty = Difficulty::Density {
+                width: width as usize,
+                height: height as usize,
+                density: percent / 100.,
+            };
+            difficulty.validate().ok().map(|()| difficulty)
+        }
+        difficulty => Some(difficulty),
+    };
+    let (config_error, set_config_error) = create_signal(String::new());
+    let read_difficulty = move || {
+        let checked = read_difficulty()
+            .ok_or_else(|| "Invalid configuration".to_string())
+            .and_then(|difficulty| {
+                BoardLimits::default()
+                    .check(&difficulty)
+                    .map(|()| difficulty)
+                    .map_err(|err| format!("Invalid configuration, {err}"))
+            });
+        match checked {
+            Ok(difficulty) => Some(difficulty),
+            Err(message) => {
+                set_config_error(message);
+                alert_toast(invalid_config_alert_ref);
+                None
+            }
+        }
+    };
+    let editor_alert_ref: NodeRef<html::Custom> = create_node_ref();
+    let (editor_message, set_editor_message) = create_signal(String::new());
+    let editor_toast = move |message: String| {
+        set_editor_message(message);
+        alert_toast(editor_alert_ref);
+    };
+    let UseClipboardReturn { copy, .. } = use_clipboard();
+    let stash = create_trigger();
+    let checkpoint = create_trigger();
+    // view waiting for the player to confirm abandoning the live game
+    let pending_view = store_value(None::<MaybeUninitGameView>);
+    let apply_view = move |next: MaybeUninitGameView| match next {
+        // everyone in the room gets the new board from the server
+        MaybeUninitGameView::Uninit { options, .. }
+            if with!(|view| matches!(view, MaybeUninitGameView::Room(_))) =>
+        {
+            update!(|view| if let MaybeUninitGameView::Room(room) = view {
+                room.send(ClientMessage::NewGame(options));
+            });
+        }
+        MaybeUninitGameView::Uninit { options, .. } => new_game(options),
+        next => {
+            view.set(next);
+            redraw.set(RedrawCells::redraw_all());
+        }
+    };
+    // the abandon dialog waits for the new game drawer to close, or the drawer would hand
+    // focus back to its trigger after the dialog took it
+    let abandon_after_hide = store_value(false);
+    let replace_view = move |next: MaybeUninitGameView| {
+        let drawer_open = drawer_is_open(new_game_drawer_ref);
+        drawer_hide(new_game_drawer_ref);
+        if view.with_untracked(|view| view.is_playing()) {
+            pending_view.set_value(Some(next));
+            if drawer_open {
+                abandon_after_hide.set_value(true);
+            } else {
+                drawer_show(abandon_dialog_ref);
+            }
+        } else {
+            apply_view(next);
+        }
+    };
+    let import_alert_ref: NodeRef<html::Custom> = create_node_ref();
+    let import_error = create_rw_signal(String::new());
+    // files dropped anywhere on the page, the board covers most of it
+    let _ = use_event_listener(document(), dragover, move |ev| ev.prevent_default());
+    let _ = use_event_listener(document(), ev::drop, move |ev| {
+        ev.prevent_default();
+        spawn_local(async move {
+            let Some(bytes) = dropped_file(ev).await else {
+                return;
+            };
+            match import(&bytes) {
+                Ok(next) => replace_view(next),
+                Err(err) => {
+                    import_error.set(err);
+                    alert_toast(import_alert_ref);
+                }
+            }
+        });
+    });
+    // boards pasted as text, from forums or bug reports, open in the editor
+    let _ = use_event_listener(document(), paste, move |ev| {
+        if is_typing(&ev) {
+            return;
+        }
+        let text = pasted_text_ffi(&ev);
+        if !is_board_text(&text) {
+            return;
+        }
+        ev.prevent_default();
+        match GameState::from_board_text(&text) {
+            Ok(state) => replace_view(MaybeUninitGameView::Editor(state)),
+            Err(err) => {
+                import_error.set(err.to_string());
+                alert_toast(import_alert_ref);
+            }
+        }
+    });
     let UseIntervalReturn {
         counter,
         reset,
@@ -531,12 +1396,16 @@ fn Controls(
         let pause = pause.clone();
         move |_| {
             with!(|view| match view {
-                MaybeUninitGameView::Uninit { .. } => {
+                MaybeUninitGameView::Uninit { .. }
+                | MaybeUninitGameView::Editor(_)
+                | MaybeUninitGameView::Tutorial(_)
+                | MaybeUninitGameView::Spectator(_)
+                | MaybeUninitGameView::Room(_) => {
                     reset();
                     pause();
                 }
                 MaybeUninitGameView::GameView(view) =>
-                    if view.result != GameResult::Playing {
+                    if view.result != GameResult::Playing || view.is_paused() {
                         pause();
                     } else if !is_active.get_untracked() {
                         resume()
@@ -549,67 +1418,585 @@ fn Controls(
         reset();
         pause();
     });
-    let (automation, set_automation) = create_signal(false);
-    let automation_switch_ref: NodeRef<html::Custom> = create_node_ref();
-    let automation_fail_ref: NodeRef<html::Custom> = create_node_ref();
-    let bridge = store_value(Automation::spawner().spawn("./automation-worker.js"));
-    let automation_result = create_resource(
-        move || (),
-        move |_| async move {
-            let view = view.get_untracked();
-            if !view.is_playing() {
-                return None;
+    // moves of the last automation step not applied yet, while inspecting
+    let (inspect, set_inspect) = create_signal(false);
+    let pending = store_value(VecDeque::<Action>::new());
+    let show_next_inspection = move || {
+        let next = view.with_untracked(|view| match view {
+            MaybeUninitGameView::GameView(view) => pending
+                .try_update_value(|queue| next_inspection(view, queue))
+                .flatten(),
+            _ => None,
+        });
+        inspection.set(next);
+    };
+    let clear_inspection = move || {
+        pending.set_value(VecDeque::new());
+        if inspection.with_untracked(Option::is_some) {
+            inspection.set(None);
+        }
+    };
+    let undo = move || {
+        clear_inspection();
+        let mut next_redraw = Default::default();
+        update!(|view| next_redraw = view.undo());
+        redraw.set(next_redraw);
+    };
+    let redo = move || {
+        clear_inspection();
+        let mut next_redraw = Default::default();
+        update!(|view| next_redraw = view.redo());
+        redraw.set(next_redraw);
+    };
+    let overlays = [
+        abandon_dialog_ref,
+        restart_dialog_ref,
+        new_game_drawer_ref,
+        history_drawer_ref,
+        benchmark_drawer_ref,
+        explorer_drawer_ref,
+        room_drawer_ref,
+        leaderboard_drawer_ref,
+        profile_drawer_ref,
+    ];
+    let _ = use_event_listener(document(), keydown, move |ev| {
+        if ev.ctrl_key() {
+            // inputs keep their own undo and redo
+            if is_typing(&ev) {
+                return;
             }
-            match view {
-                MaybeUninitGameView::Uninit { .. } => None,
-                MaybeUninitGameView::GameView(view) => {
-                    let mut bridge = with!(|bridge| bridge.fork());
-                    bridge.send(view).await.unwrap();
-                    bridge.next().await
+            match ev.key().as_str() {
+                "z" | "Z" => {
+                    ev.prevent_default();
+                    undo();
                 }
+                "y" | "Y" => {
+                    ev.prevent_default();
+                    redo();
+                }
+                _ => {}
             }
-        },
-    );
-    let automation_in_progress = automation_result.loading();
-    // redraw after automation step
+            return;
+        }
+        if ev.alt_key() || ev.meta_key() {
+            return;
+        }
+        // dialogs come first, they open on top of drawers
+        if let Some(open) = overlays
+            .into_iter()
+            .find(|overlay| drawer_is_open(*overlay))
+        {
+            if ev.key() == "Escape" {
+                ev.prevent_default();
+                drawer_hide(open);
+            }
+            return;
+        }
+        if is_typing(&ev) {
+            return;
+        }
+        match ev.key().as_str() {
+            "F2" => {
+                ev.prevent_default();
+                drawer_show(new_game_drawer_ref);
+            }
+            "r" | "R"
+                if view.with_untracked(|view| matches!(view, MaybeUninitGameView::GameView(_))) =>
+            {
+                ev.prevent_default();
+                drawer_show(restart_dialog_ref);
+            }
+            _ => {}
+        }
+    });
+    let (zen, set_zen) = create_signal(false);
+    let (smart_counter, set_smart_counter) = create_signal(false);
+    // solve once per board change while the smart counter is shown
     create_effect(move |_| {
-        if automation_in_progress() {
+        if !smart_counter() {
+            return;
+        }
+        let stale = with!(|view| matches!(
+            view,
+            MaybeUninitGameView::GameView(view)
+                if view.result == GameResult::Playing && view.known().is_none()
+        ));
+        if stale {
+            update!(|view| if let MaybeUninitGameView::GameView(view) = view {
+                view.update_known();
+            });
+        }
+    });
+    // quietly restart the same board after losing in zen mode
+    create_effect(move |_| {
+        if !zen() || !with!(|view| view.is_lost()) {
+            return;
+        }
+        set_timeout(
+            move || {
+                if zen.get_untracked() && view.with_untracked(|view| view.is_lost()) {
+                    restart.notify();
+                }
+            },
+            Duration::from_millis(1000),
+        );
+    });
+    let automation_switch_ref: NodeRef<html::Custom> = create_node_ref();
+    let automation_fail_ref: NodeRef<html::Custom> = create_node_ref();
+    let automation_crash_ref: NodeRef<html::Custom> = create_node_ref();
+    let crash_reason = create_rw_signal(String::new());
+    let fail_reason = create_rw_signal(AutomationError::NoMoves);
+    // step where the solver contradicted the board, kept for download
+    let diagnostic_bundle = create_rw_signal(None::<TraceBundle>);
+    let (solver, set_solver) = create_signal(Solver::Session);
+    let (policy, set_policy) = create_signal(AutomationPolicy::FlagsFirst);
+    let race_stats = create_rw_signal(None::<RaceStats>);
+    let last_tier = create_rw_signal(None::<SolveTier>);
+    // cells examined out of the whole solve, while a long one is in flight
+    let solve_progress = create_rw_signal(None::<(usize, usize)>);
+    health::on_progress(move |progress| solve_progress.set(progress));
+    let bridge = store_value(spawn_worker());
+    // id of the latest request, responses to older ones are stale
+    let request_id = store_value(0u64);
+    // spawned on first use by a board big enough
+    let pool = store_value(None::<Rc<WorkerPool>>);
+    // automation waiting for the player to stop clicking, run against the board by then
+    let deferred = create_rw_signal(None::<(bool, Moves)>);
+    // presses on the board, buttons such as Step leave automation alone
+    let pointer_held = store_value(false);
+    let last_input = store_value(0.);
+    window_event_listener(ev::pointerdown, move |ev| {
+        if ev
+            .target()
+            .is_some_and(|target| target.has_type::<HtmlCanvasElement>())
+        {
+            pointer_held.set_value(true);
+            last_input.set_value(timestamp());
+        }
+    });
+    window_event_listener(ev::pointerup, move |_| {
+        if pointer_held.get_value() {
+            pointer_held.set_value(false);
+            last_input.set_value(timestamp());
+        }
+    });
+    let player_busy = move || {
+        pointer_held.get_value() || timestamp() - last_input.get_value() < INTERACTION_QUIET
+    };
+    let cancel_automation = move || {
+        deferred.set(None);
+        request_id.update_value(|id| *id += 1);
+        let id = request_id.get_value();
+        with!(|bridge| bridge.send_input(AutomationRequest::Cancel { id }));
+        pool.with_value(|pool| {
+            if let Some(pool) = pool {
+                pool.cancel(id);
+            }
+        });
+    };
+    let automation_fail = move |duration: f64, err: AutomationError, moves: Moves| {
+        tracing::debug!(duration, %err, "automation failed");
+        // auto clear quietly waits for the next click
+        if moves == Moves::Safe {
             return;
         }
-        let Some(Some((duration, new_view, new_result))) = automation_result() else {
+        set_property(
+            &into_html_element_untracked(automation_switch_ref),
+            "checked",
+            &Some(JsValue::FALSE),
+        );
+        // running out of moves by winning or losing needs no alert
+        if err != AutomationError::GameOver {
+            fail_reason.set(err);
+            alert_toast(automation_fail_ref);
+        }
+    };
+    // actions count and digest of the board last known to the worker
+    let synced = store_value(None::<(usize, u64)>);
+    // apply a step solved on the board at `revision`, unless the board changed since or the
+    // player is busy with it, then the automation is deferred
+    let automation_success =
+        move |duration: f64, action: &Action, run: bool, moves: Moves, revision: u64| {
+            let fresh = with!(|view| match view {
+                MaybeUninitGameView::GameView(view) => view.revision() == revision,
+                _ => false,
+            });
+            if !fresh || player_busy() {
+                tracing::debug!(fresh, "automation step deferred");
+                // a worker board off by revisions alone is brought back by a full sync
+                if !fresh {
+                    synced.set_value(None);
+                }
+                deferred.set(Some((run, moves)));
+                return false;
+            }
+            tracing::debug!(duration, "automation step");
+            // the moves wait to be stepped through one by one
+            if inspect.get_untracked() && matches!(moves, Moves::All(_)) {
+                pending.set_value(queue_step(action));
+                show_next_inspection();
+                return true;
+            }
+            let mut next_redraw = Default::default();
+            update!(|view| next_redraw = view.apply(action));
+            redraw.set(next_redraw);
+            true
+        };
+    // replace the broken workers, the next request starts over with a full sync
+    let automation_crash = move |reason: String| {
+        tracing::error!(%reason, "automation worker crashed");
+        bridge.set_value(spawn_worker());
+        synced.set_value(None);
+        pool.set_value(None);
+        set_property(
+            &into_html_element_untracked(automation_switch_ref),
+            "checked",
+            &Some(JsValue::FALSE),
+        );
+        crash_reason.set(reason);
+        alert_toast(automation_crash_ref);
+    };
+    // solve components on the pool, the board stays on the main thread
+    let automate_on_pool = move |id: u64, mut shadow: GameView, run: bool, moves: Moves| {
+        let workers = pool
+            .with_value(Clone::clone)
+            .unwrap_or_else(|| Rc::new(WorkerPool::new()));
+        pool.set_value(Some(workers.clone()));
+        spawn_local(async move {
+            loop {
+                let sent = timestamp();
+                let (duration, result) = match workers.solve(id, &shadow).await {
+                    Ok(Some(solved)) => solved,
+                    Ok(None) => break,
+                    Err(reason) => {
+                        automation_crash(reason);
+                        break;
+                    }
+                };
+                let round_trip = timestamp() - sent;
+                telemetry.update(|telemetry| telemetry.record_step(round_trip, duration));
+                if id != request_id.get_value() {
+                    break;
+                }
+                if let Err(step) = shadow.verify_result(&result) {
+                    tracing::error!("solver contradicted the board");
+                    diagnostic_bundle.set(Some(TraceBundle {
+                        steps: vec![*step],
+                        ..Default::default()
+                    }));
+                    let err = AutomationError::Solver("a move contradicts the board".to_string());
+                    automation_fail(duration, err, moves);
+                    break;
+                }
+                let revision = shadow.revision();
+                if let Err(err) = moves.apply(&mut shadow, result) {
+                    automation_fail(duration, err, moves);
+                    break;
+                }
+                let action = shadow.actions().last().unwrap();
+                if !automation_success(duration, action, run, moves, revision) {
+                    break;
+                }
+                if !run || shadow.result != GameResult::Playing {
+                    break;
+                }
+            }
+        });
+    };
+    // ask the worker for one step, or for steps until stuck when `run`
+    let automate = move |run: bool, moves: Moves| {
+        let MaybeUninitGameView::GameView(mut shadow) = view.get_untracked() else {
             return;
         };
-        if let Some(new_result) = new_result {
-            log!("automation {duration:.3}s, success");
-            update!(move |view| *view = MaybeUninitGameView::GameView(new_view));
-            update!(move |redraw| *redraw = new_result);
-        } else {
-            log!("automation {duration:.3}s, fail");
-            set_property(
-                &into_html_element_untracked(automation_switch_ref),
-                "checked",
-                &Some(JsValue::FALSE),
+        // inspection holds one step at a time
+        let run = run && !(inspect.get_untracked() && matches!(moves, Moves::All(_)));
+        if shadow.result != GameResult::Playing {
+            return;
+        }
+        // a run may play out the rest of the game, keep where the player left it
+        if run && matches!(moves, Moves::All(_)) {
+            checkpoint.notify();
+        }
+        cancel_automation();
+        request_id.update_value(|id| *id += 1);
+        let id = request_id.get_value();
+        // the selected region limits full automation, the pool always solves everything
+        let region = region
+            .get_untracked()
+            .filter(|_| matches!(moves, Moves::All(_)));
+        let big = shadow.width() * shadow.height() >= POOL_MIN_CELLS;
+        if big && pool::cores() > 1 && region.is_none() {
+            automate_on_pool(id, shadow, run, moves);
+            return;
+        }
+        let solver = solver.get_untracked();
+        let sync = AutomationRequest::Sync {
+            id,
+            view: Box::new(shadow.clone()),
+            run,
+            solver,
+            moves,
+            region,
+        };
+        let request = match synced.get_value() {
+            Some((base, digest))
+                if base <= shadow.actions().len() && shadow.digest(base) == digest =>
+            {
+                AutomationRequest::Step {
+                    id,
+                    base,
+                    actions: shadow.actions()[base..].to_vec(),
+                    run,
+                    solver,
+                    moves,
+                    region,
+                }
+            }
+            _ => sync.clone(),
+        };
+        spawn_local(async move {
+            let (worker, mut bridge) = with!(|bridge| (bridge.fork(), bridge.fork()));
+            let mut sent = timestamp();
+            bridge.send(request).await.unwrap();
+            let mut sync = Some(sync);
+            loop {
+                let response = match health::next_response(&worker, &mut bridge).await {
+                    Ok(Some(response)) => response,
+                    Ok(None) => break,
+                    Err(reason) => {
+                        if id == request_id.get_value() {
+                            automation_crash(reason);
+                        }
+                        break;
+                    }
+                };
+                let (duration, revision, action) = match response {
+                    AutomationResponse::Desync { .. } => {
+                        let Some(sync) = sync.take() else {
+                            break;
+                        };
+                        sent = timestamp();
+                        bridge.send(sync).await.unwrap();
+                        continue;
+                    }
+                    AutomationResponse::Step {
+                        id: response_id,
+                        revision,
+                        duration,
+                        action,
+                        race,
+                        tier,
+                        diagnostic,
+                    } if response_id == request_id.get_value() => {
+                        if race.is_some() {
+                            race_stats.set(race);
+                        }
+                        if let Some(bundle) = diagnostic {
+                            diagnostic_bundle.set(Some(*bundle));
+                        }
+                        if tier.is_some() {
+                            last_tier.set(tier);
+                        }
+                        let now = timestamp();
+                        telemetry.update(|telemetry| telemetry.record_step(now - sent, duration));
+                        sent = now;
+                        (duration, revision, action)
+                    }
+                    _ => break,
+                };
+                // the worker board already includes the action
+                if let Ok(action) = &action {
+                    shadow.apply(action);
+                }
+                let len = shadow.actions().len();
+                synced.set_value(Some((len, shadow.digest(len))));
+                let action = match action {
+                    Ok(action) => action,
+                    Err(err) => {
+                        automation_fail(duration, err, moves);
+                        break;
+                    }
+                };
+                if !automation_success(duration, &action, run, moves, revision) {
+                    break;
+                }
+                if !run || shadow.result != GameResult::Playing {
+                    break;
+                }
+            }
+        });
+    };
+    // run the deferred automation once the player has been quiet for a moment
+    let retry_deferred = create_trigger();
+    let retry_scheduled = store_value(false);
+    create_effect(move |_| {
+        retry_deferred.track();
+        let Some((run, moves)) = deferred() else {
+            return;
+        };
+        if !player_busy() {
+            automate(run, moves);
+        } else if !retry_scheduled.get_value() {
+            retry_scheduled.set_value(true);
+            set_timeout(
+                move || {
+                    retry_scheduled.set_value(false);
+                    retry_deferred.notify();
+                },
+                Duration::from_secs_f64(INTERACTION_QUIET),
             );
-            alert_toast(automation_fail_ref);
         }
     });
-    // chain automation step
+    // steps of the worker along with the ones solved on the pool and applied here
+    let download_trace = move || {
+        spawn_local(async move {
+            let (worker, mut bridge) = with!(|bridge| (bridge.fork(), bridge.fork()));
+            bridge.send(AutomationRequest::Trace).await.unwrap();
+            let mut bundle = match health::next_response(&worker, &mut bridge).await {
+                Ok(Some(AutomationResponse::Traced { bundle })) => bundle,
+                _ => TraceBundle::default(),
+            };
+            bundle.merge(TraceBundle::take());
+            download(
+                "trace.json",
+                "application/json",
+                &serde_json::to_string(&bundle).unwrap(),
+            );
+        });
+    };
+    // abort the solve in flight once its board is gone, and drop what the worker learnt about it
+    let reset_automation = move || {
+        cancel_automation();
+        clear_inspection();
+        with!(|bridge| bridge.send_input(AutomationRequest::Reset));
+    };
     create_effect(move |_| {
-        if automation_in_progress()
-            || !view.with_untracked(|view| view.is_playing())
-            || !automation()
-        {
+        restart.track();
+        reset_automation();
+    });
+    let playing = create_memo(move |_| with!(|view| view.is_playing()));
+    create_effect(move |_| {
+        if !playing() {
+            reset_automation();
+        }
+    });
+    // clear what the last click made safe, unless full automation is running already
+    create_effect(move |_| {
+        player_moved.track();
+        let automating = Reflect::get(
+            &into_html_element_untracked(automation_switch_ref),
+            &"checked".into(),
+        )
+        .ok()
+        .and_then(|checked| checked.as_bool())
+        .unwrap_or(false);
+        if assists.get_untracked().auto_clear && !automating {
+            deferred.set(Some((true, Moves::Safe)));
+        }
+    });
+    // board generation hints were last asked for, answers for older boards are dropped
+    let hint_generation = store_value(0u64);
+    let request_hints = move |generation: u64| {
+        let MaybeUninitGameView::GameView(shadow) = view.get_untracked() else {
             return;
+        };
+        if shadow.result != GameResult::Playing || shadow.known().is_some() {
+            return;
+        }
+        let cells = shadow.components().into_iter().flatten().collect();
+        // automation and restarts cancel up to the next request id, hints included
+        let id = request_id.get_value() + 1;
+        spawn_local(async move {
+            let (worker, mut bridge) = with!(|bridge| (bridge.fork(), bridge.fork()));
+            let request = AutomationRequest::Solve {
+                id,
+                view: Box::new(shadow),
+                cells,
+            };
+            bridge.send(request).await.unwrap();
+            let Ok(Some(AutomationResponse::Solved {
+                revision, result, ..
+            })) = health::next_response(&worker, &mut bridge).await
+            else {
+                return;
+            };
+            if generation != hint_generation.get_value() {
+                return;
+            }
+            let mut next_redraw = Default::default();
+            update!(|view| if let MaybeUninitGameView::GameView(view) = view {
+                if view.revision() == revision {
+                    next_redraw = view.set_known(result);
+                }
+            });
+            redraw.set(next_redraw);
+        });
+    };
+    // ask for hints once the player stops clicking for a moment
+    let debounce_hints = move || {
+        hint_generation.update_value(|generation| *generation += 1);
+        let generation = hint_generation.get_value();
+        set_timeout(
+            move || {
+                if generation == hint_generation.get_value()
+                    && assists.get_untracked().solves_in_background()
+                {
+                    request_hints(generation);
+                }
+            },
+            HINT_DEBOUNCE,
+        );
+    };
+    create_effect(move |_| {
+        player_moved.track();
+        if assists.get_untracked().solves_in_background() {
+            debounce_hints();
+        }
+    });
+    // queued moves were proven for the board before the player changed it
+    create_effect(move |_| {
+        player_moved.track();
+        clear_inspection();
+    });
+    let step = move || {
+        if with!(|view| matches!(view, MaybeUninitGameView::Tutorial(_))) {
+            let mut next_redraw = Default::default();
+            update!(|view| next_redraw = view.tutorial_step());
+            redraw.set(next_redraw);
+        } else {
+            automate(false, Moves::All(policy.get_untracked()));
         }
-        let Some(Some((_, _, Some(_)))) = automation_result() else {
+    };
+    // apply the inspected move and show the next one, or ask for the next step
+    let advance_inspection = move || {
+        let Some(current) = inspection.get_untracked() else {
+            step();
             return;
         };
-        automation_result.refetch();
+        let mut next_redraw = Default::default();
+        update!(|view| next_redraw = view.apply(&Action::Automation(vec![current.action])));
+        redraw.set(next_redraw);
+        show_next_inspection();
+    };
+    let _ = use_event_listener(document(), keydown, move |ev| {
+        if ev.key() != " " || !inspect.get_untracked() {
+            return;
+        }
+        // space keeps activating the focused control
+        let on_body = ev.target().map(JsValue::from) == document().body().map(JsValue::from);
+        if !on_body || overlays.into_iter().any(drawer_is_open) {
+            return;
+        }
+        ev.prevent_default();
+        advance_inspection();
     });
     let (class_name, style_val) = style_str! {
         .non-draggable {
             cursor: auto;
         }
+        .warning {
+            color: #d33;
+        }
         #controls {
             display: flex;
             flex-direction: column;
@@ -627,6 +2014,10 @@ fn Controls(
             cursor: move;
             user-select: none;
         }
+        #tutorial-message {
+            max-width: 20rem;
+            text-align: center;
+        }
         #controls > h1 {
             font-size: 1.25rem;
             line-height: 1.75rem;
@@ -640,12 +2031,36 @@ fn Controls(
             margin-right: 30vw;
         }
         #automation,
+        #history,
+        #editor,
         #new-game-or-restart {
             display: flex;
             flex-direction: row;
             align-items: center;
             gap: 1rem;
         }
+        #solver,
+        #layout {
+            display: flex;
+            flex-direction: column;
+            align-items: center;
+            gap: 0.5rem;
+        }
+        #layout > sl-range {
+            width: 100%;
+        }
+        #mouse {
+            display: flex;
+            flex-direction: column;
+            align-items: center;
+            gap: 0.5rem;
+        }
+        #orientation {
+            display: flex;
+            flex-direction: row;
+            align-items: center;
+            gap: 1rem;
+        }
         #custom-difficulty-options {
             display: flex;
             flex-direction: row;
@@ -666,45 +2081,306 @@ fn Controls(
         }>
             <h1>"Minesweep Automated"</h1>
             { move || with!(|view| match view {
+                _ if zen() => view! {
+                    <p> "Zen 🍵" </p>
+                }.into_view(),
                 MaybeUninitGameView::Uninit { options, .. } => view! {
                     <p> "Tap to start" </p>
-                    <p> { format!("Mines: 0/{}", options.difficulty.mines()) } </p>
+                    <p> { mine_counter(0, (!options.hidden_count).then(|| options.difficulty.mines())) } </p>
                     <p> "Time: 00:00" </p>
-                },
+                }.into_view(),
                 MaybeUninitGameView::GameView(view) => view! {
                     <p> { match view.result {
                         GameResult::Playing => "Playing 😊",
                         GameResult::Win => "Win 😎",
                         GameResult::Lose => "Lose 😵",
+                    } } { if view.assistance().is_pure() { "" } else { " (assisted)" } }
+                    { if view.options().learning { " (learning)" } else { "" } } </p>
+                    <p class:warning=view.is_over_flagged()> { match view.effective_remaining_mines() {
+                        Some(left) if smart_counter() => format!("Mines left: {left}"),
+                        _ => mine_counter(view.flags, view.mines()),
                     } } </p>
-                    <p> { format!("Mines: {}/{}", view.flags, view.mines) } </p>
                     <p> { move || with!(|counter| format!("Time: {:02}:{:02}", counter / 60, counter % 60)) } </p>
-                },
+                }.into_view(),
+                MaybeUninitGameView::Editor(state) => view! {
+                    <p> "Editing ✏️" </p>
+                    <p> { format!("Mines: {}", state.mines()) } </p>
+                }.into_view(),
+                MaybeUninitGameView::Tutorial(tutorial) => view! {
+                    <p> "Tutorial 🎓" </p>
+                    <p id="tutorial-message"> { tutorial.message() } </p>
+                }.into_view(),
+                MaybeUninitGameView::Spectator(spectator) => view! {
+                    <p> "Spectating 👀" </p>
+                    <p> { mine_counter(spectator.view.flags, spectator.view.mines()) } </p>
+                    <p> { format!("Move: {}/{}", spectator.position, spectator.len()) } </p>
+                }.into_view(),
+                MaybeUninitGameView::Room(room) => view! {
+                    <p> { format!("Room {} 🤝", room.name) } </p>
+                    <p> { match room.view.as_ref().map(|view| view.result) {
+                        None => "Tap to start",
+                        Some(GameResult::Playing) => "Playing 😊",
+                        Some(GameResult::Win) => "Win 😎",
+                        Some(GameResult::Lose) => "Lose 😵",
+                    } } </p>
+                    <p> { room.view.as_ref().map_or_else(
+                        || mine_counter(0, (!room.options.hidden_count).then(|| room.options.difficulty.mines())),
+                        |view| mine_counter(view.flags, view.mines()),
+                    ) } </p>
+                    <p> { format!("Players: {}", room.players.values().map(|player| player.name.as_str()).collect::<Vec<_>>().join(", ")) } </p>
+                }.into_view(),
             }) } <br />
             <div id="automation" class="non-draggable" on:mousedown=move |ev| ev.stop_propagation()>
                 <sl-switch disabled={
-                    move || with!(|view| matches!(view, MaybeUninitGameView::Uninit { .. }))
+                    move || with!(|view| !matches!(view, MaybeUninitGameView::GameView(_)))
                 } on:sl-change=move |ev: JsValue| {
                     let target = Reflect::get(&ev, &"target".into()).unwrap();
                     let checked = Reflect::get(&target, &"checked".into()).unwrap().as_bool().unwrap();
-                    set_automation(checked);
                     if checked {
-                        automation_result.refetch()
+                        automate(true, Moves::All(policy.get_untracked()));
+                    } else {
+                        cancel_automation();
                     }
                 } ref=automation_switch_ref> "Automation" </sl-switch>
+                <sl-switch on:sl-change=move |ev: JsValue| {
+                    let target = Reflect::get(&ev, &"target".into()).unwrap();
+                    let checked = Reflect::get(&target, &"checked".into()).unwrap().as_bool().unwrap();
+                    set_zen(checked);
+                }> "Zen" </sl-switch>
+                <sl-switch on:sl-change=move |ev: JsValue| {
+                    let target = Reflect::get(&ev, &"target".into()).unwrap();
+                    let checked = Reflect::get(&target, &"checked".into()).unwrap().as_bool().unwrap();
+                    set_smart_counter(checked);
+                }> "Smart Counter" </sl-switch>
+                <sl-switch on:sl-change=move |ev: JsValue| {
+                    let target = Reflect::get(&ev, &"target".into()).unwrap();
+                    let checked = Reflect::get(&target, &"checked".into()).unwrap().as_bool().unwrap();
+                    update!(|assists| assists.autoflag = checked);
+                }> "Auto Flag" </sl-switch>
+                <sl-switch on:sl-change=move |ev: JsValue| {
+                    let target = Reflect::get(&ev, &"target".into()).unwrap();
+                    let checked = Reflect::get(&target, &"checked".into()).unwrap().as_bool().unwrap();
+                    update!(|assists| assists.auto_clear = checked);
+                }> "Auto Clear" </sl-switch>
+                <sl-switch on:sl-change=move |ev: JsValue| {
+                    let target = Reflect::get(&ev, &"target".into()).unwrap();
+                    let checked = Reflect::get(&target, &"checked".into()).unwrap().as_bool().unwrap();
+                    update!(|assists| assists.safe_chord = checked);
+                }> "Safe Chord" </sl-switch>
+                <sl-switch on:sl-change=move |ev: JsValue| {
+                    let target = Reflect::get(&ev, &"target".into()).unwrap();
+                    let checked = Reflect::get(&target, &"checked".into()).unwrap().as_bool().unwrap();
+                    update!(|assists| assists.hints = checked);
+                    redraw.set(RedrawCells::redraw_all());
+                    if checked {
+                        debounce_hints();
+                    }
+                }> "Safe Hints" </sl-switch>
+                <sl-switch on:sl-change=move |ev: JsValue| {
+                    let target = Reflect::get(&ev, &"target".into()).unwrap();
+                    let checked = Reflect::get(&target, &"checked".into()).unwrap().as_bool().unwrap();
+                    update!(|assists| assists.analysis = checked);
+                    if checked {
+                        debounce_hints();
+                    }
+                }> "Cell Analysis" </sl-switch>
+                <sl-switch on:sl-change=move |ev: JsValue| {
+                    let target = Reflect::get(&ev, &"target".into()).unwrap();
+                    let checked = Reflect::get(&target, &"checked".into()).unwrap().as_bool().unwrap();
+                    set_inspect(checked);
+                    if !checked {
+                        clear_inspection();
+                    }
+                }> "Inspect" </sl-switch>
+                { move || inspect().then(|| view! {
+                    <p> { move || match inspection() {
+                        Some(inspection) => format!("Next: {}", inspection.describe()),
+                        None => "Press Space for the next step".to_string(),
+                    } } </p>
+                }) }
                 <sl-button disabled={
-                    move || with!(|view| matches!(view, MaybeUninitGameView::Uninit { .. }))
-                } on:click=move |_| automation_result.refetch()> "Step" </sl-button>
+                    move || with!(|view| match view {
+                        MaybeUninitGameView::GameView(_) => false,
+                        MaybeUninitGameView::Tutorial(tutorial) => !tutorial.expects_automation(),
+                        _ => true,
+                    })
+                } on:click=move |_| step()> "Step" </sl-button>
+                <ExplainStep view />
+                { move || region().map(|rect| view! {
+                    <p> { format!("Region: {}×{} at {},{}", rect.width, rect.height, rect.x, rect.y) } </p>
+                    <sl-button size="small" on:click=move |_| region.set(None)> "Clear Region" </sl-button>
+                }) }
+            </div>
+            <div id="solver" class="non-draggable" on:mousedown=move |ev| ev.stop_propagation()>
+                <sl-select label="Solver" size="small" value="session" on:sl-change=move |ev: JsValue| set_solver(match select_value(&ev).as_str() {
+                    "plain" => Solver::Plain,
+                    "race" => Solver::Race,
+                    "live" => Solver::Tiered { budget: LIVE_ASSIST_BUDGET },
+                    _ => Solver::Session,
+                })>
+                    <sl-option value="session"> "Session" </sl-option>
+                    <sl-option value="plain"> "Plain" </sl-option>
+                    <sl-option value="race"> "Race" </sl-option>
+                    <sl-option value="live"> "Live Assist" </sl-option>
+                </sl-select>
+                <sl-select label="Policy" size="small" value="flags-first" on:sl-change=move |ev: JsValue| set_policy(match select_value(&ev).as_str() {
+                    "opens-first" => AutomationPolicy::OpensFirst,
+                    "no-flag" => AutomationPolicy::NoFlag,
+                    _ => AutomationPolicy::FlagsFirst,
+                })>
+                    <sl-option value="flags-first"> "Flags First" </sl-option>
+                    <sl-option value="opens-first"> "Opens First" </sl-option>
+                    <sl-option value="no-flag"> "No Flag" </sl-option>
+                </sl-select>
+                { move || race_stats().filter(|_| solver() == Solver::Race).map(|RaceStats { plain, session }| view! {
+                    <p> { format!("Plain: {} wins, {:.1}ms", plain.wins, plain.mean() * 1000.) } </p>
+                    <p> { format!("Session: {} wins, {:.1}ms", session.wins, session.mean() * 1000.) } </p>
+                }) }
+                { move || last_tier().filter(|_| matches!(solver(), Solver::Tiered { .. })).map(|tier| view! {
+                    <p> { format!("Last step by: {}", match tier {
+                        SolveTier::Rules => "rules",
+                        SolveTier::Components => "small components",
+                        SolveTier::Full => "full frontier",
+                    }) } </p>
+                }) }
+                <sl-switch prop:checked=show_components on:sl-change=move |ev: JsValue| {
+                    let target = Reflect::get(&ev, &"target".into()).unwrap();
+                    let checked = Reflect::get(&target, &"checked".into()).unwrap().as_bool().unwrap();
+                    show_components.set(checked);
+                }> "Show Components" </sl-switch>
+                { move || show_components().then(|| with!(|view| match view {
+                    MaybeUninitGameView::GameView(view) => {
+                        let components = view.components();
+                        let largest = components.iter().map(Vec::len).max().unwrap_or(0);
+                        format!("Components: {}, largest {largest} cells", components.len())
+                    }
+                    _ => "Components: -".to_string(),
+                })).map(|text| view! { <p> { text } </p> }) }
+                { move || solve_progress().map(|(examined, total)| view! {
+                    <p> { format!("Solving: {examined} of {total} cells") } </p>
+                }) }
+                { cfg!(feature = "trace").then(|| view! {
+                    <sl-button size="small" on:click=move |_| download_trace()> "Download Trace" </sl-button>
+                }) }
+            </div>
+            <div id="history" class="non-draggable" on:mousedown=move |ev| ev.stop_propagation()>
+                <sl-button disabled={ move || with!(|view| !view.can_undo()) } on:click=move |_| undo()> "Undo" </sl-button>
+                <sl-button disabled={ move || with!(|view| !view.can_redo()) } on:click=move |_| redo()> "Redo" </sl-button>
+            </div>
+            <div id="layout" class="non-draggable" on:mousedown=move |ev| ev.stop_propagation()>
+                <sl-radio-group label="Cell Size" name="cell-size" prop:value=move || with!(|layout| layout.preset())>
+                    <sl-radio-button value="small" on:click=move |_| layout.set(Layout::small())> "Small" </sl-radio-button>
+                    <sl-radio-button value="medium" on:click=move |_| layout.set(Layout::medium())> "Medium" </sl-radio-button>
+                    <sl-radio-button value="large" on:click=move |_| layout.set(Layout::large())> "Large" </sl-radio-button>
+                </sl-radio-group>
+                <sl-range min=MIN_CELL_SIZE max=MAX_CELL_SIZE step="1" prop:value=move || with!(|layout| layout.cell_size) on:sl-change=move |ev: JsValue| {
+                    let target = Reflect::get(&ev, &"target".into()).unwrap();
+                    let cell_size = Reflect::get(&target, &"value".into()).unwrap().as_f64().unwrap();
+                    layout.set(Layout::with_cell_size(cell_size));
+                }></sl-range>
+                <div id="orientation">
+                    <sl-button size="small" on:click=move |_| orientation.update(|o| *o = o.rotate())>
+                        <sl-icon slot="prefix" name="arrow-clockwise"></sl-icon>
+                        "Rotate"
+                    </sl-button>
+                    <sl-switch prop:checked=move || with!(|orientation| orientation.mirrored) on:sl-change=move |ev: JsValue| {
+                        let target = Reflect::get(&ev, &"target".into()).unwrap();
+                        let checked = Reflect::get(&target, &"checked".into()).unwrap().as_bool().unwrap();
+                        orientation.update(|o| o.mirrored = checked);
+                    }> "Mirror" </sl-switch>
+                </div>
+            </div>
+            <div id="mouse" class="non-draggable" on:mousedown=move |ev| ev.stop_propagation()>
+                <MouseControls settings=mouse />
+                <MotionControls settings=motion />
+                <SpriteControls pack=sprites />
+                <PauseControls settings=pause_settings />
             </div>
             <sl-alert variant="danger" duration="2000" countdown="ltr" closable ref=automation_fail_ref>
                 <sl-icon slot="icon" name="exclamation-octagon"></sl-icon>
-                "No possible move found"
+                { move || match fail_reason() {
+                    AutomationError::NoMoves => "No possible move found".to_string(),
+                    AutomationError::BudgetExceeded => "Solver ran out of time".to_string(),
+                    err => format!("Automation stopped: {err}"),
+                } }
+                { move || diagnostic_bundle().filter(|_| matches!(fail_reason(), AutomationError::Solver(_))).map(|bundle| view! {
+                    <sl-button size="small" on:click=move |_| download(
+                        "diagnostic.json",
+                        "application/json",
+                        &serde_json::to_string(&bundle).unwrap(),
+                    )> "Download Diagnostic" </sl-button>
+                }) }
+            </sl-alert>
+            <sl-alert variant="danger" duration="4000" countdown="ltr" closable ref=automation_crash_ref>
+                <sl-icon slot="icon" name="exclamation-octagon"></sl-icon>
+                "Automation worker crashed and was restarted: " { crash_reason }
+            </sl-alert>
+            <sl-alert variant="danger" duration="4000" countdown="ltr" closable ref=import_alert_ref>
+                <sl-icon slot="icon" name="exclamation-octagon"></sl-icon>
+                "Could not import: " { import_error }
+            </sl-alert>
+            <div id="editor" class="non-draggable" style:display=move || {
+                if with!(|view| matches!(view, MaybeUninitGameView::Editor(_))) { "flex" } else { "none" }
+            } on:mousedown=move |ev| ev.stop_propagation()>
+                <sl-button on:click=move |_| {
+                    let Some(solvable) = with!(|view| match view {
+                        MaybeUninitGameView::Editor(state) => Some(state.validate().map(|_| state.test_solve())),
+                        _ => None,
+                    }) else {
+                        return;
+                    };
+                    editor_toast(match solvable {
+                        Ok(true) => "Solvable without guessing".to_string(),
+                        Ok(false) => "Guessing required".to_string(),
+                        Err(err) => format!("Invalid board: {err}"),
+                    });
+                }> "Test Solve" </sl-button>
+                <sl-button on:click=move |_| {
+                    let Some(text) = with!(|view| match view {
+                        MaybeUninitGameView::Editor(state) => Some(state.to_board_text()),
+                        _ => None,
+                    }) else {
+                        return;
+                    };
+                    copy(&text);
+                    editor_toast("Board copied to clipboard".to_string());
+                }> "Export" </sl-button>
+                <sl-button variant="primary" on:click=move |_| {
+                    let mut result = Ok(());
+                    update!(|view| result = view.play_edited());
+                    match result {
+                        Ok(()) => {
+                            redraw.set(RedrawCells::redraw_all());
+                        }
+                        Err(err) => editor_toast(format!("Invalid board: {err}")),
+                    }
+                }> "Play" </sl-button>
+            </div>
+            <SpectatorControls view redraw />
+            <sl-alert variant="primary" duration="2000" countdown="ltr" closable ref=editor_alert_ref>
+                <sl-icon slot="icon" name="info-circle"></sl-icon>
+                { editor_message }
             </sl-alert>
             <div id="new-game-or-restart" class="non-draggable" on:mousedown=move |ev| ev.stop_propagation()>
                 <sl-button on:click=move |_| drawer_show(new_game_drawer_ref)> "New Game" </sl-button>
-                <sl-button disabled={ move || with!(|view| matches!(view, MaybeUninitGameView::Uninit { .. })) } on:click=move |_| drawer_show(restart_dialog_ref)> "Restart" </sl-button>
+                <sl-button on:click=move |_| drawer_show(history_drawer_ref)> "History" </sl-button>
+                <sl-button on:click=move |_| drawer_show(benchmark_drawer_ref)> "Benchmark" </sl-button>
+                <sl-button on:click=move |_| drawer_show(explorer_drawer_ref)> "Seed Explorer" </sl-button>
+                <sl-button on:click=move |_| drawer_show(room_drawer_ref)> "Join Room" </sl-button>
+                <sl-button on:click=move |_| drawer_show(leaderboard_drawer_ref)> "Leaderboard" </sl-button>
+                <sl-button on:click=move |_| drawer_show(profile_drawer_ref)> "Profiles" </sl-button>
+                <sl-button disabled={ move || with!(|view| !matches!(view, MaybeUninitGameView::GameView(_))) } on:click=move |_| drawer_show(restart_dialog_ref)> "Restart" </sl-button>
             </div>
-            <sl-drawer label="New Game" id="new-game-drawer" class="non-draggable" ref=new_game_drawer_ref on:mousedown=move |ev| ev.stop_propagation()>
+            <sl-drawer label="New Game" id="new-game-drawer" class="non-draggable" ref=new_game_drawer_ref on:mousedown=move |ev| ev.stop_propagation() on:sl-after-hide=move |ev: JsValue| {
+                // nested shoelace elements emit their own sl-after-hide
+                let target = Reflect::get(&ev, &"target".into()).unwrap();
+                if target == JsValue::from(into_html_element_untracked(new_game_drawer_ref))
+                    && abandon_after_hide.get_value()
+                {
+                    abandon_after_hide.set_value(false);
+                    drawer_show(abandon_dialog_ref);
+                }
+            }>
                 <sl-input label="Random Seed" id="random-seed" pattern="[0-9]*" ref=seed_ref> "0" </sl-input> <br />
                 <sl-radio-group label="Difficulty" name="difficulty" value="easy">
                     <sl-radio-button value="easy" on:click=move |_| set_difficulty(Difficulty::Easy)> "Easy" </sl-radio-button>
@@ -717,58 +2393,115 @@ fn Controls(
                             mines: 0,
                         });
                     }> "Custom" </sl-radio-button>
+                    <sl-radio-button value="density" on:click=move |_| {
+                        set_difficulty(Difficulty::Density {
+                            width: 0,
+                            height: 0,
+                            density: 0.,
+                        });
+                    }> "Density" </sl-radio-button>
                 </sl-radio-group> <br />
                 <div id="custom-difficulty-options">
                     <sl-input label="Width" pattern="[0-9]*" ref=width_ref disabled={
-                        move || !matches!(difficulty(), Difficulty::Custom { .. })
+                        move || difficulty().is_standard()
                     }> "30" </sl-input>
                     <sl-input label="Height" pattern="[0-9]*" ref=height_ref disabled={
-                        move || !matches!(difficulty(), Difficulty::Custom { .. })
+                        move || difficulty().is_standard()
                     }> "16" </sl-input>
                     <sl-input label="Mines" pattern="[0-9]*" ref=mines_ref disabled={
                         move || !matches!(difficulty(), Difficulty::Custom { .. })
                     }> "99" </sl-input>
+                    <sl-input label="Density (%)" pattern="[0-9.]*" ref=density_ref disabled={
+                        move || !matches!(difficulty(), Difficulty::Density { .. })
+                    }> "20" </sl-input>
                 </div>
+                <sl-select label="Symmetry" value="none" on:sl-change=move |ev: JsValue| {
+                    set_symmetry(match select_value(&ev).as_str() {
+                        "horizontal" => Symmetry::Horizontal,
+                        "vertical" => Symmetry::Vertical,
+                        "rotational" => Symmetry::Rotational,
+                        _ => Symmetry::None,
+                    });
+                }>
+                    <sl-option value="none"> "None" </sl-option>
+                    <sl-option value="horizontal"> "Horizontal" </sl-option>
+                    <sl-option value="vertical"> "Vertical" </sl-option>
+                    <sl-option value="rotational"> "Rotational" </sl-option>
+                </sl-select>
+                <sl-switch on:sl-change=move |ev: JsValue| {
+                    let target = Reflect::get(&ev, &"target".into()).unwrap();
+                    let checked = Reflect::get(&target, &"checked".into()).unwrap().as_bool().unwrap();
+                    set_learning(checked);
+                }> "Learning Mode" </sl-switch>
+                <sl-switch on:sl-change=move |ev: JsValue| {
+                    let target = Reflect::get(&ev, &"target".into()).unwrap();
+                    let checked = Reflect::get(&target, &"checked".into()).unwrap().as_bool().unwrap();
+                    set_hidden_count(checked);
+                }> "Hidden Mine Count" </sl-switch>
                 <sl-button slot="footer" variant="primary" on:click=move |_| {
                     let seed = read_input_untracked(seed_ref).map(|seed| seed as u64);
-                    let difficulty = match difficulty() {
-                        Difficulty::Custom {..} => {
-                            let Some(width) = read_input_untracked(width_ref) else {
-                                alert_toast(invalid_config_alert_ref);
-                                return;
-                            };
-                            let Some(height) = read_input_untracked(height_ref) else {
-                                alert_toast(invalid_config_alert_ref);
-                                return;
-                            };
-                            let Some(mines) = read_input_untracked(mines_ref) else {
-                                alert_toast(invalid_config_alert_ref);
-                                return;
-                            };
-                            if width <= 0 || height <= 0 || mines <= 0 || width * height <= mines {
-                                alert_toast(invalid_config_alert_ref);
-                                return;
-                            }
-                            Difficulty::Custom {
-                                width: width as usize,
-                                height: height as usize,
-                                mines: mines as usize,
-                            }
-                        }
-                        difficulty => difficulty,
+                    let Some(difficulty) = read_difficulty() else {
+                        return;
                     };
-                    drawer_hide(new_game_drawer_ref);
-                    new_game(GameOptions { difficulty, safe_pos: None, seed });
+                    let symmetry = symmetry.get_untracked();
+                    let learning = learning.get_untracked();
+                    let hidden_count = hidden_count.get_untracked();
+                    replace_view(
+                        GameOptions {
+                            difficulty,
+                            safe_pos: None,
+                            seed,
+                            symmetry,
+                            learning,
+                            hidden_count,
+                            generation: GENERATION_VERSION,
+                            limits: BoardLimits::default(),
+                        }
+                        .into(),
+                    );
                 }> "New Game" </sl-button>
+                <sl-button slot="footer" on:click=move |_| {
+                    let Some(difficulty) = read_difficulty() else {
+                        return;
+                    };
+                    let (w, h) = (difficulty.width(), difficulty.height());
+                    replace_view(MaybeUninitGameView::Editor(GameState::empty(w, h)));
+                }> "Edit Board" </sl-button>
+                <sl-button slot="footer" on:click=move |_| {
+                    replace_view(MaybeUninitGameView::Tutorial(Tutorial::new()));
+                }> "Tutorial" </sl-button>
                 <sl-button slot="footer" on:click=move |_| drawer_hide(new_game_drawer_ref)> "Cancel" </sl-button>
             </sl-drawer>
             <sl-alert variant="danger" duration="2000" countdown="ltr" closable ref=invalid_config_alert_ref>
                 <sl-icon slot="icon" name="exclamation-octagon"></sl-icon>
-                "Invalid configuration"
+                {config_error}
             </sl-alert>
+            <HistoryDrawer view redraw time=counter drawer_ref=history_drawer_ref stash checkpoint />
+            <ResultCard view time=counter />
+            <BenchmarkDrawer drawer_ref=benchmark_drawer_ref />
+            <SeedExplorerDrawer drawer_ref=explorer_drawer_ref />
+            <RoomDrawer drawer_ref=room_drawer_ref view redraw />
+            <LeaderboardDrawer drawer_ref=leaderboard_drawer_ref view redraw time=counter player=profile.player_name() />
+            <ProfileDrawer drawer_ref=profile_drawer_ref current=profile />
+            <sl-dialog label="Abandon Game" class="non-draggable" ref=abandon_dialog_ref on:mousedown=move |ev| ev.stop_propagation()>
+                "The current game will be saved to history, where it can be resumed later."
+                <sl-button slot="footer" variant="primary" autofocus on:click=move |_| {
+                    drawer_hide(abandon_dialog_ref);
+                    let Some(next) = pending_view.get_value() else {
+                        return;
+                    };
+                    pending_view.set_value(None);
+                    stash.notify();
+                    apply_view(next);
+                }> "Continue" </sl-button>
+                <sl-button slot="footer" on:click=move |_| {
+                    pending_view.set_value(None);
+                    drawer_hide(abandon_dialog_ref);
+                }> "Cancel" </sl-button>
+            </sl-dialog>
             <sl-dialog label="Restart Confirm" class="non-draggable" ref=restart_dialog_ref on:mousedown=move |ev| ev.stop_propagation()>
                 "Do you want to restart the game?"
-                <sl-button slot="footer" variant="primary" on:click=move |_| {
+                <sl-button slot="footer" variant="primary" autofocus on:click=move |_| {
                     drawer_hide(restart_dialog_ref);
                     restart.notify();
                 }> "Restart" </sl-button>
@@ -781,9 +2514,17 @@ fn Controls(
                     } else {
                         ().into_view()
                     }
-                MaybeUninitGameView::GameView(view) => view! {
-                    <p> { format!("Seed: {}", view.options().seed.unwrap()) } </p>
-                }.into_view(),
+                MaybeUninitGameView::GameView(view) => match view.options().seed {
+                    Some(seed) => view! { <p> { format!("Seed: {seed}") } </p> }.into_view(),
+                    None => ().into_view(),
+                },
+                MaybeUninitGameView::Spectator(spectator) => match spectator.replay.options.seed {
+                    Some(seed) => view! { <p> { format!("Seed: {seed}") } </p> }.into_view(),
+                    None => ().into_view(),
+                },
+                MaybeUninitGameView::Editor(_)
+                | MaybeUninitGameView::Tutorial(_)
+                | MaybeUninitGameView::Room(_) => ().into_view(),
             }) } <br />
             <a href="https://github.com/NKID00" target="_blank" id="footer" class="link non-draggable" on:mousedown=move |ev| ev.stop_propagation()>
                 <p> "© 2024 NKID00, under AGPL-3.0-or-later" </p>
@@ -799,6 +2540,12 @@ enum MaybeUninitGameView {
         options: GameOptions,
     },
     GameView(GameView),
+    Editor(GameState),
+    Tutorial(Tutorial),
+    /// Read-only playback, input is ignored
+    Spectator(Spectator),
+    /// Board shared with other players, input is sent to the server
+    Room(Room),
 }
 
 impl MaybeUninitGameView {
@@ -806,6 +2553,10 @@ impl MaybeUninitGameView {
         match self {
             MaybeUninitGameView::Uninit { options, .. } => options.difficulty.width(),
             MaybeUninitGameView::GameView(view) => view.width(),
+            MaybeUninitGameView::Editor(state) => state.width(),
+            MaybeUninitGameView::Tutorial(tutorial) => tutorial.view.width(),
+            MaybeUninitGameView::Spectator(spectator) => spectator.view.width(),
+            MaybeUninitGameView::Room(room) => room.width(),
         }
     }
 
@@ -813,19 +2564,48 @@ impl MaybeUninitGameView {
         match self {
             MaybeUninitGameView::Uninit { options, .. } => options.difficulty.height(),
             MaybeUninitGameView::GameView(view) => view.height(),
+            MaybeUninitGameView::Editor(state) => state.height(),
+            MaybeUninitGameView::Tutorial(tutorial) => tutorial.view.height(),
+            MaybeUninitGameView::Spectator(spectator) => spectator.view.height(),
+            MaybeUninitGameView::Room(room) => room.height(),
+        }
+    }
+
+    fn topology(&self) -> Topology {
+        match self {
+            MaybeUninitGameView::GameView(view) => view.topology(),
+            MaybeUninitGameView::Tutorial(tutorial) => tutorial.view.topology(),
+            MaybeUninitGameView::Spectator(spectator) => spectator.view.topology(),
+            _ => Topology::default(),
         }
     }
 
     fn restart(&mut self) {
         if let MaybeUninitGameView::GameView(view) = self {
-            *self = MaybeUninitGameView::Uninit {
-                gesture: view.gesture,
-                options: view.options(),
-            };
-            self.init();
+            view.restart();
+        }
+    }
+
+    fn is_lost(&self) -> bool {
+        match self {
+            MaybeUninitGameView::Uninit { .. }
+            | MaybeUninitGameView::Editor(_)
+            | MaybeUninitGameView::Tutorial(_)
+            | MaybeUninitGameView::Spectator(_)
+            | MaybeUninitGameView::Room(_) => false,
+            MaybeUninitGameView::GameView(view) => view.result == GameResult::Lose,
         }
     }
 
+    /// Start playing the board being edited
+    fn play_edited(&mut self) -> Result<(), BoardError> {
+        if let MaybeUninitGameView::Editor(state) = self {
+            state.validate()?;
+            *self = MaybeUninitGameView::GameView(GameView::from(state.clone()));
+        }
+        Ok(())
+    }
+
     fn init(&mut self) {
         if let MaybeUninitGameView::Uninit { gesture, options } = self {
             let mut view = GameView::from(options.clone().build());
@@ -840,21 +2620,18 @@ impl MaybeUninitGameView {
             MaybeUninitGameView::Uninit {
                 gesture,
                 options: _,
-            } => match *gesture {
-                Gesture::Hover(x0, y0) if x == x0 && y == y0 => Hovered,
-                Gesture::LeftOrRightPush(x0, y0) if x == x0 && y == y0 => Pushed,
-                Gesture::MidPush(x0, y0) if x == x0 && y == y0 => Hovered,
-                Gesture::MidPush(x0, y0)
-                    if x as i32 - 1 <= x0 as i32
-                        && x0 <= x + 1
-                        && y as i32 - 1 <= y0 as i32
-                        && y0 <= y + 1 =>
-                {
-                    Pushed
-                }
-                _ => Unopened,
-            },
+            } => gesture.intact_cell(x, y),
             MaybeUninitGameView::GameView(view) => view.cell(x, y),
+            MaybeUninitGameView::Editor(state) => {
+                if state.is_mine(x, y) {
+                    Mine
+                } else {
+                    Unopened
+                }
+            }
+            MaybeUninitGameView::Tutorial(tutorial) => tutorial.view.cell(x, y),
+            MaybeUninitGameView::Spectator(spectator) => spectator.view.cell(x, y),
+            MaybeUninitGameView::Room(room) => room.cell(x, y),
         }
     }
 
@@ -868,21 +2645,63 @@ impl MaybeUninitGameView {
                 self.init();
                 self.left_click(x, y)
             }
-            MaybeUninitGameView::GameView(view) => view.left_click(x, y),
+            MaybeUninitGameView::GameView(view) => view.stamp(now()).left_click(x, y).redraw,
+            MaybeUninitGameView::Editor(state) => {
+                state.toggle_mine(x, y);
+                RedrawCells::single(x, y)
+            }
+            MaybeUninitGameView::Tutorial(tutorial) => {
+                tutorial.click(Action::LeftClick(x, y)).unwrap_or_default()
+            }
+            MaybeUninitGameView::Spectator(_) => RedrawCells::default(),
+            MaybeUninitGameView::Room(room) => room.act(Action::LeftClick(x, y)),
+        }
+    }
+
+    fn refuses_flag(&self, x: usize, y: usize) -> bool {
+        match self {
+            MaybeUninitGameView::GameView(view) => view.refuses_flag(x, y),
+            _ => false,
+        }
+    }
+
+    fn refuses_chord(&self, x: usize, y: usize) -> bool {
+        match self {
+            MaybeUninitGameView::GameView(view) => view.refuses_chord(x, y),
+            _ => false,
         }
     }
 
     fn right_click(&mut self, x: usize, y: usize) -> RedrawCells {
         match self {
-            MaybeUninitGameView::Uninit { .. } => RedrawCells::default(),
-            MaybeUninitGameView::GameView(view) => view.right_click(x, y),
+            MaybeUninitGameView::Uninit { .. }
+            | MaybeUninitGameView::Editor(_)
+            | MaybeUninitGameView::Spectator(_) => RedrawCells::default(),
+            MaybeUninitGameView::GameView(view) => view.stamp(now()).right_click(x, y).redraw,
+            MaybeUninitGameView::Tutorial(tutorial) => {
+                tutorial.click(Action::RightClick(x, y)).unwrap_or_default()
+            }
+            MaybeUninitGameView::Room(room) => room.act(Action::RightClick(x, y)),
         }
     }
 
     fn middle_click(&mut self, x: usize, y: usize) -> RedrawCells {
         match self {
-            MaybeUninitGameView::Uninit { .. } => RedrawCells::default(),
-            MaybeUninitGameView::GameView(view) => view.middle_click(x, y),
+            MaybeUninitGameView::Uninit { .. }
+            | MaybeUninitGameView::Editor(_)
+            | MaybeUninitGameView::Spectator(_) => RedrawCells::default(),
+            MaybeUninitGameView::GameView(view) => view.stamp(now()).middle_click(x, y).redraw,
+            MaybeUninitGameView::Tutorial(tutorial) => tutorial
+                .click(Action::MiddleClick(x, y))
+                .unwrap_or_default(),
+            MaybeUninitGameView::Room(room) => room.act(Action::MiddleClick(x, y)),
+        }
+    }
+
+    fn autoflag(&mut self) -> RedrawCells {
+        match self {
+            MaybeUninitGameView::GameView(view) => view.autoflag(),
+            _ => RedrawCells::default(),
         }
     }
 
@@ -892,65 +2711,157 @@ impl MaybeUninitGameView {
                 gesture: previous_gesture,
                 options,
             } => {
-                let mut redraw = Vec::new();
-                match previous_gesture {
-                    Gesture::Hover(x, y) | Gesture::LeftOrRightPush(x, y) => redraw.push((*x, *y)),
-                    Gesture::MidPush(x, y) => {
-                        let x = *x as i32;
-                        let y = *y as i32;
-                        for y1 in [y - 1, y, y + 1] {
-                            if y1 < 0 || y1 >= options.difficulty.height() as i32 {
-                                continue;
-                            }
-                            for x1 in [x - 1, x, x + 1] {
-                                if x1 < 0 || x1 >= options.difficulty.width() as i32 {
-                                    continue;
-                                }
-                                redraw.push((x1 as usize, y1 as usize));
-                            }
-                        }
-                    }
-                    Gesture::None => Default::default(),
-                }
-                match gesture {
-                    Gesture::Hover(x, y) | Gesture::LeftOrRightPush(x, y) => redraw.push((x, y)),
-                    Gesture::MidPush(x, y) => {
-                        let x = x as i32;
-                        let y = y as i32;
-                        for y1 in [y - 1, y, y + 1] {
-                            if y1 < 0 || y1 >= options.difficulty.height() as i32 {
-                                continue;
-                            }
-                            for x1 in [x - 1, x, x + 1] {
-                                if x1 < 0 || x1 >= options.difficulty.width() as i32 {
-                                    continue;
-                                }
-                                redraw.push((x1 as usize, y1 as usize));
-                            }
-                        }
+                let mut redraw = RedrawCells::default();
+                for gesture in [*previous_gesture, gesture] {
+                    let Some((x, y)) = gesture.position() else {
+                        continue;
+                    };
+                    if !gesture.is_area() {
+                        redraw.insert((x, y));
+                        continue;
                     }
-                    Gesture::None => Default::default(),
+                    let (x0, y0) = (x.saturating_sub(1), y.saturating_sub(1));
+                    redraw.merge(RedrawCells::region(RedrawRegion::Rect {
+                        x: x0,
+                        y: y0,
+                        width: x + 2 - x0,
+                        height: y + 2 - y0,
+                    }));
                 }
                 *self = MaybeUninitGameView::Uninit {
                     gesture,
                     options: options.clone(),
                 };
-                RedrawCells(redraw)
+                redraw
             }
             MaybeUninitGameView::GameView(view) => view.gesture(gesture),
+            MaybeUninitGameView::Tutorial(tutorial) => tutorial.view.gesture(gesture),
+            MaybeUninitGameView::Room(room) => room.hover(gesture.position()),
+            MaybeUninitGameView::Editor(_) | MaybeUninitGameView::Spectator(_) => {
+                RedrawCells::default()
+            }
+        }
+    }
+
+    fn apply(&mut self, action: &Action) -> RedrawCells {
+        match self {
+            MaybeUninitGameView::Uninit { .. }
+            | MaybeUninitGameView::Editor(_)
+            | MaybeUninitGameView::Tutorial(_)
+            | MaybeUninitGameView::Spectator(_)
+            | MaybeUninitGameView::Room(_) => RedrawCells::default(),
+            MaybeUninitGameView::GameView(view) => view.apply(action),
+        }
+    }
+
+    fn undo(&mut self) -> RedrawCells {
+        match self {
+            MaybeUninitGameView::Uninit { .. }
+            | MaybeUninitGameView::Editor(_)
+            | MaybeUninitGameView::Tutorial(_)
+            | MaybeUninitGameView::Spectator(_) => RedrawCells::default(),
+            MaybeUninitGameView::GameView(view) => view.undo(),
+            MaybeUninitGameView::Room(room) => room.act(Action::Undo),
+        }
+    }
+
+    fn redo(&mut self) -> RedrawCells {
+        match self {
+            MaybeUninitGameView::Uninit { .. }
+            | MaybeUninitGameView::Editor(_)
+            | MaybeUninitGameView::Tutorial(_)
+            | MaybeUninitGameView::Spectator(_) => RedrawCells::default(),
+            MaybeUninitGameView::GameView(view) => view.redo(),
+            MaybeUninitGameView::Room(room) => room.act(Action::Redo),
+        }
+    }
+
+    fn can_undo(&self) -> bool {
+        match self {
+            MaybeUninitGameView::Uninit { .. }
+            | MaybeUninitGameView::Editor(_)
+            | MaybeUninitGameView::Tutorial(_)
+            | MaybeUninitGameView::Spectator(_) => false,
+            MaybeUninitGameView::GameView(view) => view.can_undo(),
+            MaybeUninitGameView::Room(room) => room.view.as_ref().is_some_and(GameView::can_undo),
+        }
+    }
+
+    fn can_redo(&self) -> bool {
+        match self {
+            MaybeUninitGameView::Uninit { .. }
+            | MaybeUninitGameView::Editor(_)
+            | MaybeUninitGameView::Tutorial(_)
+            | MaybeUninitGameView::Spectator(_) => false,
+            MaybeUninitGameView::GameView(view) => view.can_redo(),
+            MaybeUninitGameView::Room(room) => room.view.as_ref().is_some_and(GameView::can_redo),
+        }
+    }
+
+    fn is_flag_warning(&self, x: usize, y: usize) -> bool {
+        match self {
+            MaybeUninitGameView::Uninit { .. } | MaybeUninitGameView::Editor(_) => false,
+            MaybeUninitGameView::GameView(view) => view.is_flag_warning(x, y),
+            MaybeUninitGameView::Tutorial(tutorial) => tutorial.view.is_flag_warning(x, y),
+            MaybeUninitGameView::Spectator(spectator) => spectator.view.is_flag_warning(x, y),
+            MaybeUninitGameView::Room(room) => room
+                .view
+                .as_ref()
+                .is_some_and(|view| view.is_flag_warning(x, y)),
+        }
+    }
+
+    fn known_safe(&self) -> &[(usize, usize)] {
+        match self {
+            MaybeUninitGameView::GameView(view) => view.known_safe(),
+            _ => &[],
+        }
+    }
+
+    fn is_highlighted(&self, x: usize, y: usize) -> bool {
+        match self {
+            MaybeUninitGameView::Tutorial(tutorial) => tutorial.expected_cell() == Some((x, y)),
+            _ => false,
+        }
+    }
+
+    fn cursor_color(&self, x: usize, y: usize) -> Option<&'static str> {
+        match self {
+            MaybeUninitGameView::Room(room) => room.cursor_color(x, y),
+            _ => None,
+        }
+    }
+
+    /// Let automation take the tutorial step that asks for it
+    fn tutorial_step(&mut self) -> RedrawCells {
+        match self {
+            MaybeUninitGameView::Tutorial(tutorial) => {
+                tutorial.automation_step().unwrap_or_default()
+            }
+            _ => RedrawCells::default(),
         }
     }
 
     fn is_draggable(&self, x: usize, y: usize) -> bool {
         match self {
-            MaybeUninitGameView::Uninit { .. } => false,
+            MaybeUninitGameView::Uninit { .. } | MaybeUninitGameView::Editor(_) => false,
             MaybeUninitGameView::GameView(view) => view.is_draggable(x, y),
+            MaybeUninitGameView::Tutorial(tutorial) => tutorial.view.is_draggable(x, y),
+            MaybeUninitGameView::Spectator(_) => true,
+            MaybeUninitGameView::Room(room) => room
+                .view
+                .as_ref()
+                .is_some_and(|view| view.is_draggable(x, y)),
         }
     }
 
     fn is_playing(&self) -> bool {
         match self {
-            MaybeUninitGameView::Uninit { .. } => false,
+            MaybeUninitGameView::Uninit { .. }
+            | MaybeUninitGameView::Editor(_)
+            | MaybeUninitGameView::Tutorial(_)
+            | MaybeUninitGameView::Spectator(_)
+            | MaybeUninitGameView::Room(_) => false,
             MaybeUninitGameView::GameView(view) => view.result == GameResult::Playing,
         }
     }
@@ -968,33 +2879,79 @@ impl From<GameOptions> for MaybeUninitGameView {
 #[component]
 pub fn App() -> impl IntoView {
     provide_meta_context();
+    let profile = create_rw_signal(None::<Profile>);
+    view! {
+        <ProfileChooser chosen=profile />
+        { move || profile().map(|profile| view! { <Game profile /> }) }
+    }
+}
+
+/// Everything of one profile, set up once it is chosen since saved data depends on it
+#[component]
+fn Game(profile: Profile) -> impl IntoView {
     let view = create_rw_signal(MaybeUninitGameView::Uninit {
         gesture: Gesture::None,
         options: GameOptions {
             difficulty: Difficulty::Easy,
             safe_pos: None,
             seed: Some(1),
+            symmetry: Symmetry::None,
+            learning: false,
+            hidden_count: false,
+            generation: GENERATION_VERSION,
+            limits: BoardLimits::default(),
         },
     });
     let redraw: RwSignal<RedrawCells> = create_rw_signal(Default::default());
     let (get_new_game, new_game) = create_signal(GameOptions::default());
     let restart = create_trigger();
-    create_effect(move |_| {
-        update!(|view| *view = get_new_game().into());
-        let (w, h) = view.with_untracked(|view| (view.width(), view.height()));
-        update!(|redraw| *redraw = RedrawCells::redraw_all(w, h));
+    let layout = create_rw_signal(Layout::default());
+    let orientation = create_rw_signal(Orientation::default());
+    let mouse = persisted_mouse_settings();
+    let motion = persisted_motion_settings();
+    let sprites = persisted_sprite_pack();
+    let pause_settings = persisted_pause_settings();
+    pause_when_away(view, pause_settings);
+    let assists = create_rw_signal(Assists::default());
+    let player_moved = create_trigger();
+    let region = create_rw_signal(None::<Rect>);
+    let inspection = create_rw_signal(None::<Inspection>);
+    let telemetry = create_rw_signal(Telemetry::default());
+    let show_components = create_rw_signal(false);
+    // a shared link starts its board as if the first click was made
+    let open_shared = move |options: GameOptions| {
+        if let Ok(state) = options.try_build() {
+            view.set(MaybeUninitGameView::GameView(GameView::from(state)));
+            region.set(None);
+            redraw.set(RedrawCells::redraw_all());
+        }
+    };
+    create_effect(move |first| {
+        let options = get_new_game();
+        if let Some(shared) = first.is_none().then(shared_game).flatten() {
+            open_shared(shared);
+            return;
+        }
+        update!(|view| *view = options.into());
+        region.set(None);
+        update!(|redraw| *redraw = RedrawCells::redraw_all());
+    });
+    let _ = use_event_listener(window(), ev::hashchange, move |_| {
+        if let Some(shared) = shared_game() {
+            open_shared(shared);
+        }
     });
     create_effect(move |_| {
         restart.track();
         update!(|view| view.restart());
-        let (w, h) = view.with_untracked(|view| (view.width(), view.height()));
-        update!(|redraw| *redraw = RedrawCells::redraw_all(w, h));
+        update!(|redraw| *redraw = RedrawCells::redraw_all());
     });
     let (_class_name, style_val) = style_str! {};
     view! {
         class = class_name,
         <Style> { style_val } </Style>
-        <Map view redraw />
-        <Controls view redraw new_game restart />
+        <Map view redraw layout orientation mouse motion sprites assists player_moved region inspection telemetry show_components />
+        <Controls profile view redraw layout orientation mouse motion sprites pause_settings new_game restart assists player_moved region inspection telemetry show_components />
+        <TelemetryOverlay telemetry />
     }
 }
@@ -0,0 +1,123 @@
+use automation_protocol::{AutomationRequest, AutomationResponse, BenchmarkStats, Solver};
+use automation_worker::Automation;
+use futures::SinkExt;
+use gloo_worker::reactor::ReactorBridge;
+use js_sys::Date;
+use leptos::*;
+use leptos_meta::*;
+use stylers::style_str;
+use wasm_bindgen::JsValue;
+
+use minesweep_core::Difficulty;
+
+use super::pool::spawn_worker;
+use super::{drawer_hide, health, read_input_untracked, select_value};
+
+const BENCHMARK_ID: u64 = 1;
+
+/// Automate many random boards on a worker of its own, away from the game being played
+#[component]
+pub fn BenchmarkDrawer(drawer_ref: NodeRef<html::Custom>) -> impl IntoView {
+    let boards_ref: NodeRef<html::Custom> = create_node_ref();
+    let budget_ref: NodeRef<html::Custom> = create_node_ref();
+    let (difficulty, set_difficulty) = create_signal(Difficulty::Medium);
+    let (solver, set_solver) = create_signal(Solver::Session);
+    let stats = create_rw_signal(None::<Result<BenchmarkStats, String>>);
+    let worker = store_value(None::<ReactorBridge<Automation>>);
+    // bumped on every run or cancel, so that a stale run reports nothing
+    let generation = store_value(0u64);
+    let running = create_rw_signal(false);
+
+    let run = move || {
+        let request = AutomationRequest::Benchmark {
+            id: BENCHMARK_ID,
+            difficulty: difficulty.get_untracked(),
+            boards: read_input_untracked(boards_ref).unwrap_or(20).max(1) as u64,
+            seed: Date::now() as u64,
+            solver: solver.get_untracked(),
+            budget: read_input_untracked(budget_ref).unwrap_or(60).max(1) as f64,
+        };
+        generation.update_value(|generation| *generation += 1);
+        let current = generation.get_value();
+        let spawned = spawn_worker();
+        let (pinger, mut bridge) = (spawned.fork(), spawned.fork());
+        worker.set_value(Some(spawned));
+        running.set(true);
+        stats.set(None);
+        spawn_local(async move {
+            bridge.send(request).await.unwrap();
+            let result = match health::next_response(&pinger, &mut bridge).await {
+                Ok(Some(AutomationResponse::Benchmarked { stats, .. })) => Some(Ok(stats)),
+                Ok(_) => None,
+                Err(reason) => Some(Err(reason)),
+            };
+            if generation.get_value() == current {
+                stats.set(result);
+                running.set(false);
+                worker.set_value(None);
+            }
+        });
+    };
+    let cancel = move || {
+        worker.with_value(|worker| {
+            if let Some(worker) = worker {
+                worker.send_input(AutomationRequest::Cancel { id: BENCHMARK_ID });
+            }
+        });
+        generation.update_value(|generation| *generation += 1);
+        worker.set_value(None);
+        running.set(false);
+    };
+
+    let (class_name, style_val) = style_str! {
+        #benchmark-drawer {
+            --size: 40vw;
+        }
+        #benchmark-table {
+            width: 100%;
+            text-align: left;
+        }
+    };
+    view! {
+        class = class_name,
+        <Style> { style_val } </Style>
+        <sl-drawer label="Benchmark" id="benchmark-drawer" class="non-draggable" ref=drawer_ref on:mousedown=move |ev| ev.stop_propagation()>
+            <sl-select label="Difficulty" value="medium" on:sl-change=move |ev: JsValue| set_difficulty(match select_value(&ev).as_str() {
+                "easy" => Difficulty::Easy,
+                "hard" => Difficulty::Hard,
+                _ => Difficulty::Medium,
+            })>
+                <sl-option value="easy"> "Easy" </sl-option>
+                <sl-option value="medium"> "Medium" </sl-option>
+                <sl-option value="hard"> "Hard" </sl-option>
+            </sl-select> <br />
+            <sl-select label="Solver" value="session" on:sl-change=move |ev: JsValue| set_solver(match select_value(&ev).as_str() {
+                "plain" => Solver::Plain,
+                "race" => Solver::Race,
+                _ => Solver::Session,
+            })>
+                <sl-option value="session"> "Session" </sl-option>
+                <sl-option value="plain"> "Plain" </sl-option>
+                <sl-option value="race"> "Race" </sl-option>
+            </sl-select> <br />
+            <sl-input label="Boards" pattern="[0-9]*" ref=boards_ref> "20" </sl-input> <br />
+            <sl-input label="Budget (seconds)" pattern="[0-9]*" ref=budget_ref> "60" </sl-input> <br />
+            { move || match stats() {
+                Some(Ok(stats)) => view! {
+                    <table id="benchmark-table">
+                        <tr> <th> "Boards" </th> <td> { stats.boards } </td> </tr>
+                        <tr> <th> "Win rate" </th> <td> { format!("{:.1}%", stats.win_rate() * 100.) } </td> </tr>
+                        <tr> <th> "Steps" </th> <td> { stats.steps } </td> </tr>
+                        <tr> <th> "Mean per board" </th> <td> { format!("{:.3}s", stats.mean_board()) } </td> </tr>
+                        <tr> <th> "Longest step" </th> <td> { format!("{:.3}s", stats.longest_step) } </td> </tr>
+                    </table>
+                }.into_view(),
+                Some(Err(reason)) => view! { <p> "Benchmark failed: " { reason } </p> }.into_view(),
+                None => ().into_view(),
+            } }
+            <sl-button slot="footer" variant="primary" loading=running on:click=move |_| run()> "Run" </sl-button>
+            <sl-button slot="footer" disabled=move || !running() on:click=move |_| cancel()> "Cancel" </sl-button>
+            <sl-button slot="footer" on:click=move |_| drawer_hide(drawer_ref)> "Close" </sl-button>
+        </sl-drawer>
+    }
+}
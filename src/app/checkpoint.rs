@@ -0,0 +1,186 @@
+use js_sys::Date;
+use leptos::logging::log;
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+use minesweep_core::{GameResult, GameView, RedrawCells, Replay};
+
+use super::history::format_time;
+use super::{drawer_hide, storage, MaybeUninitGameView};
+
+const CHECKPOINT_STORE: &str = "checkpoints";
+/// Actions between automatic checkpoints of a game
+const CHECKPOINT_INTERVAL: usize = 25;
+/// Newest checkpoints kept of each game, older ones are deleted
+const CHECKPOINTS_PER_GAME: usize = 3;
+
+/// Game in progress saved in case the browser goes away, restored from its replay
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Milliseconds since epoch when the checkpoint was taken
+    pub id: f64,
+    /// `GameState::board_hash` of the game, checkpoints of one board are pruned together
+    pub game: String,
+    pub time: u64,
+    pub replay: Replay,
+}
+
+impl Checkpoint {
+    fn new(view: &GameView, time: u64) -> Self {
+        let replay = view.replay();
+        Self {
+            id: Date::now(),
+            game: replay.board_hash.clone().unwrap_or_default(),
+            time,
+            replay,
+        }
+    }
+
+    fn date(&self) -> String {
+        Date::new(&self.id.into())
+            .to_locale_string("default", &JsValue::UNDEFINED)
+            .into()
+    }
+}
+
+/// Checkpoints taken every few actions and on `checkpoint`, listed for restoring, the ones of a
+/// game are dropped once it ends
+#[component]
+pub fn Checkpoints(
+    view: RwSignal<MaybeUninitGameView>,
+    redraw: RwSignal<RedrawCells>,
+    time: Signal<u64>,
+    drawer_ref: NodeRef<html::Custom>,
+    /// Take a checkpoint now, before automation runs
+    checkpoint: Trigger,
+) -> impl IntoView {
+    let checkpoints = create_rw_signal(Vec::<Checkpoint>::new());
+    let reload = move || {
+        spawn_local(async move {
+            match storage::get_all::<Checkpoint>(CHECKPOINT_STORE).await {
+                Ok(mut all) => {
+                    all.sort_by(|a, b| b.id.total_cmp(&a.id));
+                    checkpoints.set(all);
+                }
+                Err(err) => log!("checkpoint load failed {err:?}"),
+            }
+        })
+    };
+    reload();
+    // delete the checkpoints of `game` past the newest `keep`
+    let prune = move |game: String, keep: usize| {
+        let stale: Vec<_> = checkpoints.with_untracked(|checkpoints| {
+            checkpoints
+                .iter()
+                .filter(|checkpoint| checkpoint.game == game)
+                .skip(keep)
+                .map(|checkpoint| checkpoint.id)
+                .collect()
+        });
+        spawn_local(async move {
+            for id in stale {
+                if let Err(err) = storage::delete(CHECKPOINT_STORE, id).await {
+                    log!("checkpoint delete failed {err:?}");
+                }
+            }
+            reload();
+        });
+    };
+    let save = move || {
+        let checkpoint = view.with_untracked(|view| match view {
+            MaybeUninitGameView::GameView(view)
+                if view.result == GameResult::Playing && !view.actions().is_empty() =>
+            {
+                Some(Checkpoint::new(view, time.get_untracked()))
+            }
+            _ => None,
+        });
+        let Some(checkpoint) = checkpoint else {
+            return;
+        };
+        checkpoints.update(|checkpoints| checkpoints.insert(0, checkpoint.clone()));
+        spawn_local(async move {
+            match storage::put(CHECKPOINT_STORE, &checkpoint).await {
+                Ok(()) => prune(checkpoint.game, CHECKPOINTS_PER_GAME),
+                Err(err) => log!("checkpoint save failed {err:?}"),
+            }
+        });
+    };
+
+    // actions of the game when the last checkpoint was taken
+    let saved_at = store_value(0usize);
+    create_effect(move |_| {
+        let Some(actions) = with!(|view| match view {
+            MaybeUninitGameView::GameView(view) if view.result == GameResult::Playing => {
+                Some(view.actions().len())
+            }
+            _ => None,
+        }) else {
+            return;
+        };
+        // a new game or undone actions start counting again
+        if actions < saved_at.get_value() {
+            saved_at.set_value(actions);
+        }
+        if actions >= saved_at.get_value() + CHECKPOINT_INTERVAL {
+            saved_at.set_value(actions);
+            save();
+        }
+    });
+    create_effect(move |first| {
+        checkpoint.track();
+        if first.is_some() {
+            save();
+        }
+    });
+    // finished games are in the history, their checkpoints are no longer needed
+    create_effect(move |previous_result| {
+        let result = with!(|view| match view {
+            MaybeUninitGameView::GameView(view) => Some(view.result),
+            _ => None,
+        });
+        if previous_result == Some(Some(GameResult::Playing))
+            && matches!(result, Some(GameResult::Win | GameResult::Lose))
+        {
+            let game = view.with_untracked(|view| match view {
+                MaybeUninitGameView::GameView(view) => view.replay().board_hash,
+                _ => None,
+            });
+            if let Some(game) = game {
+                prune(game, 0);
+            }
+        }
+        result
+    });
+
+    let restore = move |replay: Replay| {
+        let Ok(game) = replay.finish() else {
+            return;
+        };
+        drawer_hide(drawer_ref);
+        view.set(MaybeUninitGameView::GameView(game));
+        redraw.set(RedrawCells::redraw_all());
+    };
+
+    view! {
+        { move || (!checkpoints.with(Vec::is_empty)).then(|| view! {
+            <h3> "Checkpoints" </h3>
+            <table id="checkpoint-table">
+                { checkpoints().into_iter().map(|checkpoint| {
+                    let replay = checkpoint.replay.clone();
+                    view! {
+                        <tr>
+                            <td> { checkpoint.date() } </td>
+                            <td> { format!("{} moves", checkpoint.replay.actions.len()) } </td>
+                            <td> { format_time(checkpoint.time) } </td>
+                            <td>
+                                <sl-button size="small" on:click=move |_| restore(replay.clone())> "Restore" </sl-button>
+                            </td>
+                        </tr>
+                    }
+                }).collect_view() }
+            </table>
+        }) }
+    }
+}
@@ -0,0 +1,84 @@
+use leptos::*;
+use leptos_meta::*;
+use stylers::style_str;
+use wasm_bindgen::JsValue;
+
+use minesweep_core::{Action, Deduction, Reasoning};
+
+use super::inspect::Inspection;
+use super::MaybeUninitGameView;
+
+const TIERS: [(Reasoning, &str); 3] = [
+    (Reasoning::Rule, "Basic rule"),
+    (Reasoning::Local, "Local SAT"),
+    (Reasoning::Unproven, "Unproven"),
+];
+
+/// Popover listing the moves of the last automation step by the reasoning behind them,
+/// explained when opened since it plays the game again up to the step
+#[component]
+pub fn ExplainStep(view: RwSignal<MaybeUninitGameView>) -> impl IntoView {
+    let deductions = create_rw_signal(Vec::<Deduction>::new());
+    let last_is_step = move || {
+        with!(|view| match view {
+            MaybeUninitGameView::GameView(view) => {
+                matches!(view.actions().last(), Some(Action::Automation(_)))
+            }
+            _ => false,
+        })
+    };
+    let explain = move |_: JsValue| {
+        deductions.set(view.with_untracked(|view| match view {
+            MaybeUninitGameView::GameView(view) => view.explain_last_step(),
+            _ => vec![],
+        }))
+    };
+    let (class_name, style_val) = style_str! {
+        #explanation {
+            max-width: 20rem;
+            max-height: 24rem;
+            overflow-y: auto;
+            padding: 0.25rem 1rem;
+            background-color: var(--sl-panel-background-color);
+            border: solid var(--sl-panel-border-width) var(--sl-panel-border-color);
+            border-radius: var(--sl-border-radius-medium);
+            box-shadow: var(--sl-shadow-large);
+        }
+        #explanation h4 {
+            margin: 0.5rem 0 0.25rem;
+        }
+        #explanation ul {
+            margin: 0;
+            padding-left: 1.25rem;
+        }
+    };
+    view! {
+        class = class_name,
+        <Style> { style_val } </Style>
+        <sl-dropdown placement="bottom-start" on:sl-show=explain>
+            <sl-button slot="trigger" size="small" caret disabled=move || !last_is_step()> "Explain Last Step" </sl-button>
+            <div id="explanation">
+                { move || with!(|deductions| {
+                    if deductions.is_empty() {
+                        return view! { class = class_name, <p> "No moves to explain" </p> }.into_view();
+                    }
+                    TIERS.into_iter().filter_map(|(reasoning, name)| {
+                        let moves: Vec<_> = deductions
+                            .iter()
+                            .filter(|deduction| deduction.reasoning() == reasoning)
+                            .map(|Deduction { action, reasons }| Inspection {
+                                action: action.clone(),
+                                reasons: reasons.clone(),
+                            }.describe())
+                            .collect();
+                        (!moves.is_empty()).then(|| view! {
+                            class = class_name,
+                            <h4> { format!("{name} ({})", moves.len()) } </h4>
+                            <ul> { moves.into_iter().map(|text| view! { class = class_name, <li> { text } </li> }).collect_view() } </ul>
+                        })
+                    }).collect_view()
+                }) }
+            </div>
+        </sl-dropdown>
+    }
+}
@@ -0,0 +1,180 @@
+use automation_protocol::{AutomationRequest, AutomationResponse};
+use automation_worker::Automation;
+use futures::SinkExt;
+use gloo_worker::reactor::ReactorBridge;
+use leptos::*;
+use leptos_meta::*;
+use leptos_use::{use_clipboard, UseClipboardReturn};
+use stylers::style_str;
+use wasm_bindgen::JsValue;
+
+use minesweep_core::{Difficulty, Rating};
+
+use super::pool::spawn_worker;
+use super::result::shared_link;
+use super::{drawer_hide, health, read_input_untracked, select_value};
+
+const EXPLORER_ID: u64 = 2;
+
+/// Order of the rated boards, hardest first
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortBy {
+    Score,
+    Guesses,
+}
+
+impl SortBy {
+    fn sort(&self, ratings: &mut [Rating]) {
+        match self {
+            Self::Score => ratings.sort_by(|a, b| b.score().total_cmp(&a.score())),
+            Self::Guesses => ratings.sort_by(|a, b| {
+                b.guesses
+                    .cmp(&a.guesses)
+                    .then(b.score().total_cmp(&a.score()))
+            }),
+        }
+    }
+}
+
+/// Rate a range of seeds on a worker of its own and list the boards hardest first,
+/// each with a link to play it
+#[component]
+pub fn SeedExplorerDrawer(drawer_ref: NodeRef<html::Custom>) -> impl IntoView {
+    let seed_ref: NodeRef<html::Custom> = create_node_ref();
+    let boards_ref: NodeRef<html::Custom> = create_node_ref();
+    let (difficulty, set_difficulty) = create_signal(Difficulty::Medium);
+    let (sort_by, set_sort_by) = create_signal(SortBy::Score);
+    let ratings = create_rw_signal(None::<Result<Vec<Rating>, String>>);
+    let sorted = move || {
+        ratings().map(|ratings| {
+            ratings.map(|mut ratings| {
+                sort_by().sort(&mut ratings);
+                ratings
+            })
+        })
+    };
+    let worker = store_value(None::<ReactorBridge<Automation>>);
+    // bumped on every run or cancel, so that a stale run reports nothing
+    let generation = store_value(0u64);
+    let running = create_rw_signal(false);
+    let UseClipboardReturn { copy, .. } = use_clipboard();
+
+    let run = move || {
+        let request = AutomationRequest::Explore {
+            id: EXPLORER_ID,
+            difficulty: difficulty.get_untracked(),
+            seed: read_input_untracked(seed_ref).unwrap_or(0).max(0) as u64,
+            boards: read_input_untracked(boards_ref).unwrap_or(50).max(1) as u64,
+        };
+        generation.update_value(|generation| *generation += 1);
+        let current = generation.get_value();
+        let spawned = spawn_worker();
+        let (pinger, mut bridge) = (spawned.fork(), spawned.fork());
+        worker.set_value(Some(spawned));
+        running.set(true);
+        ratings.set(None);
+        spawn_local(async move {
+            bridge.send(request).await.unwrap();
+            let result = match health::next_response(&pinger, &mut bridge).await {
+                Ok(Some(AutomationResponse::Explored { ratings, .. })) => Some(Ok(ratings)),
+                Ok(_) => None,
+                Err(reason) => Some(Err(reason)),
+            };
+            if generation.get_value() == current {
+                ratings.set(result);
+                running.set(false);
+                worker.set_value(None);
+            }
+        });
+    };
+    let cancel = move || {
+        worker.with_value(|worker| {
+            if let Some(worker) = worker {
+                worker.send_input(AutomationRequest::Cancel { id: EXPLORER_ID });
+            }
+        });
+        generation.update_value(|generation| *generation += 1);
+        worker.set_value(None);
+        running.set(false);
+    };
+
+    let (class_name, style_val) = style_str! {
+        #seed-explorer-drawer {
+            --size: 50vw;
+        }
+        #seed-explorer-table {
+            width: 100%;
+            text-align: left;
+        }
+    };
+    view! {
+        class = class_name,
+        <Style> { style_val } </Style>
+        <sl-drawer label="Seed Explorer" id="seed-explorer-drawer" class="non-draggable" ref=drawer_ref on:mousedown=move |ev| ev.stop_propagation()>
+            <sl-select label="Difficulty" value="medium" on:sl-change=move |ev: JsValue| set_difficulty(match select_value(&ev).as_str() {
+                "easy" => Difficulty::Easy,
+                "hard" => Difficulty::Hard,
+                _ => Difficulty::Medium,
+            })>
+                <sl-option value="easy"> "Easy" </sl-option>
+                <sl-option value="medium"> "Medium" </sl-option>
+                <sl-option value="hard"> "Hard" </sl-option>
+            </sl-select> <br />
+            <sl-input label="First Seed" pattern="[0-9]*" ref=seed_ref> "0" </sl-input> <br />
+            <sl-input label="Boards" pattern="[0-9]*" ref=boards_ref> "50" </sl-input> <br />
+            <sl-select label="Sort By" value="score" on:sl-change=move |ev: JsValue| set_sort_by(match select_value(&ev).as_str() {
+                "guesses" => SortBy::Guesses,
+                _ => SortBy::Score,
+            })>
+                <sl-option value="score"> "Score" </sl-option>
+                <sl-option value="guesses"> "Guesses" </sl-option>
+            </sl-select> <br />
+            { move || match sorted() {
+                Some(Ok(ratings)) => view! {
+                    class = class_name,
+                    <table id="seed-explorer-table">
+                        <tr>
+                            <th> "Seed" </th>
+                            <th> "3BV" </th>
+                            <th> "Rounds" </th>
+                            <th> "Deep" </th>
+                            <th> "Guesses" </th>
+                            <th> "Score" </th>
+                            <th> "Solver" </th>
+                            <th></th>
+                        </tr>
+                        { ratings.into_iter().map(|rating| {
+                            let link = shared_link(&rating.options);
+                            let copy = copy.clone();
+                            view! {
+                                class = class_name,
+                                <tr>
+                                    <td> { rating.options.seed.unwrap_or_default() } </td>
+                                    <td> { rating.bbbv } </td>
+                                    <td> { rating.rounds } </td>
+                                    <td> { rating.deep_rounds } </td>
+                                    <td> { rating.guesses } </td>
+                                    <td> { rating.score() } </td>
+                                    <td> { if rating.win { "Win" } else { "Lose" } } </td>
+                                    <td> { link.map(|link| view! {
+                                        class = class_name,
+                                        <sl-icon-button name="clipboard" label="Copy Link" on:click={
+                                            let link = link.clone();
+                                            move |_| copy(&link)
+                                        }></sl-icon-button>
+                                        <a href=link on:click=move |_| drawer_hide(drawer_ref)> "Play" </a>
+                                    }) } </td>
+                                </tr>
+                            }
+                        }).collect_view() }
+                    </table>
+                }.into_view(),
+                Some(Err(reason)) => view! { <p> "Exploring failed: " { reason } </p> }.into_view(),
+                None => ().into_view(),
+            } }
+            <sl-button slot="footer" variant="primary" loading=running on:click=move |_| run()> "Run" </sl-button>
+            <sl-button slot="footer" disabled=move || !running() on:click=move |_| cancel()> "Cancel" </sl-button>
+            <sl-button slot="footer" on:click=move |_| drawer_hide(drawer_ref)> "Close" </sl-button>
+        </sl-drawer>
+    }
+}
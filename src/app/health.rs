@@ -0,0 +1,101 @@
+use std::{cell::RefCell, pin::pin, time::Duration};
+
+use automation_protocol::{AutomationRequest, AutomationResponse, PROTOCOL_VERSION};
+use automation_worker::Automation;
+use futures::{
+    channel::oneshot,
+    future::{select, Either},
+    SinkExt, StreamExt,
+};
+use gloo_worker::reactor::ReactorBridge;
+use leptos::set_timeout;
+
+/// Time waited for a response before checking on the worker
+const HEALTH_INTERVAL: Duration = Duration::from_secs(2);
+/// Time a healthy worker takes at most to answer a ping, it yields every solve slice
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+
+type ProgressCallback = Box<dyn Fn(Option<(usize, usize)>)>;
+
+thread_local! {
+    /// Told the progress of the solve in flight after every health check, `None` once answered
+    static ON_PROGRESS: RefCell<Option<ProgressCallback>> = const { RefCell::new(None) };
+}
+
+/// Follow the progress of long solves, cells examined out of the whole task
+pub fn on_progress(callback: impl Fn(Option<(usize, usize)>) + 'static) {
+    ON_PROGRESS.set(Some(Box::new(callback)));
+}
+
+fn report(progress: Option<(usize, usize)>) {
+    ON_PROGRESS.with_borrow(|callback| {
+        if let Some(callback) = callback {
+            callback(progress);
+        }
+    });
+}
+
+pub async fn sleep(duration: Duration) {
+    let (sender, receiver) = oneshot::channel();
+    set_timeout(
+        move || {
+            let _ = sender.send(());
+        },
+        duration,
+    );
+    let _ = receiver.await;
+}
+
+/// Ping the worker, returns the progress of its solve in flight or why it is considered crashed
+pub async fn check(worker: &ReactorBridge<Automation>) -> Result<Option<(usize, usize)>, String> {
+    let mut bridge = worker.fork();
+    if bridge
+        .send(AutomationRequest::Ping {
+            version: PROTOCOL_VERSION,
+        })
+        .await
+        .is_err()
+    {
+        return Err("worker is gone".to_string());
+    }
+    match select(bridge.next(), pin!(sleep(HEALTH_TIMEOUT))).await {
+        Either::Left((Some(AutomationResponse::Pong { version, progress }), _))
+            if version == PROTOCOL_VERSION =>
+        {
+            Ok(progress)
+        }
+        Either::Left((Some(AutomationResponse::Pong { version, .. }), _)) => Err(format!(
+            "worker speaks protocol version {version}, not {PROTOCOL_VERSION}"
+        )),
+        Either::Left((Some(AutomationResponse::Failed { reason }), _)) => Err(reason),
+        Either::Left(_) => Err("worker is gone".to_string()),
+        Either::Right(_) => Err("worker stopped responding".to_string()),
+    }
+}
+
+/// Wait for the next response on `bridge` while checking on the worker behind it
+pub async fn next_response(
+    worker: &ReactorBridge<Automation>,
+    bridge: &mut ReactorBridge<Automation>,
+) -> Result<Option<AutomationResponse>, String> {
+    let mut reported = false;
+    loop {
+        match select(bridge.next(), pin!(sleep(HEALTH_INTERVAL))).await {
+            Either::Left((response, _)) => {
+                if reported {
+                    report(None);
+                }
+                return Ok(response);
+            }
+            Either::Right(_) => {
+                let progress = check(worker).await.inspect_err(|_| {
+                    if reported {
+                        report(None);
+                    }
+                })?;
+                reported |= progress.is_some();
+                report(progress);
+            }
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use js_sys::Date;
+use leptos::logging::log;
+use leptos::*;
+use leptos_meta::*;
+use leptos_use::{use_interval, UseIntervalReturn};
+use serde::{Deserialize, Serialize};
+use stylers::style_str;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+use minesweep_core::{
+    CellView, Difficulty, GameOptions, GameResult, GameView, RedrawCells, Replay,
+};
+
+use super::checkpoint::Checkpoints;
+use super::spectator::{replay_from_json, replay_to_json, Spectator};
+use super::{download, drawer_hide, pick_text_file, select_value, storage, MaybeUninitGameView};
+
+const HISTORY_STORE: &str = "history";
+const PLAYBACK_INTERVAL: u64 = 400;
+/// Largest side of a thumbnail in pixels, cells stay between 1 and `THUMBNAIL_MAX_CELL` pixels
+const THUMBNAIL_SIZE: usize = 96;
+const THUMBNAIL_MAX_CELL: usize = 6;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Milliseconds since epoch when the game finished or was abandoned
+    pub id: f64,
+    pub options: GameOptions,
+    pub result: GameResult,
+    pub time: u64,
+    pub assisted: bool,
+    pub replay: Replay,
+    /// PNG data URL of the final position, finished games only
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+}
+
+impl HistoryEntry {
+    fn new(view: &GameView, time: u64) -> Self {
+        Self {
+            id: Date::now(),
+            options: view.options(),
+            result: view.result,
+            time,
+            assisted: !view.assistance().is_pure(),
+            replay: view.replay(),
+            thumbnail: (view.result != GameResult::Playing)
+                .then(|| thumbnail(view))
+                .flatten(),
+        }
+    }
+
+    fn date(&self) -> String {
+        Date::new(&self.id.into())
+            .to_locale_string("default", &JsValue::UNDEFINED)
+            .into()
+    }
+
+    fn csv_row(&self) -> String {
+        let difficulty = &self.options.difficulty;
+        format!(
+            "{},{},{},{},{},{},{},{},{},{}",
+            Date::new(&self.id.into()).to_iso_string(),
+            difficulty_name(difficulty),
+            difficulty.width(),
+            difficulty.height(),
+            difficulty.mines(),
+            self.options
+                .seed
+                .map(|seed| seed.to_string())
+                .unwrap_or_default(),
+            result_name(self.result),
+            self.time,
+            self.assisted,
+            self.options.learning,
+        )
+    }
+}
+
+/// Board drawn with one flat square per cell on a canvas of its own
+fn thumbnail(view: &GameView) -> Option<String> {
+    let (width, height) = (view.width(), view.height());
+    let cell = (THUMBNAIL_SIZE / width.max(height)).clamp(1, THUMBNAIL_MAX_CELL);
+    let canvas: HtmlCanvasElement = document().create_element("canvas").ok()?.dyn_into().ok()?;
+    canvas.set_width((width * cell) as u32);
+    canvas.set_height((height * cell) as u32);
+    let ctx: CanvasRenderingContext2d = canvas.get_context("2d").ok()??.dyn_into().ok()?;
+    for y in 0..height {
+        for x in 0..width {
+            let color = match view.cell(x, y) {
+                CellView::Opened(0) => "#ffffff",
+                CellView::Opened(_) => "#dbeafe",
+                CellView::Flagged | CellView::Questioned => "#f97316",
+                CellView::Mine => "#404040",
+                CellView::WrongMine => "#a855f7",
+                CellView::Exploded => "#ef4444",
+                CellView::Unopened | CellView::Hovered | CellView::Pushed => "#d4d4d4",
+            };
+            ctx.set_fill_style(&color.into());
+            ctx.fill_rect(
+                (x * cell) as f64,
+                (y * cell) as f64,
+                cell as f64,
+                cell as f64,
+            );
+        }
+    }
+    canvas.to_data_url().ok()
+}
+
+pub fn difficulty_name(difficulty: &Difficulty) -> &'static str {
+    match difficulty {
+        Difficulty::Easy => "easy",
+        Difficulty::Medium => "medium",
+        Difficulty::Hard => "hard",
+        Difficulty::Custom { .. } => "custom",
+        Difficulty::Density { .. } => "density",
+    }
+}
+
+fn result_name(result: GameResult) -> &'static str {
+    match result {
+        GameResult::Win => "win",
+        GameResult::Lose => "lose",
+        GameResult::Playing => "playing",
+    }
+}
+
+pub fn format_time(time: u64) -> String {
+    format!("{:02}:{:02}", time / 60, time % 60)
+}
+
+#[component]
+pub fn HistoryDrawer(
+    view: RwSignal<MaybeUninitGameView>,
+    redraw: RwSignal<RedrawCells>,
+    time: Signal<u64>,
+    drawer_ref: NodeRef<html::Custom>,
+    /// Save the game in progress before it gets replaced
+    stash: Trigger,
+    checkpoint: Trigger,
+) -> impl IntoView {
+    let entries = create_rw_signal(Vec::<HistoryEntry>::new());
+    let reload = move || {
+        spawn_local(async move {
+            match storage::get_all::<HistoryEntry>(HISTORY_STORE).await {
+                Ok(all) => entries.set(all),
+                Err(err) => log!("history load failed {err:?}"),
+            }
+        })
+    };
+    reload();
+    let save = move |entry: HistoryEntry| {
+        spawn_local(async move {
+            match storage::put(HISTORY_STORE, &entry).await {
+                Ok(()) => reload(),
+                Err(err) => log!("history save failed {err:?}"),
+            }
+        });
+    };
+
+    // replay being played back and index of the next action
+    let playback = create_rw_signal(None::<(Replay, usize)>);
+
+    // record finished games
+    create_effect(move |previous_result| {
+        let result = with!(|view| match view {
+            MaybeUninitGameView::GameView(view) => Some(view.result),
+            _ => None,
+        });
+        if previous_result == Some(Some(GameResult::Playing))
+            && matches!(result, Some(GameResult::Win | GameResult::Lose))
+            && playback.with_untracked(Option::is_none)
+        {
+            let entry = view.with_untracked(|view| match view {
+                MaybeUninitGameView::GameView(view) => {
+                    Some(HistoryEntry::new(view, time.get_untracked()))
+                }
+                _ => None,
+            });
+            if let Some(entry) = entry {
+                save(entry);
+            }
+        }
+        result
+    });
+
+    // record abandoned games so they can be resumed
+    create_effect(move |first| {
+        stash.track();
+        if first.is_none() || playback.with_untracked(Option::is_some) {
+            return;
+        }
+        let entry = view.with_untracked(|view| match view {
+            MaybeUninitGameView::GameView(view) if view.result == GameResult::Playing => {
+                Some(HistoryEntry::new(view, time.get_untracked()))
+            }
+            _ => None,
+        });
+        if let Some(entry) = entry {
+            save(entry);
+        }
+    });
+
+    let UseIntervalReturn { counter: tick, .. } = use_interval(PLAYBACK_INTERVAL);
+    create_effect(move |_| {
+        tick.track();
+        let Some((replay, index)) = playback.get_untracked() else {
+            return;
+        };
+        // stop when another game took over the board
+        let same_game = with!(|view| match view {
+            MaybeUninitGameView::GameView(view) => view.options() == replay.options,
+            _ => false,
+        });
+        let Some(action) = replay.actions.get(index).filter(|_| same_game) else {
+            playback.set(None);
+            return;
+        };
+        let mut next_redraw = Default::default();
+        update!(|view| next_redraw = view.apply(action));
+        redraw.set(next_redraw);
+        playback.set(Some((replay, index + 1)));
+    });
+
+    let launch = move |replay: Replay| {
+        let Ok(start) = replay.start() else {
+            return;
+        };
+        drawer_hide(drawer_ref);
+        view.set(MaybeUninitGameView::GameView(start));
+        redraw.set(RedrawCells::redraw_all());
+        playback.set(Some((replay, 0)));
+    };
+    let remove = move |id: f64| {
+        spawn_local(async move {
+            match storage::delete(HISTORY_STORE, id).await {
+                Ok(()) => reload(),
+                Err(err) => log!("history delete failed {err:?}"),
+            }
+        })
+    };
+    let spectate = move |replay: Replay| {
+        let Ok(spectator) = Spectator::new(replay) else {
+            return;
+        };
+        drawer_hide(drawer_ref);
+        view.set(MaybeUninitGameView::Spectator(spectator));
+        redraw.set(RedrawCells::redraw_all());
+    };
+    let load = move || {
+        spawn_local(async move {
+            if let Some(replay) = pick_text_file()
+                .await
+                .and_then(|json| replay_from_json(&json))
+            {
+                spectate(replay);
+            }
+        });
+    };
+    let resume = move |id: f64, replay: Replay| {
+        let Ok(game) = replay.finish() else {
+            return;
+        };
+        drawer_hide(drawer_ref);
+        view.set(MaybeUninitGameView::GameView(game));
+        redraw.set(RedrawCells::redraw_all());
+        remove(id);
+    };
+
+    let (difficulty_filter, set_difficulty_filter) = create_signal("all".to_string());
+    let (result_filter, set_result_filter) = create_signal("all".to_string());
+    let (sort, set_sort) = create_signal("newest".to_string());
+    let filtered = move || {
+        let difficulty_filter = difficulty_filter();
+        let result_filter = result_filter();
+        let mut filtered: Vec<_> = entries()
+            .into_iter()
+            .filter(|entry| {
+                (difficulty_filter == "all"
+                    || difficulty_filter == difficulty_name(&entry.options.difficulty))
+                    && (result_filter == "all" || result_filter == result_name(entry.result))
+            })
+            .collect();
+        match sort().as_str() {
+            "oldest" => filtered.sort_by(|a, b| a.id.total_cmp(&b.id)),
+            "fastest" => filtered.sort_by_key(|entry| entry.time),
+            _ => filtered.sort_by(|a, b| b.id.total_cmp(&a.id)),
+        }
+        filtered
+    };
+    let export_csv = move || {
+        let mut csv =
+            "date,difficulty,width,height,mines,seed,result,time,assisted,learning\n".to_string();
+        for entry in filtered() {
+            csv += &entry.csv_row();
+            csv += "\n";
+        }
+        download("history.csv", "text/csv", &csv);
+    };
+
+    let (class_name, style_val) = style_str! {
+        #history-drawer {
+            --size: 60vw;
+        }
+        #history-filters {
+            display: flex;
+            flex-direction: row;
+            gap: 1rem;
+        }
+        #history-table, #checkpoint-table {
+            width: 100%;
+            text-align: left;
+        }
+        .history-thumbnail {
+            display: block;
+            image-rendering: pixelated;
+        }
+    };
+    view! {
+        class = class_name,
+        <Style> { style_val } </Style>
+        <sl-drawer label="History" id="history-drawer" class="non-draggable" ref=drawer_ref on:mousedown=move |ev| ev.stop_propagation()>
+            <div id="history-filters">
+                <sl-select label="Difficulty" value="all" on:sl-change=move |ev: JsValue| set_difficulty_filter(select_value(&ev))>
+                    <sl-option value="all"> "All" </sl-option>
+                    <sl-option value="easy"> "Easy" </sl-option>
+                    <sl-option value="medium"> "Medium" </sl-option>
+                    <sl-option value="hard"> "Hard" </sl-option>
+                    <sl-option value="custom"> "Custom" </sl-option>
+                </sl-select>
+                <sl-select label="Result" value="all" on:sl-change=move |ev: JsValue| set_result_filter(select_value(&ev))>
+                    <sl-option value="all"> "All" </sl-option>
+                    <sl-option value="win"> "Win" </sl-option>
+                    <sl-option value="lose"> "Lose" </sl-option>
+                    <sl-option value="playing"> "Unfinished" </sl-option>
+                </sl-select>
+                <sl-select label="Sort" value="newest" on:sl-change=move |ev: JsValue| set_sort(select_value(&ev))>
+                    <sl-option value="newest"> "Newest" </sl-option>
+                    <sl-option value="oldest"> "Oldest" </sl-option>
+                    <sl-option value="fastest"> "Fastest" </sl-option>
+                </sl-select>
+            </div> <br />
+            <Checkpoints view redraw time drawer_ref checkpoint />
+            <table id="history-table">
+                <tr>
+                    <th></th>
+                    <th> "Date" </th>
+                    <th> "Difficulty" </th>
+                    <th> "Result" </th>
+                    <th> "Time" </th>
+                    <th> "Assisted" </th>
+                    <th></th>
+                </tr>
+                { move || filtered().into_iter().map(|entry| {
+                    let HistoryEntry { id, result, time, assisted, .. } = entry;
+                    view! {
+                        <tr>
+                            <td> { entry.thumbnail.clone().map(|src| view! {
+                                class = class_name,
+                                <img class="history-thumbnail" src=src alt="Final position" />
+                            }) } </td>
+                            <td> { entry.date() } </td>
+                            <td> { difficulty_name(&entry.options.difficulty) } </td>
+                            <td> { result_name(result) } </td>
+                            <td> { format_time(time) } </td>
+                            <td> { if assisted { "yes" } else { "no" } } </td>
+                            <td>
+                                { (result == GameResult::Playing).then(|| {
+                                    let replay = entry.replay.clone();
+                                    view! {
+                                        <sl-button size="small" variant="primary" on:click=move |_| resume(id, replay.clone())> "Resume" </sl-button>
+                                    }
+                                }) }
+                                <sl-button size="small" on:click={
+                                    let replay = entry.replay.clone();
+                                    move |_| launch(replay.clone())
+                                }> "Replay" </sl-button>
+                                <sl-button size="small" on:click={
+                                    let replay = entry.replay.clone();
+                                    move |_| spectate(replay.clone())
+                                }> "Spectate" </sl-button>
+                                <sl-icon-button name="share" label="Share" on:click={
+                                    let replay = entry.replay.clone();
+                                    move |_| download("replay.json", "application/json", &replay_to_json(&replay))
+                                }></sl-icon-button>
+                                <sl-icon-button name="trash" label="Delete" on:click=move |_| remove(id)></sl-icon-button>
+                            </td>
+                        </tr>
+                    }
+                }).collect_view() }
+            </table>
+            <sl-button slot="footer" on:click=move |_| load()> "Load Replay" </sl-button>
+            <sl-button slot="footer" on:click=move |_| export_csv()> "Export CSV" </sl-button>
+            <sl-button slot="footer" on:click=move |_| drawer_hide(drawer_ref)> "Close" </sl-button>
+        </sl-drawer>
+    }
+}
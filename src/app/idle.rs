@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+use leptos::*;
+use leptos_meta::*;
+use serde::{Deserialize, Serialize};
+use stylers::style_str;
+use wasm_bindgen::JsValue;
+
+use minesweep_core::GameResult;
+
+use super::{now, select_value, storage, MaybeUninitGameView};
+
+/// Key of the pause settings in the settings store
+const PAUSE_SETTINGS_ID: f64 = 3.;
+/// Seconds without input before the game pauses, offered in the settings
+const IDLE_CHOICES: [(u32, &str); 5] = [
+    (0, "Never"),
+    (30, "30 seconds"),
+    (60, "1 minute"),
+    (120, "2 minutes"),
+    (300, "5 minutes"),
+];
+
+/// When the clock of a game in progress stops by itself
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PauseSettings {
+    /// Always `PAUSE_SETTINGS_ID`, the key in the settings store
+    id: f64,
+    /// Seconds without input, `0` never pauses
+    pub idle: u32,
+    /// Pause when the window loses focus
+    pub blur: bool,
+}
+
+impl Default for PauseSettings {
+    fn default() -> Self {
+        Self {
+            id: PAUSE_SETTINGS_ID,
+            idle: 60,
+            blur: true,
+        }
+    }
+}
+
+/// Pause settings saved in the browser, loaded into a signal
+pub fn persisted_pause_settings() -> RwSignal<PauseSettings> {
+    storage::persisted_setting(PAUSE_SETTINGS_ID, PauseSettings::default())
+}
+
+fn pause(view: RwSignal<MaybeUninitGameView>) {
+    let playing = view.with_untracked(|view| match view {
+        MaybeUninitGameView::GameView(view) => {
+            view.result == GameResult::Playing && !view.is_paused()
+        }
+        _ => false,
+    });
+    if playing {
+        view.update(|view| {
+            if let MaybeUninitGameView::GameView(view) = view {
+                view.pause(now());
+            }
+        });
+    }
+}
+
+/// Pause the game in progress after the input stops for long enough or the window blurs,
+/// as the settings ask
+pub fn pause_when_away(view: RwSignal<MaybeUninitGameView>, settings: RwSignal<PauseSettings>) {
+    let last_input = store_value(now());
+    let active = move || last_input.set_value(now());
+    window_event_listener(ev::pointerdown, move |_| active());
+    window_event_listener(ev::pointermove, move |_| active());
+    window_event_listener(ev::keydown, move |_| active());
+    window_event_listener(ev::wheel, move |_| active());
+    window_event_listener(ev::blur, move |_| {
+        if settings.with_untracked(|settings| settings.blur) {
+            pause(view);
+        }
+    });
+    let _ = set_interval_with_handle(
+        move || {
+            let idle = settings.with_untracked(|settings| settings.idle) as u64;
+            if idle > 0 && now().saturating_sub(last_input.get_value()) >= idle * 1000 {
+                pause(view);
+            }
+        },
+        Duration::from_secs(1),
+    );
+}
+
+/// Cover over the paused board, the game goes on when clicked
+#[component]
+pub fn ResumeOverlay(view: RwSignal<MaybeUninitGameView>) -> impl IntoView {
+    let paused = move || {
+        with!(|view| match view {
+            MaybeUninitGameView::GameView(view) => view.is_paused(),
+            _ => false,
+        })
+    };
+    let resume = move |_| {
+        view.update(|view| {
+            if let MaybeUninitGameView::GameView(view) = view {
+                view.resume(now());
+            }
+        })
+    };
+    let (class_name, style_val) = style_str! {
+        #resume-overlay {
+            position: absolute;
+            inset: 0;
+            z-index: 20;
+            display: flex;
+            flex-direction: column;
+            align-items: center;
+            justify-content: center;
+            gap: 1rem;
+            background-color: rgb(0 0 0 / 0.5);
+            color: white;
+            font-size: var(--sl-font-size-x-large);
+            cursor: pointer;
+        }
+    };
+    view! {
+        class = class_name,
+        <Style> { style_val } </Style>
+        <Show when=paused>
+            <div id="resume-overlay" on:mousedown=move |ev| ev.stop_propagation() on:click=resume>
+                "Paused"
+                <sl-button variant="primary"> "Resume" </sl-button>
+            </div>
+        </Show>
+    }
+}
+
+#[component]
+pub fn PauseControls(settings: RwSignal<PauseSettings>) -> impl IntoView {
+    view! {
+        <sl-select label="Pause When Idle" size="small" prop:value=move || with!(|settings| settings.idle.to_string()) on:sl-change=move |ev: JsValue| {
+            if let Ok(idle) = select_value(&ev).parse() {
+                settings.update(|settings| settings.idle = idle);
+            }
+        }>
+            { IDLE_CHOICES.into_iter().map(|(seconds, label)| view! {
+                <sl-option value=seconds.to_string()> { label } </sl-option>
+            }).collect_view() }
+        </sl-select>
+        <sl-switch size="small" prop:checked=move || with!(|settings| settings.blur) on:sl-change=move |ev: JsValue| {
+            let target = js_sys::Reflect::get(&ev, &"target".into()).unwrap();
+            let checked = js_sys::Reflect::get(&target, &"checked".into()).unwrap().as_bool().unwrap();
+            settings.update(|settings| settings.blur = checked);
+        }> "Pause On Blur" </sl-switch>
+    }
+}
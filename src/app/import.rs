@@ -0,0 +1,32 @@
+use minesweep_core::GameState;
+
+use super::spectator::{replay_from_json, Spectator};
+use super::MaybeUninitGameView;
+
+/// Whether pasted text is meant as a board, only mines, safe cells and line breaks
+pub fn is_board_text(text: &str) -> bool {
+    text.contains(['*', '.'])
+        && text
+            .chars()
+            .all(|c| matches!(c, '*' | '.') || c.is_whitespace())
+}
+
+/// Board of a replay file opened in the replay viewer, or a board in text or MBF loaded into the
+/// editor to be tested or played
+pub fn import(bytes: &[u8]) -> Result<MaybeUninitGameView, String> {
+    let text = std::str::from_utf8(bytes).ok();
+    if let Some(json) = text.filter(|text| text.trim_start().starts_with('{')) {
+        let replay = replay_from_json(json).ok_or("not a replay")?;
+        let spectator = Spectator::new(replay).map_err(|err| err.to_string())?;
+        return Ok(MaybeUninitGameView::Spectator(spectator));
+    }
+    let text_error = match text.map(GameState::from_board_text) {
+        Some(Ok(state)) => return Ok(MaybeUninitGameView::Editor(state)),
+        Some(Err(err)) => Some(err),
+        None => None,
+    };
+    GameState::from_mbf(bytes)
+        .map(MaybeUninitGameView::Editor)
+        // text that is no board says more than a failed binary read of it
+        .map_err(|err| text_error.unwrap_or(err).to_string())
+}
@@ -0,0 +1,71 @@
+use std::collections::VecDeque;
+
+use minesweep_core::{Action, GameView};
+
+/// Next move of an automation step held back for inspection, with the cells proving it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inspection {
+    pub action: Action,
+    /// `None` when the numbers around the cell do not settle it
+    pub reasons: Option<Vec<(usize, usize)>>,
+}
+
+impl Inspection {
+    pub fn cell(&self) -> (usize, usize) {
+        match self.action {
+            Action::LeftClick(x, y) | Action::RightClick(x, y) => (x, y),
+            _ => unreachable!("only clicks are inspected"),
+        }
+    }
+
+    pub fn is_mine(&self) -> bool {
+        matches!(self.action, Action::RightClick(..))
+    }
+
+    /// Cells drawn differently while this is shown
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        std::iter::once(self.cell()).chain(self.reasons.iter().flatten().copied())
+    }
+
+    pub fn describe(&self) -> String {
+        let (x, y) = self.cell();
+        let verb = match self.is_mine() {
+            true => "Flag",
+            false => "Open",
+        };
+        let reason = match &self.reasons {
+            Some(reasons) => format!("proven by {} cells", reasons.len()),
+            None => "not proven by the numbers".to_string(),
+        };
+        format!("{verb} ({x}, {y}), {reason}")
+    }
+}
+
+/// Opens and flags of an automation step, one per cell, chords are left to later steps
+pub fn queue_step(action: &Action) -> VecDeque<Action> {
+    let Action::Automation(actions) = action else {
+        return VecDeque::new();
+    };
+    actions
+        .iter()
+        .filter(|action| matches!(action, Action::LeftClick(..) | Action::RightClick(..)))
+        .cloned()
+        .collect()
+}
+
+/// Take queued moves until one still has an intact cell to act on, cells opened by an earlier
+/// move of the step are skipped
+pub fn next_inspection(view: &GameView, queue: &mut VecDeque<Action>) -> Option<Inspection> {
+    while let Some(action) = queue.pop_front() {
+        let (Action::LeftClick(x, y) | Action::RightClick(x, y)) = action else {
+            continue;
+        };
+        if view.cell(x, y).is_intact() {
+            return Some(Inspection {
+                reasons: view.explain(x, y),
+                action,
+            });
+        }
+    }
+    None
+}
@@ -0,0 +1,186 @@
+use leptos::*;
+use leptos_meta::*;
+use multiplayer_protocol::{Entry, Submission};
+use stylers::style_str;
+use wasm_bindgen::prelude::*;
+
+use minesweep_core::{RedrawCells, Replay};
+
+use super::history::{difficulty_name, format_time};
+use super::spectator::Spectator;
+use super::{drawer_hide, read_text_untracked, select_value, MaybeUninitGameView};
+
+/// Secret shared with the server for signing results, set when building the app
+const LEADERBOARD_KEY: &str = match option_env!("LEADERBOARD_KEY") {
+    Some(key) => key,
+    None => "",
+};
+
+#[wasm_bindgen(inline_js = "export function fetch_text_ffi(url, body) { \
+    const init = body === undefined ? {} \
+        : { method: 'POST', headers: { 'Content-Type': 'application/json' }, body }; \
+    return fetch(url, init).then((response) => response.text().then((text) => { \
+        if (!response.ok) throw new Error(text || response.statusText); \
+        return text; \
+    })); \
+}")]
+extern "C" {
+    #[wasm_bindgen(catch)]
+    async fn fetch_text_ffi(url: &str, body: Option<String>) -> Result<JsValue, JsValue>;
+}
+
+/// GET `url`, or POST `body` as JSON to it, and read the response as text
+async fn fetch_text(url: &str, body: Option<String>) -> Result<String, String> {
+    match fetch_text_ffi(url, body).await {
+        Ok(text) => Ok(text.as_string().unwrap_or_default()),
+        Err(err) => Err(js_sys::Error::from(err).message().into()),
+    }
+}
+
+fn default_server() -> String {
+    let host = window().location().hostname().unwrap_or_default();
+    format!(
+        "http://{}:3000",
+        if host.is_empty() { "localhost" } else { &host }
+    )
+}
+
+#[component]
+pub fn LeaderboardDrawer(
+    drawer_ref: NodeRef<html::Custom>,
+    view: RwSignal<MaybeUninitGameView>,
+    redraw: RwSignal<RedrawCells>,
+    time: Signal<u64>,
+    /// Name filled in for submissions, the profile in use
+    player: String,
+) -> impl IntoView {
+    let server_ref: NodeRef<html::Custom> = create_node_ref();
+    let name_ref: NodeRef<html::Custom> = create_node_ref();
+    let (difficulty, set_difficulty) = create_signal("all".to_string());
+    let entries = create_rw_signal(Vec::<Entry>::new());
+    let message = create_rw_signal(String::new());
+    let server = move || {
+        read_text_untracked(server_ref)
+            .trim_end_matches('/')
+            .to_string()
+    };
+
+    let refresh = move || {
+        let url = match difficulty.get_untracked().as_str() {
+            "all" => format!("{}/leaderboard", server()),
+            difficulty => format!("{}/leaderboard?difficulty={difficulty}", server()),
+        };
+        spawn_local(async move {
+            let result = fetch_text(&url, None).await.and_then(|json| {
+                serde_json::from_str::<Vec<Entry>>(&json).map_err(|err| err.to_string())
+            });
+            match result {
+                Ok(ranking) => entries.set(ranking),
+                Err(err) => message.set(format!("Loading rankings failed: {err}")),
+            }
+        });
+    };
+    let submit = move || {
+        let name = read_text_untracked(name_ref);
+        if name.trim().is_empty() {
+            message.set("Enter a name first".to_string());
+            return;
+        }
+        let submission = view.with_untracked(|view| match view {
+            MaybeUninitGameView::GameView(view) => {
+                Submission::new(name, view, time.get_untracked(), LEADERBOARD_KEY)
+            }
+            _ => None,
+        });
+        let Some(submission) = submission else {
+            message
+                .set("Only games won without assistance on standard boards are ranked".to_string());
+            return;
+        };
+        let url = format!("{}/leaderboard", server());
+        let body = serde_json::to_string(&submission).unwrap();
+        spawn_local(async move {
+            match fetch_text(&url, Some(body)).await {
+                Ok(_) => {
+                    message.set("Result submitted".to_string());
+                    refresh();
+                }
+                Err(err) => message.set(format!("Submitting failed: {err}")),
+            }
+        });
+    };
+    // watch the uploaded replay of a result
+    let spectate = move |id: u64| {
+        let url = format!("{}/leaderboard/replay/{id}", server());
+        spawn_local(async move {
+            let result = fetch_text(&url, None).await.and_then(|json| {
+                let replay =
+                    serde_json::from_str::<Replay>(&json).map_err(|err| err.to_string())?;
+                Spectator::new(replay).map_err(|err| err.to_string())
+            });
+            let spectator = match result {
+                Ok(spectator) => spectator,
+                Err(err) => {
+                    message.set(format!("Loading replay failed: {err}"));
+                    return;
+                }
+            };
+            drawer_hide(drawer_ref);
+            view.set(MaybeUninitGameView::Spectator(spectator));
+            redraw.set(RedrawCells::redraw_all());
+        });
+    };
+
+    let (class_name, style_val) = style_str! {
+        #leaderboard-drawer {
+            --size: 50vw;
+        }
+        #leaderboard-table {
+            width: 100%;
+            text-align: left;
+        }
+    };
+    view! {
+        class = class_name,
+        <Style> { style_val } </Style>
+        <sl-drawer label="Leaderboard" id="leaderboard-drawer" class="non-draggable" ref=drawer_ref on:mousedown=move |ev| ev.stop_propagation() on:sl-show=move |_: JsValue| refresh()>
+            <sl-input label="Server" ref=server_ref value=default_server()></sl-input> <br />
+            <sl-input label="Name" ref=name_ref value=player></sl-input> <br />
+            <sl-select label="Difficulty" value="all" on:sl-change=move |ev: JsValue| {
+                set_difficulty(select_value(&ev));
+                refresh();
+            }>
+                <sl-option value="all"> "All" </sl-option>
+                <sl-option value="easy"> "Easy" </sl-option>
+                <sl-option value="medium"> "Medium" </sl-option>
+                <sl-option value="hard"> "Hard" </sl-option>
+            </sl-select> <br />
+            <p> { message } </p>
+            <table id="leaderboard-table">
+                <tr>
+                    <th> "Rank" </th>
+                    <th> "Name" </th>
+                    <th> "Difficulty" </th>
+                    <th> "Time" </th>
+                    <th> "Seed" </th>
+                    <th></th>
+                </tr>
+                { move || entries().into_iter().enumerate().map(|(rank, entry)| view! {
+                    <tr>
+                        <td> { rank + 1 } </td>
+                        <td> { entry.name } </td>
+                        <td> { difficulty_name(&entry.difficulty) } </td>
+                        <td> { format_time(entry.time) } </td>
+                        <td> { entry.seed.to_string() } </td>
+                        <td>
+                            <sl-button size="small" on:click=move |_| spectate(entry.id)> "Spectate" </sl-button>
+                        </td>
+                    </tr>
+                }).collect_view() }
+            </table>
+            <sl-button slot="footer" variant="primary" on:click=move |_| submit()> "Submit Current Game" </sl-button>
+            <sl-button slot="footer" on:click=move |_| refresh()> "Refresh" </sl-button>
+            <sl-button slot="footer" on:click=move |_| drawer_hide(drawer_ref)> "Close" </sl-button>
+        </sl-drawer>
+    }
+}
@@ -0,0 +1,65 @@
+use js_sys::Reflect;
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+use super::storage;
+
+/// Key of the motion settings in the settings store
+const MOTION_SETTINGS_ID: f64 = 1.;
+
+/// Feedback the board draws as the pointer moves, read by the renderer
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MotionSettings {
+    /// Always `MOTION_SETTINGS_ID`, the key in the settings store
+    id: f64,
+    /// Lighten the cell under the pointer
+    pub hover: bool,
+    /// Darken the cells about to be opened while a button is held
+    pub press: bool,
+}
+
+impl MotionSettings {
+    fn all(on: bool) -> Self {
+        Self {
+            id: MOTION_SETTINGS_ID,
+            hover: on,
+            press: on,
+        }
+    }
+
+    /// Everything on, unless the system asks for reduced motion
+    fn system() -> Self {
+        let reduce = window()
+            .match_media("(prefers-reduced-motion: reduce)")
+            .ok()
+            .flatten()
+            .is_some_and(|query| query.matches());
+        Self::all(!reduce)
+    }
+}
+
+/// Motion settings saved in the browser, following `prefers-reduced-motion` until changed
+pub fn persisted_motion_settings() -> RwSignal<MotionSettings> {
+    storage::persisted_setting(MOTION_SETTINGS_ID, MotionSettings::system())
+}
+
+#[component]
+pub fn MotionControls(settings: RwSignal<MotionSettings>) -> impl IntoView {
+    let switch = move |label: &'static str,
+                       get: fn(&MotionSettings) -> bool,
+                       set: fn(&mut MotionSettings, bool)| {
+        view! {
+            <sl-switch size="small" prop:checked=move || with!(|settings| get(settings)) on:sl-change=move |ev: JsValue| {
+                let target = Reflect::get(&ev, &"target".into()).unwrap();
+                let checked = Reflect::get(&target, &"checked".into()).unwrap().as_bool().unwrap();
+                settings.update(|settings| set(settings, checked));
+            }> { label } </sl-switch>
+        }
+    };
+    view! {
+        { switch("Hover Effects", |s| s.hover, |s, on| s.hover = on) }
+        { switch("Press Effects", |s| s.press, |s, on| s.press = on) }
+        <sl-button size="small" on:click=move |_| settings.set(MotionSettings::all(false))> "Reduce Motion" </sl-button>
+    }
+}
@@ -0,0 +1,116 @@
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+use super::{select_value, storage};
+
+/// Key of the mouse settings in the settings store
+const MOUSE_SETTINGS_ID: f64 = 0.;
+
+/// What pressing a mouse button on the board does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ButtonAction {
+    /// Open the cell released over, or pan when pressed on an opened cell or off the board
+    Open,
+    Flag,
+    Chord,
+    /// Pan wherever pressed, without touching cells
+    Pan,
+}
+
+impl ButtonAction {
+    const ALL: [ButtonAction; 4] = [Self::Open, Self::Flag, Self::Chord, Self::Pan];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Flag => "flag",
+            Self::Chord => "chord",
+            Self::Pan => "pan",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Open => "Open",
+            Self::Flag => "Flag",
+            Self::Chord => "Chord",
+            Self::Pan => "Pan",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MouseSettings {
+    /// Always `MOUSE_SETTINGS_ID`, the key in the settings store
+    id: f64,
+    pub left: ButtonAction,
+    pub middle: ButtonAction,
+    pub right: ButtonAction,
+}
+
+impl Default for MouseSettings {
+    fn default() -> Self {
+        Self {
+            id: MOUSE_SETTINGS_ID,
+            left: ButtonAction::Open,
+            middle: ButtonAction::Chord,
+            right: ButtonAction::Flag,
+        }
+    }
+}
+
+impl MouseSettings {
+    /// Action of a `MouseEvent::button`, `None` for the back and forward buttons
+    pub fn action(&self, button: i16) -> Option<ButtonAction> {
+        match button {
+            0 => Some(self.left),
+            1 => Some(self.middle),
+            2 => Some(self.right),
+            _ => None,
+        }
+    }
+
+    fn swap_sides(self) -> Self {
+        Self {
+            left: self.right,
+            right: self.left,
+            ..self
+        }
+    }
+}
+
+/// Mouse settings saved in the browser, loaded into a signal that saves them on change
+pub fn persisted_mouse_settings() -> RwSignal<MouseSettings> {
+    storage::persisted_setting(MOUSE_SETTINGS_ID, MouseSettings::default())
+}
+
+#[component]
+pub fn MouseControls(settings: RwSignal<MouseSettings>) -> impl IntoView {
+    let select = move |label: &'static str,
+                       get: fn(&MouseSettings) -> ButtonAction,
+                       set: fn(&mut MouseSettings, ButtonAction)| {
+        view! {
+            <sl-select label=label size="small" prop:value=move || with!(|settings| get(settings).name()) on:sl-change=move |ev: JsValue| {
+                if let Some(action) = ButtonAction::from_name(&select_value(&ev)) {
+                    settings.update(|settings| set(settings, action));
+                }
+            }>
+                { ButtonAction::ALL.map(|action| view! {
+                    <sl-option value=action.name()> { action.label() } </sl-option>
+                }).collect_view() }
+            </sl-select>
+        }
+    };
+    view! {
+        { select("Left Button", |s| s.left, |s, a| s.left = a) }
+        { select("Middle Button", |s| s.middle, |s, a| s.middle = a) }
+        { select("Right Button", |s| s.right, |s, a| s.right = a) }
+        <sl-button size="small" on:click=move |_| settings.update(|s| *s = s.swap_sides())> "Swap Left and Right" </sl-button>
+        <sl-button size="small" on:click=move |_| settings.set(MouseSettings::default())> "Reset" </sl-button>
+    }
+}
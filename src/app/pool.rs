@@ -0,0 +1,89 @@
+use std::cmp::Reverse;
+
+use automation_protocol::{AutomationRequest, AutomationResponse, VarintBincode};
+use automation_worker::Automation;
+use futures::{future::join_all, SinkExt};
+use gloo_worker::{reactor::ReactorBridge, Spawnable};
+use leptos::window;
+use minesweep_core::{GameView, SolveResult};
+
+use super::{health, timestamp};
+
+/// Boards smaller than expert are solved faster than they are copied to every worker
+pub const POOL_MIN_CELLS: usize = 30 * 16;
+const MAX_WORKERS: usize = 8;
+
+/// Logical processors available, at most the pool size
+pub fn cores() -> usize {
+    (window().navigator().hardware_concurrency() as usize).clamp(1, MAX_WORKERS)
+}
+
+pub fn spawn_worker() -> ReactorBridge<Automation> {
+    Automation::spawner()
+        .encoding::<VarintBincode>()
+        .spawn("./automation-worker.js")
+}
+
+/// Automation workers solving independent frontier components in parallel
+pub struct WorkerPool {
+    workers: Vec<ReactorBridge<Automation>>,
+}
+
+impl WorkerPool {
+    pub fn new() -> Self {
+        Self {
+            workers: (0..cores()).map(|_| spawn_worker()).collect(),
+        }
+    }
+
+    pub fn cancel(&self, id: u64) {
+        for worker in self.workers.iter() {
+            worker.send_input(AutomationRequest::Cancel { id });
+        }
+    }
+
+    /// Split the frontier over the workers by component, returns `None` once cancelled
+    /// or why a worker crashed
+    pub async fn solve(
+        &self,
+        id: u64,
+        view: &GameView,
+    ) -> Result<Option<(f64, SolveResult)>, String> {
+        let begin = timestamp();
+        let mut components = view.components();
+        components.sort_by_key(|component| Reverse(component.len()));
+        let mut shares = vec![Vec::new(); self.workers.len()];
+        for component in components {
+            let share = shares.iter_mut().min_by_key(|share| share.len()).unwrap();
+            share.extend(component);
+        }
+        let requests = self
+            .workers
+            .iter()
+            .zip(shares)
+            .filter(|(_, cells)| !cells.is_empty())
+            .map(|(worker, cells)| async move {
+                let mut bridge = worker.fork();
+                bridge
+                    .send(AutomationRequest::Solve {
+                        id,
+                        view: Box::new(view.clone()),
+                        cells,
+                    })
+                    .await
+                    .unwrap();
+                match health::next_response(worker, &mut bridge).await? {
+                    Some(AutomationResponse::Solved { result, .. }) => Ok(Some(result)),
+                    _ => Ok::<_, String>(None),
+                }
+            });
+        let mut merged = SolveResult::default();
+        for result in join_all(requests).await {
+            let Some(result) = result? else {
+                return Ok(None);
+            };
+            merged.merge(result);
+        }
+        Ok(Some((timestamp() - begin, merged)))
+    }
+}
@@ -0,0 +1,205 @@
+use js_sys::Date;
+use leptos::logging::log;
+use leptos::*;
+use leptos_meta::*;
+use serde::{Deserialize, Serialize};
+use stylers::style_str;
+
+use super::read_text_untracked;
+use super::storage::{self, PROFILE_STORE};
+
+/// Profile using the data saved before profiles were added
+const DEFAULT_PROFILE_ID: f64 = 0.;
+/// Session storage key of the profile chosen in this tab, kept across reloads
+const SESSION_KEY: &str = "profile";
+
+/// Player keeping settings, history and stats of their own
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    /// Milliseconds since epoch when the profile was added, `0` for the default profile
+    pub id: f64,
+    pub name: String,
+}
+
+impl Profile {
+    fn is_default(&self) -> bool {
+        self.id == DEFAULT_PROFILE_ID
+    }
+
+    /// Name filled in for leaderboards, none for the default profile
+    pub fn player_name(&self) -> String {
+        match self.is_default() {
+            true => String::new(),
+            false => self.name.clone(),
+        }
+    }
+}
+
+/// Every profile, the default one first even if never saved
+async fn load_profiles() -> Vec<Profile> {
+    let mut profiles = match storage::get_all::<Profile>(PROFILE_STORE).await {
+        Ok(profiles) => profiles,
+        Err(err) => {
+            log!("profiles load failed {err:?}");
+            vec![]
+        }
+    };
+    if !profiles.iter().any(Profile::is_default) {
+        profiles.insert(
+            0,
+            Profile {
+                id: DEFAULT_PROFILE_ID,
+                name: "Default".to_string(),
+            },
+        );
+    }
+    profiles
+}
+
+async fn add_profile(name: String) -> Option<Profile> {
+    let profile = Profile {
+        id: Date::now(),
+        name,
+    };
+    match storage::put(PROFILE_STORE, &profile).await {
+        Ok(()) => Some(profile),
+        Err(err) => {
+            log!("profile save failed {err:?}");
+            None
+        }
+    }
+}
+
+fn session_profile() -> Option<f64> {
+    window()
+        .session_storage()
+        .ok()??
+        .get_item(SESSION_KEY)
+        .ok()??
+        .parse()
+        .ok()
+}
+
+/// Start using `profile`, everything saved from now on is kept apart for it
+fn choose(profile: Profile, chosen: RwSignal<Option<Profile>>) {
+    storage::use_profile(profile.id);
+    if let Ok(Some(session)) = window().session_storage() {
+        let _ = session.set_item(SESSION_KEY, &profile.id.to_string());
+    }
+    chosen.set(Some(profile));
+}
+
+/// Reload the page with `profile`, the game and its saved data are set up again for it
+fn switch_to(profile: &Profile) {
+    if let Ok(Some(session)) = window().session_storage() {
+        let _ = session.set_item(SESSION_KEY, &profile.id.to_string());
+    }
+    let _ = window().location().reload();
+}
+
+/// Asks who is playing when there are several profiles, unless one was chosen in this tab,
+/// and sets `chosen` once decided
+#[component]
+pub fn ProfileChooser(chosen: RwSignal<Option<Profile>>) -> impl IntoView {
+    let profiles = create_rw_signal(Vec::<Profile>::new());
+    let name_ref: NodeRef<html::Custom> = create_node_ref();
+    spawn_local(async move {
+        let loaded = load_profiles().await;
+        let session = session_profile();
+        match loaded.iter().find(|profile| Some(profile.id) == session) {
+            Some(profile) => choose(profile.clone(), chosen),
+            None if loaded.len() == 1 => choose(loaded[0].clone(), chosen),
+            None => profiles.set(loaded),
+        }
+    });
+    let create = move |_| {
+        let name = read_text_untracked(name_ref).trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        spawn_local(async move {
+            if let Some(profile) = add_profile(name).await {
+                choose(profile, chosen);
+            }
+        });
+    };
+    let (class_name, style_val) = style_str! {
+        #profile-chooser {
+            position: fixed;
+            inset: 0;
+            display: flex;
+            flex-direction: column;
+            align-items: center;
+            justify-content: center;
+            gap: 0.5rem;
+        }
+    };
+    view! {
+        class = class_name,
+        <Style> { style_val } </Style>
+        <Show when=move || chosen.with(Option::is_none) && profiles.with(|profiles| !profiles.is_empty())>
+            <div id="profile-chooser">
+                <h2> "Who is playing?" </h2>
+                { move || profiles().into_iter().map(|profile| {
+                    let name = profile.name.clone();
+                    view! {
+                        class = class_name,
+                        <sl-button on:click=move |_| choose(profile.clone(), chosen)> { name } </sl-button>
+                    }
+                }).collect_view() }
+                <sl-input label="New Profile" ref=name_ref></sl-input>
+                <sl-button variant="primary" on:click=create> "Add and Play" </sl-button>
+            </div>
+        </Show>
+    }
+}
+
+/// Profiles listed for switching, adding or deleting, the one in use cannot be deleted
+#[component]
+pub fn ProfileDrawer(drawer_ref: NodeRef<html::Custom>, current: Profile) -> impl IntoView {
+    let profiles = create_rw_signal(Vec::<Profile>::new());
+    let name_ref: NodeRef<html::Custom> = create_node_ref();
+    let reload = move || spawn_local(async move { profiles.set(load_profiles().await) });
+    let create = move |_| {
+        let name = read_text_untracked(name_ref).trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        spawn_local(async move {
+            if add_profile(name).await.is_some() {
+                profiles.set(load_profiles().await);
+            }
+        });
+    };
+    let remove = move |id: f64| {
+        spawn_local(async move {
+            if let Err(err) = storage::delete(PROFILE_STORE, id).await {
+                log!("profile delete failed {err:?}");
+            }
+            if let Err(err) = storage::delete_profile(id).await {
+                log!("profile data delete failed {err:?}");
+            }
+            profiles.set(load_profiles().await);
+        });
+    };
+    let current_id = current.id;
+    view! {
+        <sl-drawer label="Profiles" id="profile-drawer" class="non-draggable" ref=drawer_ref on:mousedown=move |ev| ev.stop_propagation() on:sl-show=move |_: wasm_bindgen::JsValue| reload()>
+            <p> { format!("Playing as {}", current.name) } </p>
+            { move || profiles().into_iter().map(|profile| {
+                let id = profile.id;
+                let in_use = id == current_id;
+                let name = profile.name.clone();
+                view! {
+                    <p>
+                        { name }
+                        <sl-button size="small" disabled=in_use on:click=move |_| switch_to(&profile)> "Switch" </sl-button>
+                        <sl-icon-button name="trash" label="Delete" disabled=in_use || id == DEFAULT_PROFILE_ID on:click=move |_| remove(id)></sl-icon-button>
+                    </p>
+                }
+            }).collect_view() }
+            <sl-input label="New Profile" ref=name_ref></sl-input> <br />
+            <sl-button on:click=create> "Add" </sl-button>
+        </sl-drawer>
+    }
+}
@@ -0,0 +1,227 @@
+use leptos::*;
+use leptos_meta::*;
+use leptos_use::{use_clipboard, UseClipboardReturn};
+use stylers::style_str;
+use wasm_bindgen::prelude::*;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+use minesweep_core::{GameOptions, GameResult, GameView};
+
+use super::history::{difficulty_name, format_time};
+use super::{drawer_hide, drawer_show, MaybeUninitGameView};
+
+/// Fragment of a link that starts the same board, followed by the options as JSON
+const GAME_FRAGMENT: &str = "#game=";
+const CARD_WIDTH: u32 = 480;
+const CARD_LINE_HEIGHT: f64 = 28.;
+
+#[wasm_bindgen(inline_js = "export function download_url_ffi(name, url) { \
+    const a = document.createElement('a'); \
+    a.href = url; \
+    a.download = name; \
+    a.click(); \
+}")]
+extern "C" {
+    fn download_url_ffi(name: &str, url: &str);
+}
+
+/// Options of the board a shared link points to, the link is taken out of the address bar
+pub fn shared_game() -> Option<GameOptions> {
+    let hash = window().location().hash().ok()?;
+    let json: String = js_sys::decode_uri_component(hash.strip_prefix(GAME_FRAGMENT)?)
+        .ok()?
+        .into();
+    let options = serde_json::from_str(&json).ok()?;
+    // the link served its purpose, reloading starts a new game
+    let path = window().location().pathname().ok()?;
+    let _ = window()
+        .history()
+        .ok()?
+        .replace_state_with_url(&JsValue::NULL, "", Some(&path));
+    Some(options)
+}
+
+/// Link that starts the same board
+pub fn shared_link(options: &GameOptions) -> Option<String> {
+    let location = window().location();
+    let json = serde_json::to_string(options).ok()?;
+    let encoded: String = js_sys::encode_uri_component(&json).into();
+    Some(format!(
+        "{}{}{GAME_FRAGMENT}{encoded}",
+        location.origin().ok()?,
+        location.pathname().ok()?,
+    ))
+}
+
+/// What the result card shows of a finished game
+#[derive(Debug, Clone, PartialEq)]
+struct Summary {
+    win: bool,
+    difficulty: String,
+    time: u64,
+    bbbv: usize,
+    guesses: Option<usize>,
+    assists: Vec<&'static str>,
+    link: Option<String>,
+}
+
+impl Summary {
+    fn new(view: &GameView, time: u64) -> Self {
+        let options = view.options();
+        let difficulty = &options.difficulty;
+        let assistance = view.assistance();
+        let assists = [
+            (assistance.automation, "automation"),
+            (assistance.undo, "undo"),
+            (assistance.hints, "hints"),
+            (assistance.safe_chord, "safe chord"),
+            (assistance.learning, "learning mode"),
+        ]
+        .into_iter()
+        .filter_map(|(used, name)| used.then_some(name))
+        .collect();
+        Self {
+            win: view.result == GameResult::Win,
+            difficulty: format!(
+                "{} {}×{}, {} mines",
+                difficulty_name(difficulty),
+                difficulty.width(),
+                difficulty.height(),
+                difficulty.mines(),
+            ),
+            time,
+            bbbv: view.bbbv(),
+            guesses: view.replay().guesses().ok(),
+            assists,
+            link: shared_link(&options),
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self.win {
+            true => "Win 😎",
+            false => "Lose 😵",
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        // a game over within the first second still shows its 3BV
+        let rate = match self.time {
+            0 => "-".to_string(),
+            time => format!("{:.2}", self.bbbv as f64 / time as f64),
+        };
+        let guesses = match self.guesses {
+            Some(guesses) => guesses.to_string(),
+            None => "-".to_string(),
+        };
+        let assists = match self.assists.is_empty() {
+            true => "none".to_string(),
+            false => self.assists.join(", "),
+        };
+        vec![
+            format!("Difficulty: {}", self.difficulty),
+            format!("Time: {}", format_time(self.time)),
+            format!("3BV: {}, {rate} 3BV/s", self.bbbv),
+            format!("Guesses: {guesses}"),
+            format!("Assists: {assists}"),
+        ]
+    }
+
+    fn text(&self) -> String {
+        let mut text = format!("Minesweeper {}\n", self.title());
+        for line in self.lines() {
+            text += &line;
+            text.push('\n');
+        }
+        if let Some(link) = &self.link {
+            text += link;
+            text.push('\n');
+        }
+        text
+    }
+
+    /// PNG data URL of the card
+    fn image(&self) -> Option<String> {
+        let lines = self.lines();
+        let canvas: HtmlCanvasElement =
+            document().create_element("canvas").ok()?.dyn_into().ok()?;
+        canvas.set_width(CARD_WIDTH);
+        canvas.set_height(((lines.len() + 2) as f64 * CARD_LINE_HEIGHT + 16.) as u32);
+        let ctx: CanvasRenderingContext2d = canvas.get_context("2d").ok()??.dyn_into().ok()?;
+        ctx.set_fill_style(&"#ffffff".into());
+        ctx.fill_rect(0., 0., canvas.width() as f64, canvas.height() as f64);
+        ctx.set_fill_style(&if self.win { "#16a34a" } else { "#dc2626" }.into());
+        ctx.set_font("bold 24px sans-serif");
+        ctx.fill_text(self.title(), 20., 1.5 * CARD_LINE_HEIGHT)
+            .ok()?;
+        ctx.set_fill_style(&"#262626".into());
+        ctx.set_font("18px sans-serif");
+        for (i, line) in lines.iter().enumerate() {
+            ctx.fill_text(line, 20., (i + 3) as f64 * CARD_LINE_HEIGHT)
+                .ok()?;
+        }
+        canvas.to_data_url().ok()
+    }
+}
+
+/// Card shown when a game ends, with its result to copy as text or save as an image
+#[component]
+pub fn ResultCard(view: RwSignal<MaybeUninitGameView>, time: Signal<u64>) -> impl IntoView {
+    let dialog_ref: NodeRef<html::Custom> = create_node_ref();
+    let summary = create_rw_signal(None::<Summary>);
+    let UseClipboardReturn { copy, copied, .. } = use_clipboard();
+    create_effect(move |previous_result| {
+        let result = with!(|view| match view {
+            MaybeUninitGameView::GameView(view) => Some(view.result),
+            _ => None,
+        });
+        if previous_result == Some(Some(GameResult::Playing))
+            && matches!(result, Some(GameResult::Win | GameResult::Lose))
+        {
+            summary.set(view.with_untracked(|view| match view {
+                MaybeUninitGameView::GameView(view) => {
+                    Some(Summary::new(view, time.get_untracked()))
+                }
+                _ => None,
+            }));
+            drawer_show(dialog_ref);
+        }
+        result
+    });
+    let save_image = move |_| {
+        if let Some(url) =
+            summary.with_untracked(|summary| summary.as_ref().and_then(Summary::image))
+        {
+            download_url_ffi("minesweeper-result.png", &url);
+        }
+    };
+    let (class_name, style_val) = style_str! {
+        #result-card p {
+            margin: 0.25rem 0;
+        }
+        #result-card a {
+            word-break: break-all;
+        }
+    };
+    view! {
+        class = class_name,
+        <Style> { style_val } </Style>
+        <sl-dialog id="result-card" label=move || with!(|summary| summary.as_ref().map(Summary::title).unwrap_or_default()) class="non-draggable" ref=dialog_ref on:mousedown=move |ev| ev.stop_propagation()>
+            { move || with!(|summary| summary.as_ref().map(|summary| view! {
+                class = class_name,
+                { summary.lines().into_iter().map(|line| view! { class = class_name, <p> { line } </p> }).collect_view() }
+                { summary.link.clone().map(|link| view! {
+                    class = class_name,
+                    <p> <a href=link.clone()> "Link to this board" </a> </p>
+                }) }
+            })) }
+            <sl-button slot="footer" on:click=move |_| {
+                if let Some(text) = summary.with_untracked(|summary| summary.as_ref().map(Summary::text)) {
+                    copy(&text);
+                }
+            }> { move || if copied() { "Copied" } else { "Copy Text" } } </sl-button>
+            <sl-button slot="footer" on:click=save_image> "Save Image" </sl-button>
+            <sl-button slot="footer" variant="primary" autofocus on:click=move |_| drawer_hide(dialog_ref)> "Close" </sl-button>
+        </sl-dialog>
+    }
+}
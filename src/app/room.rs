@@ -0,0 +1,331 @@
+use std::collections::BTreeMap;
+
+use leptos::*;
+use leptos_meta::*;
+use multiplayer_protocol::{ClientMessage, Player, ServerMessage, PROTOCOL_VERSION};
+use serde::{Deserialize, Serialize};
+use stylers::style_str;
+use wasm_bindgen::{prelude::*, JsValue};
+use web_sys::{MessageEvent, WebSocket};
+
+use minesweep_core::{
+    Action, BoardLimits, CellView, Difficulty, GameOptions, GameView, RedrawCells, Symmetry,
+    GENERATION_VERSION,
+};
+
+use super::{alert_toast, drawer_hide, read_text_untracked, select_value, MaybeUninitGameView};
+
+const CURSOR_COLORS: [&str; 6] = [
+    "#ef4444", "#f59e0b", "#10b981", "#3b82f6", "#8b5cf6", "#ec4899",
+];
+
+/// Board shared with other players through the server, actions only apply once echoed back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Room {
+    pub name: String,
+    pub options: GameOptions,
+    /// `None` until someone clicks, the server builds the board around the first click
+    pub view: Option<GameView>,
+    pub player: u64,
+    pub players: BTreeMap<u64, Player>,
+    /// Messages for the server, sent by `RoomDrawer` whenever the room changes
+    #[serde(skip)]
+    outbox: Vec<ClientMessage>,
+}
+
+impl Room {
+    pub fn new(name: String, player: u64, options: GameOptions) -> Self {
+        Self {
+            name,
+            options,
+            view: None,
+            player,
+            players: BTreeMap::new(),
+            outbox: Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.options.difficulty.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.options.difficulty.height()
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> CellView {
+        self.view
+            .as_ref()
+            .map_or(CellView::Unopened, |view| view.cell(x, y))
+    }
+
+    pub fn send(&mut self, message: ClientMessage) {
+        self.outbox.push(message);
+    }
+
+    pub fn act(&mut self, action: Action) -> RedrawCells {
+        self.send(ClientMessage::Act(action));
+        RedrawCells::default()
+    }
+
+    pub fn hover(&mut self, cursor: Option<(usize, usize)>) -> RedrawCells {
+        let own = self
+            .players
+            .get(&self.player)
+            .and_then(|player| player.cursor);
+        if own != cursor {
+            self.send(ClientMessage::Cursor(cursor));
+        }
+        RedrawCells::default()
+    }
+
+    /// Color of the other player pointing at the cell
+    pub fn cursor_color(&self, x: usize, y: usize) -> Option<&'static str> {
+        self.players
+            .values()
+            .find(|player| player.id != self.player && player.cursor == Some((x, y)))
+            .map(|player| CURSOR_COLORS[player.id as usize % CURSOR_COLORS.len()])
+    }
+
+    pub fn receive(&mut self, message: ServerMessage) -> RedrawCells {
+        match message {
+            ServerMessage::Welcome { player } => {
+                self.player = player;
+                RedrawCells::default()
+            }
+            ServerMessage::Sync {
+                options,
+                replay,
+                players,
+            } => {
+                self.options = options;
+                self.view = replay.and_then(|replay| replay.finish().ok());
+                self.players = players
+                    .into_iter()
+                    .map(|player| (player.id, player))
+                    .collect();
+                RedrawCells::redraw_all()
+            }
+            ServerMessage::Applied { action, .. } => match &mut self.view {
+                Some(view) => view.apply(&action),
+                None => RedrawCells::default(),
+            },
+            ServerMessage::Cursor { player, cursor } => {
+                let Some(player) = self.players.get_mut(&player) else {
+                    return RedrawCells::default();
+                };
+                let previous = std::mem::replace(&mut player.cursor, cursor);
+                previous.into_iter().chain(cursor).collect()
+            }
+            ServerMessage::Joined(player) => {
+                self.players.insert(player.id, player);
+                RedrawCells::default()
+            }
+            ServerMessage::Left { player } => match self.players.remove(&player) {
+                Some(player) => player.cursor.into_iter().collect(),
+                None => RedrawCells::default(),
+            },
+            ServerMessage::Failed { .. } => RedrawCells::default(),
+        }
+    }
+
+    /// Board to keep playing alone after leaving
+    pub fn into_view(self) -> MaybeUninitGameView {
+        match self.view {
+            Some(view) => MaybeUninitGameView::GameView(view),
+            None => self.options.into(),
+        }
+    }
+}
+
+fn default_server() -> String {
+    let host = window().location().hostname().unwrap_or_default();
+    format!(
+        "ws://{}:3000/ws",
+        if host.is_empty() { "localhost" } else { &host }
+    )
+}
+
+#[component]
+pub fn RoomDrawer(
+    drawer_ref: NodeRef<html::Custom>,
+    view: RwSignal<MaybeUninitGameView>,
+    redraw: RwSignal<RedrawCells>,
+) -> impl IntoView {
+    let server_ref: NodeRef<html::Custom> = create_node_ref();
+    let room_ref: NodeRef<html::Custom> = create_node_ref();
+    let name_ref: NodeRef<html::Custom> = create_node_ref();
+    let failed_ref: NodeRef<html::Custom> = create_node_ref();
+    let (difficulty, set_difficulty) = create_signal(Difficulty::Easy);
+    let failure = create_rw_signal(String::new());
+    let socket = store_value(None::<WebSocket>);
+    let in_room = create_memo(move |_| with!(|view| matches!(view, MaybeUninitGameView::Room(_))));
+
+    let fail = move |reason: String| {
+        failure.set(reason);
+        alert_toast(failed_ref);
+    };
+    // keep playing the shared board alone
+    let into_solo = move || {
+        let MaybeUninitGameView::Room(room) = view.get_untracked() else {
+            return;
+        };
+        let next = room.into_view();
+        view.set(next);
+        redraw.set(RedrawCells::redraw_all());
+    };
+    let leave = move || {
+        if let Some(socket) = socket.get_value() {
+            let _ = socket.close();
+        }
+        socket.set_value(None);
+        into_solo();
+    };
+    let join = move || {
+        leave();
+        let room = read_text_untracked(room_ref);
+        let name = read_text_untracked(name_ref);
+        if room.is_empty() || name.is_empty() {
+            fail("Room and name are required".to_string());
+            return;
+        }
+        let ws = match WebSocket::new(&read_text_untracked(server_ref)) {
+            Ok(ws) => ws,
+            Err(err) => {
+                fail(format!("{err:?}"));
+                return;
+            }
+        };
+        let options = GameOptions {
+            difficulty: difficulty.get_untracked(),
+            safe_pos: None,
+            seed: None,
+            symmetry: Symmetry::None,
+            learning: false,
+            hidden_count: false,
+            generation: GENERATION_VERSION,
+            limits: BoardLimits::default(),
+        };
+        let join = ClientMessage::Join {
+            version: PROTOCOL_VERSION,
+            room: room.clone(),
+            name,
+            options: options.clone(),
+        };
+        let onopen = Closure::<dyn FnMut()>::new({
+            let ws = ws.clone();
+            move || {
+                let _ = ws.send_with_str(&join.to_json());
+            }
+        });
+        let onmessage = Closure::<dyn FnMut(MessageEvent)>::new({
+            let ws = ws.clone();
+            move |ev: MessageEvent| {
+                let Some(text) = ev.data().as_string() else {
+                    return;
+                };
+                let message = match ServerMessage::from_json(&text) {
+                    Ok(ServerMessage::Failed { reason }) | Err(reason) => {
+                        fail(reason);
+                        return;
+                    }
+                    Ok(message) => message,
+                };
+                // messages of a room already left
+                if socket.with_value(|socket| socket.as_ref() != Some(&ws)) {
+                    return;
+                }
+                if let ServerMessage::Welcome { player } = message {
+                    let room = Room::new(room.clone(), player, options.clone());
+                    view.set(MaybeUninitGameView::Room(room));
+                    redraw.set(RedrawCells::redraw_all());
+                    drawer_hide(drawer_ref);
+                    return;
+                }
+                let mut next_redraw = Default::default();
+                update!(|view| if let MaybeUninitGameView::Room(room) = view {
+                    next_redraw = room.receive(message);
+                });
+                redraw.set(next_redraw);
+            }
+        });
+        let onclose = Closure::<dyn FnMut()>::new({
+            let ws = ws.clone();
+            move || {
+                if socket.with_value(|socket| socket.as_ref() != Some(&ws)) {
+                    return;
+                }
+                socket.set_value(None);
+                if in_room.get_untracked() {
+                    fail("Disconnected from the room".to_string());
+                }
+                into_solo();
+            }
+        });
+        ws.set_onopen(Some(onopen.as_ref().unchecked_ref()));
+        ws.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+        ws.set_onclose(Some(onclose.as_ref().unchecked_ref()));
+        onopen.forget();
+        onmessage.forget();
+        onclose.forget();
+        socket.set_value(Some(ws));
+    };
+    // send what the board queued, and leave once another board replaced the room
+    create_effect(move |was_in_room| {
+        view.track();
+        let mut outbox = None;
+        view.update_untracked(|view| {
+            if let MaybeUninitGameView::Room(room) = view {
+                outbox = Some(std::mem::take(&mut room.outbox));
+            }
+        });
+        let Some(outbox) = outbox else {
+            if was_in_room == Some(true) {
+                leave();
+            }
+            return false;
+        };
+        socket.with_value(|socket| {
+            if let Some(socket) = socket {
+                for message in outbox {
+                    let _ = socket.send_with_str(&message.to_json());
+                }
+            }
+        });
+        true
+    });
+
+    let (class_name, style_val) = style_str! {
+        #room-drawer {
+            --size: 40vw;
+        }
+    };
+    view! {
+        class = class_name,
+        <Style> { style_val } </Style>
+        <sl-drawer label="Join Room" id="room-drawer" class="non-draggable" ref=drawer_ref on:mousedown=move |ev| ev.stop_propagation()>
+            <sl-input label="Server" ref=server_ref value=default_server()></sl-input> <br />
+            <sl-input label="Room" ref=room_ref></sl-input> <br />
+            <sl-input label="Name" ref=name_ref></sl-input> <br />
+            <sl-select label="Difficulty of a new room" value="easy" on:sl-change=move |ev: JsValue| set_difficulty(match select_value(&ev).as_str() {
+                "medium" => Difficulty::Medium,
+                "hard" => Difficulty::Hard,
+                _ => Difficulty::Easy,
+            })>
+                <sl-option value="easy"> "Easy" </sl-option>
+                <sl-option value="medium"> "Medium" </sl-option>
+                <sl-option value="hard"> "Hard" </sl-option>
+            </sl-select>
+            <sl-button slot="footer" variant="primary" on:click=move |_| join()> "Join" </sl-button>
+            <sl-button slot="footer" disabled=move || !in_room() on:click=move |_| {
+                leave();
+                drawer_hide(drawer_ref);
+            }> "Leave" </sl-button>
+            <sl-button slot="footer" on:click=move |_| drawer_hide(drawer_ref)> "Close" </sl-button>
+        </sl-drawer>
+        <sl-alert variant="danger" duration="3000" countdown="ltr" closable ref=failed_ref>
+            <sl-icon slot="icon" name="exclamation-octagon"></sl-icon>
+            { failure }
+        </sl-alert>
+    }
+}
@@ -0,0 +1,198 @@
+use js_sys::{Reflect, JSON};
+use leptos::*;
+use leptos_meta::*;
+use leptos_use::{use_interval, UseIntervalReturn};
+use serde::{Deserialize, Serialize};
+use stylers::style_str;
+use wasm_bindgen::JsValue;
+
+use minesweep_core::{BoardError, GameView, RedrawCells, Replay};
+
+use super::MaybeUninitGameView;
+
+const SPEEDS: [(&str, u64); 3] = [("0.5x", 800), ("1x", 400), ("2x", 200)];
+/// Speed following the timestamps of the replay, untimed actions take the 1x interval
+const REAL_TIME: &str = "Real";
+/// Longest wait between actions played in real time, in milliseconds
+const MAX_REAL_GAP: u64 = 5000;
+
+/// Read-only view of a recorded game, positioned somewhere along its actions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Spectator {
+    pub replay: Replay,
+    pub position: usize,
+    pub view: GameView,
+}
+
+impl Spectator {
+    pub fn new(replay: Replay) -> Result<Self, BoardError> {
+        Ok(Self {
+            view: replay.start()?,
+            replay,
+            position: 0,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.replay.actions.len()
+    }
+
+    /// Milliseconds the player took before the next action, when recorded
+    pub fn next_gap(&self) -> Option<u64> {
+        self.replay.gap(self.position)
+    }
+
+    pub fn forward(&mut self) -> RedrawCells {
+        let Some(action) = self.replay.actions.get(self.position) else {
+            return RedrawCells::default();
+        };
+        self.position += 1;
+        self.view.apply(action)
+    }
+
+    /// Jump to any position, replaying from the start when going backwards
+    pub fn seek(&mut self, position: usize) -> RedrawCells {
+        let position = position.min(self.len());
+        if position >= self.position {
+            let mut redraw = RedrawCells::default();
+            while self.position < position {
+                redraw.merge(self.forward());
+            }
+            return redraw;
+        }
+        // the board already parsed once so seeking cannot fail
+        self.view = self.replay.seek(position).unwrap();
+        self.position = position;
+        RedrawCells::redraw_all()
+    }
+}
+
+/// Encode a replay for sharing as a file
+pub fn replay_to_json(replay: &Replay) -> String {
+    let value = serde_wasm_bindgen::to_value(replay).unwrap();
+    JSON::stringify(&value).unwrap().into()
+}
+
+pub fn replay_from_json(json: &str) -> Option<Replay> {
+    let value = JSON::parse(json).ok()?;
+    serde_wasm_bindgen::from_value(value).ok()
+}
+
+#[component]
+pub fn SpectatorControls(
+    view: RwSignal<MaybeUninitGameView>,
+    redraw: RwSignal<RedrawCells>,
+) -> impl IntoView {
+    let (interval, set_interval) = create_signal(SPEEDS[1].1);
+    let (real_time, set_real_time) = create_signal(false);
+    let UseIntervalReturn {
+        counter: tick,
+        is_active: playing,
+        pause,
+        resume,
+        ..
+    } = use_interval(interval);
+    pause();
+    let seek = move |position: usize| {
+        let mut next_redraw = Default::default();
+        update!(
+            |view| if let MaybeUninitGameView::Spectator(spectator) = view {
+                next_redraw = spectator.seek(position);
+            }
+        );
+        redraw.set(next_redraw);
+    };
+    let position = move || {
+        with!(|view| match view {
+            MaybeUninitGameView::Spectator(spectator) =>
+                Some((spectator.position, spectator.len())),
+            _ => None,
+        })
+    };
+    create_effect({
+        let pause = pause.clone();
+        move |_| {
+            tick.track();
+            if !playing.get_untracked() {
+                return;
+            }
+            match position() {
+                Some((current, len)) if current < len => seek(current + 1),
+                _ => pause(),
+            }
+            if real_time.get_untracked() {
+                let gap = with!(|view| match view {
+                    MaybeUninitGameView::Spectator(spectator) => spectator.next_gap(),
+                    _ => None,
+                });
+                set_interval(gap.unwrap_or(SPEEDS[1].1).clamp(1, MAX_REAL_GAP));
+            }
+        }
+    });
+    let toggle = move || {
+        if playing.get_untracked() {
+            pause();
+        } else {
+            resume();
+        }
+    };
+    let (class_name, style_val) = style_str! {
+        #spectator {
+            display: flex;
+            flex-direction: column;
+            align-items: center;
+            gap: 0.5rem;
+        }
+        #spectator-buttons {
+            display: flex;
+            flex-direction: row;
+            align-items: center;
+            gap: 0.5rem;
+        }
+        #spectator > sl-range {
+            width: 100%;
+        }
+    };
+    view! {
+        class = class_name,
+        <Style> { style_val } </Style>
+        <div id="spectator" class="non-draggable" style:display=move || {
+            if position().is_some() { "flex" } else { "none" }
+        } on:mousedown=move |ev| ev.stop_propagation()>
+            <div id="spectator-buttons">
+                <sl-icon-button name="skip-start" label="Start" on:click=move |_| seek(0)></sl-icon-button>
+                <sl-icon-button name="chevron-left" label="Back" on:click=move |_| {
+                    if let Some((current, _)) = position() {
+                        seek(current.saturating_sub(1));
+                    }
+                }></sl-icon-button>
+                <sl-icon-button name=move || if playing() { "pause" } else { "play" } label="Play" on:click=move |_| toggle()></sl-icon-button>
+                <sl-icon-button name="chevron-right" label="Forward" on:click=move |_| {
+                    if let Some((current, _)) = position() {
+                        seek(current + 1);
+                    }
+                }></sl-icon-button>
+                <sl-icon-button name="skip-end" label="End" on:click=move |_| seek(usize::MAX)></sl-icon-button>
+                <sl-select size="small" value="1x" on:sl-change=move |ev: JsValue| {
+                    let target = Reflect::get(&ev, &"target".into()).unwrap();
+                    let value = Reflect::get(&target, &"value".into()).unwrap().as_string();
+                    set_real_time(value.as_deref() == Some(REAL_TIME));
+                    if let Some((_, interval)) = SPEEDS.iter().find(|(name, _)| Some(*name) == value.as_deref()) {
+                        set_interval(*interval);
+                    }
+                }>
+                    { SPEEDS.iter().map(|(name, _)| view! { <sl-option value=*name> { *name } </sl-option> }).collect_view() }
+                    <sl-option value=REAL_TIME> { REAL_TIME } </sl-option>
+                </sl-select>
+            </div>
+            <sl-range min="0" step="1"
+                prop:max=move || position().map(|(_, len)| len).unwrap_or_default() as f64
+                prop:value=move || position().map(|(current, _)| current).unwrap_or_default() as f64
+                on:sl-change=move |ev: JsValue| {
+                    let target = Reflect::get(&ev, &"target".into()).unwrap();
+                    let value = Reflect::get(&target, &"value".into()).unwrap().as_f64().unwrap();
+                    seek(value as usize);
+                }></sl-range>
+        </div>
+    }
+}
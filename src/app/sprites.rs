@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use js_sys::{Array, Uint8Array};
+use leptos::logging::log;
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::HtmlImageElement;
+
+use super::storage::{self, SETTINGS_STORE};
+
+/// Key of the sprite pack in the settings store
+const SPRITE_PACK_ID: f64 = 2.;
+/// Largest sprite accepted, after decompression
+const MAX_SPRITE_BYTES: usize = 1 << 20;
+
+/// File names of the sprites without extension, `0` is an opened cell with no mine around
+pub const SPRITE_NAMES: [&str; 14] = [
+    "0",
+    "1",
+    "2",
+    "3",
+    "4",
+    "5",
+    "6",
+    "7",
+    "8",
+    "flag",
+    "question",
+    "mine",
+    "wrong_mine",
+    "explosion",
+];
+
+#[wasm_bindgen(inline_js = "export function pick_files_ffi(directory) { \
+    return new Promise((resolve) => { \
+        const input = document.createElement('input'); \
+        input.type = 'file'; \
+        input.multiple = true; \
+        input.webkitdirectory = directory; \
+        if (!directory) input.accept = '.zip,.svg,.png'; \
+        input.onchange = () => Promise.all(Array.from(input.files, (file) => \
+            file.arrayBuffer().then((buffer) => [file.name, new Uint8Array(buffer)]))).then(resolve); \
+        input.click(); \
+    }); \
+}")]
+extern "C" {
+    fn pick_files_ffi(directory: bool) -> js_sys::Promise;
+}
+
+/// Names and bytes of the files picked by the user, or of every file in a picked folder
+async fn pick_files(directory: bool) -> Vec<(String, Vec<u8>)> {
+    let Ok(files) = JsFuture::from(pick_files_ffi(directory)).await else {
+        return vec![];
+    };
+    Array::from(&files)
+        .iter()
+        .filter_map(|file| {
+            let file = Array::from(&file);
+            Some((
+                file.get(0).as_string()?,
+                Uint8Array::new(&file.get(1)).to_vec(),
+            ))
+        })
+        .collect()
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<usize> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?) as usize)
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<usize> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?) as usize)
+}
+
+/// Files of a zip archive, stored or deflated, read through its central directory
+fn unzip(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    const END: [u8; 4] = [0x50, 0x4b, 0x05, 0x06];
+    const ENTRY: [u8; 4] = [0x50, 0x4b, 0x01, 0x02];
+    const LOCAL: [u8; 4] = [0x50, 0x4b, 0x03, 0x04];
+    let broken = || "broken zip archive".to_string();
+    let end = bytes
+        .windows(4)
+        .rposition(|window| window == END)
+        .ok_or_else(broken)?;
+    let entries = read_u16(bytes, end + 10).ok_or_else(broken)?;
+    let mut at = read_u32(bytes, end + 16).ok_or_else(broken)?;
+    let mut files = vec![];
+    for _ in 0..entries {
+        if bytes.get(at..at + 4) != Some(&ENTRY) {
+            return Err(broken());
+        }
+        let entry = || -> Option<_> {
+            let method = read_u16(bytes, at + 10)?;
+            let compressed = read_u32(bytes, at + 20)?;
+            let name_len = read_u16(bytes, at + 28)?;
+            let skip = name_len + read_u16(bytes, at + 30)? + read_u16(bytes, at + 32)?;
+            let local = read_u32(bytes, at + 42)?;
+            let name = String::from_utf8_lossy(bytes.get(at + 46..at + 46 + name_len)?);
+            if bytes.get(local..local + 4)? != LOCAL {
+                return None;
+            }
+            let data = local + 30 + read_u16(bytes, local + 26)? + read_u16(bytes, local + 28)?;
+            let data = bytes.get(data..data + compressed)?;
+            Some((method, name.into_owned(), data, 46 + skip))
+        };
+        let (method, name, data, len) = entry().ok_or_else(broken)?;
+        at += len;
+        if name.ends_with('/') {
+            continue;
+        }
+        let data = match method {
+            0 => data.to_vec(),
+            8 => miniz_oxide::inflate::decompress_to_vec_with_limit(data, MAX_SPRITE_BYTES)
+                .map_err(|_| format!("{name} is too large or broken"))?,
+            _ => return Err(format!("{name} is compressed in an unsupported way")),
+        };
+        files.push((name, data));
+    }
+    Ok(files)
+}
+
+/// Images replacing the built-in sprites, kept as data URLs by sprite name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpritePack {
+    /// Always `SPRITE_PACK_ID`, the key in the settings store
+    id: f64,
+    pub name: String,
+    sprites: BTreeMap<String, String>,
+}
+
+impl SpritePack {
+    /// Pack of the SVG and PNG files named after sprites, zip archives are opened and other
+    /// files such as a readme are left out
+    fn from_files(name: String, files: Vec<(String, Vec<u8>)>) -> Result<Self, String> {
+        let mut sprites = BTreeMap::new();
+        let mut expanded = vec![];
+        for (path, bytes) in files {
+            match path.to_lowercase().ends_with(".zip") {
+                true => expanded.extend(unzip(&bytes)?),
+                false => expanded.push((path, bytes)),
+            }
+        }
+        for (path, bytes) in expanded {
+            let file = path.rsplit('/').next().unwrap_or(&path);
+            let Some((stem, extension)) = file.rsplit_once('.') else {
+                continue;
+            };
+            let mime = match extension.to_lowercase().as_str() {
+                "svg" => "image/svg+xml",
+                "png" => "image/png",
+                _ => continue,
+            };
+            if file.starts_with('.') || path.starts_with("__MACOSX/") {
+                continue;
+            }
+            if !SPRITE_NAMES.contains(&stem) {
+                return Err(format!("{file} is not named after a sprite"));
+            }
+            if bytes.len() > MAX_SPRITE_BYTES {
+                return Err(format!("{file} is too large"));
+            }
+            let url = format!("data:{mime};base64,{}", STANDARD.encode(bytes));
+            if sprites.insert(stem.to_string(), url).is_some() {
+                return Err(format!("more than one {stem} sprite"));
+            }
+        }
+        if sprites.is_empty() {
+            return Err("no sprites found".to_string());
+        }
+        Ok(Self {
+            id: SPRITE_PACK_ID,
+            name,
+            sprites,
+        })
+    }
+
+    /// Data URL of a sprite, `None` when the pack keeps the built-in one
+    pub fn url(&self, name: &str) -> Option<&str> {
+        self.sprites.get(name).map(String::as_str)
+    }
+
+    /// Fails on the first sprite the browser cannot decode
+    async fn validate(&self) -> Result<(), String> {
+        for (name, url) in &self.sprites {
+            let image = HtmlImageElement::new().map_err(|_| "no image support".to_string())?;
+            image.set_src(url);
+            JsFuture::from(image.decode())
+                .await
+                .map_err(|_| format!("{name} is not a valid image"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Sprite pack saved in the browser, loaded into a signal, `None` for the built-in sprites
+pub fn persisted_sprite_pack() -> RwSignal<Option<SpritePack>> {
+    let pack = create_rw_signal(None);
+    spawn_local(async move {
+        match storage::get::<SpritePack>(SETTINGS_STORE, SPRITE_PACK_ID).await {
+            Ok(saved) => pack.set(saved),
+            Err(err) => log!("sprite pack load failed {err:?}"),
+        }
+    });
+    pack
+}
+
+#[component]
+pub fn SpriteControls(pack: RwSignal<Option<SpritePack>>) -> impl IntoView {
+    let (error, set_error) = create_signal(None::<String>);
+    let load = move |directory: bool| {
+        spawn_local(async move {
+            let files = pick_files(directory).await;
+            let Some((first, _)) = files.first() else {
+                return;
+            };
+            let name = match directory {
+                true => "folder".to_string(),
+                false => first.clone(),
+            };
+            let loaded = match SpritePack::from_files(name, files) {
+                Ok(loaded) => loaded,
+                Err(err) => return set_error(Some(err)),
+            };
+            if let Err(err) = loaded.validate().await {
+                return set_error(Some(err));
+            }
+            set_error(None);
+            if let Err(err) = storage::put(SETTINGS_STORE, &loaded).await {
+                log!("sprite pack save failed {err:?}");
+            }
+            pack.set(Some(loaded));
+        })
+    };
+    let reset = move |_| {
+        set_error(None);
+        pack.set(None);
+        spawn_local(async move {
+            if let Err(err) = storage::delete(SETTINGS_STORE, SPRITE_PACK_ID).await {
+                log!("sprite pack delete failed {err:?}");
+            }
+        });
+    };
+    view! {
+        <p> { move || match pack() {
+            Some(pack) => format!("Sprites: {}", pack.name),
+            None => "Sprites: built-in".to_string(),
+        } } </p>
+        <sl-button size="small" on:click=move |_| load(false)> "Load Sprite Pack" </sl-button>
+        <sl-button size="small" on:click=move |_| load(true)> "Load Sprite Folder" </sl-button>
+        <sl-button size="small" disabled=move || with!(|pack| pack.is_none()) on:click=reset> "Built-in Sprites" </sl-button>
+        { move || error().map(|error| view! { <p> { format!("Could not load sprites: {error}") } </p> }) }
+    }
+}
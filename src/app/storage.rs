@@ -0,0 +1,170 @@
+use std::cell::RefCell;
+
+use leptos::logging::log;
+use leptos::*;
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::prelude::*;
+
+pub const SETTINGS_STORE: &str = "settings";
+/// Shared by every profile, kept in the database of the default profile
+pub const PROFILE_STORE: &str = "profiles";
+/// Database of the default profile, which holds data saved before profiles were added
+const DB_NAME: &str = "minesweep-automated";
+
+thread_local! {
+    static DATABASE: RefCell<String> = RefCell::new(DB_NAME.to_string());
+}
+
+#[wasm_bindgen(inline_js = r#"
+const DB_VERSION = 4;
+const STORES = ["history", "settings", "checkpoints", "profiles"];
+
+function open_db(name) {
+    return new Promise((resolve, reject) => {
+        const request = indexedDB.open(name, DB_VERSION);
+        request.onupgradeneeded = () => {
+            for (const store of STORES) {
+                if (!request.result.objectStoreNames.contains(store)) {
+                    request.result.createObjectStore(store, { keyPath: "id" });
+                }
+            }
+        };
+        request.onsuccess = () => resolve(request.result);
+        request.onerror = () => reject(request.error);
+    });
+}
+
+function transaction(name, store, mode, f) {
+    return open_db(name).then((db) => new Promise((resolve, reject) => {
+        const tx = db.transaction(store, mode);
+        const request = f(tx.objectStore(store));
+        tx.oncomplete = () => resolve(request.result);
+        tx.onerror = () => reject(tx.error);
+    }));
+}
+
+export function idb_put_ffi(name, store, value) {
+    return transaction(name, store, "readwrite", (s) => s.put(value));
+}
+
+export function idb_get_ffi(name, store, key) {
+    return transaction(name, store, "readonly", (s) => s.get(key));
+}
+
+export function idb_get_all_ffi(name, store) {
+    return transaction(name, store, "readonly", (s) => s.getAll());
+}
+
+export function idb_delete_ffi(name, store, key) {
+    return transaction(name, store, "readwrite", (s) => s.delete(key));
+}
+
+export function idb_delete_database_ffi(name) {
+    return new Promise((resolve, reject) => {
+        const request = indexedDB.deleteDatabase(name);
+        request.onsuccess = () => resolve();
+        request.onerror = () => reject(request.error);
+    });
+}
+"#)]
+extern "C" {
+    #[wasm_bindgen(catch)]
+    async fn idb_put_ffi(name: &str, store: &str, value: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch)]
+    async fn idb_get_ffi(name: &str, store: &str, key: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch)]
+    async fn idb_get_all_ffi(name: &str, store: &str) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch)]
+    async fn idb_delete_ffi(name: &str, store: &str, key: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch)]
+    async fn idb_delete_database_ffi(name: &str) -> Result<JsValue, JsValue>;
+}
+
+fn profile_database(profile: f64) -> String {
+    match profile == 0. {
+        true => DB_NAME.to_string(),
+        false => format!("{DB_NAME}-{profile}"),
+    }
+}
+
+/// Keep what is saved from now on apart for `profile`, `0` is the default profile
+pub fn use_profile(profile: f64) {
+    DATABASE.set(profile_database(profile));
+}
+
+/// Drop everything saved for `profile`
+pub async fn delete_profile(profile: f64) -> Result<(), JsValue> {
+    idb_delete_database_ffi(&profile_database(profile)).await?;
+    Ok(())
+}
+
+/// Database `store` is kept in, the one of the profile in use unless shared
+fn database(store: &str) -> String {
+    match store {
+        PROFILE_STORE => DB_NAME.to_string(),
+        _ => DATABASE.with_borrow(Clone::clone),
+    }
+}
+
+/// Every value is stored with its `id` field as key
+pub async fn put<T: Serialize>(store: &str, value: &T) -> Result<(), JsValue> {
+    // seeds don't fit in js numbers
+    let serializer =
+        serde_wasm_bindgen::Serializer::new().serialize_large_number_types_as_bigints(true);
+    idb_put_ffi(&database(store), store, value.serialize(&serializer)?).await?;
+    Ok(())
+}
+
+/// Value stored under `key`, `None` when there is none
+pub async fn get<T: DeserializeOwned>(store: &str, key: f64) -> Result<Option<T>, JsValue> {
+    let value = idb_get_ffi(&database(store), store, key.into()).await?;
+    if value.is_undefined() {
+        return Ok(None);
+    }
+    Ok(Some(serde_wasm_bindgen::from_value(value)?))
+}
+
+pub async fn get_all<T: DeserializeOwned>(store: &str) -> Result<Vec<T>, JsValue> {
+    Ok(serde_wasm_bindgen::from_value(
+        idb_get_all_ffi(&database(store), store).await?,
+    )?)
+}
+
+pub async fn delete(store: &str, key: f64) -> Result<(), JsValue> {
+    idb_delete_ffi(&database(store), store, key.into()).await?;
+    Ok(())
+}
+
+/// Setting saved in the browser under `id`, loaded into a signal that saves it on change,
+/// `default` until it is loaded or when none was saved
+pub fn persisted_setting<T>(id: f64, default: T) -> RwSignal<T>
+where
+    T: Clone + Serialize + DeserializeOwned + 'static,
+{
+    let setting = create_rw_signal(default);
+    let loaded = store_value(false);
+    spawn_local(async move {
+        match get::<T>(SETTINGS_STORE, id).await {
+            Ok(Some(saved)) => setting.set(saved),
+            Ok(None) => {}
+            Err(err) => log!("settings load failed {err:?}"),
+        }
+        loaded.set_value(true);
+    });
+    create_effect(move |_| {
+        let current = setting();
+        if !loaded.get_value() {
+            return;
+        }
+        spawn_local(async move {
+            if let Err(err) = put(SETTINGS_STORE, &current).await {
+                log!("settings save failed {err:?}");
+            }
+        });
+    });
+    setting
+}
@@ -0,0 +1,118 @@
+use automation_protocol::{codec_stats, CodecStats};
+use leptos::ev::keydown;
+use leptos::*;
+use leptos_meta::*;
+use leptos_use::{use_event_listener, use_interval, UseIntervalReturn};
+use stylers::style_str;
+
+use super::is_typing;
+
+/// Weight of the newest sample in the rolling averages
+const SMOOTHING: f64 = 0.2;
+/// Milliseconds between polls of the codec totals while the overlay is shown
+const CODEC_POLL: u64 = 500;
+
+/// Exponential moving average, `None` until the first sample
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rolling(Option<f64>);
+
+impl Rolling {
+    pub fn record(&mut self, sample: f64) {
+        self.0 = Some(match self.0 {
+            Some(average) => average * (1. - SMOOTHING) + sample * SMOOTHING,
+            None => sample,
+        });
+    }
+
+    fn millis(&self) -> String {
+        match self.0 {
+            Some(seconds) => format!("{:.2} ms", seconds * 1000.),
+            None => "-".to_string(),
+        }
+    }
+}
+
+/// Timings of this session, all in seconds
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Telemetry {
+    pub redraw: Rolling,
+    /// From sending a request to its step arriving, or since the previous step of a run
+    pub round_trip: Rolling,
+    /// Solve time reported by the worker
+    pub solve: Rolling,
+    pub steps: u64,
+}
+
+impl Telemetry {
+    pub fn record_step(&mut self, round_trip: f64, solve: f64) {
+        self.round_trip.record(round_trip);
+        self.solve.record(solve);
+        self.steps += 1;
+    }
+}
+
+/// Developer overlay of the session timings, toggled with the backquote key
+#[component]
+pub fn TelemetryOverlay(telemetry: RwSignal<Telemetry>) -> impl IntoView {
+    let (shown, set_shown) = create_signal(false);
+    let _ = use_event_listener(document(), keydown, move |ev| {
+        if ev.key() == "`" && !ev.ctrl_key() && !ev.alt_key() && !is_typing(&ev) {
+            set_shown.update(|shown| *shown = !*shown);
+        }
+    });
+    let UseIntervalReturn { counter, .. } = use_interval(CODEC_POLL);
+    let codec = create_rw_signal(Rolling::default());
+    let last_codec = store_value(CodecStats::default());
+    create_effect(move |_| {
+        counter.track();
+        if !shown() {
+            return;
+        }
+        let current = codec_stats();
+        let last = last_codec.get_value();
+        if current.messages > last.messages {
+            let seconds = current.seconds - last.seconds;
+            codec.update(|codec| codec.record(seconds / (current.messages - last.messages) as f64));
+        }
+        last_codec.set_value(current);
+    });
+    let (class_name, style_val) = style_str! {
+        #telemetry {
+            position: fixed;
+            right: 0.5rem;
+            bottom: 0.5rem;
+            z-index: 20;
+            padding: 0.5rem 0.75rem;
+            border-radius: 0.5rem;
+            background-color: rgba(0, 0, 0, 0.7);
+            color: white;
+            font-family: monospace;
+            font-size: 0.75rem;
+            pointer-events: none;
+        }
+        #telemetry > p {
+            margin: 0;
+        }
+    };
+    view! {
+        class = class_name,
+        <Style> { style_val } </Style>
+        { move || shown().then(|| with!(|telemetry| view! {
+            class = class_name,
+            <div id="telemetry">
+                <p> { format!("Redraw: {}", telemetry.redraw.millis()) } </p>
+                <p> { format!("Worker round trip: {}", telemetry.round_trip.millis()) } </p>
+                <p> { format!("Solve: {}", telemetry.solve.millis()) } </p>
+                <p> { format!("Steps: {}", telemetry.steps) } </p>
+                <p> { move || {
+                    let CodecStats { messages, bytes, .. } = last_codec.get_value();
+                    format!(
+                        "Codec: {} per message, {messages} messages, {} KiB",
+                        codec().millis(),
+                        bytes / 1024,
+                    )
+                } } </p>
+            </div>
+        })) }
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use leptos::*;
+use leptos_meta::*;
+use leptos_use::signal_throttled;
+use stylers::style_str;
+
+use minesweep_core::{GameResult, GameView, QuestionHints};
+
+use super::MaybeUninitGameView;
+
+/// Milliseconds between analyses of the hovered cell
+const TOOLTIP_THROTTLE: f64 = 100.;
+/// Pixels between the pointer and the tooltip
+const TOOLTIP_OFFSET: f64 = 16.;
+
+/// What is known of an intact cell
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CellAnalysis {
+    Safe,
+    Mine,
+    /// Chance of a mine over the arrangements of its component
+    Chance(f64),
+    /// Not next to a number, or in a component too big to count
+    Unknown,
+}
+
+impl CellAnalysis {
+    /// Kept solve results first, then the arrangements of the component, which prove the cell
+    /// when all of them agree
+    fn of(view: &GameView, cell: (usize, usize), chances: &HashMap<(usize, usize), f64>) -> Self {
+        if view.known_safe().contains(&cell) {
+            return Self::Safe;
+        }
+        if view.known_mines().contains(&cell) {
+            return Self::Mine;
+        }
+        match chances.get(&cell) {
+            Some(&chance) if chance <= 0. => Self::Safe,
+            Some(&chance) if chance >= 1. => Self::Mine,
+            Some(&chance) => Self::Chance(chance),
+            None => Self::Unknown,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Safe => "Proven safe".to_string(),
+            Self::Mine => "Proven mine".to_string(),
+            Self::Chance(chance) => format!("Mine chance {:.0}%", chance * 100.),
+            Self::Unknown => "Unknown".to_string(),
+        }
+    }
+}
+
+/// Analysis of the intact cell under the pointer, drawn over the canvas next to the pointer
+#[component]
+pub fn CellTooltip(
+    view: RwSignal<MaybeUninitGameView>,
+    hover: ReadSignal<Option<(usize, usize)>>,
+    enabled: Signal<bool>,
+    /// Pointer position on the page
+    x: Signal<f64>,
+    y: Signal<f64>,
+) -> impl IntoView {
+    let hover = signal_throttled(hover, TOOLTIP_THROTTLE);
+    // mine chances of the frontier keyed by `GameView::frontier_key`, counted once per board
+    let chances = store_value(None::<(u64, HashMap<(usize, usize), f64>)>);
+    let analysis = create_memo(move |_| {
+        if !enabled() {
+            return None;
+        }
+        let (cx, cy) = hover()?;
+        with!(|view| {
+            let MaybeUninitGameView::GameView(view) = view else {
+                return None;
+            };
+            if view.result != GameResult::Playing || !view.cell(cx, cy).is_intact() {
+                return None;
+            }
+            let key = view.frontier_key();
+            if chances.with_value(|chances| !matches!(chances, Some((k, _)) if *k == key)) {
+                let counted = view.mine_probabilities(QuestionHints::Ignore);
+                chances.set_value(Some((key, counted.into_iter().collect())));
+            }
+            chances.with_value(|chances| {
+                let (_, chances) = chances.as_ref()?;
+                Some(CellAnalysis::of(view, (cx, cy), chances))
+            })
+        })
+    });
+    let (class_name, style_val) = style_str! {
+        #cell-tooltip {
+            position: absolute;
+            z-index: 10;
+            padding: 0.25rem 0.5rem;
+            border-radius: var(--sl-tooltip-border-radius);
+            background-color: var(--sl-tooltip-background-color);
+            color: var(--sl-tooltip-color);
+            font-size: var(--sl-tooltip-font-size);
+            pointer-events: none;
+            white-space: nowrap;
+        }
+    };
+    view! {
+        class = class_name,
+        <Style> { style_val } </Style>
+        { move || analysis().map(|analysis| view! {
+            class = class_name,
+            <div id="cell-tooltip"
+                style:left=move || format!("{}px", x() + TOOLTIP_OFFSET)
+                style:top=move || format!("{}px", y() + TOOLTIP_OFFSET)>
+                { analysis.describe() }
+            </div>
+        }) }
+    }
+}
@@ -6,6 +6,7 @@ use wasm_bindgen::prelude::*;
 
 fn main() {
     console_error_panic_hook::set_once();
+    tracing_wasm::set_as_global_default();
     let mount_point: web_sys::HtmlElement = document()
         .get_elements_by_tag_name("main")
         .item(0)
@@ -0,0 +1,146 @@
+//! Combinational circuits in the ASCII AIGER format, `aag M I L O A`: variable `v` of the
+//! circuit is literal `2v`, `2v + 1` its negation, and `0` and `1` are false and true
+
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{Cnf, Formula, Literal, Variable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AigerError {
+    Header,
+    /// Latches make the circuit sequential, which a single formula cannot express
+    Latches,
+    /// A line is missing, malformed or names a literal out of range, counted from 1
+    Line(usize),
+}
+
+impl Display for AigerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use AigerError::*;
+        match self {
+            Header => write!(f, "expected an `aag M I L O A` header"),
+            Latches => write!(f, "sequential circuits with latches are not supported"),
+            Line(line) => write!(f, "invalid line {line}"),
+        }
+    }
+}
+
+/// And-inverter graph, literals kept as in the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aiger {
+    pub max_variable: usize,
+    pub inputs: Vec<usize>,
+    pub outputs: Vec<usize>,
+    /// `[lhs, rhs0, rhs1]` with `lhs` set to `rhs0 ∧ rhs1`
+    pub ands: Vec<[usize; 3]>,
+}
+
+impl Aiger {
+    /// Symbols and comments after the gates are ignored
+    pub fn parse(text: &str) -> Result<Self, AigerError> {
+        let mut lines = text.lines().enumerate();
+        let header = lines.next().ok_or(AigerError::Header)?.1;
+        let counts: Vec<usize> = match header.split_whitespace().collect::<Vec<_>>()[..] {
+            ["aag", ref counts @ ..] if counts.len() == 5 => counts
+                .iter()
+                .map(|count| count.parse().map_err(|_| AigerError::Header))
+                .collect::<Result<_, _>>()?,
+            _ => return Err(AigerError::Header),
+        };
+        let [max_variable, inputs, latches, outputs, ands] = counts[..] else {
+            unreachable!()
+        };
+        if latches > 0 {
+            return Err(AigerError::Latches);
+        }
+        let mut numbers = |count: usize| {
+            let (index, line) = lines
+                .next()
+                .ok_or(AigerError::Line(text.lines().count() + 1))?;
+            let numbers: Vec<usize> = line
+                .split_whitespace()
+                .map(|n| n.parse().ok().filter(|l| *l <= 2 * max_variable + 1))
+                .collect::<Option<_>>()
+                .ok_or(AigerError::Line(index + 1))?;
+            match numbers.len() == count {
+                true => Ok((index + 1, numbers)),
+                false => Err(AigerError::Line(index + 1)),
+            }
+        };
+        let mut aiger = Aiger {
+            max_variable,
+            inputs: vec![],
+            outputs: vec![],
+            ands: vec![],
+        };
+        for _ in 0..inputs {
+            let (index, numbers) = numbers(1)?;
+            if numbers[0] < 2 || numbers[0] % 2 == 1 {
+                return Err(AigerError::Line(index));
+            }
+            aiger.inputs.push(numbers[0]);
+        }
+        for _ in 0..outputs {
+            aiger.outputs.push(numbers(1)?.1[0]);
+        }
+        for _ in 0..ands {
+            let (index, numbers) = numbers(3)?;
+            if numbers[0] < 2 || numbers[0] % 2 == 1 {
+                return Err(AigerError::Line(index));
+            }
+            aiger.ands.push([numbers[0], numbers[1], numbers[2]]);
+        }
+        Ok(aiger)
+    }
+
+    /// Satisfiable when every output can be true at once, variables numbered as in the
+    /// circuit with variable 0 held false
+    pub fn to_cnf(&self) -> Cnf {
+        let mut cnf = Cnf::default();
+        cnf.push(vec![Literal::negative(Variable(0))]);
+        for &[lhs, rhs0, rhs1] in &self.ands {
+            let (lhs, rhs0, rhs1) = (literal(lhs), literal(rhs0), literal(rhs1));
+            cnf.push(vec![lhs.negate(), rhs0]);
+            cnf.push(vec![lhs.negate(), rhs1]);
+            cnf.push(vec![lhs, rhs0.negate(), rhs1.negate()]);
+        }
+        for output in &self.outputs {
+            cnf.push(vec![literal(*output)]);
+        }
+        cnf
+    }
+
+    /// Output `index` over the inputs, gates shared in the circuit are repeated in the tree
+    pub fn output(&self, index: usize) -> Option<Formula> {
+        let gates: HashMap<usize, (usize, usize)> = self
+            .ands
+            .iter()
+            .map(|&[lhs, rhs0, rhs1]| (lhs / 2, (rhs0, rhs1)))
+            .collect();
+        fn build(l: usize, gates: &HashMap<usize, (usize, usize)>) -> Formula {
+            let f = match (l / 2, gates.get(&(l / 2))) {
+                // false is `x_0 ∧ ¬x_0`, whatever x_0 is
+                (0, _) => Formula::Conjunction(
+                    Box::new(Formula::Variable(Variable(0))),
+                    Box::new(Formula::Negation(Box::new(Formula::Variable(Variable(0))))),
+                ),
+                (_, Some(&(rhs0, rhs1))) => {
+                    Formula::Conjunction(Box::new(build(rhs0, gates)), Box::new(build(rhs1, gates)))
+                }
+                (v, None) => Formula::Variable(Variable(v)),
+            };
+            match l % 2 {
+                0 => f,
+                _ => Formula::Negation(Box::new(f)),
+            }
+        }
+        Some(build(*self.outputs.get(index)?, &gates))
+    }
+}
+
+fn literal(l: usize) -> Literal {
+    match l % 2 {
+        0 => Literal::positive(Variable(l / 2)),
+        _ => Literal::negative(Variable(l / 2)),
+    }
+}
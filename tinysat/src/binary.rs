@@ -0,0 +1,182 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{Clause, Cnf, Literal, Polarity, Variable};
+
+/// Implication graph of the binary clauses, `a ∨ b` gives `¬a → b` and `¬b → a`
+#[derive(Debug, Clone, Default)]
+pub struct Implications {
+    edges: HashMap<Literal, Vec<Literal>>,
+}
+
+impl Implications {
+    pub fn new(cnf: &Cnf) -> Self {
+        let mut edges: HashMap<Literal, Vec<Literal>> = HashMap::new();
+        for clause in cnf.0.iter() {
+            if let [a, b] = clause.0[..] {
+                edges.entry(a.negate()).or_default().push(b);
+                edges.entry(b.negate()).or_default().push(a);
+            }
+        }
+        Self { edges }
+    }
+
+    /// `literal` along with every literal it implies, `None` once they contradict
+    pub fn closure(&self, literal: Literal) -> Option<Vec<Literal>> {
+        let mut seen = HashSet::from([literal]);
+        let mut stack = vec![literal];
+        while let Some(l) = stack.pop() {
+            for next in self.edges.get(&l).into_iter().flatten() {
+                if seen.insert(*next) {
+                    stack.push(*next);
+                }
+            }
+        }
+        if seen.iter().any(|l| seen.contains(&l.negate())) {
+            return None;
+        }
+        Some(seen.into_iter().collect())
+    }
+
+    /// Variables equivalent to a literal of another variable, found as strongly connected
+    /// components of the graph, `None` when a variable is equivalent to its own negation
+    pub fn equivalences(&self) -> Option<HashMap<Variable, Literal>> {
+        let mut tarjan = Tarjan {
+            edges: &self.edges,
+            index: HashMap::new(),
+            low: HashMap::new(),
+            stack: vec![],
+            on_stack: HashSet::new(),
+            components: vec![],
+        };
+        for literal in self.edges.keys() {
+            if !tarjan.index.contains_key(literal) {
+                tarjan.visit(*literal);
+            }
+        }
+        let mut equivalent = HashMap::new();
+        for component in tarjan.components {
+            let members: HashSet<_> = component.iter().collect();
+            if component.iter().any(|l| members.contains(&l.negate())) {
+                return None;
+            }
+            let representative = *component.iter().min_by_key(|l| order(l)).unwrap();
+            for l in component {
+                if l.variable == representative.variable || equivalent.contains_key(&l.variable) {
+                    continue;
+                }
+                let literal = match l.polarity {
+                    Polarity::Positive => representative,
+                    Polarity::Negative => representative.negate(),
+                };
+                equivalent.insert(l.variable, literal);
+            }
+        }
+        Some(equivalent)
+    }
+}
+
+fn order(l: &Literal) -> (Variable, bool) {
+    (l.variable, l.polarity == Polarity::Negative)
+}
+
+struct Tarjan<'a> {
+    edges: &'a HashMap<Literal, Vec<Literal>>,
+    index: HashMap<Literal, usize>,
+    low: HashMap<Literal, usize>,
+    stack: Vec<Literal>,
+    on_stack: HashSet<Literal>,
+    components: Vec<Vec<Literal>>,
+}
+
+impl Tarjan<'_> {
+    fn visit(&mut self, literal: Literal) {
+        let index = self.index.len();
+        self.index.insert(literal, index);
+        self.low.insert(literal, index);
+        self.stack.push(literal);
+        self.on_stack.insert(literal);
+        for next in self.edges.get(&literal).into_iter().flatten() {
+            if !self.index.contains_key(next) {
+                self.visit(*next);
+                let low = self.low[&literal].min(self.low[next]);
+                self.low.insert(literal, low);
+            } else if self.on_stack.contains(next) {
+                let low = self.low[&literal].min(self.index[next]);
+                self.low.insert(literal, low);
+            }
+        }
+        if self.low[&literal] == index {
+            let mut component = vec![];
+            while let Some(l) = self.stack.pop() {
+                self.on_stack.remove(&l);
+                component.push(l);
+                if l == literal {
+                    break;
+                }
+            }
+            if component.len() > 1 {
+                self.components.push(component);
+            }
+        }
+    }
+}
+
+/// Replace every variable by the literal it is equivalent to, clauses made true are dropped
+pub fn substitute(cnf: &Cnf, equivalent: &HashMap<Variable, Literal>) -> Cnf {
+    let clauses = cnf.0.iter().filter_map(|clause| {
+        let mut literals: Vec<Literal> = clause
+            .0
+            .iter()
+            .map(|l| match (equivalent.get(&l.variable), l.polarity) {
+                (Some(literal), Polarity::Positive) => *literal,
+                (Some(literal), Polarity::Negative) => literal.negate(),
+                (None, _) => *l,
+            })
+            .collect();
+        literals.sort_by_key(order);
+        literals.dedup();
+        let tautology = literals.windows(2).any(|w| w[0].variable == w[1].variable);
+        (!tautology).then_some(Clause(literals))
+    });
+    Cnf(clauses.collect())
+}
+
+/// Drop binary clauses whose implication follows from a path through the others, the graph
+/// loses each dropped edge right away so that edges never justify each other
+pub fn transitive_reduction(cnf: &mut Cnf) {
+    let mut edges = Implications::new(cnf).edges;
+    let mut kept: Vec<Clause> = vec![];
+    for clause in std::mem::take(&mut cnf.0) {
+        let [a, b] = clause.0[..] else {
+            kept.push(clause);
+            continue;
+        };
+        // `a ∨ b` is the edge `¬a → b`, look for `b` from the other successors of `¬a`
+        let mut seen = HashSet::from([a.negate()]);
+        let mut stack: Vec<_> = edges[&a.negate()]
+            .iter()
+            .filter(|l| **l != b)
+            .copied()
+            .collect();
+        let mut redundant = false;
+        while let Some(l) = stack.pop() {
+            if l == b {
+                redundant = true;
+                break;
+            }
+            if seen.insert(l) {
+                stack.extend(edges.get(&l).into_iter().flatten());
+            }
+        }
+        if !redundant {
+            kept.push(clause);
+            continue;
+        }
+        for (from, to) in [(a.negate(), b), (b.negate(), a)] {
+            let successors = edges.get_mut(&from).unwrap();
+            let position = successors.iter().position(|l| *l == to).unwrap();
+            successors.swap_remove(position);
+        }
+    }
+    cnf.0 = kept;
+}
@@ -0,0 +1,80 @@
+use crate::Variable;
+
+/// Variables of a problem over a grid, `layers` of them per cell, followed by extra
+/// variables reserved in ranges that never overlap the grid or each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VarGrid {
+    width: usize,
+    height: usize,
+    layers: usize,
+    next_extra: usize,
+}
+
+impl VarGrid {
+    pub fn new(width: usize, height: usize, layers: usize) -> Self {
+        Self {
+            width,
+            height,
+            layers,
+            next_extra: width * height * layers,
+        }
+    }
+
+    /// Variable of the cell on the layer, `None` out of bounds
+    pub fn var(&self, x: usize, y: usize, layer: usize) -> Option<Variable> {
+        if x >= self.width || y >= self.height || layer >= self.layers {
+            return None;
+        }
+        Some(Variable((layer * self.height + y) * self.width + x))
+    }
+
+    /// Cell and layer of a grid variable, `None` for extra variables
+    pub fn cell(&self, variable: Variable) -> Option<(usize, usize, usize)> {
+        let v = *variable;
+        if v >= self.width * self.height * self.layers {
+            return None;
+        }
+        let (layer, v) = (
+            v / (self.width * self.height),
+            v % (self.width * self.height),
+        );
+        Some((v % self.width, v / self.width, layer))
+    }
+
+    /// Variables taken by the grid, extra variables come after
+    pub fn len(&self) -> usize {
+        self.width * self.height * self.layers
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// First of `count` fresh variables, such as the extra variables of a Tseitin encoding
+    pub fn reserve(&mut self, count: usize) -> Variable {
+        let first = Variable(self.next_extra);
+        self.next_extra += count;
+        first
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn var_grid() {
+        let mut grid = VarGrid::new(3, 2, 2);
+        for (x, y, layer) in [(0, 0, 0), (2, 1, 0), (1, 0, 1), (2, 1, 1)] {
+            let v = grid.var(x, y, layer).unwrap();
+            assert!(*v < grid.len());
+            assert_eq!(grid.cell(v), Some((x, y, layer)));
+        }
+        assert_eq!(grid.var(3, 0, 0), None);
+        assert_eq!(grid.var(0, 0, 2), None);
+        let extra = grid.reserve(4);
+        assert_eq!(*extra, grid.len());
+        assert_eq!(grid.cell(extra), None);
+        assert_eq!(*grid.reserve(1), grid.len() + 4);
+    }
+}
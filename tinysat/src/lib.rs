@@ -1,8 +1,19 @@
+pub mod aiger;
+mod binary;
+mod grid;
+mod qbf;
+mod simplify;
 mod solver;
-use solver::solve;
-pub use solver::Model;
+mod trace;
+mod visit;
+pub use grid::VarGrid;
+use solver::{solve, solve_assuming};
+pub use solver::{Model, NamedModel, SolverConfig};
+pub use trace::{Event, Trace};
+pub use visit::Visitor;
 
 use std::{
+    collections::HashSet,
     fmt::Display,
     ops::{BitAnd, BitOr, BitXor, Deref},
 };
@@ -196,7 +207,11 @@ impl Formula {
     }
 
     pub fn solve(&self) -> Model {
-        solve(self.clone().into())
+        solve(
+            &self.clone().into(),
+            &SolverConfig::default(),
+            &mut Trace::default(),
+        )
     }
 }
 
@@ -205,12 +220,151 @@ pub struct Cnf(Vec<Clause>);
 
 impl Cnf {
     pub fn solve(&self) -> Model {
-        solve(self.clone())
+        self.solve_with(&SolverConfig::default())
+    }
+
+    pub fn solve_with(&self, config: &SolverConfig) -> Model {
+        solve(self, config, &mut Trace::default())
+    }
+
+    /// Solve while recording decisions, propagations and conflicts into `trace`
+    pub fn solve_traced(&self, config: &SolverConfig, trace: &mut Trace) -> Model {
+        solve(self, config, trace)
+    }
+
+    /// Solve with the `assumptions` taken as assigned, without cloning the formula to add
+    /// them as unit clauses
+    pub fn solve_assuming(&self, assumptions: &[Literal]) -> Model {
+        self.solve_assuming_with(assumptions, &SolverConfig::default())
+    }
+
+    pub fn solve_assuming_with(&self, assumptions: &[Literal], config: &SolverConfig) -> Model {
+        solve_assuming(self, assumptions, config, &mut Trace::default())
+    }
+
+    pub fn solve_assuming_traced(
+        &self,
+        assumptions: &[Literal],
+        config: &SolverConfig,
+        trace: &mut Trace,
+    ) -> Model {
+        solve_assuming(self, assumptions, config, trace)
+    }
+
+    /// Models extending every assignment of `universal`, otherwise the first assignment that
+    /// no model extends
+    pub fn forall_exists(&self, universal: &[Variable]) -> Result<Vec<Model>, Vec<Literal>> {
+        self.forall_exists_with(universal, &SolverConfig::default())
+    }
+
+    pub fn forall_exists_with(
+        &self,
+        universal: &[Variable],
+        config: &SolverConfig,
+    ) -> Result<Vec<Model>, Vec<Literal>> {
+        qbf::forall_exists(self, universal, config)
+    }
+
+    /// Remove tautologies and subsumed clauses and strengthen clauses by self-subsuming
+    /// resolution
+    pub fn simplify(&mut self) {
+        simplify::simplify(self);
+    }
+
+    /// Drop binary clauses implied by a chain of other binary clauses
+    pub fn reduce_binary(&mut self) {
+        binary::transitive_reduction(self);
+    }
+
+    pub fn clauses(&self) -> impl Iterator<Item = &[Literal]> {
+        self.0.iter().map(|clause| clause.0.as_slice())
+    }
+
+    /// Every literal of every clause, repeated as often as it occurs
+    pub fn literals(&self) -> impl Iterator<Item = Literal> + '_ {
+        self.0.iter().flat_map(|clause| clause.0.iter().copied())
+    }
+
+    /// Distinct variables occurring in the formula
+    pub fn num_vars(&self) -> usize {
+        self.literals()
+            .map(|l| l.variable)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    pub fn num_clauses(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Number of clauses of each length, indexed by length
+    pub fn clause_lengths(&self) -> Vec<usize> {
+        let mut histogram = vec![];
+        for clause in self.0.iter() {
+            if histogram.len() <= clause.0.len() {
+                histogram.resize(clause.0.len() + 1, 0);
+            }
+            histogram[clause.0.len()] += 1;
+        }
+        histogram
     }
 
     pub fn merge(&mut self, other: Cnf) {
         self.0.extend(other.0);
     }
+
+    /// Add a clause directly, an empty one makes the formula unsatisfiable
+    pub fn push(&mut self, literals: Vec<Literal>) {
+        self.0.push(Clause(literals));
+    }
+
+    /// Exactly `k` of the variables are true, encoded without extra variables: every `k + 1`
+    /// of them have one false and every `len - k + 1` of them have one true, which stays small
+    /// for the few variables of one Minesweeper number
+    pub fn exactly(vars: &[Variable], k: usize) -> Self {
+        let mut cnf = Cnf::default();
+        if k > vars.len() {
+            cnf.push(vec![]);
+            return cnf;
+        }
+        for_each_subset(vars, k + 1, &mut |subset| {
+            cnf.push(subset.iter().map(|v| Literal::negative(*v)).collect())
+        });
+        for_each_subset(vars, vars.len() - k + 1, &mut |subset| {
+            cnf.push(subset.iter().map(|v| Literal::positive(*v)).collect())
+        });
+        cnf
+    }
+
+    /// First variable after both `max` and every variable of the formula, where the extra
+    /// variables of another encoding can start without colliding
+    pub fn fresh_var_after(&self, max: Variable) -> Variable {
+        self.0
+            .iter()
+            .flat_map(|clause| clause.0.iter())
+            .map(|l| l.variable)
+            .fold(max, Variable::max)
+            .next_variable()
+    }
+
+    /// Formula in DIMACS format, variable `n` is written as `n + 1` since DIMACS counts from 1
+    pub fn to_dimacs(&self) -> String {
+        let literals = || self.0.iter().flat_map(|clause| clause.0.iter());
+        let vars = literals().map(|l| *l.variable + 1).max().unwrap_or(0);
+        let mut dimacs = format!("p cnf {vars} {}\n", self.0.len());
+        for clause in self.0.iter() {
+            for l in clause.0.iter() {
+                let var = *l.variable as isize + 1;
+                let var = match l.polarity {
+                    Polarity::Positive => var,
+                    Polarity::Negative => -var,
+                };
+                dimacs.push_str(&format!("{var} "));
+            }
+            dimacs.push_str("0\n");
+        }
+        dimacs
+    }
 }
 
 impl Display for Cnf {
@@ -234,6 +388,32 @@ impl From<Formula> for Cnf {
     }
 }
 
+/// Call `f` with every subset of `size` items, in lexicographic order
+fn for_each_subset(items: &[Variable], size: usize, f: &mut impl FnMut(&[Variable])) {
+    fn rec(
+        items: &[Variable],
+        size: usize,
+        chosen: &mut Vec<Variable>,
+        f: &mut impl FnMut(&[Variable]),
+    ) {
+        if chosen.len() == size {
+            f(chosen);
+            return;
+        }
+        for (i, item) in items.iter().enumerate() {
+            if items.len() - i < size - chosen.len() {
+                break;
+            }
+            chosen.push(*item);
+            rec(&items[i + 1..], size, chosen, f);
+            chosen.pop();
+        }
+    }
+    if size <= items.len() {
+        rec(items, size, &mut Vec::with_capacity(size), f);
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Clause(Vec<Literal>);
 
@@ -252,26 +432,35 @@ impl Display for Clause {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Literal {
     variable: Variable,
     polarity: Polarity,
 }
 
 impl Literal {
-    fn positive(variable: Variable) -> Self {
+    pub fn positive(variable: Variable) -> Self {
         Literal {
             variable,
             polarity: Polarity::Positive,
         }
     }
 
-    fn negative(variable: Variable) -> Self {
+    pub fn negative(variable: Variable) -> Self {
         Literal {
             variable,
             polarity: Polarity::Negative,
         }
     }
 
+    pub fn variable(&self) -> Variable {
+        self.variable
+    }
+
+    pub fn polarity(&self) -> Polarity {
+        self.polarity
+    }
+
     fn negate(&self) -> Self {
         Self {
             polarity: self.polarity.negate(),
@@ -291,6 +480,7 @@ impl Display for Literal {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Variable(pub usize);
 
 impl Deref for Variable {
@@ -320,6 +510,7 @@ impl From<usize> for Variable {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Polarity {
     Positive,
     Negative,
@@ -416,4 +607,333 @@ mod tests {
         let model = cnf.solve();
         println!("{model}");
     }
+
+    #[test]
+    fn dimacs() {
+        let cnf = Cnf::from(Formula::Negation(Box::new(Formula::Variable(1.into()))));
+        assert_eq!(cnf.to_dimacs(), "p cnf 2 1\n-2 0\n");
+    }
+
+    #[test]
+    fn fresh_var_after() {
+        let cnf = Cnf::from(default_formula());
+        let fresh = cnf.fresh_var_after(0.into());
+        assert!(*fresh > 5);
+        assert_eq!(cnf.fresh_var_after(100.into()), 101.into());
+        let extra = default_formula().tseitin_encode(fresh);
+        assert!(extra.fresh_var_after(0.into()) > fresh);
+    }
+
+    #[test]
+    fn exactly() {
+        let vars: Vec<Variable> = (0..4).map(Variable).collect();
+        for k in 0..=5 {
+            for ones in 0..=4 {
+                let mut cnf = Cnf::exactly(&vars, k);
+                for (i, v) in vars.iter().enumerate() {
+                    cnf.push(vec![match i < ones {
+                        true => Literal::positive(*v),
+                        false => Literal::negative(*v),
+                    }]);
+                }
+                assert_eq!(cnf.solve().is_unsat(), ones != k);
+            }
+        }
+    }
+
+    #[test]
+    fn solve_assuming() {
+        let vars: Vec<Variable> = (0..4).map(Variable).collect();
+        let cnf = Cnf::exactly(&vars, 2);
+        for bits in 0..16 {
+            let assumptions: Vec<_> = vars
+                .iter()
+                .take(3)
+                .enumerate()
+                .map(|(i, v)| match bits >> i & 1 {
+                    1 => Literal::positive(*v),
+                    _ => Literal::negative(*v),
+                })
+                .collect();
+            let mut injected = cnf.clone();
+            for literal in assumptions.iter() {
+                injected.push(vec![*literal]);
+            }
+            let model = cnf.solve_assuming(&assumptions);
+            assert_eq!(model.is_unsat(), injected.solve().is_unsat());
+            if let Model::Satisfied(assignment) = model {
+                for literal in assumptions.iter() {
+                    assert_eq!(assignment.get(&literal.variable), Some(&literal.polarity));
+                }
+            }
+        }
+        let v = vars[0];
+        let contradiction = [Literal::positive(v), Literal::negative(v)];
+        assert!(cnf.solve_assuming(&contradiction).is_unsat());
+    }
+
+    #[test]
+    fn simplify() {
+        let [a, b, c] = [0, 1, 2].map(Variable);
+        let mut cnf = Cnf::default();
+        cnf.push(vec![Literal::positive(a), Literal::positive(b)]);
+        cnf.push(vec![
+            Literal::positive(a),
+            Literal::positive(b),
+            Literal::positive(c),
+        ]);
+        cnf.push(vec![Literal::negative(a), Literal::positive(b)]);
+        cnf.push(vec![Literal::positive(c), Literal::negative(c)]);
+        cnf.simplify();
+        // the longer clause is subsumed, then resolving on a leaves b alone, which subsumes the rest
+        assert_eq!(cnf.0, vec![Clause(vec![Literal::positive(b)])]);
+
+        let vars: Vec<Variable> = (0..5).map(Variable).collect();
+        for k in 0..=5 {
+            let mut cnf = Cnf::exactly(&vars, k);
+            cnf.merge(Cnf::exactly(&vars[1..4], 1));
+            let mut simplified = cnf.clone();
+            simplified.simplify();
+            assert!(simplified.0.len() <= cnf.0.len());
+            for bits in 0..32 {
+                let assumptions: Vec<_> = vars
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| match bits >> i & 1 {
+                        1 => Literal::positive(*v),
+                        _ => Literal::negative(*v),
+                    })
+                    .collect();
+                assert_eq!(
+                    simplified.solve_assuming(&assumptions).is_unsat(),
+                    cnf.solve_assuming(&assumptions).is_unsat()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn binary() {
+        let [a, b, c, d] = [0, 1, 2, 3].map(Variable);
+        let mut cnf = Cnf::default();
+        // a ≡ b ≡ ¬c, then a chain a → d that makes b → d redundant
+        cnf.push(vec![Literal::negative(a), Literal::positive(b)]);
+        cnf.push(vec![Literal::positive(a), Literal::negative(b)]);
+        cnf.push(vec![Literal::negative(b), Literal::negative(c)]);
+        cnf.push(vec![Literal::positive(b), Literal::positive(c)]);
+        cnf.push(vec![Literal::negative(a), Literal::positive(d)]);
+        cnf.push(vec![Literal::negative(b), Literal::positive(d)]);
+        cnf.push(vec![
+            Literal::positive(c),
+            Literal::positive(d),
+            Literal::positive(a),
+        ]);
+        let Model::Satisfied(model) = cnf.solve_assuming(&[Literal::negative(d)]) else {
+            panic!("satisfiable");
+        };
+        assert_eq!(model[&a], Polarity::Negative);
+        assert_eq!(model[&b], Polarity::Negative);
+        assert_eq!(model[&c], Polarity::Positive);
+        assert!(cnf
+            .solve_assuming(&[Literal::positive(a), Literal::positive(c)])
+            .is_unsat());
+        let mut reduced = cnf.clone();
+        reduced.reduce_binary();
+        assert!(reduced.0.len() < cnf.0.len());
+        for bits in 0..16 {
+            let assumptions: Vec<_> = [a, b, c, d]
+                .iter()
+                .enumerate()
+                .map(|(i, v)| match bits >> i & 1 {
+                    1 => Literal::positive(*v),
+                    _ => Literal::negative(*v),
+                })
+                .collect();
+            assert_eq!(
+                reduced.solve_assuming(&assumptions).is_unsat(),
+                cnf.solve_assuming(&assumptions).is_unsat()
+            );
+        }
+
+        // a variable equivalent to its own negation
+        cnf.push(vec![Literal::positive(c), Literal::negative(a)]);
+        assert!(!cnf.solve().is_unsat());
+        cnf.push(vec![Literal::negative(c), Literal::positive(a)]);
+        assert!(cnf.solve().is_unsat());
+    }
+
+    #[test]
+    fn seed() {
+        let vars: Vec<Variable> = (0..8).map(Variable).collect();
+        let cnf = Cnf::exactly(&vars, 3);
+        let sorted = |model: Model| match model {
+            Model::Satisfied(assignment) => {
+                let mut assignment: Vec<_> = assignment.iter().map(|(v, p)| (*v, *p)).collect();
+                assignment.sort_by_key(|(v, _)| *v);
+                assignment
+            }
+            Model::Unsatisfiable => panic!("satisfiable"),
+        };
+        let config = |seed| SolverConfig {
+            seed,
+            random_polarity: true,
+            ..Default::default()
+        };
+        let model = |seed| sorted(cnf.solve_with(&config(seed)));
+        assert_eq!(model(7), model(7));
+        assert!((0..16).any(|seed| model(seed) != model(0)));
+
+        // the previous model is found again whatever the seed
+        let mut warm = config(0);
+        warm.warm_start(&cnf.solve_with(&config(3)));
+        assert_eq!(sorted(cnf.solve_with(&warm)), model(3));
+    }
+
+    #[test]
+    fn trace() {
+        let vars: Vec<Variable> = (0..4).map(Variable).collect();
+        let mut cnf = Cnf::exactly(&vars, 2);
+        cnf.merge(Cnf::exactly(&vars[..2], 0));
+        let mut trace = Trace::new(64);
+        let model = cnf.solve_assuming_traced(
+            &[Literal::negative(vars[2])],
+            &SolverConfig::default(),
+            &mut trace,
+        );
+        assert!(model.is_unsat());
+        assert_eq!(
+            trace.events().next(),
+            Some(&Event::Assumption(Literal::negative(vars[2])))
+        );
+        assert!(trace.conflicts() > 0);
+        let mut again = Trace::new(64);
+        cnf.solve_assuming_traced(
+            &[Literal::negative(vars[2])],
+            &SolverConfig::default(),
+            &mut again,
+        );
+        assert_eq!(trace, again);
+
+        let mut short = Trace::new(2);
+        cnf.solve_traced(&SolverConfig::default(), &mut short);
+        assert!(short.events().count() <= 2);
+    }
+
+    #[test]
+    fn statistics() {
+        let vars: Vec<Variable> = (0..4).map(Variable).collect();
+        let mut cnf = Cnf::exactly(&vars[..3], 1);
+        cnf.push(vec![Literal::negative(vars[3])]);
+        assert_eq!(cnf.num_vars(), 4);
+        assert_eq!(cnf.num_clauses(), 5);
+        assert_eq!(cnf.clause_lengths(), vec![0, 1, 3, 1]);
+        assert_eq!(cnf.literals().count(), 10);
+        assert_eq!(
+            cnf.clauses().last(),
+            Some(&[Literal::negative(vars[3])][..])
+        );
+        assert!(Cnf::default().clause_lengths().is_empty());
+    }
+
+    #[test]
+    fn visit() {
+        let f = default_formula();
+        let nnf = f.nnf();
+        let vars: Vec<Variable> = (1..=5).map(Variable).collect();
+        for bits in 0..32 {
+            let value = |v: Variable| bits >> (*v - 1) & 1 == 1;
+            assert_eq!(nnf.evaluate(value), f.evaluate(value));
+        }
+        // negations only ever apply to variables
+        let mut stack = vec![&nnf];
+        while let Some(f) = stack.pop() {
+            match f {
+                Formula::Variable(_) => {}
+                Formula::Negation(f) => assert!(matches!(**f, Formula::Variable(_))),
+                Formula::Conjunction(f0, f1) | Formula::Disjunction(f0, f1) => {
+                    stack.extend([&**f0, &**f1])
+                }
+                _ => panic!("not in negation normal form"),
+            }
+        }
+        assert_eq!(f.disjuncts().len(), 2);
+        assert_eq!(f.conjuncts().len(), 1);
+
+        // (3 → 3 ∧ 4) ∨ (3 → 3 ∧ 5) once 1 and 2 are both 3
+        let substituted = f
+            .substitute(vars[0], &Formula::Variable(vars[2]))
+            .substitute(vars[1], &Formula::Variable(vars[2]));
+        assert!(substituted.evaluate(|_| false));
+        assert!(!substituted.evaluate(|v| v == vars[2]));
+        assert!(substituted.evaluate(|v| v == vars[2] || v == vars[4]));
+    }
+
+    #[test]
+    fn aiger() {
+        use aiger::{Aiger, AigerError};
+        // output 3 ∧ ¬(1 ∧ 2), with 3 = 1 ∧ ¬2 and 4 = 1 ∧ 2
+        let text = "aag 5 2 0 1 3\n2\n4\n10\n6 2 5\n8 2 4\n10 6 9\nc\nxor-ish\n";
+        let circuit = Aiger::parse(text).unwrap();
+        assert_eq!(circuit.inputs, vec![2, 4]);
+        let Model::Satisfied(model) = circuit.to_cnf().solve() else {
+            panic!("satisfiable");
+        };
+        assert_eq!(model[&Variable(1)], Polarity::Positive);
+        assert_eq!(model[&Variable(2)], Polarity::Negative);
+        let output = circuit.output(0).unwrap();
+        assert!(output.evaluate(|v| v == Variable(1)));
+        assert!(!output.evaluate(|v| v == Variable(1) || v == Variable(2)));
+        assert!(circuit.output(1).is_none());
+
+        // 3 ∧ 4 needs both 2 and ¬2
+        let unsat = "aag 5 2 0 1 3\n2\n4\n10\n6 2 5\n8 2 4\n10 6 8\n";
+        assert!(Aiger::parse(unsat).unwrap().to_cnf().solve().is_unsat());
+        assert_eq!(Aiger::parse("aig 1 1 0 0 0"), Err(AigerError::Header));
+        assert_eq!(Aiger::parse("aag 1 0 1 0 0\n2 3"), Err(AigerError::Latches));
+        assert_eq!(Aiger::parse("aag 1 1 0 0 0\n4"), Err(AigerError::Line(2)));
+        assert_eq!(Aiger::parse("aag 1 1 0 1 0\n2"), Err(AigerError::Line(3)));
+    }
+
+    #[test]
+    fn forall_exists() {
+        let vars: Vec<Variable> = (0..4).map(Variable).collect();
+        let mut cnf = Cnf::exactly(&vars[..3], 1);
+        cnf.merge(Cnf::exactly(&vars[1..], 1));
+        assert_eq!(
+            cnf.forall_exists(&[vars[3]]).map(|models| models.len()),
+            Ok(2)
+        );
+        assert_eq!(
+            cnf.forall_exists(&[vars[0], vars[3]]).err(),
+            Some(vec![Literal::positive(vars[0]), Literal::negative(vars[3])])
+        );
+        assert!(cnf.forall_exists(&[]).is_ok());
+        cnf.push(vec![Literal::negative(vars[1])]);
+        cnf.push(vec![Literal::negative(vars[2])]);
+        assert_eq!(
+            cnf.forall_exists(&[vars[3]]).err(),
+            Some(vec![Literal::negative(vars[3])])
+        );
+    }
+
+    #[test]
+    fn named() {
+        let mut cnf = Cnf::exactly(&[Variable(2), Variable(0)], 1);
+        cnf.push(vec![Literal::positive(Variable(2))]);
+        let name = |v: Variable| format!("v{}", *v);
+        assert_eq!(cnf.solve().named(name).to_string(), "sat: v0=0, v2=1");
+        cnf.push(vec![Literal::positive(Variable(0))]);
+        assert_eq!(cnf.solve().named(name).to_string(), "unsat");
+    }
+
+    #[test]
+    fn degenerate() {
+        assert!(!Cnf::default().solve().is_unsat());
+        let mut empty_clause = Cnf::default();
+        empty_clause.push(vec![]);
+        assert!(empty_clause.solve().is_unsat());
+        assert!(Cnf::exactly(&[], 1).solve().is_unsat());
+        assert!(Cnf::exactly(&[Variable(0)], 2).solve().is_unsat());
+        assert!(!Cnf::exactly(&[], 0).solve().is_unsat());
+    }
 }
@@ -0,0 +1,31 @@
+use crate::{Cnf, Literal, Model, SolverConfig, Variable};
+
+/// Whether `∀ universal ∃ rest: cnf` holds, by expanding the universal variables into one
+/// solve per assignment of them. A model for each assignment is returned when it holds,
+/// otherwise the first assignment left without one, with positive tried first
+pub fn forall_exists(
+    cnf: &Cnf,
+    universal: &[Variable],
+    config: &SolverConfig,
+) -> Result<Vec<Model>, Vec<Literal>> {
+    assert!(
+        universal.len() < u64::BITS as usize,
+        "too many universal variables to expand"
+    );
+    let mut models = vec![];
+    for bits in 0..1u64 << universal.len() {
+        let assumptions: Vec<_> = universal
+            .iter()
+            .enumerate()
+            .map(|(i, v)| match bits >> (universal.len() - 1 - i) & 1 {
+                0 => Literal::positive(*v),
+                _ => Literal::negative(*v),
+            })
+            .collect();
+        match cnf.solve_assuming_with(&assumptions, config) {
+            Model::Unsatisfiable => return Err(assumptions),
+            model => models.push(model),
+        }
+    }
+    Ok(models)
+}
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use crate::{Clause, Cnf, Literal, Polarity};
+
+/// Drop tautologies and clauses subsumed by another, and strengthen clauses by
+/// self-subsuming resolution: `l ∨ A` removes `¬l` from any clause containing `¬l ∨ A`
+pub fn simplify(cnf: &mut Cnf) {
+    let mut clauses: Vec<Option<Vec<Literal>>> = std::mem::take(&mut cnf.0)
+        .into_iter()
+        .map(|Clause(mut literals)| {
+            literals.sort_by_key(|l| (l.variable, l.polarity == Polarity::Positive));
+            literals.dedup();
+            let tautology = literals.windows(2).any(|w| w[0].variable == w[1].variable);
+            (!tautology).then_some(literals)
+        })
+        .collect();
+    let mut occurrences: HashMap<Literal, Vec<usize>> = HashMap::new();
+    for (i, clause) in clauses.iter().enumerate() {
+        for l in clause.iter().flatten() {
+            occurrences.entry(*l).or_default().push(i);
+        }
+    }
+    // shortest clauses are popped first, they subsume the most
+    let mut queue: Vec<usize> = (0..clauses.len())
+        .filter(|i| clauses[*i].is_some())
+        .collect();
+    queue.sort_by_key(|i| std::cmp::Reverse(clauses[*i].as_ref().unwrap().len()));
+    while let Some(i) = queue.pop() {
+        let Some(clause) = clauses[i].clone() else {
+            continue;
+        };
+        for l in clause.iter() {
+            for &j in occurrences.get(l).into_iter().flatten() {
+                let Some(other) = &clauses[j] else {
+                    continue;
+                };
+                if j != i && other.len() >= clause.len() && clause.iter().all(|l| other.contains(l))
+                {
+                    clauses[j] = None;
+                }
+            }
+            let negated = l.negate();
+            for &j in occurrences.get(&negated).into_iter().flatten() {
+                let Some(other) = &mut clauses[j] else {
+                    continue;
+                };
+                let resolvable = other.len() >= clause.len()
+                    && other.contains(&negated)
+                    && clause.iter().all(|m| m == l || other.contains(m));
+                if resolvable {
+                    other.retain(|m| *m != negated);
+                    queue.push(j);
+                }
+            }
+        }
+    }
+    cnf.0 = clauses.into_iter().flatten().map(Clause).collect();
+}
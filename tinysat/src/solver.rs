@@ -4,9 +4,47 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
-use crate::{Clause, Cnf, Literal, Polarity, Variable};
+use crate::{
+    binary::{substitute, Implications},
+    trace::{Event, Trace},
+    Clause, Cnf, Literal, Polarity, Variable,
+};
 use Polarity::*;
 
+/// Choices the search is free to make, all derived from `seed` so that a formula solves to
+/// the same model on every run and every target
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SolverConfig {
+    /// Orders decision variables, and their polarities with `random_polarity`
+    pub seed: u64,
+    /// Try either polarity of a decision first instead of always positive, the latter is
+    /// faster on minesweeper formulas
+    pub random_polarity: bool,
+    /// Polarity tried first for the variables given, before either of the above applies
+    pub phases: HashMap<Variable, Polarity>,
+}
+
+impl SolverConfig {
+    /// Try the values of a previous model first, so that a formula close to the one it came
+    /// from is satisfied with few backtracks, phases of other variables are kept
+    pub fn warm_start(&mut self, model: &Model) {
+        if let Model::Satisfied(assignment) = model {
+            self.phases.extend(assignment.iter());
+        }
+    }
+
+    /// splitmix64 of the seed and the variable, stable unlike the iteration order of a
+    /// `HashSet`
+    fn key(&self, variable: Variable) -> u64 {
+        let mut z = self
+            .seed
+            .wrapping_add((variable.0 as u64).wrapping_mul(0x9e3779b97f4a7c15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Model {
     Satisfied(Assignment),
@@ -17,6 +55,33 @@ impl Model {
     pub fn is_unsat(&self) -> bool {
         matches!(self, Model::Unsatisfiable)
     }
+
+    /// Display as `name=1` for each variable in order, with names given by `name`
+    pub fn named<F: Fn(Variable) -> String>(&self, name: F) -> NamedModel<'_, F> {
+        NamedModel { model: self, name }
+    }
+}
+
+pub struct NamedModel<'a, F> {
+    model: &'a Model,
+    name: F,
+}
+
+impl<F: Fn(Variable) -> String> Display for NamedModel<'_, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Model::Satisfied(assignment) = self.model else {
+            return write!(f, "unsat");
+        };
+        let mut assignment: Vec<_> = assignment.iter().collect();
+        assignment.sort_by_key(|(v, _)| **v);
+        write!(f, "sat")?;
+        for (i, (v, polarity)) in assignment.into_iter().enumerate() {
+            let value = (*polarity == Positive) as u8;
+            let separator = if i == 0 { ": " } else { ", " };
+            write!(f, "{separator}{}={value}", (self.name)(*v))?;
+        }
+        Ok(())
+    }
 }
 
 impl Display for Model {
@@ -74,14 +139,15 @@ enum AssignResult {
     Unchanged(Cnf),
 }
 
-fn assign(cnf: Cnf, assignment: &Assignment) -> AssignResult {
+/// Only reads `cnf`, the clauses left are copied into the result
+fn assign(cnf: &Cnf, assignment: &Assignment) -> AssignResult {
     let mut reduced = false;
     let cnf = Cnf(cnf
         .0
-        .into_iter()
+        .iter()
         .filter_map(|clause| {
             let mut new_clause = Vec::new();
-            for Literal { variable, polarity } in clause.0 {
+            for &Literal { variable, polarity } in clause.0.iter() {
                 match assignment.get(&variable) {
                     Some(a) => {
                         reduced = true;
@@ -133,7 +199,7 @@ fn unit_propagation(mut cnf: Cnf) -> UnitPropagationResult {
                 }
             }
         }
-        match assign(cnf, &implies) {
+        match assign(&cnf, &implies) {
             AssignResult::Reduced(new_cnf) => {
                 cnf = new_cnf;
                 continue;
@@ -147,62 +213,139 @@ fn unit_propagation(mut cnf: Cnf) -> UnitPropagationResult {
     UnitPropagationResult::Continue(cnf, implies)
 }
 
-fn solve_rec(cnf: Cnf, mut variables: HashSet<Variable>) -> Model {
+fn solve_rec(
+    cnf: &Cnf,
+    mut variables: HashSet<Variable>,
+    implications: &Implications,
+    config: &SolverConfig,
+    trace: &mut Trace,
+    depth: usize,
+) -> Model {
     if cnf.0.is_empty() {
         return Model::Satisfied(Assignment(HashMap::new()));
     }
-    let victim = *variables.iter().take(1).collect::<Vec<_>>()[0];
+    // clauses left without any variable are empty
+    let Some((victim, key)) = variables
+        .iter()
+        .map(|v| (*v, config.key(*v)))
+        .min_by_key(|(v, key)| (*key, *v))
+    else {
+        return Model::Unsatisfiable;
+    };
     variables.remove(&victim);
-    let AssignResult::Reduced(new_cnf) = assign(
-        cnf.clone(),
-        &Assignment(HashMap::from_iter([(victim, Positive)])),
-    ) else {
-        unreachable!();
+    let polarities = match (
+        config.phases.get(&victim),
+        key & config.random_polarity as u64,
+    ) {
+        (Some(Positive), _) | (None, 0) => [Positive, Negative],
+        _ => [Negative, Positive],
     };
-    match unit_propagation(new_cnf) {
-        UnitPropagationResult::Unsatisfiable => {}
-        UnitPropagationResult::Continue(cnf, implies) => {
-            let mut variables = variables.clone();
-            for v in implies.keys() {
-                variables.remove(v);
-            }
-            match solve_rec(cnf, variables) {
-                Model::Satisfied(mut assignment) => {
-                    assignment.insert(victim, Positive);
+    let depth = depth + 1;
+    for polarity in polarities {
+        let decision = Literal {
+            variable: victim,
+            polarity,
+        };
+        trace.push(Event::Decision {
+            literal: decision,
+            depth,
+        });
+        // the literals implied through binary clauses come along without propagating
+        let Some(implied) = implications.closure(decision) else {
+            trace.push(Event::Conflict { depth });
+            continue;
+        };
+        trace.propagate(implied.iter().copied().filter(|l| *l != decision), depth);
+        let branch = Assignment(implied.iter().map(|l| (l.variable, l.polarity)).collect());
+        // victim may be gone from cnf after its clauses are satisfied
+        let (AssignResult::Reduced(new_cnf) | AssignResult::Unchanged(new_cnf)) =
+            assign(cnf, &branch);
+        match unit_propagation(new_cnf) {
+            UnitPropagationResult::Unsatisfiable => trace.push(Event::Conflict { depth }),
+            UnitPropagationResult::Continue(cnf, implies) => {
+                trace.propagate(
+                    implies
+                        .iter()
+                        .map(|(&variable, &polarity)| Literal { variable, polarity }),
+                    depth,
+                );
+                let mut variables = variables.clone();
+                for v in implies.keys().chain(branch.keys()) {
+                    variables.remove(v);
+                }
+                if let Model::Satisfied(mut assignment) =
+                    solve_rec(&cnf, variables, implications, config, trace, depth)
+                {
+                    assignment.extend(branch.0);
                     assignment.extend(implies.0);
                     return Model::Satisfied(assignment);
                 }
-                Model::Unsatisfiable => {}
             }
         }
     }
-    let AssignResult::Reduced(new_cnf) = assign(
-        cnf.clone(),
-        &Assignment(HashMap::from_iter([(victim, Negative)])),
+    Model::Unsatisfiable
+}
+
+/// Variables equivalent through binary clauses are merged before searching
+pub fn solve(cnf: &Cnf, config: &SolverConfig, trace: &mut Trace) -> Model {
+    let implications = Implications::new(cnf);
+    let Some(equivalent) = implications.equivalences() else {
+        trace.push(Event::Conflict { depth: 0 });
+        return Model::Unsatisfiable;
+    };
+    if equivalent.is_empty() {
+        return solve_rec(cnf, all_variables(cnf), &implications, config, trace, 0);
+    }
+    let merged = substitute(cnf, &equivalent);
+    let implications = Implications::new(&merged);
+    let Model::Satisfied(mut assignment) = solve_rec(
+        &merged,
+        all_variables(&merged),
+        &implications,
+        config,
+        trace,
+        0,
     ) else {
-        unreachable!();
+        return Model::Unsatisfiable;
     };
-    match unit_propagation(new_cnf) {
-        UnitPropagationResult::Unsatisfiable => {}
-        UnitPropagationResult::Continue(cnf, implies) => {
-            let mut variables = variables.clone();
-            for v in implies.keys() {
-                variables.remove(v);
-            }
-            match solve(cnf) {
-                Model::Satisfied(mut assignment) => {
-                    assignment.insert(victim, Negative);
-                    assignment.extend(implies.0);
-                    return Model::Satisfied(assignment);
-                }
-                Model::Unsatisfiable => {}
-            }
+    for (variable, literal) in equivalent {
+        if let Some(value) = assignment.get(&literal.variable).copied() {
+            let value = match literal.polarity {
+                Positive => value,
+                Negative => value.negate(),
+            };
+            assignment.insert(variable, value);
         }
     }
-    Model::Unsatisfiable
+    Model::Satisfied(assignment)
 }
 
-pub fn solve(cnf: Cnf) -> Model {
-    let variables = all_variables(&cnf);
-    solve_rec(cnf, variables)
+/// Only the clauses left once `assumptions` are assigned are copied out of `cnf`
+pub fn solve_assuming(
+    cnf: &Cnf,
+    assumptions: &[Literal],
+    config: &SolverConfig,
+    trace: &mut Trace,
+) -> Model {
+    let mut assumed = Assignment(HashMap::new());
+    for literal @ Literal { variable, polarity } in assumptions.iter().copied() {
+        trace.push(Event::Assumption(literal));
+        if *assumed.entry(variable).or_insert(polarity) != polarity {
+            trace.push(Event::Conflict { depth: 0 });
+            return Model::Unsatisfiable;
+        }
+    }
+    let (AssignResult::Reduced(reduced) | AssignResult::Unchanged(reduced)) = assign(cnf, &assumed);
+    // every literal of a clause assumed false
+    if reduced.0.iter().any(|clause| clause.0.is_empty()) {
+        trace.push(Event::Conflict { depth: 0 });
+        return Model::Unsatisfiable;
+    }
+    match solve(&reduced, config, trace) {
+        Model::Satisfied(mut assignment) => {
+            assignment.extend(assumed.0);
+            Model::Satisfied(assignment)
+        }
+        Model::Unsatisfiable => Model::Unsatisfiable,
+    }
 }
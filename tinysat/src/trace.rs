@@ -0,0 +1,90 @@
+use std::collections::VecDeque;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Literal, Polarity};
+
+/// One step of the search, `depth` counts the decisions it happened under
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Event {
+    /// Taken as assigned before searching
+    Assumption(Literal),
+    Decision {
+        literal: Literal,
+        depth: usize,
+    },
+    /// Forced by the clauses under the decisions so far
+    Propagation {
+        literal: Literal,
+        depth: usize,
+    },
+    /// Some clause is false under the decisions so far, the last of them is undone
+    Conflict {
+        depth: usize,
+    },
+}
+
+/// The last `capacity` events of a search, a capacity of zero records nothing
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Trace {
+    capacity: usize,
+    events: VecDeque<Event>,
+    /// Events pushed out of the buffer by newer ones
+    dropped: usize,
+}
+
+impl Trace {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            events: VecDeque::with_capacity(capacity),
+            dropped: 0,
+        }
+    }
+
+    /// Oldest first
+    pub fn events(&self) -> impl Iterator<Item = &Event> {
+        self.events.iter()
+    }
+
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    pub fn conflicts(&self) -> usize {
+        self.events
+            .iter()
+            .filter(|event| matches!(event, Event::Conflict { .. }))
+            .count()
+    }
+
+    pub(crate) fn enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub(crate) fn push(&mut self, event: Event) {
+        if !self.enabled() {
+            return;
+        }
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+    }
+
+    /// Literals are sorted so that traces of the same search compare equal
+    pub(crate) fn propagate(&mut self, literals: impl IntoIterator<Item = Literal>, depth: usize) {
+        if !self.enabled() {
+            return;
+        }
+        let mut literals: Vec<_> = literals.into_iter().collect();
+        literals.sort_by_key(|l| (l.variable, l.polarity == Polarity::Negative));
+        for literal in literals {
+            self.push(Event::Propagation { literal, depth });
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{Formula, Variable};
+
+/// Fold over a formula bottom up, each method is given what the subformulas folded into
+pub trait Visitor {
+    type Output;
+
+    fn variable(&mut self, v: Variable) -> Self::Output;
+    fn negation(&mut self, f: Self::Output) -> Self::Output;
+    fn conjunction(&mut self, f0: Self::Output, f1: Self::Output) -> Self::Output;
+    fn disjunction(&mut self, f0: Self::Output, f1: Self::Output) -> Self::Output;
+    fn equivalence(&mut self, f0: Self::Output, f1: Self::Output) -> Self::Output;
+    fn implication(&mut self, f0: Self::Output, f1: Self::Output) -> Self::Output;
+}
+
+impl Formula {
+    pub fn visit<V: Visitor>(&self, visitor: &mut V) -> V::Output {
+        use Formula::*;
+        match self {
+            Variable(v) => visitor.variable(*v),
+            Negation(f) => {
+                let f = f.visit(visitor);
+                visitor.negation(f)
+            }
+            Conjunction(f0, f1) => {
+                let (f0, f1) = (f0.visit(visitor), f1.visit(visitor));
+                visitor.conjunction(f0, f1)
+            }
+            Disjunction(f0, f1) => {
+                let (f0, f1) = (f0.visit(visitor), f1.visit(visitor));
+                visitor.disjunction(f0, f1)
+            }
+            Equivalence(f0, f1) => {
+                let (f0, f1) = (f0.visit(visitor), f1.visit(visitor));
+                visitor.equivalence(f0, f1)
+            }
+            Implication(f0, f1) => {
+                let (f0, f1) = (f0.visit(visitor), f1.visit(visitor));
+                visitor.implication(f0, f1)
+            }
+        }
+    }
+
+    /// Truth value with every variable valued by `value`
+    pub fn evaluate(&self, value: impl FnMut(Variable) -> bool) -> bool {
+        struct Evaluate<F>(F);
+        impl<F: FnMut(Variable) -> bool> Visitor for Evaluate<F> {
+            type Output = bool;
+            fn variable(&mut self, v: Variable) -> bool {
+                (self.0)(v)
+            }
+            fn negation(&mut self, f: bool) -> bool {
+                !f
+            }
+            fn conjunction(&mut self, f0: bool, f1: bool) -> bool {
+                f0 && f1
+            }
+            fn disjunction(&mut self, f0: bool, f1: bool) -> bool {
+                f0 || f1
+            }
+            fn equivalence(&mut self, f0: bool, f1: bool) -> bool {
+                f0 == f1
+            }
+            fn implication(&mut self, f0: bool, f1: bool) -> bool {
+                !f0 || f1
+            }
+        }
+        self.visit(&mut Evaluate(value))
+    }
+
+    /// Same formula with every variable replaced by what `f` gives for it
+    pub fn map_variables(&self, f: impl FnMut(Variable) -> Formula) -> Formula {
+        struct Map<F>(F);
+        impl<F: FnMut(Variable) -> Formula> Visitor for Map<F> {
+            type Output = Formula;
+            fn variable(&mut self, v: Variable) -> Formula {
+                (self.0)(v)
+            }
+            fn negation(&mut self, f: Formula) -> Formula {
+                not(f)
+            }
+            fn conjunction(&mut self, f0: Formula, f1: Formula) -> Formula {
+                and(f0, f1)
+            }
+            fn disjunction(&mut self, f0: Formula, f1: Formula) -> Formula {
+                or(f0, f1)
+            }
+            fn equivalence(&mut self, f0: Formula, f1: Formula) -> Formula {
+                Formula::Equivalence(Box::new(f0), Box::new(f1))
+            }
+            fn implication(&mut self, f0: Formula, f1: Formula) -> Formula {
+                Formula::Implication(Box::new(f0), Box::new(f1))
+            }
+        }
+        self.visit(&mut Map(f))
+    }
+
+    pub fn substitute(&self, variable: Variable, by: &Formula) -> Formula {
+        self.map_variables(|v| match v == variable {
+            true => by.clone(),
+            false => Formula::Variable(v),
+        })
+    }
+
+    /// Negation normal form, only conjunctions and disjunctions of possibly negated variables
+    pub fn nnf(&self) -> Formula {
+        // every subformula folds into itself and its negation, both in normal form
+        struct Nnf;
+        impl Visitor for Nnf {
+            type Output = (Formula, Formula);
+            fn variable(&mut self, v: Variable) -> Self::Output {
+                (Formula::Variable(v), not(Formula::Variable(v)))
+            }
+            fn negation(&mut self, (f, not_f): Self::Output) -> Self::Output {
+                (not_f, f)
+            }
+            fn conjunction(
+                &mut self,
+                (f0, not_f0): Self::Output,
+                (f1, not_f1): Self::Output,
+            ) -> Self::Output {
+                (and(f0, f1), or(not_f0, not_f1))
+            }
+            fn disjunction(
+                &mut self,
+                (f0, not_f0): Self::Output,
+                (f1, not_f1): Self::Output,
+            ) -> Self::Output {
+                (or(f0, f1), and(not_f0, not_f1))
+            }
+            fn equivalence(
+                &mut self,
+                (f0, not_f0): Self::Output,
+                (f1, not_f1): Self::Output,
+            ) -> Self::Output {
+                (
+                    or(
+                        and(f0.clone(), f1.clone()),
+                        and(not_f0.clone(), not_f1.clone()),
+                    ),
+                    or(and(f0, not_f1), and(not_f0, f1)),
+                )
+            }
+            fn implication(
+                &mut self,
+                (f0, not_f0): Self::Output,
+                (f1, not_f1): Self::Output,
+            ) -> Self::Output {
+                (or(not_f0, f1), and(f0, not_f1))
+            }
+        }
+        self.visit(&mut Nnf).0
+    }
+
+    /// Operands of a chain of conjunctions however it is nested, the formula alone otherwise
+    pub fn conjuncts(&self) -> Vec<&Formula> {
+        self.flatten(|f| match f {
+            Formula::Conjunction(f0, f1) => Some((f0, f1)),
+            _ => None,
+        })
+    }
+
+    /// Operands of a chain of disjunctions however it is nested, the formula alone otherwise
+    pub fn disjuncts(&self) -> Vec<&Formula> {
+        self.flatten(|f| match f {
+            Formula::Disjunction(f0, f1) => Some((f0, f1)),
+            _ => None,
+        })
+    }
+
+    fn flatten(&self, split: impl Fn(&Formula) -> Option<(&Formula, &Formula)>) -> Vec<&Formula> {
+        let mut operands = vec![];
+        let mut stack = vec![self];
+        while let Some(f) = stack.pop() {
+            match split(f) {
+                // pushed in reverse to come out left to right
+                Some((f0, f1)) => stack.extend([f1, f0]),
+                None => operands.push(f),
+            }
+        }
+        operands
+    }
+}
+
+fn not(f: Formula) -> Formula {
+    Formula::Negation(Box::new(f))
+}
+
+fn and(f0: Formula, f1: Formula) -> Formula {
+    Formula::Conjunction(Box::new(f0), Box::new(f1))
+}
+
+fn or(f0: Formula, f1: Formula) -> Formula {
+    Formula::Disjunction(Box::new(f0), Box::new(f1))
+}